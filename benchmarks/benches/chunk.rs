@@ -0,0 +1,51 @@
+//! Benchmarks for chunk data, the largest packets sent while joining a world.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion, Throughput};
+use sandstone::protocol::game::world::chunk::{PaletteFormatType, PalletedContainer};
+use sandstone::protocol::packets::packet_definer::{PacketDirection, PacketState};
+use sandstone::protocol::packets::Packet;
+use sandstone::protocol::serialization::{McDeserializer, McSerialize, McSerializer, StateBasedDeserializer};
+use sandstone_benchmarks::{block_palette, chunk_packet};
+
+fn paletted_container(c: &mut Criterion) {
+	let (palette, indices) = block_palette();
+
+	let mut group = c.benchmark_group("paletted_container");
+	group.throughput(Throughput::Elements(indices.len() as u64));
+
+	group.bench_function("pack", |b| {
+		b.iter_batched(|| palette.clone(), |palette| PalletedContainer::indirect(palette, black_box(&indices), PaletteFormatType::BLOCKS).unwrap(), BatchSize::SmallInput)
+	});
+
+	group.finish();
+}
+
+fn chunk_data(c: &mut Criterion) {
+	let packet = chunk_packet();
+	let mut serializer = McSerializer::new();
+	packet.mc_serialize(&mut serializer).unwrap();
+	let encoded = serializer.output;
+
+	let mut group = c.benchmark_group("chunk_data_update_light");
+	group.throughput(Throughput::Bytes(encoded.len() as u64));
+
+	group.bench_function("serialize", |b| {
+		let mut serializer = McSerializer::init_size(encoded.len());
+
+		b.iter(|| {
+			serializer.clear();
+			black_box(&packet).mc_serialize(&mut serializer).unwrap();
+		})
+	});
+
+	group.bench_function("deserialize", |b| {
+		b.iter(|| Packet::deserialize_state(&mut McDeserializer::new(black_box(&encoded)), PacketState::PLAY, PacketDirection::CLIENT).unwrap())
+	});
+
+	group.finish();
+}
+
+criterion_group!(benches, paletted_container, chunk_data);
+criterion_main!(benches);
@@ -0,0 +1,46 @@
+//! Benchmarks for framing packets before they are sent, the work done by `CraftConnection::send_packet`
+//! besides writing to the socket.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use sandstone::network::PacketFramer;
+use sandstone::protocol::packets::packet_definer::PacketState;
+use sandstone::protocol::packets::{KeepAlivePacket, Packet};
+use sandstone::protocol_types::protocol_verison::ProtocolVerison;
+use sandstone_benchmarks::chunk_packet;
+
+/// The default compression threshold of a vanilla server.
+const THRESHOLD: Option<u32> = Some(256);
+
+fn framing(c: &mut Criterion) {
+	let chunk = chunk_packet();
+	let keep_alive = Packet::KeepAlive(KeepAlivePacket::new(42));
+	let version = ProtocolVerison::latest();
+
+	let mut group = c.benchmark_group("framing");
+	let mut framer = PacketFramer::new();
+
+	group.bench_function("uncompressed", |b| {
+		b.iter(|| {
+			black_box(framer.frame(black_box(&chunk), PacketState::PLAY, version, None).unwrap());
+		})
+	});
+
+	group.bench_function("below_threshold", |b| {
+		b.iter(|| {
+			black_box(framer.frame(black_box(&keep_alive), PacketState::PLAY, version, THRESHOLD).unwrap());
+		})
+	});
+
+	group.bench_function("compressed", |b| {
+		b.iter(|| {
+			black_box(framer.frame(black_box(&chunk), PacketState::PLAY, version, THRESHOLD).unwrap());
+		})
+	});
+
+	group.finish();
+}
+
+criterion_group!(benches, framing);
+criterion_main!(benches);
@@ -0,0 +1,114 @@
+//! Benchmarks for the protocol types that are in almost every packet: VarInts, NBT and text components.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use sandstone::protocol::packets::packet_definer::{PacketDirection, PacketState};
+use sandstone::protocol::packets::{HandshakingPacket, Packet};
+use sandstone::protocol::serialization::serializer_types::BoundedString;
+use sandstone::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, StateBasedDeserializer};
+use sandstone::protocol_types::datatypes::nbt::NbtCompound;
+use sandstone::protocol_types::datatypes::var_types::{RangedVarInt, VarInt};
+use sandstone_benchmarks::{bigtest_nbt, text_component, varints};
+
+fn varint(c: &mut Criterion) {
+	let values = varints();
+	let mut serializer = McSerializer::new();
+	for value in &values {
+		value.mc_serialize(&mut serializer).unwrap();
+	}
+	let encoded = serializer.output;
+
+	let mut group = c.benchmark_group("varint");
+	group.throughput(Throughput::Elements(values.len() as u64));
+
+	group.bench_function("encode", |b| {
+		let mut serializer = McSerializer::init_size(encoded.len());
+
+		b.iter(|| {
+			serializer.clear();
+			for value in black_box(&values) {
+				value.mc_serialize(&mut serializer).unwrap();
+			}
+		})
+	});
+
+	group.bench_function("decode", |b| {
+		b.iter(|| {
+			let mut deserializer = McDeserializer::new(black_box(&encoded));
+			while !deserializer.is_at_end() {
+				black_box(VarInt::mc_deserialize(&mut deserializer).unwrap());
+			}
+		})
+	});
+
+	group.finish();
+}
+
+fn nbt(c: &mut Criterion) {
+	let input = bigtest_nbt();
+	let compound = NbtCompound::from_root(&mut McDeserializer::new(&input)).unwrap();
+
+	let mut group = c.benchmark_group("nbt");
+	group.throughput(Throughput::Bytes(input.len() as u64));
+
+	group.bench_function("parse", |b| {
+		b.iter(|| NbtCompound::from_root(&mut McDeserializer::new(black_box(&input))).unwrap())
+	});
+
+	group.bench_function("write", |b| {
+		let mut serializer = McSerializer::init_size(input.len());
+
+		b.iter(|| {
+			serializer.clear();
+			black_box(&compound).mc_serialize(&mut serializer).unwrap();
+		})
+	});
+
+	group.finish();
+}
+
+fn text(c: &mut Criterion) {
+	let component = text_component();
+
+	c.bench_function("text_component/nbt_encode", |b| {
+		let mut serializer = McSerializer::new();
+
+		b.iter(|| {
+			serializer.clear();
+			black_box(&component).mc_serialize(&mut serializer).unwrap();
+		})
+	});
+}
+
+fn packet(c: &mut Criterion) {
+	let packet = Packet::Handshaking(HandshakingPacket {
+		protocol_version: VarInt(774),
+		server_address: BoundedString::new("localhost").unwrap(),
+		port: 25565,
+		next_state: RangedVarInt::new(2).unwrap(),
+	});
+	let mut serializer = McSerializer::new();
+	packet.mc_serialize(&mut serializer).unwrap();
+	let encoded = serializer.output;
+
+	let mut group = c.benchmark_group("packet/handshake");
+
+	group.bench_function("serialize", |b| {
+		let mut serializer = McSerializer::new();
+
+		b.iter(|| {
+			serializer.clear();
+			black_box(&packet).mc_serialize(&mut serializer).unwrap();
+		})
+	});
+
+	group.bench_function("deserialize", |b| {
+		b.iter(|| Packet::deserialize_state(&mut McDeserializer::new(black_box(&encoded)), PacketState::HANDSHAKING, PacketDirection::SERVER).unwrap())
+	});
+
+	group.finish();
+}
+
+criterion_group!(benches, varint, nbt, text, packet);
+criterion_main!(benches);
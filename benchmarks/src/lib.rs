@@ -0,0 +1,79 @@
+//! Fixtures for the benchmarks in `benches/`. Every fixture is built the same way on every run, so that
+//! the results of two runs can be compared to find performance regressions.
+//!
+//! Run the benchmarks with `cargo bench -p sandstone-benchmarks`.
+
+use std::io::Read;
+
+use flate2::read::GzDecoder;
+use sandstone::protocol::game::world::chunk::{ChunkByteData, ChunkSection, PaletteFormatType, PalletedContainer};
+use sandstone::protocol::game::world::generator::superflat::superflat_chunk;
+use sandstone::protocol::packets::{ChunkDataUpdateLightPacket, Packet};
+use sandstone::protocol_types::datatypes::chat::TextComponent;
+use sandstone::protocol_types::datatypes::var_types::VarInt;
+
+/// The number of sections in an overworld chunk.
+const SECTION_COUNT: usize = 24;
+
+/// VarInts of every encoded length, from 1 to 5 bytes.
+pub fn varints() -> Vec<VarInt> {
+	(0..1000).flat_map(|i: i32| [i, i << 7, i << 14, i << 21, -i]).map(VarInt).collect()
+}
+
+/// A palette of 16 block states and the indices of a 4096 entry section into it. The indices are spread
+/// over the whole palette so that every packed long is different.
+pub fn block_palette() -> (Vec<VarInt>, Vec<u16>) {
+	let palette = (0..16).map(|i| VarInt(i * 97 + 1)).collect();
+	let indices = (0..4096).map(|i: u16| (i.wrapping_mul(7) ^ (i >> 4)) % 16).collect();
+
+	(palette, indices)
+}
+
+/// A `Chunk Data and Update Light` packet for a full overworld chunk. The bottom section is the one from the
+/// superflat generator, and every other section uses the 16 block palette of [block_palette].
+pub fn chunk_packet() -> Packet {
+	let (mut data, light) = superflat_chunk();
+	let (palette, indices) = block_palette();
+
+	let mut sections = vec![data.data.data.swap_remove(0)];
+	for _ in 1..SECTION_COUNT {
+		sections.push(ChunkSection {
+			block_count: 4096,
+			block_states: PalletedContainer::indirect(palette.clone(), &indices, PaletteFormatType::BLOCKS).unwrap(),
+			biomes: PalletedContainer::single_valued(VarInt(0)),
+		});
+	}
+	data.data = ChunkByteData {
+		data: sections,
+	};
+
+	Packet::ChunkDataUpdateLight(ChunkDataUpdateLightPacket::new(3, -7, data, light))
+}
+
+/// The uncompressed contents of `bigtest.nbt`, an NBT file with every tag type.
+pub fn bigtest_nbt() -> Vec<u8> {
+	let compressed = include_bytes!("../../sandstone/tests/nbt/assets/bigtest.nbt");
+
+	let mut decompressed = Vec::new();
+	GzDecoder::new(&compressed[..]).read_to_end(&mut decompressed).expect("Failed to decompress bigtest.nbt");
+	decompressed
+}
+
+/// A chat message like those sent by plugins, with formatting and a few children.
+pub fn text_component() -> TextComponent {
+	let mut name = TextComponent::new("dec4234");
+	name.color = Some("gold".to_string());
+	name.bold = Some(true);
+	name.insertion = Some("dec4234".to_string());
+
+	let mut message = TextComponent::new(": has made the advancement ");
+	message.color = Some("gray".to_string());
+
+	let mut advancement = TextComponent::translatable("advancements.story.mine_diamond.title");
+	advancement.color = Some("green".to_string());
+	advancement.italic = Some(true);
+
+	let mut component = TextComponent::new("[Server] ");
+	component.set_extra(vec![name, message, advancement]);
+	component
+}
@@ -9,8 +9,8 @@ use sandstone::protocol::packets::{
 	AcknowledgeFinishConfigurationPacket, ClientCommandPacket, ConfirmTeleportPacket, HandshakingPacket, LoginAcknowledgedPacket, LoginStartPacket, Packet, ServerboundKeepAlivePacket,
 	ServerboundKnownPacksPacket,
 };
-use sandstone::protocol::serialization::serializer_types::PrefixedArray;
-use sandstone::protocol_types::datatypes::var_types::VarInt;
+use sandstone::protocol::serialization::serializer_types::{BoundedString, PrefixedArray};
+use sandstone::protocol_types::datatypes::var_types::{RangedVarInt, VarInt};
 use sandstone::protocol_types::protocol_verison::ProtocolVerison;
 use simple_logger::SimpleLogger;
 use std::str::FromStr;
@@ -26,16 +26,16 @@ async fn main() {
 
 	let handshake = Packet::Handshaking(HandshakingPacket {
 		protocol_version: VarInt(ProtocolVerison::latest().get_version_number() as i32),
-		server_address: "127.0.0.1".to_string(),
+		server_address: BoundedString::new("127.0.0.1").unwrap(),
 		port: 25565,
-		next_state: VarInt(2),
+		next_state: RangedVarInt::new(2).unwrap(),
 	});
 
 	debug!("Sending handshake packet: {handshake:?}");
 	client.send_packet(handshake).await.unwrap();
 
 	let login_start = Packet::LoginStart(LoginStartPacket {
-		username: "dec4234".to_string(),
+		username: BoundedString::new("dec4234").unwrap(),
 		uuid: Uuid::from_str("ef39c197-3c3d-4776-a226-22096378a966").unwrap(),
 	});
 
@@ -272,7 +272,7 @@ async fn main() {
 				debug!("Received set experience: {se:?}");
 				continue;
 			}
-			Packet::ClientboundKeepAlive(ka) => {
+			Packet::KeepAlive(ka) => {
 				debug!("Received clientbound keep alive: {ka:?}");
 
 				let keep_alive = Packet::ServerboundKeepAlive(ServerboundKeepAlivePacket {
@@ -292,7 +292,7 @@ async fn main() {
 				debug!("Received chunk batch start.");
 				break;
 			}
-			Packet::DisconnectPlay(dp) => {
+			Packet::Disconnect(dp) => {
 				debug!("Disconnected: {dp:?}");
 				return;
 			}
@@ -318,7 +318,7 @@ async fn main() {
 				debug!("Finished receiving {} chunks.", cbf.size.0);
 				return;
 			}
-			Packet::DisconnectPlay(dp) => {
+			Packet::Disconnect(dp) => {
 				debug!("Disconnected: {dp:?}");
 				return;
 			}
@@ -0,0 +1,571 @@
+//! Turns a minecraft-data `protocol.json` into a `packets!` invocation.
+//!
+//! Every packet in protocol.json is a container of fields. The simple field types, like VarInts, strings, options
+//! and arrays, are mapped onto the sandstone types. Anything else, like inline containers and switches, has to be
+//! given a hand-written type in the [Overrides], and is reported as unresolved until it is.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// The states in protocol.json and their sandstone names, in the order they are written.
+const STATES: [(&str, &str); 5] = [("handshaking", "HANDSHAKING"), ("status", "STATUS"), ("login", "LOGIN"), ("configuration", "CONFIGURATION"), ("play", "PLAY")];
+
+/// The directions in protocol.json and their sandstone names, which are the destination of the packet.
+const DIRECTIONS: [(&str, &str); 2] = [("toClient", "CLIENT"), ("toServer", "SERVER")];
+
+/// The native types of protocol.json that map directly onto a sandstone type.
+const NATIVE_TYPES: [(&str, &str); 18] = [
+	("varint", "VarInt"),
+	("optvarint", "VarInt"),
+	("varlong", "VarLong"),
+	("bool", "bool"),
+	("u8", "u8"),
+	("i8", "i8"),
+	("u16", "u16"),
+	("i16", "i16"),
+	("u32", "u32"),
+	("i32", "i32"),
+	("u64", "u64"),
+	("i64", "i64"),
+	("f32", "f32"),
+	("f64", "f64"),
+	("UUID", "Uuid"),
+	("anonymousNbt", "NbtTag"),
+	("anonOptionalNbt", "NbtTag"),
+	("string", "String"),
+];
+
+/// Hand-written parts of the packet definitions that protocol.json can't describe.
+#[derive(Debug, Default, Deserialize)]
+pub struct Overrides {
+	/// Sandstone types for named protocol.json types, like `Slot` to `SlotData`.
+	#[serde(default)]
+	pub types: BTreeMap<String, String>,
+	/// Changes to single packets, by `state.direction.name`, like `play.toClient.map_chunk`.
+	#[serde(default)]
+	pub packets: BTreeMap<String, PacketOverride>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PacketOverride {
+	/// The name of the packet, instead of its protocol.json name in PascalCase.
+	pub name: Option<String>,
+	pub doc: Option<String>,
+	/// Leave the packet out, for packets that sandstone handles elsewhere.
+	#[serde(default)]
+	pub skip: bool,
+	/// Changes to single fields, by their protocol.json name.
+	#[serde(default)]
+	pub fields: BTreeMap<String, FieldOverride>,
+	/// Every field of the packet, replacing the ones from protocol.json. Used for packets that are read into
+	/// hand-written types, like chunk data.
+	pub body: Option<Vec<FieldOverride>>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct FieldOverride {
+	pub name: Option<String>,
+	#[serde(rename = "type")]
+	pub typ: Option<String>,
+	/// Attributes for the field, like `#[mc(rest)]`.
+	#[serde(default)]
+	pub attrs: Vec<String>,
+	pub doc: Option<String>,
+}
+
+/// A field of a generated packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+	pub name: String,
+	pub typ: String,
+	pub attrs: Vec<String>,
+	pub doc: Option<String>,
+}
+
+/// A generated packet, in the first state it is used in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PacketDef {
+	pub name: String,
+	pub state: &'static str,
+	pub direction: &'static str,
+	pub id: u32,
+	/// Later states the packet is also used in, with its id in each.
+	pub other_states: Vec<(&'static str, u32)>,
+	pub doc: Option<String>,
+	pub fields: Vec<Field>,
+}
+
+/// The packets generated from protocol.json, along with anything that still needs an override.
+#[derive(Debug, Default)]
+pub struct Generated {
+	pub packets: Vec<PacketDef>,
+	/// Fields without a sandstone type, as `state.direction.packet.field: reason`.
+	pub unresolved: Vec<String>,
+	/// Overrides that didn't match anything in protocol.json.
+	pub unused_overrides: Vec<String>,
+}
+
+/// Generate the packets in protocol.json, with the overrides applied.
+pub fn generate(protocol: &Value, overrides: &Overrides) -> Result<Generated, String> {
+	let empty = Map::new();
+	let global_types = protocol.get("types").and_then(Value::as_object).unwrap_or(&empty);
+
+	let mut generated = Generated::default();
+	let mut used_overrides = vec![];
+
+	for (json_state, state) in STATES {
+		let Some(state_json) = protocol.get(json_state) else {
+			continue;
+		};
+
+		for (json_direction, direction) in DIRECTIONS {
+			let Some(types) = state_json.get(json_direction).and_then(|d| d.get("types")).and_then(Value::as_object) else {
+				continue;
+			};
+			// no packets in this direction, like clientbound handshaking
+			if !types.contains_key("packet") {
+				continue;
+			}
+
+			for (id, packet_name, packet_type) in packet_ids(types).map_err(|e| format!("{json_state}.{json_direction}: {e}"))? {
+				let key = format!("{json_state}.{json_direction}.{packet_name}");
+				let packet_override = overrides.packets.get(&key);
+				if packet_override.is_some() {
+					used_overrides.push(key.clone());
+				}
+
+				if packet_override.is_some_and(|o| o.skip) {
+					continue;
+				}
+
+				let definition = types.get(&packet_type).ok_or_else(|| format!("{key}: no type `{packet_type}`"))?;
+				let mut resolver = Resolver {
+					global_types,
+					local_types: types,
+					overrides,
+					context: key.clone(),
+					unresolved: &mut generated.unresolved,
+				};
+
+				let fields = match packet_override.and_then(|o| o.body.as_ref()) {
+					Some(body) => body_fields(body).map_err(|e| format!("{key}: {e}"))?,
+					None => resolver.packet_fields(definition, packet_override, &mut generated.unused_overrides),
+				};
+
+				generated.packets.push(PacketDef {
+					name: packet_override.and_then(|o| o.name.clone()).unwrap_or_else(|| pascal_case(&packet_name)),
+					state,
+					direction,
+					id,
+					other_states: vec![],
+					doc: packet_override.and_then(|o| o.doc.clone()),
+					fields,
+				});
+			}
+		}
+	}
+
+	generated.packets = merge_states(generated.packets);
+
+	for key in overrides.packets.keys() {
+		if !used_overrides.contains(key) {
+			generated.unused_overrides.push(format!("{key}: no such packet"));
+		}
+	}
+
+	Ok(generated)
+}
+
+/// The id, name and type of every packet in a direction, read from the `packet` container which maps the id to
+/// the name, and the name to the type.
+fn packet_ids(types: &Map<String, Value>) -> Result<Vec<(u32, String, String)>, String> {
+	let fields = types.get("packet").and_then(|p| p.get(1)).and_then(Value::as_array).ok_or("no `packet` container")?;
+
+	let field = |name: &str| fields.iter().find(|f| f.get("name").and_then(Value::as_str) == Some(name)).and_then(|f| f.get("type")).and_then(|t| t.get(1));
+	let mappings = field("name").and_then(|m| m.get("mappings")).and_then(Value::as_object).ok_or("no packet id mappings")?;
+	let switch = field("params").and_then(|s| s.get("fields")).and_then(Value::as_object).ok_or("no packet type switch")?;
+
+	let mut ids = vec![];
+	for (id, name) in mappings {
+		let id = u32::from_str_radix(id.trim_start_matches("0x"), 16).map_err(|e| format!("bad packet id `{id}`: {e}"))?;
+		let name = name.as_str().ok_or_else(|| format!("packet name for id {id} is not a string"))?;
+		let typ = switch.get(name).and_then(Value::as_str).ok_or_else(|| format!("no type for packet `{name}`"))?;
+		ids.push((id, name.to_string(), typ.to_string()));
+	}
+
+	ids.sort();
+	Ok(ids)
+}
+
+/// The fields given in full by an override.
+fn body_fields(body: &[FieldOverride]) -> Result<Vec<Field>, String> {
+	body.iter()
+		.map(|field| {
+			Ok(Field {
+				name: field.name.clone().ok_or("a body field has no name")?,
+				typ: field.typ.clone().ok_or("a body field has no type")?,
+				attrs: field.attrs.clone(),
+				doc: field.doc.clone(),
+			})
+		})
+		.collect()
+}
+
+/// Merge packets that are used in more than one state into the first state they are used in, like `KeepAlive`,
+/// and rename any other packets that end up with the same name.
+fn merge_states(packets: Vec<PacketDef>) -> Vec<PacketDef> {
+	let mut merged: Vec<PacketDef> = vec![];
+	// the names before renaming, which later states are merged by
+	let mut names: Vec<String> = vec![];
+
+	for mut packet in packets {
+		let first = merged.iter_mut().zip(&names).find(|(p, name)| **name == packet.name && p.direction == packet.direction && p.fields == packet.fields && p.state != packet.state);
+		if let Some((first, _)) = first {
+			first.other_states.push((packet.state, packet.id));
+			continue;
+		}
+
+		names.push(packet.name.clone());
+
+		if merged.iter().any(|p| p.name == packet.name && p.direction != packet.direction) {
+			let prefix = if packet.direction == "SERVER" { "Serverbound" } else { "Clientbound" };
+			packet.name = format!("{prefix}{}", packet.name);
+		}
+
+		// still taken, by a packet of the same direction in another state
+		if merged.iter().any(|p| p.name == packet.name) {
+			packet.name = format!("{}{}", pascal_case(&packet.state.to_lowercase()), packet.name);
+		}
+
+		merged.push(packet);
+	}
+
+	merged
+}
+
+/// Finds the sandstone types for the fields of a packet.
+struct Resolver<'a> {
+	global_types: &'a Map<String, Value>,
+	local_types: &'a Map<String, Value>,
+	overrides: &'a Overrides,
+	/// Where the resolver is, as `state.direction.packet.field`, for reporting unresolved types.
+	context: String,
+	unresolved: &'a mut Vec<String>,
+}
+
+impl Resolver<'_> {
+	fn packet_fields(&mut self, definition: &Value, packet_override: Option<&PacketOverride>, unused_overrides: &mut Vec<String>) -> Vec<Field> {
+		let packet = self.context.clone();
+		let empty = BTreeMap::new();
+		let field_overrides = packet_override.map(|o| &o.fields).unwrap_or(&empty);
+
+		let Some(fields) = container_fields(definition) else {
+			self.unresolved.push(format!("{packet}: not a container"));
+			return vec![];
+		};
+
+		for name in field_overrides.keys() {
+			if !fields.iter().any(|f| f.get("name").and_then(Value::as_str) == Some(name)) {
+				unused_overrides.push(format!("{packet}.{name}: no such field"));
+			}
+		}
+
+		let packet_name = packet.rsplit('.').next().unwrap_or_default().to_string();
+		let mut out = vec![];
+		for field in fields {
+			let Some(name) = field.get("name").and_then(Value::as_str) else {
+				self.unresolved.push(format!("{packet}: anonymous field"));
+				continue;
+			};
+			let typ = field.get("type").unwrap_or(&Value::Null);
+			let field_override = field_overrides.get(name).cloned().unwrap_or_default();
+
+			self.context = format!("{packet}.{name}");
+			let (typ, mut attrs) = match field_override.typ {
+				Some(typ) => (typ, vec![]),
+				None => self.resolve(typ, &format!("{}{}", pascal_case(&packet_name), pascal_case(name))),
+			};
+			attrs.extend(field_override.attrs);
+
+			out.push(Field {
+				name: field_override.name.unwrap_or_else(|| field_name(name)),
+				typ,
+				attrs,
+				doc: field_override.doc,
+			});
+		}
+
+		self.context = packet;
+		out
+	}
+
+	/// The sandstone type and any attributes for a protocol.json type. Types that can't be resolved are reported,
+	/// and written as `fallback` or their name, to be hand-written.
+	fn resolve(&mut self, typ: &Value, fallback: &str) -> (String, Vec<String>) {
+		match typ {
+			Value::String(name) => self.resolve_named(name),
+			Value::Array(parts) if parts.len() == 2 => {
+				let (kind, options) = (parts[0].as_str().unwrap_or_default(), &parts[1]);
+				let count_type = options.get("countType").and_then(Value::as_str);
+				let count = options.get("count").and_then(Value::as_u64);
+
+				match kind {
+					"pstring" if count_type == Some("varint") => ("String".to_string(), vec![]),
+					"buffer" if count_type == Some("varint") => ("PrefixedArray<u8>".to_string(), vec![]),
+					"buffer" if count.is_some() => (format!("[u8; {}]", count.unwrap()), vec![]),
+					"array" if count_type == Some("varint") => {
+						let (inner, _) = self.resolve(options.get("type").unwrap_or(&Value::Null), fallback);
+						(format!("PrefixedArray<{inner}>"), vec![])
+					}
+					"array" if count.is_some() && options.get("type").and_then(Value::as_str) == Some("u8") => (format!("[u8; {}]", count.unwrap()), vec![]),
+					"option" => {
+						let (inner, _) = self.resolve(options, fallback);
+						(format!("PrefixedOptional<{inner}>"), vec![])
+					}
+					// the mapped names are only for reading, the id is what is sent
+					"mapper" => self.resolve(options.get("type").unwrap_or(&Value::Null), fallback),
+					"bitfield" => {
+						let bits: u64 = options.as_array().into_iter().flatten().filter_map(|f| f.get("size").and_then(Value::as_u64)).sum();
+						match bits {
+							8 | 16 | 32 | 64 => (format!("u{bits}"), vec![]),
+							_ => self.unresolved(format!("bitfield of {bits} bits"), fallback),
+						}
+					}
+					_ => self.unresolved(format!("`{kind}`"), fallback),
+				}
+			}
+			_ => self.unresolved(format!("unknown type {typ}"), fallback),
+		}
+	}
+
+	fn resolve_named(&mut self, name: &str) -> (String, Vec<String>) {
+		if let Some(typ) = self.overrides.types.get(name) {
+			return (typ.clone(), vec![]);
+		}
+
+		if name == "restBuffer" {
+			return ("Vec<u8>".to_string(), vec!["#[mc(rest)]".to_string()]);
+		}
+
+		if let Some((_, typ)) = NATIVE_TYPES.iter().find(|(native, _)| *native == name) {
+			return (typ.to_string(), vec![]);
+		}
+
+		let definition = self.local_types.get(name).or_else(|| self.global_types.get(name));
+		match definition {
+			Some(Value::String(native)) if native == "native" => self.unresolved(format!("native type `{name}`"), &pascal_case(name)),
+			Some(definition) => {
+				// an alias of a type that can be resolved, like `ContainerID` for a VarInt. Anything more is
+				// reported as the named type, which is what gets an override.
+				let before = self.unresolved.len();
+				let resolved = self.resolve(definition, &pascal_case(name));
+				if self.unresolved.len() > before {
+					self.unresolved.truncate(before);
+					return self.unresolved(format!("type `{name}`"), &pascal_case(name));
+				}
+				resolved
+			}
+			None => self.unresolved(format!("unknown type `{name}`"), &pascal_case(name)),
+		}
+	}
+
+	fn unresolved(&mut self, reason: String, fallback: &str) -> (String, Vec<String>) {
+		self.unresolved.push(format!("{}: {reason}", self.context));
+		(fallback.to_string(), vec![])
+	}
+}
+
+/// The fields of a `["container", [...]]` type.
+fn container_fields(typ: &Value) -> Option<&Vec<Value>> {
+	match typ.as_array()?.as_slice() {
+		[kind, fields] if kind == "container" => fields.as_array(),
+		_ => None,
+	}
+}
+
+/// Write the packets as a `packets!` invocation, in the layout of `protocol/packets/mod.rs`.
+pub fn render(packets: &[PacketDef], version: &str, source: &str) -> String {
+	let mut states = vec![];
+
+	for (_, state) in STATES {
+		let mut directions = vec![];
+
+		for (_, direction) in DIRECTIONS {
+			let mut in_direction: Vec<&PacketDef> = packets.iter().filter(|p| p.state == state && p.direction == direction).collect();
+			if in_direction.is_empty() {
+				continue;
+			}
+			in_direction.sort_by_key(|p| p.id);
+
+			let packets: Vec<String> = in_direction.into_iter().map(render_packet).collect();
+			directions.push(format!("\t\t{direction} => {{\n{}\n\t\t}}", packets.join(",\n")));
+		}
+
+		if !directions.is_empty() {
+			states.push(format!("\t{state} => {{\n{}\n\t}}", directions.join(",\n")));
+		}
+	}
+
+	let version = version.replace('.', "_");
+	format!("// Generated by packet_codegen from {source}\npackets!(v{version} => {{\n{}\n}});\n", states.join(",\n"))
+}
+
+fn render_packet(packet: &PacketDef) -> String {
+	let mut head = format!("\t\t\t{}, 0x{:02X}", packet.name, packet.id);
+	for (state, id) in &packet.other_states {
+		head.push_str(&format!(" | {state} 0x{id:02X}"));
+	}
+	if let Some(doc) = &packet.doc {
+		head.push_str(&format!(" #[doc = {doc:?}]"));
+	}
+
+	if packet.fields.is_empty() {
+		return format!("{head} => {{\n\t\t\t\t// none\n\t\t\t}}");
+	}
+
+	let fields: Vec<String> = packet
+		.fields
+		.iter()
+		.map(|field| {
+			let mut out = String::new();
+			if let Some(doc) = &field.doc {
+				out.push_str(&format!("\t\t\t\t#[doc = {doc:?}]\n"));
+			}
+			for attr in &field.attrs {
+				out.push_str(&format!("\t\t\t\t{attr}\n"));
+			}
+			out.push_str(&format!("\t\t\t\t{}: {}", field.name, field.typ));
+			out
+		})
+		.collect();
+
+	format!("{head} => {{\n{}\n\t\t\t}}", fields.join(",\n"))
+}
+
+/// `set_protocol` or `setProtocol` to `SetProtocol`.
+pub fn pascal_case(name: &str) -> String {
+	let mut out = String::with_capacity(name.len());
+	let mut upper = true;
+
+	for c in name.chars() {
+		if c == '_' || c == '-' || c == '.' {
+			upper = true;
+		} else if upper {
+			out.extend(c.to_uppercase());
+			upper = false;
+		} else {
+			out.push(c);
+		}
+	}
+
+	out
+}
+
+/// `serverPort` to `server_port` and `playerUUID` to `player_uuid`. `type` is a keyword, so it becomes `typ` like elsewhere in sandstone.
+pub fn field_name(name: &str) -> String {
+	let mut out = String::with_capacity(name.len() + 4);
+
+	let mut previous_lower = false;
+
+	for c in name.chars() {
+		if c.is_uppercase() {
+			// acronyms like `UUID` stay in one word
+			if previous_lower {
+				out.push('_');
+			}
+			out.extend(c.to_lowercase());
+		} else {
+			out.push(c);
+		}
+		previous_lower = c.is_lowercase() || c.is_ascii_digit();
+	}
+
+	match out.as_str() {
+		"type" => "typ".to_string(),
+		"match" | "move" | "ref" | "mod" | "loop" | "use" | "in" | "fn" | "box" | "where" | "impl" | "as" | "crate" | "self" | "super" | "dyn" => format!("r#{out}"),
+		_ => out,
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use serde_json::Value;
+
+	use crate::codegen::{field_name, generate, pascal_case, render, Overrides};
+
+	fn sample() -> (Value, Overrides) {
+		let protocol = serde_json::from_str(include_str!("../fixtures/protocol.json")).unwrap();
+		let overrides = serde_json::from_str(include_str!("../fixtures/overrides.json")).unwrap();
+		(protocol, overrides)
+	}
+
+	#[test]
+	fn names() {
+		assert_eq!(pascal_case("set_protocol"), "SetProtocol");
+		assert_eq!(pascal_case("Slot"), "Slot");
+		assert_eq!(field_name("serverPort"), "server_port");
+		assert_eq!(field_name("entityId"), "entity_id");
+		assert_eq!(field_name("playerUUID"), "player_uuid");
+		assert_eq!(field_name("type"), "typ");
+	}
+
+	#[test]
+	fn packets_with_overrides() {
+		let (protocol, overrides) = sample();
+		let generated = generate(&protocol, &overrides).unwrap();
+		let out = render(&generated.packets, "1.21.11", "pc/1.21.11");
+
+		assert!(out.starts_with("// Generated by packet_codegen from pc/1.21.11\npackets!(v1_21_11 => {\n\tHANDSHAKING => {\n\t\tSERVER => {\n"), "{out}");
+
+		// renamed with its field types changed, and the legacy ping is skipped
+		assert!(out.contains("\t\t\tHandshaking, 0x00 => {\n\t\t\t\tprotocol_version: VarInt,\n\t\t\t\tserver_address: BoundedString<255>,\n\t\t\t\tport: u16,\n\t\t\t\t#[doc = \"1 for STATUS, 2 for LOGIN, 3 for TRANSFER\"]\n\t\t\t\tnext_state: RangedVarInt<1, 3>\n\t\t\t}"), "{out}");
+		assert!(!out.contains("LegacyServerListPing"));
+
+		// the same packet in configuration and play is defined once, in the first state
+		assert!(out.contains("\t\t\tKeepAlive, 0x04 | PLAY 0x27 => {\n\t\t\t\tkeep_alive_id: i64\n\t\t\t}"), "{out}");
+		assert!(out.contains("\t\t\tServerboundKeepAlive, 0x04 | PLAY 0x1B => {"), "{out}");
+		assert!(out.contains("\t\t\tFinishConfiguration, 0x03 => {\n\t\t\t\t// none\n\t\t\t}"), "{out}");
+
+		// a hand-written body
+		assert!(out.contains("\t\t\tChunkDataUpdateLight, 0x28 => {\n\t\t\t\tx: i32,\n\t\t\t\tz: i32,\n\t\t\t\tdata: ChunkData,\n\t\t\t\tlight: LightData\n\t\t\t}"), "{out}");
+
+		// the simple types, and named types from the overrides
+		assert!(out.contains("\t\t\t\tlocation: Position,\n"), "{out}");
+		assert!(out.contains("\t\t\t\tcontainer_id: VarInt,\n\t\t\t\tslot_data: SlotData,\n"), "{out}");
+		assert!(out.contains("\t\t\t\tslot: VarInt\n"), "{out}");
+		assert!(out.contains("\t\t\t\tplayer_uuid: Uuid\n"), "{out}");
+		assert!(out.contains("\t\t\t\treason: NbtTag\n"), "{out}");
+		assert!(out.contains("\t\t\t\tfeatures: PrefixedArray<String>\n"), "{out}");
+		assert!(out.contains("\t\t\t\tsignature: PrefixedOptional<[u8; 256]>,\n"), "{out}");
+		assert!(out.contains("\t\t\t\ticon: PrefixedOptional<PrefixedArray<u8>>\n"), "{out}");
+		assert!(out.contains("\t\t\t\t#[mc(rest)]\n\t\t\t\tdata: Vec<u8>\n"), "{out}");
+	}
+
+	#[test]
+	fn unresolved_types_are_reported() {
+		let (protocol, overrides) = sample();
+		let generated = generate(&protocol, &overrides).unwrap();
+		let out = render(&generated.packets, "1.21.11", "pc/1.21.11");
+
+		assert_eq!(generated.unresolved, ["play.toClient.unlock_recipes.entries: `container`", "play.toClient.unlock_recipes.book: type `RecipeBookSettings`"]);
+		assert!(out.contains("\t\t\t\tentries: PrefixedArray<UnlockRecipesEntries>,\n\t\t\t\tbook: RecipeBookSettings\n"), "{out}");
+
+		assert_eq!(generated.unused_overrides, ["play.toServer.not_a_packet: no such packet"]);
+	}
+
+	#[test]
+	fn packets_with_the_same_name_are_renamed() {
+		let (protocol, overrides) = sample();
+		let generated = generate(&protocol, &overrides).unwrap();
+		let names: Vec<&str> = generated.packets.iter().map(|p| p.name.as_str()).collect();
+
+		// `disconnect` in login has a different body than in configuration
+		assert!(names.contains(&"Disconnect"));
+		assert!(names.contains(&"ConfigurationDisconnect"));
+		// `ping` is in both directions of status
+		assert!(names.contains(&"Ping"));
+		assert!(names.contains(&"ServerboundPing"));
+	}
+}
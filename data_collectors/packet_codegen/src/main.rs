@@ -0,0 +1,149 @@
+//! Generate the `packets!` invocation from the minecraft-data `protocol.json` of a version.
+//!
+//! The output is meant to be diffed against `sandstone/src/protocol/packets/mod.rs` when updating to a new version,
+//! not to replace it wholesale. See the README for the options.
+
+mod codegen;
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::codegen::{generate, render, Overrides};
+
+/// Base URL for raw files in the minecraft-data repo `data/` directory, the same as sandstone's build.rs.
+const RAW_BASE: &str = "https://raw.githubusercontent.com/PrismarineJS/minecraft-data/master/data";
+
+const DEFAULT_MC_VERSION: &str = "1.21.11";
+
+struct Args {
+	version: String,
+	input: Option<PathBuf>,
+	fetch: bool,
+	overrides: PathBuf,
+	out: Option<PathBuf>,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+	let args = parse_args()?;
+	let vendored = vendored_path(&args.version);
+
+	if args.fetch {
+		fetch_protocol(&args.version, &vendored)?;
+	}
+
+	let input = args.input.as_ref().unwrap_or(&vendored);
+	let protocol_raw = fs::read_to_string(input).map_err(|e| match args.input {
+		Some(_) => format!("could not read {}: {e}", input.display()),
+		None => format!("no protocol.json for version `{}` at {} ({e}), run with --fetch to download it", args.version, input.display()),
+	})?;
+	let source = match args.input {
+		Some(_) => input.display().to_string(),
+		None => format!("protocol/{}/protocol.json", args.version),
+	};
+
+	let protocol: serde_json::Value = serde_json::from_str(&protocol_raw)?;
+	let overrides: Overrides = serde_json::from_str(&fs::read_to_string(&args.overrides)?)?;
+
+	let generated = generate(&protocol, &overrides)?;
+	let out = render(&generated.packets, &args.version, &source);
+
+	for unresolved in &generated.unresolved {
+		eprintln!("unresolved: {unresolved}");
+	}
+	for unused in &generated.unused_overrides {
+		eprintln!("unused override: {unused}");
+	}
+	eprintln!("{} packets, {} unresolved fields", generated.packets.len(), generated.unresolved.len());
+
+	match &args.out {
+		Some(path) => fs::write(path, out)?,
+		None => print!("{out}"),
+	}
+
+	Ok(())
+}
+
+fn parse_args() -> Result<Args, String> {
+	let mut args = Args {
+		version: DEFAULT_MC_VERSION.to_string(),
+		input: None,
+		fetch: false,
+		overrides: Path::new(env!("CARGO_MANIFEST_DIR")).join("overrides.json"),
+		out: None,
+	};
+
+	let mut raw = std::env::args().skip(1);
+	while let Some(arg) = raw.next() {
+		let mut value = || raw.next().ok_or_else(|| format!("`{arg}` needs a value"));
+		match arg.as_str() {
+			"--version" => args.version = value()?,
+			"--input" => args.input = Some(value()?.into()),
+			"--fetch" => args.fetch = true,
+			"--overrides" => args.overrides = value()?.into(),
+			"--out" => args.out = Some(value()?.into()),
+			_ => return Err(format!("unknown argument `{arg}`, expected --version, --input, --fetch, --overrides or --out")),
+		}
+	}
+
+	Ok(args)
+}
+
+/// The `protocol.json` of a version that is kept in this directory, so that generating does not need the network.
+fn vendored_path(version: &str) -> PathBuf {
+	Path::new(env!("CARGO_MANIFEST_DIR")).join("protocol").join(version).join("protocol.json")
+}
+
+/// Download the `protocol.json` of a version from minecraft-data to `path`.
+fn fetch_protocol(version: &str, path: &Path) -> Result<(), Box<dyn Error>> {
+	let data_paths: serde_json::Value = serde_json::from_str(&fetch(&format!("{RAW_BASE}/dataPaths.json"))?)?;
+	let folder = data_paths
+		.get("pc")
+		.and_then(|pc| pc.get(version))
+		.and_then(|entry| entry.get("protocol"))
+		.and_then(|v| v.as_str())
+		.ok_or_else(|| format!("no `protocol` data path for version `{version}`"))?;
+
+	let body = fetch(&format!("{RAW_BASE}/{folder}/protocol.json"))?;
+	if let Some(parent) = path.parent() {
+		fs::create_dir_all(parent)?;
+	}
+	fs::write(path, body)?;
+	eprintln!("saved {folder}/protocol.json to {}", path.display());
+
+	Ok(())
+}
+
+fn fetch(url: &str) -> Result<String, Box<dyn Error>> {
+	ureq::get(url).call().map_err(|e| format!("fetch of {url} failed ({e})"))?.into_string().map_err(Into::into)
+}
+
+#[cfg(test)]
+mod test {
+	use std::fs;
+	use std::path::Path;
+
+	use crate::codegen::{generate, render, Overrides};
+	use crate::vendored_path;
+
+	/// Every vendored `protocol.json` generates with the default overrides. The unresolved fields are printed, since
+	/// they are expected until sandstone has a type for each of them.
+	#[test]
+	fn vendored_versions_generate() {
+		let overrides: Overrides = serde_json::from_str(include_str!("../overrides.json")).unwrap();
+		let Ok(versions) = fs::read_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("protocol")) else {
+			return;
+		};
+
+		for version in versions {
+			let version = version.unwrap().file_name().into_string().unwrap();
+			let protocol: serde_json::Value = serde_json::from_str(&fs::read_to_string(vendored_path(&version)).unwrap()).unwrap();
+
+			let generated = generate(&protocol, &overrides).unwrap_or_else(|e| panic!("{version}: {e}"));
+			let out = render(&generated.packets, &version, &format!("protocol/{version}/protocol.json"));
+
+			assert!(generated.packets.iter().any(|p| p.name == "Handshaking"), "{version}: {out}");
+			eprintln!("{version}: {} packets, {} unresolved fields: {:#?}", generated.packets.len(), generated.unresolved.len(), generated.unresolved);
+		}
+	}
+}
@@ -8,17 +8,16 @@ use sandstone::network::CraftConnection;
 use sandstone::protocol::packets::packet_definer::PacketState;
 use sandstone::protocol::packets::{HandshakingPacket, LoginAcknowledgedPacket, LoginStartPacket, Packet, ServerboundKnownPacksPacket};
 use sandstone::protocol::serialization::serializer_error::SerializingErr;
-use sandstone::protocol::serialization::serializer_types::{PrefixedArray, PrefixedOptional};
+use sandstone::protocol::serialization::serializer_types::{BoundedString, PrefixedArray, PrefixedOptional};
 use sandstone::protocol::serialization::McDeserialize;
 use sandstone::protocol::serialization::McDeserializer;
 use sandstone::protocol::serialization::McSerialize;
 use sandstone::protocol::serialization::McSerializer;
 use sandstone::protocol::serialization::SerializingResult;
 use sandstone::protocol_types::datatypes::nbt::NbtCompound;
-use sandstone::protocol_types::datatypes::var_types::VarInt;
+use sandstone::protocol_types::datatypes::var_types::{RangedVarInt, VarInt};
 use sandstone::protocol_types::protocol_verison::ProtocolVerison;
 use sandstone_derive::{McDeserialize, McSerialize};
-use serde::ser::SerializeStruct;
 use serde::Serialize;
 use simple_logger::SimpleLogger;
 use std::fs;
@@ -38,16 +37,16 @@ async fn main() {
 
 	let handshake = Packet::Handshaking(HandshakingPacket {
 		protocol_version: VarInt(ProtocolVerison::latest().get_version_number() as i32),
-		server_address: "127.0.0.1".to_string(),
+		server_address: BoundedString::new("127.0.0.1").unwrap(),
 		port: 25565,
-		next_state: VarInt(2),
+		next_state: RangedVarInt::new(2).unwrap(),
 	});
 
 	debug!("Sending handshake packet: {handshake:?}");
 	client.send_packet(handshake).await.unwrap();
 
 	let login_start = Packet::LoginStart(LoginStartPacket {
-		username: "dec4234".to_string(),
+		username: BoundedString::new("dec4234").unwrap(),
 		uuid: Uuid::from_str("ef39c197-3c3d-4776-a226-22096378a966").unwrap(),
 	});
 
@@ -160,44 +159,20 @@ impl<T: McDeserialize + McSerialize> McSerialize for RawPacket<T> {
 	}
 }
 
-#[derive(Debug, McDeserialize, McSerialize)]
+#[derive(Debug, McDeserialize, McSerialize, Serialize)]
 pub struct RegPacket {
 	pub id: String,
 	pub entries: PrefixedArray<Entry>,
 }
 
-impl Serialize for RegPacket {
-	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-	where
-		S: serde::Serializer,
-	{
-		let mut state = serializer.serialize_struct("RegPacket", 2)?;
-		state.serialize_field("id", &self.id)?;
-
-		// Use slice() to get the actual entries for serialization
-		state.serialize_field("entries", self.entries.slice())?;
-
-		state.end()
-	}
-}
-
-#[derive(Debug, McDeserialize, McSerialize)]
+#[derive(Debug, McDeserialize, McSerialize, Serialize)]
 pub struct Entry {
 	pub identifier: String,
+	#[serde(skip_serializing_if = "is_absent")]
 	pub data: PrefixedOptional<NbtCompound>,
 }
 
-impl Serialize for Entry {
-	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-	where
-		S: serde::Serializer,
-	{
-		let mut state = serializer.serialize_struct("Entry", 2)?;
-		state.serialize_field("identifier", &self.identifier)?;
-
-		if self.data.is_present() {
-			state.serialize_field("data", &self.data.value().unwrap())?;
-		}
-		state.end()
-	}
+/// Entries without data are written without the field, rather than as null.
+fn is_absent(data: &PrefixedOptional<NbtCompound>) -> bool {
+	!data.is_present()
 }
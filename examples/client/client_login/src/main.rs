@@ -1,13 +1,13 @@
-use log::{debug, error, trace, LevelFilter};
+use log::{debug, error, LevelFilter};
 use sandstone::network::CraftConnection;
+use sandstone::protocol::configuration::ConfigurationPhase;
 use sandstone::protocol::game::player::ClientStatusAction;
 use sandstone::protocol::packets::packet_definer::PacketState;
 use sandstone::protocol::packets::{
-	AcknowledgeFinishConfigurationPacket, ClientCommandPacket, ConfirmTeleportPacket, HandshakingPacket, LoginAcknowledgedPacket, LoginStartPacket, Packet, ServerboundKeepAlivePacket,
-	ServerboundKnownPacksPacket,
+	ClientCommandPacket, ConfirmTeleportPacket, HandshakingPacket, LoginAcknowledgedPacket, LoginStartPacket, Packet, ServerboundKeepAlivePacket,
 };
-use sandstone::protocol::serialization::serializer_types::PrefixedArray;
-use sandstone::protocol_types::datatypes::var_types::VarInt;
+use sandstone::protocol::serialization::serializer_types::BoundedString;
+use sandstone::protocol_types::datatypes::var_types::{RangedVarInt, VarInt};
 use sandstone::protocol_types::protocol_verison::ProtocolVerison;
 use simple_logger::SimpleLogger;
 use std::str::FromStr;
@@ -26,16 +26,16 @@ async fn main() {
 
 	let handshake = Packet::Handshaking(HandshakingPacket {
 		protocol_version: VarInt(ProtocolVerison::V1_21.get_version_number() as i32),
-		server_address: "127.0.0.1".to_string(),
+		server_address: BoundedString::new("127.0.0.1").unwrap(),
 		port: 25565,
-		next_state: VarInt(2),
+		next_state: RangedVarInt::new(2).unwrap(),
 	});
 
 	debug!("Sending handshake packet: {handshake:?}");
 	client.send_packet(handshake).await.unwrap();
 
 	let login_start = Packet::LoginStart(LoginStartPacket {
-		username: "dec4234".to_string(),
+		username: BoundedString::new("dec4234").unwrap(),
 		uuid: Uuid::from_str("ef39c197-3c3d-4776-a226-22096378a966").unwrap(),
 	});
 
@@ -65,65 +65,9 @@ async fn main() {
 
 	client.change_state(PacketState::CONFIGURATION);
 
-	loop {
-		let packet = client.receive_packet().await.unwrap();
-
-		match packet {
-			Packet::ClientboundPluginMessage(_) => {
-				debug!("Received clientbound plugin message: {packet:?}");
-				continue;
-			}
-			Packet::FeatureFlags(_) => {
-				debug!("Received feature flags: {packet:?}");
-				continue;
-			}
-			Packet::ClientboundKnownPacks(_) => {
-				debug!("Received known packs: {packet:?}");
-				break;
-			}
-			_ => {
-				panic!("Received unexpected packet: {packet:?}");
-			}
-		}
-	}
-
-	let serverbound_known_packs = Packet::ServerboundKnownPacks(ServerboundKnownPacksPacket { entries: PrefixedArray::new(vec![]) });
-
-	debug!("Sending serverbound known packs: {serverbound_known_packs:?}");
-	client.send_packet(serverbound_known_packs).await.unwrap();
-
-	loop {
-		let packet = client.receive_packet().await;
-
-		if let Ok(packet) = packet {
-			match packet {
-				Packet::RegistryData(pack) => {
-					trace!("Received registry data: {pack:?}");
-
-					continue;
-				}
-				Packet::UpdateTags(_) => {
-					trace!("Received update tags");
-					continue;
-				}
-				Packet::FinishConfiguration(fc) => {
-					debug!("Received finish configuration packet: {fc:?}");
-					break;
-				}
-				_ => {
-					panic!("Received unexpected packet: {packet:?}");
-				}
-			}
-		} else {
-			error!("Failed to receive packet: {packet:?}");
-		}
-	}
-
-	let ack_config = Packet::AcknowledgeFinishConfiguration(AcknowledgeFinishConfigurationPacket {});
-	debug!("Sending acknowledge finish configuration packet: {ack_config:?}");
-	client.send_packet(ack_config).await.unwrap();
-
-	client.change_state(PacketState::PLAY);
+	// sends our brand and settings, answers the server's known packs and keep alives until configuration finishes
+	let server_config = ConfigurationPhase::new().run_client(&mut client).await.unwrap();
+	debug!("Configured by server with brand {:?} and {} registries", server_config.brand, server_config.registries.len());
 
 	let packet = client.receive_packet().await.unwrap();
 	match packet {
@@ -280,7 +224,7 @@ async fn main() {
 				debug!("Received set experience: {se:?}");
 				continue;
 			}
-			Packet::ClientboundKeepAlive(ka) => {
+			Packet::KeepAlive(ka) => {
 				debug!("Received clientbound keep alive: {ka:?}");
 
 				let keep_alive = Packet::ServerboundKeepAlive(ServerboundKeepAlivePacket { keep_alive_id: ka.keep_alive_id });
@@ -378,7 +322,7 @@ async fn main() {
 				debug!("Damage event {de:?}");
 				continue;
 			}
-			Packet::DisconnectPlay(dp) => {
+			Packet::Disconnect(dp) => {
 				debug!("Disconnected: {dp:?}");
 				break;
 			}
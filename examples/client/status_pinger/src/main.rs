@@ -1,18 +1,30 @@
 use log::{debug, LevelFilter};
-use sandstone::network::server::server_handler::ClientStatusHandler;
-use sandstone::network::CraftConnection;
-use sandstone::protocol::status::DefaultClientStatusHandler;
+use sandstone::protocol::status::status_pinger::StatusPinger;
 use simple_logger::SimpleLogger;
+use std::time::Duration;
 
+/// Pings a handful of servers concurrently and prints their server list status and latency.
 #[tokio::main]
 async fn main() {
 	SimpleLogger::new().with_level(LevelFilter::Debug).init().unwrap();
 	debug!("Starting client");
 
-	let mut client = CraftConnection::connect("hypixel.net").await.unwrap();
+	let mut pinger = StatusPinger::new();
+	pinger.set_timeout(Duration::from_secs(3));
+	pinger.set_concurrency(4);
 
-	let response = DefaultClientStatusHandler::handle_status(&mut client).await.unwrap();
+	let results = pinger.scan(["hypixel.net", "mc.hypixel.net", "2b2t.org"]).await;
 
-	println!("Server response: {:?}", response);
-	println!("Number of players online: {}", response.players.online);
+	for (address, result) in results {
+		match result {
+			Ok(status) => {
+				println!("{address}: {}/{} players online, {}ms", status.response.players.online, status.response.players.max, status.latency.as_millis());
+
+				if let Ok(Some(favicon)) = status.favicon() {
+					println!("{address}: favicon is {}x{}", favicon.width(), favicon.height());
+				}
+			}
+			Err(e) => println!("{address}: failed to ping: {e}"),
+		}
+	}
 }
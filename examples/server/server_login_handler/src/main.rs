@@ -6,27 +6,26 @@ use std::sync::Arc;
 use tokio::net::TcpListener;
 
 use sandstone::game::player::PlayerGamemode;
-use sandstone::network::client::client_handlers::{ServerHandshakeHandler, ServerStatusHandler};
+use sandstone::network::client::client_handlers::{ServerHandshakeHandler, ServerLoginHandler, ServerStatusHandler};
 use sandstone::network::network_error::NetworkError;
 use sandstone::network::CraftConnection;
-use sandstone::protocol::game::info::registry::registry_generator;
+use sandstone::protocol::configuration::ConfigurationPhase;
 use sandstone::protocol::game::world::generator::superflat;
+use sandstone::protocol::login::DefaultServerLoginHandler;
 use sandstone::protocol::packets::packet_definer::{PacketDirection, PacketState};
 use sandstone::protocol::packets::packet_parts::player::TeleportFlags;
 use sandstone::protocol::packets::packet_parts::{GameEventType, Tag};
 use sandstone::protocol::packets::{
-	ChunkBatchFinishedPacket, ChunkBatchStartPacket, ChunkDataUpdateLightPacket, ClientboundKeepAlivePacket, ClientboundKnownPacksPacket, FinishConfigurationPacket, GameEventPacket, LoginInfoPacket,
-	LoginSuccessPacket, Packet, SetCenterChunkPacket, SetCompressionPacket, StatusResponsePacket, SyncPlayerPositionPacket, UpdateTagsPacket,
+	ChunkBatchFinishedPacket, ChunkBatchStartPacket, ChunkDataUpdateLightPacket, GameEventPacket, KeepAlivePacket, LoginInfoPacket, Packet, SetCenterChunkPacket, StatusResponsePacket,
+	SyncPlayerPositionPacket,
 };
 use sandstone::protocol::serialization::serializer_types::PrefixedArray;
 use sandstone::protocol::status::status_components::{PlayerSample, StatusResponseSpec};
 use sandstone::protocol::status::{DefaultServerHandshakeHandler, DefaultServerPingHandler, DefaultServerStatusHandler};
-use sandstone::protocol_types::datatypes::internal_types::Mapping;
 use sandstone::protocol_types::datatypes::var_types::VarInt;
 use sandstone::protocol_types::protocol_verison::ProtocolVerison;
 use std::time::Duration;
 use tokio::sync::Mutex;
-use uuid::Uuid;
 
 #[tokio::main]
 async fn main() {
@@ -38,6 +37,15 @@ async fn main() {
 	let mut response = StatusResponseSpec::new(ProtocolVerison::latest(), "&a&lThis is a test description &b§kttt");
 	response.set_player_info(1, 0, vec![PlayerSample::new_random("&6&lTest")]);
 
+	let mut configuration = ConfigurationPhase::new();
+	configuration.add_tags(
+		"minecraft:timeline",
+		vec![Tag {
+			identifier: "minecraft:in_overworld".to_string(),
+			entries: PrefixedArray::new(vec![VarInt(0), VarInt(2)]),
+		}],
+	);
+
 	loop {
 		let (socket, _) = server.accept().await.unwrap();
 
@@ -54,109 +62,24 @@ async fn main() {
 
 		debug!("Beginning login sequence for {client}");
 
-		let login_start = client.receive_packet().await.unwrap();
-		match login_start {
-			Packet::LoginStart(packet) => {
-				debug!("Received login start packet from {client}");
-				debug!("Login start packet: {packet:?}");
-
-				// todo: extract player details like username
-			}
-			_ => {
-				debug!("Expected login start packet, got {login_start:?}");
-				continue;
-			}
-		}
-
-		let set_compression = Packet::SetCompression(SetCompressionPacket::new(VarInt(400)));
-		client.send_packet(set_compression).await.unwrap();
-		client.enable_compression(Some(400));
-		debug!("Sent set compression to {client} and enabled compression");
-
-		let login_success = Packet::LoginSuccess(LoginSuccessPacket::new(Uuid::new_v4(), "TestUser".to_string(), PrefixedArray::new(vec![])));
-		client.send_packet(login_success).await.unwrap();
-
-		let login_ack = client.receive_packet().await.unwrap();
-		match login_ack {
-			Packet::LoginAcknowledged(..) => {
-				debug!("Received login acknowledged packet from {client}");
-				debug!("Login acknowledged packet: {login_ack:?}");
-				client.change_state(PacketState::CONFIGURATION);
-			}
-			_ => {
-				debug!("Expected login acknowledged, got {login_ack:?}");
+		let profile = match DefaultServerLoginHandler::handle_login(&mut client, Some(400)).await {
+			Ok(profile) => profile,
+			Err(e) => {
+				debug!("Login failed for {client}: {e}");
 				continue;
 			}
-		}
-
-		let packs = Packet::ClientboundKnownPacks(ClientboundKnownPacksPacket::new(PrefixedArray::new(vec![])));
-		client.send_packet(packs).await.unwrap();
-
-		debug!("Sent clientbound known packs to {client}");
-
-		loop {
-			let packs = client.receive_packet().await.unwrap();
-			match packs {
-				Packet::ServerboundPluginMessage(..) => {
-					// optional: before known packs
-					debug!("Received plugin message from {client}");
-					debug!("Plugin message: {packs:?}");
-					continue;
-				}
-				Packet::ClientInformation(..) => {
-					// optional: before known packs
-					debug!("Received client information from {client}");
-					debug!("Client information: {packs:?}");
-					continue;
-				}
-				Packet::ServerboundKnownPacks(..) => {
-					debug!("Received serverbound known packs from {client}");
-					debug!("Serverbound known packs: {packs:?}");
-					break;
-				}
-				_ => {
-					debug!("Expected serverbound known packs, got {packs:?}");
-					break;
-				}
-			}
-		}
+		};
 
-		// send all registry packets
-		debug!("Sending registry packets to {client}");
-		for p in registry_generator::default() {
-			client.send_packet(p).await.unwrap();
-		}
-
-		let tags = Packet::UpdateTags(UpdateTagsPacket::new(PrefixedArray::new(vec![Mapping {
-			key: "minecraft:timeline".to_string(),
-			value: PrefixedArray::new(vec![Tag {
-				identifier: "minecraft:in_overworld".to_string(),
-				entries: PrefixedArray::new(vec![VarInt(0), VarInt(2)]),
-			}]),
-		}])));
-		client.send_packet(tags).await.unwrap();
-
-		debug!("Sent update tags to {client}");
-
-		let packet = Packet::FinishConfiguration(FinishConfigurationPacket::new());
-		client.send_packet(packet).await.unwrap();
+		debug!("{} logged in with UUID {}", profile.username, profile.uuid);
 
-		debug!("Sent finish configuration to {client}");
-
-		let ack = client.receive_packet().await.unwrap();
-		match ack {
-			Packet::AcknowledgeFinishConfiguration(..) => {
-				debug!("Received acknowledge finish configuration from {client}");
-				debug!("Acknowledge finish configuration: {ack:?}");
-			}
-			_ => {
-				debug!("Expected acknowledge finish configuration, got {ack:?}");
+		match configuration.run_server(&mut client).await {
+			Ok(details) => debug!("Configured {client}, client brand: {:?}", details.brand),
+			Err(e) => {
+				debug!("Configuration failed for {client}: {e}");
 				continue;
 			}
 		}
 
-		client.change_state(PacketState::PLAY);
-
 		let login = Packet::LoginInfo(LoginInfoPacket::new(
 			9,
 			false,
@@ -252,7 +175,7 @@ async fn main() {
 				tokio::time::sleep(Duration::from_secs(10)).await;
 
 				keep_alive_id += 1;
-				let ka = Packet::ClientboundKeepAlive(ClientboundKeepAlivePacket::new(keep_alive_id));
+				let ka = Packet::KeepAlive(KeepAlivePacket::new(keep_alive_id));
 				if let Err(e) = clone.lock().await.send_packet(ka).await {
 					debug!("Keep-alive send failed, connection closed: {e:?}");
 					break;
@@ -35,7 +35,7 @@ async fn main() {
 		response.set_player_info(1, 0, vec![PlayerSample::new_random("&6&lTest")]);
 
 		let image = image::open("examples/status_handler/src/server-icon.png").unwrap();
-		response.set_favicon_image(image);
+		response.set_favicon_image(image).unwrap();
 
 		DefaultServerHandshakeHandler::handle_handshake(&mut client).await.unwrap();
 		DefaultServerStatusHandler::handle_status(&mut client, StatusResponsePacket::new(response), DefaultServerPingHandler)
@@ -0,0 +1,12 @@
+//! Reads the block section data of a chunk, including the paletted containers inside it.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sandstone::protocol::game::world::chunk::ChunkByteData;
+use sandstone::protocol::serialization::{McDeserialize, McDeserializer};
+
+fuzz_target!(|data: &[u8]| {
+	let mut deserializer = McDeserializer::new(data);
+	let _ = ChunkByteData::mc_deserialize(&mut deserializer);
+});
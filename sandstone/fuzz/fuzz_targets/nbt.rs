@@ -0,0 +1,12 @@
+//! Reads an NBT compound with a root name, as it is stored on disk.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sandstone::protocol::serialization::McDeserializer;
+use sandstone::protocol_types::datatypes::nbt::NbtCompound;
+
+fuzz_target!(|data: &[u8]| {
+	let mut deserializer = McDeserializer::new(data);
+	let _ = NbtCompound::from_root(&mut deserializer);
+});
@@ -0,0 +1,23 @@
+//! Deserializes packets in every state and direction. The first byte of the input picks the state and direction.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sandstone::protocol::packets::packet_definer::{PacketDirection, PacketState};
+use sandstone::protocol::packets::Packet;
+use sandstone::protocol::serialization::{McDeserializer, StateBasedDeserializer};
+
+const STATES: [PacketState; 5] = [PacketState::HANDSHAKING, PacketState::STATUS, PacketState::LOGIN, PacketState::CONFIGURATION, PacketState::PLAY];
+const DIRECTIONS: [PacketDirection; 2] = [PacketDirection::SERVER, PacketDirection::CLIENT];
+
+fuzz_target!(|data: &[u8]| {
+	let Some((&selector, data)) = data.split_first() else {
+		return;
+	};
+
+	let state = STATES[selector as usize % STATES.len()];
+	let direction = DIRECTIONS[(selector as usize / STATES.len()) % DIRECTIONS.len()];
+
+	let mut deserializer = McDeserializer::new(data);
+	let _ = Packet::deserialize_state(&mut deserializer, state, direction);
+});
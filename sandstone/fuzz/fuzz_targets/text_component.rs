@@ -0,0 +1,12 @@
+//! Reads a text component, which is network NBT that can be nested arbitrarily deep.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sandstone::protocol::serialization::{McDeserialize, McDeserializer};
+use sandstone::protocol_types::datatypes::chat::TextComponent;
+
+fuzz_target!(|data: &[u8]| {
+	let mut deserializer = McDeserializer::new(data);
+	let _ = TextComponent::mc_deserialize(&mut deserializer);
+});
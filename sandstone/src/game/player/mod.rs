@@ -5,10 +5,11 @@ use crate::protocol::serialization::McSerialize;
 use crate::protocol::serialization::McSerializer;
 use crate::protocol::serialization::SerializingResult;
 use crate::protocol::testing::McDefault;
-use sandstone_derive::McDefault;
+use sandstone_derive::{McArbitrary, McDefault};
+use serde::Serialize;
 
 /// The Gamemode of a player, which is represented as a byte.
-#[derive(McDefault, Debug, Clone, Hash, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, Hash, PartialEq)]
 #[repr(i8)]
 pub enum PlayerGamemode {
 	UNDEFINED = -1,
@@ -1,3 +1,4 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![forbid(unsafe_code)]
 #![allow(async_fn_in_trait)]
 #![allow(dead_code)]
@@ -10,8 +11,29 @@
 //! See the project on GitHub [dec4234/sandstone](https://www.github.com/dec4234/sandstone)
 //! or crates.io [sandstone](https://crates.io/crates/sandstone) for more info.
 
+//!
+//! ## Features
+//! - `std` - everything outside of the protocol core. Without it, the serialization traits, protocol types,
+//!   NBT, chat components and packets build with `no_std` and `alloc`
+//! - `network` - connections, encryption, compression and the handlers for each connection state
+//! - `dns` - look up the SRV record of a server when connecting without a port
+//! - `favicon` - read and write the server icon of the status response as an image
+//!
+//! All of them are enabled by default.
+
+#[macro_use]
+extern crate alloc;
+
 pub mod game;
+#[cfg(feature = "network")]
 pub mod network;
 pub mod protocol;
 pub mod protocol_types;
 pub mod util;
+
+/// The items of the std prelude that are not in the core prelude, so that modules build the same with and without `std`.
+pub(crate) mod prelude {
+	pub use alloc::boxed::Box;
+	pub use alloc::string::{String, ToString};
+	pub use alloc::vec::Vec;
+}
@@ -3,6 +3,7 @@
 
 use crate::network::network_error::NetworkError;
 use crate::network::CraftConnection;
+use crate::protocol::login::login_components::GameProfile;
 use crate::protocol::packets::StatusResponsePacket;
 
 /// The procedure required to handle a handshake as a server. Check [DefaultHandshakeHandler] for a default implementation.
@@ -26,7 +27,12 @@ pub trait ServerPingHandler {
 	async fn handle_ping(connection: &mut CraftConnection) -> Result<(), NetworkError>;
 }
 
-/// Lists the methods required to handle a login request as a server. Check [DefaultLoginHandler] for a default implementation.
+/// Lists the methods required to handle a login request as a server. Check [DefaultServerLoginHandler] for a default implementation.
+///
+/// This is called after the handshake, while the connection is in the LOGIN state. Implementations should leave the connection
+/// in the CONFIGURATION state once the client has acknowledged the login.
+///
+/// The login procedure can be found [here](https://minecraft.wiki/w/Java_Edition_protocol/FAQ#What's_the_normal_login_sequence_for_a_client?)
 pub trait ServerLoginHandler {
-	fn handle_login(connection: &mut CraftConnection) -> Result<(), NetworkError>;
+	async fn handle_login(connection: &mut CraftConnection, compression_threshold: Option<u32>) -> Result<GameProfile, NetworkError>;
 }
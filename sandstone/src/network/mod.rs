@@ -5,22 +5,31 @@
 //! See the documentation for the [client](client) and [server](server) modules for more information on how to use the network API.
 
 use crate::network::network_error::NetworkError;
+use crate::protocol::login::login_query::LoginQueries;
 use crate::protocol::packets::packet_definer::{PacketDirection, PacketState};
-use crate::protocol::packets::Packet;
+use crate::protocol::packets::packet_versions;
+use crate::protocol::packets::{LoginDisconnectPacket, LoginPluginRequestPacket, Packet};
+use crate::protocol::serialization::dump;
 use crate::protocol::serialization::serializer_error::SerializingErr;
+use crate::protocol::serialization::serializer_types::BoundedString;
 use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, StateBasedDeserializer};
+use crate::protocol_types::datatypes::chat::{JsonTextComponent, TextComponent};
 use crate::protocol_types::datatypes::var_types::VarInt;
 use crate::protocol_types::protocol_verison::ProtocolVerison;
 use flate2::read::ZlibDecoder;
 use flate2::write::ZlibEncoder;
 use flate2::Compression;
+#[cfg(feature = "dns")]
 use hickory_resolver::proto::rr::rdata::SRV;
+#[cfg(feature = "dns")]
 use hickory_resolver::proto::rr::RecordRef;
+#[cfg(feature = "dns")]
 use hickory_resolver::Resolver;
 use log::{debug, error, trace};
 use std::fmt::Display;
 use std::io::{Read, Write};
 use std::net::SocketAddr;
+use std::time::Duration;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
 
@@ -36,6 +45,8 @@ pub const DEFAULT_PORT: u16 = 25565;
 /// The maximum size of a packet in bytes.
 const PACKET_MAX_SIZE: usize = 2097151;
 // max of 3 byte VarInt
+/// The space left in front of a packet for its frame header, the packet length and data length VarInts.
+const FRAME_HEADER_SPACE: usize = 10;
 /// The bit that indicates if a VarInt is continuing into another byte.
 pub(crate) const CONTINUE_BIT: u8 = 0b10000000;
 
@@ -56,6 +67,13 @@ pub struct CraftConnection {
 	pub client_type: ConnectionRole,
 	/// Reusable buffer for packet reads, avoids allocating per packet
 	read_buffer: Vec<u8>,
+	/// Reusable buffers that packets are framed in before they are sent
+	framer: PacketFramer,
+	/// Login plugin queries sent to the client that have not been awaited yet
+	login_queries: LoginQueries,
+	/// Set by [CraftConnection::close], after which no packets are sent or received. A read that was cancelled
+	/// part way through a packet also closes the connection, since the rest of the stream can not be framed.
+	closed: bool,
 }
 
 impl CraftConnection {
@@ -92,6 +110,7 @@ impl CraftConnection {
 	/// actual server lives elsewhere; without this lookup such hostnames connect to the wrong
 	/// endpoint and hang. Returns `None` (so the caller falls back to `host:25565`) on any DNS
 	/// failure or when no record is published.
+	#[cfg(feature = "dns")]
 	async fn resolve_srv(host: &str) -> Option<String> {
 		let resolver = Resolver::builder_tokio().ok()?.build().ok()?;
 		let lookup = resolver.srv_lookup(format!("_minecraft._tcp.{host}.")).await.ok()?;
@@ -102,6 +121,12 @@ impl CraftConnection {
 		Some(format!("{target}:{}", srv.port))
 	}
 
+	/// Without the `dns` feature there is no resolver, so the default port is always used.
+	#[cfg(not(feature = "dns"))]
+	async fn resolve_srv(_host: &str) -> Option<String> {
+		None
+	}
+
 	/// Create a `CraftConnection` from an already-established `TcpStream`. This is for server-side
 	/// connections accepted from a listener; clients should use [`CraftConnection::connect`] so the
 	/// dialed hostname is preserved. Sets the `TcpStream` to use `nodelay`, returning an error if it fails to do so.
@@ -119,48 +144,23 @@ impl CraftConnection {
 			protocol_version: None,
 			client_type,
 			read_buffer: Vec::with_capacity(1024),
+			framer: PacketFramer::new(),
+			login_queries: LoginQueries::new(),
+			closed: false,
 		})
 	}
 
 	/// Send a minecraft packet to the client. This will block until the packet is sent.
+	///
+	/// The packet is framed by a [PacketFramer] that is reused for every packet sent on this connection.
 	pub async fn send_packet(&mut self, packet: Packet) -> Result<(), NetworkError> {
-		let mut serializer = McSerializer::new();
-		packet.mc_serialize(&mut serializer)?;
-		let output = &serializer.output;
+		self.check_open()?;
+		self.record_handshake(&packet)?;
+		trace!("Sending to {self} : {}", packet.to_json());
 
-		trace!("Sending to {self} : {output:?}");
-
-		let mut prefix_deserializer = McDeserializer::new(output);
-		VarInt::mc_deserialize(&mut prefix_deserializer)?;
-		let prefix_len = prefix_deserializer.index;
-		let body = &output[prefix_len..];
-
-		let mut frame = McSerializer::new();
-		if let Some(threshold) = self.compression_threshold {
-			if body.len() >= threshold as usize {
-				let mut enc = ZlibEncoder::new(Vec::new(), Compression::default());
-				enc.write_all(body)?;
-				let compressed = enc.finish()?;
-
-				let mut inner = McSerializer::new();
-				VarInt(body.len() as i32).mc_serialize(&mut inner)?;
-				inner.output.extend_from_slice(&compressed);
-
-				VarInt(inner.output.len() as i32).mc_serialize(&mut frame)?;
-				frame.merge(inner);
-			} else {
-				let mut inner = McSerializer::new();
-				VarInt(0).mc_serialize(&mut inner)?;
-				inner.output.extend_from_slice(body);
-
-				VarInt(inner.output.len() as i32).mc_serialize(&mut frame)?;
-				frame.merge(inner);
-			}
-			trace!("Compressed packet for {self} : {} bytes compressed to {} bytes", body.len(), frame.output.len());
-			self.tcp_stream.write_all(&frame.output).await?;
-		} else {
-			self.tcp_stream.write_all(output).await?;
-		}
+		let (state, version) = (self.packet_state, self.get_protocol_version());
+		let frame = self.framer.frame(&packet, state, version, self.compression_threshold)?;
+		self.tcp_stream.write_all(frame).await?;
 
 		// TODO: encrypt here
 
@@ -190,7 +190,8 @@ impl CraftConnection {
 					return Err(NetworkError::PacketTooLarge);
 				}
 
-				let mut decoder = ZlibDecoder::new(remaining);
+				// never inflate past the length the packet claims, so a small frame can't expand without limit
+				let mut decoder = ZlibDecoder::new(remaining).take(data_length.0 as u64);
 				let mut decompressed = Vec::with_capacity(data_length.0 as usize);
 				decoder.read_to_end(&mut decompressed)?;
 				decompressed
@@ -207,6 +208,8 @@ impl CraftConnection {
 
 	/// Receive a minecraft packet from the client. This will block until a packet is received. This removes data from the TCP buffer
 	pub async fn receive_packet(&mut self) -> Result<Packet, NetworkError> {
+		self.check_open()?;
+
 		// Read VarInt length prefix using a stack array (no heap allocation)
 		let mut varint_buf = [0u8; 3];
 		let mut varint_len = 0usize;
@@ -251,13 +254,21 @@ impl CraftConnection {
 			}
 		}
 
-		trace!("Received from {} : {:?}", self, &self.read_buffer[..total_len]);
-
 		// TODO: decrypt here
 
 		let buffer = self.build_deserializer_buffer(&self.read_buffer[varint_len..total_len])?;
-		let mut deserializer = McDeserializer::new(&buffer);
-		let packet = Packet::deserialize_state(&mut deserializer, self.packet_state, self.client_type)?;
+		let packet = Packet::deserialize_state_version(&mut McDeserializer::new(&buffer), self.packet_state, self.client_type, self.get_protocol_version())
+			.inspect_err(|e| trace!("Could not deserialize a packet from {self} ({e}) : {}", dump::hex_summary(&buffer)))?;
+		trace!("Received from {self} : {}", packet.to_json());
+
+		if let Err(e) = self.record_handshake(&packet) {
+			debug!("Closing the connection to {self}: {e}");
+			if let NetworkError::UnsupportedVersion(version) = e {
+				self.disconnect_outdated(version).await;
+			}
+			self.close().await;
+			return Err(e);
+		}
 
 		Ok(packet)
 	}
@@ -265,6 +276,8 @@ impl CraftConnection {
 	/// Try to receive a packet from the buffer without blocking. This will return 'NoDataReceived'
 	/// if no data is available.
 	pub fn try_receive_packet(&mut self) -> Result<Packet, NetworkError> {
+		self.check_open()?;
+
 		let vari = VarInt::from_tcp_stream(&self.tcp_stream)?;
 		let (var_buf, var_len) = vari.to_byte_array();
 
@@ -279,8 +292,6 @@ impl CraftConnection {
 
 		let length = self.tcp_stream.try_read(&mut buffer[var_len..])?;
 
-		trace!("Received from {} : {:?}", self, &buffer);
-
 		if length == 0 {
 			return Err(NetworkError::NoDataReceived);
 		} else if length == PACKET_MAX_SIZE {
@@ -291,7 +302,10 @@ impl CraftConnection {
 
 		let deser_buf = self.build_deserializer_buffer(&buffer[var_len..])?;
 		let mut deserializer = McDeserializer::new(&deser_buf);
-		let packet = Packet::deserialize_state(&mut deserializer, self.packet_state, PacketDirection::SERVER)?;
+		let packet = Packet::deserialize_state_version(&mut deserializer, self.packet_state, PacketDirection::SERVER, self.get_protocol_version())
+			.inspect_err(|e| trace!("Could not deserialize a packet from {self} ({e}) : {}", dump::hex_summary(&deser_buf)))?;
+		trace!("Received from {self} : {}", packet.to_json());
+		self.record_handshake(&packet)?;
 
 		Ok(packet)
 	}
@@ -314,6 +328,8 @@ impl CraftConnection {
 
 	/// Peek the next packet in the queue without removing it. This will block until a packet is received.
 	pub async fn peek_packet(&mut self) -> Result<Packet, NetworkError> {
+		self.check_open()?;
+
 		// Peek VarInt length using a stack array — we peek incrementally since we
 		// don't know how many bytes the VarInt occupies
 		let mut peek_buf = [0u8; 3];
@@ -356,8 +372,6 @@ impl CraftConnection {
 			}
 		};
 
-		trace!("Peeked from {} : {:?}", self, &buffer);
-
 		if length == 0 {
 			self.close().await;
 			return Err(NetworkError::NoDataReceived);
@@ -369,7 +383,9 @@ impl CraftConnection {
 
 		let deser_buf = self.build_deserializer_buffer(&buffer[varint_len..])?;
 		let mut deserializer = McDeserializer::new(&deser_buf);
-		let packet = Packet::deserialize_state(&mut deserializer, self.packet_state, PacketDirection::SERVER)?;
+		let packet = Packet::deserialize_state_version(&mut deserializer, self.packet_state, PacketDirection::SERVER, self.get_protocol_version())
+			.inspect_err(|e| trace!("Could not deserialize a peeked packet from {self} ({e}) : {}", dump::hex_summary(&deser_buf)))?;
+		trace!("Peeked from {self} : {}", packet.to_json());
 
 		Ok(packet)
 	}
@@ -419,17 +435,243 @@ impl CraftConnection {
 		self.compression_threshold = threshold;
 	}
 
-	/// Shutdown the connection as soon as possible
+	/// Shutdown the connection as soon as possible. Packets can not be sent or received afterwards.
 	pub async fn close(&mut self) -> bool {
 		debug!("Closing connection to {self}");
+		self.closed = true;
 		self.tcp_stream.shutdown().await.is_ok()
 	}
 
+	/// Whether [CraftConnection::close] was called.
+	pub fn is_closed(&self) -> bool {
+		self.closed
+	}
+
+	fn check_open(&self) -> Result<(), NetworkError> {
+		if self.closed {
+			return Err(NetworkError::ConnectionAbortedLocally);
+		}
+
+		Ok(())
+	}
+
+	/// Send a login plugin request to the client and wait for its answer. Returns `None` if the client did not
+	/// understand the request. Only valid for a server in the LOGIN state.
+	///
+	/// Fails with [NetworkError::TimedOut] if the client does not answer within the login query timeout,
+	/// see [CraftConnection::set_login_query_timeout]. The connection is closed when that happens.
+	pub async fn login_query(&mut self, channel: impl Into<String>, data: Vec<u8>) -> Result<Option<Vec<u8>>, NetworkError> {
+		let id = self.send_login_query(channel, data).await?;
+		self.await_login_query(id).await
+	}
+
+	/// Send several login plugin requests at once and wait for all of their answers, which are returned in the
+	/// same order as the requests. The client may answer them in any order.
+	pub async fn login_queries(&mut self, queries: Vec<(String, Vec<u8>)>) -> Result<Vec<Option<Vec<u8>>>, NetworkError> {
+		let mut ids = Vec::with_capacity(queries.len());
+
+		for (channel, data) in queries {
+			ids.push(self.send_login_query(channel, data).await?);
+		}
+
+		let mut answers = Vec::with_capacity(ids.len());
+
+		for id in ids {
+			answers.push(self.await_login_query(id).await?);
+		}
+
+		Ok(answers)
+	}
+
+	/// Send a login plugin request without waiting for the answer, returning the message id to pass to
+	/// [CraftConnection::await_login_query].
+	pub async fn send_login_query(&mut self, channel: impl Into<String>, data: Vec<u8>) -> Result<i32, NetworkError> {
+		if self.packet_state != PacketState::LOGIN || self.client_type != ConnectionRole::SERVER {
+			return Err(NetworkError::InvalidPacketState);
+		}
+
+		let id = self.login_queries.allocate();
+		self.send_packet(Packet::LoginPluginRequest(LoginPluginRequestPacket::new(VarInt(id), BoundedString::new(channel)?, data))).await?;
+
+		Ok(id)
+	}
+
+	/// Wait for the answer to a login plugin request sent with [CraftConnection::send_login_query]. Answers to
+	/// other requests received in the meantime are kept until they are awaited.
+	///
+	/// If the answer does not arrive in time, the connection is closed, since the timeout may have stopped a packet
+	/// part way through being read.
+	pub async fn await_login_query(&mut self, id: i32) -> Result<Option<Vec<u8>>, NetworkError> {
+		let timeout = self.login_queries.timeout;
+
+		let wait = async {
+			loop {
+				if let Some(answer) = self.login_queries.take(id) {
+					return Ok(answer);
+				}
+
+				if !self.login_queries.is_pending(id) {
+					return Err(NetworkError::ExpectedDifferentPacket(format!("No login query with id {id} was sent")));
+				}
+
+				match self.receive_packet().await? {
+					Packet::LoginPluginResponse(response) => {
+						let message_id = response.response.message_id.0;
+
+						if !self.login_queries.answer(response.response) {
+							trace!("Ignoring answer to unknown login query {message_id} from {self}");
+						}
+					}
+					_ => return Err(NetworkError::ExpectedDifferentPacket("Invalid packet received, expected login plugin response".to_string())),
+				}
+			}
+		};
+
+		match tokio::time::timeout(timeout, wait).await {
+			Ok(result) => result,
+			Err(_) => {
+				self.login_queries.forget(id);
+				self.close().await;
+				Err(NetworkError::TimedOut(format!("login query {id} was not answered within {}ms", timeout.as_millis())))
+			}
+		}
+	}
+
+	/// Set the time [CraftConnection::login_query] waits for an answer, [crate::protocol::login::login_query::DEFAULT_LOGIN_QUERY_TIMEOUT] by default.
+	pub fn set_login_query_timeout(&mut self, timeout: Duration) {
+		self.login_queries.timeout = timeout;
+	}
+
 	/// Get the protocol version of this client as a `ProtocolVersion` enum. This will return `None` if the
 	/// handshake has not been performed or if the protocol version number is not known to the library
 	pub fn get_client_version(&self) -> Option<ProtocolVerison> {
 		ProtocolVerison::try_from(self.protocol_version?.0 as i16).ok()
 	}
+
+	/// The protocol version packets are sent and received in. This is the version from the handshake if the
+	/// library supports it, otherwise the latest version, which is what a connection without a handshake uses too.
+	///
+	/// A handshake with a version that is not supported is only accepted for a server list ping, since the status
+	/// packets are the same in every version. See [packet_versions::supports_state].
+	pub fn get_protocol_version(&self) -> ProtocolVerison {
+		self.get_client_version().filter(|v| packet_versions::is_supported(*v)).unwrap_or_else(ProtocolVerison::latest)
+	}
+
+	/// Store the protocol version of a handshake, so the rest of the connection uses the ids of that version.
+	/// Fails with [NetworkError::UnsupportedVersion] if the handshake is for logging in or a transfer, and the
+	/// version can not be used in the LOGIN state.
+	fn record_handshake(&mut self, packet: &Packet) -> Result<(), NetworkError> {
+		if let Packet::Handshaking(handshake) = packet {
+			self.protocol_version = Some(handshake.protocol_version);
+
+			let ping = handshake.next_state.get() == PacketState::STATUS.get_id().unwrap() as i32;
+			let can_login = self.get_client_version().is_some_and(|v| packet_versions::supports_state(v, PacketState::LOGIN));
+
+			if !ping && !can_login {
+				return Err(NetworkError::UnsupportedVersion(handshake.protocol_version.0));
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Tell a client that can not log in with its version why, like the Notchian server does. The client is in
+	/// the LOGIN state after its handshake, and the disconnect packet is the same in every version.
+	async fn disconnect_outdated(&mut self, version: i32) {
+		let latest = ProtocolVerison::latest();
+		let reason = if version < latest.get_version_number() as i32 {
+			format!("Outdated client! Please use {latest}")
+		} else {
+			format!("Outdated server! I'm still on {latest}")
+		};
+
+		let packet = Packet::LoginDisconnect(LoginDisconnectPacket::new(JsonTextComponent::from(TextComponent::from(reason))));
+		let sent = match self.framer.frame(&packet, PacketState::LOGIN, latest, self.compression_threshold) {
+			Ok(frame) => self.tcp_stream.write_all(frame).await.map_err(NetworkError::from),
+			Err(e) => Err(e),
+		};
+
+		if let Err(e) = sent {
+			debug!("Could not send the outdated version disconnect to {self}: {e}");
+		}
+	}
+}
+
+/// Write the VarInts of a frame header so that they end at [FRAME_HEADER_SPACE], right before the packet.
+/// Returns the index the frame starts at.
+fn write_frame_header(buffer: &mut [u8], values: &[i32]) -> usize {
+	let mut start = FRAME_HEADER_SPACE;
+
+	for value in values.iter().rev() {
+		let (bytes, len) = VarInt(*value).to_byte_array();
+		start -= len;
+		buffer[start..start + len].copy_from_slice(&bytes[..len]);
+	}
+
+	start
+}
+
+/// Serializes packets into frames that can be written to a connection, with the length prefix and compression
+/// used by [CraftConnection::send_packet].
+///
+/// The packet is serialized once into a reusable buffer, after space left for the frame header. The header
+/// is filled in right before the packet once its length is known, and compression reads from the same buffer.
+#[derive(Debug, Default)]
+pub struct PacketFramer {
+	/// Reusable buffer that packets are serialized into
+	write_buffer: McSerializer,
+	/// Reusable buffer for compressed packets
+	compress_buffer: Vec<u8>,
+}
+
+impl PacketFramer {
+	pub fn new() -> Self {
+		Self {
+			write_buffer: McSerializer::init_size(1024),
+			compress_buffer: Vec::new(),
+		}
+	}
+
+	/// Frame a packet for the given state and protocol version. Packets at least as long as `compression_threshold`
+	/// are compressed, and `None` disables compression. The frame is valid until the next packet is framed.
+	pub fn frame(&mut self, packet: &Packet, state: PacketState, version: ProtocolVerison, compression_threshold: Option<u32>) -> Result<&[u8], NetworkError> {
+		let buffer = &mut self.write_buffer.output;
+		buffer.clear();
+		buffer.resize(FRAME_HEADER_SPACE, 0);
+		packet.serialize_body_state_version(&mut self.write_buffer, state, version)?;
+
+		let body_len = self.write_buffer.output.len() - FRAME_HEADER_SPACE;
+
+		let frame = match compression_threshold {
+			None => {
+				let start = write_frame_header(&mut self.write_buffer.output, &[body_len as i32]);
+				&self.write_buffer.output[start..]
+			}
+			// a Data Length of 0 marks a packet below the threshold, which is sent uncompressed
+			Some(threshold) if body_len < threshold as usize => {
+				let start = write_frame_header(&mut self.write_buffer.output, &[body_len as i32 + 1, 0]);
+				&self.write_buffer.output[start..]
+			}
+			Some(_) => {
+				let mut compressed = std::mem::take(&mut self.compress_buffer);
+				compressed.clear();
+				compressed.resize(FRAME_HEADER_SPACE, 0);
+
+				let mut enc = ZlibEncoder::new(compressed, Compression::default());
+				enc.write_all(&self.write_buffer.output[FRAME_HEADER_SPACE..])?;
+				self.compress_buffer = enc.finish()?;
+
+				let compressed_len = self.compress_buffer.len() - FRAME_HEADER_SPACE;
+				let data_length_len = VarInt(body_len as i32).to_byte_array().1;
+				let start = write_frame_header(&mut self.compress_buffer, &[(data_length_len + compressed_len) as i32, body_len as i32]);
+
+				trace!("Compressed packet {} : {body_len} bytes compressed to {compressed_len} bytes", packet.name());
+				&self.compress_buffer[start..]
+			}
+		};
+
+		Ok(frame)
+	}
 }
 
 impl Display for CraftConnection {
@@ -439,3 +681,28 @@ impl Display for CraftConnection {
 		write!(f, "CraftConnection: {s}")
 	}
 }
+
+#[cfg(test)]
+mod test {
+	use crate::protocol::packets::packet_definer::PacketState;
+	use crate::protocol::packets::{ClientboundPluginMessagePacket, Packet};
+	use crate::protocol::testing::connection_pair;
+
+	#[tokio::test]
+	async fn test_send_with_and_without_compression() {
+		let (mut client, mut server) = connection_pair(PacketState::PLAY).await;
+
+		let small = Packet::ClientboundPluginMessage(ClientboundPluginMessagePacket::new("sandstone:test".try_into().unwrap(), vec![1, 2, 3]));
+		let large = Packet::ClientboundPluginMessage(ClientboundPluginMessagePacket::new("sandstone:test".try_into().unwrap(), vec![9; 40_000]));
+
+		for threshold in [None, Some(256)] {
+			client.enable_compression(threshold);
+			server.enable_compression(threshold);
+
+			for packet in [&small, &large, &small] {
+				server.send_packet(packet.clone()).await.unwrap();
+				assert_eq!(&client.receive_packet().await.unwrap(), packet);
+			}
+		}
+	}
+}
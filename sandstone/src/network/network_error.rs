@@ -27,6 +27,21 @@ pub enum NetworkError {
 	PacketTooLarge,
 	#[error("Expected different packet: {0}")]
 	ExpectedDifferentPacket(String),
+	/// The other side of the connection disconnected us, with the given reason.
+	#[error("Disconnected: {0}")]
+	Disconnected(String),
+	/// The username provided by a client is not a valid Minecraft username.
+	#[error("Invalid username: {0}")]
+	InvalidUsername(String),
+	/// The operation did not complete within the allotted time.
+	#[error("Timed out: {0}")]
+	TimedOut(String),
+	/// The client declined or failed to load a resource pack that the server requires.
+	#[error("Resource pack rejected: {0}")]
+	ResourcePackRejected(String),
+	/// The handshake is for logging in with a protocol version that the library can not log in with.
+	#[error("Unsupported protocol version: {0}")]
+	UnsupportedVersion(i32),
 	#[error(transparent)]
 	SerializingErr(#[from] SerializingErr),
 	#[error(transparent)]
@@ -44,6 +59,11 @@ impl PartialEq for NetworkError {
 			(NetworkError::InvalidPacketDirection, NetworkError::InvalidPacketDirection) => true,
 			(NetworkError::PacketTooLarge, NetworkError::PacketTooLarge) => true,
 			(NetworkError::ExpectedDifferentPacket(a), NetworkError::ExpectedDifferentPacket(b)) => a == b,
+			(NetworkError::Disconnected(a), NetworkError::Disconnected(b)) => a == b,
+			(NetworkError::InvalidUsername(a), NetworkError::InvalidUsername(b)) => a == b,
+			(NetworkError::TimedOut(a), NetworkError::TimedOut(b)) => a == b,
+			(NetworkError::ResourcePackRejected(a), NetworkError::ResourcePackRejected(b)) => a == b,
+			(NetworkError::UnsupportedVersion(a), NetworkError::UnsupportedVersion(b)) => a == b,
 
 			(NetworkError::SerializingErr(a), NetworkError::SerializingErr(b)) => a == b,
 			(NetworkError::IOError(a), NetworkError::IOError(b)) => a.to_string() == b.to_string(),
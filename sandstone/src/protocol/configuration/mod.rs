@@ -0,0 +1,397 @@
+//! Drives the CONFIGURATION phase for both the server and the client.
+//!
+//! After login (or after `StartConfiguration` during PLAY) the server sends its brand, the enabled feature
+//! flags, the resource packs it knows, the registries and the tags before finishing the configuration. The
+//! client responds with its own brand, its settings and the packs it shares with the server.
+//!
+//! [ConfigurationPhase] holds what is sent by either side. The server runs it with [ConfigurationPhase::run_server]
+//! and the client with [ConfigurationPhase::run_client]. Both leave the connection in the PLAY state.
+//!
+//! https://minecraft.wiki/w/Java_Edition_protocol/FAQ#What's_the_normal_login_sequence_for_a_client?
+
+use log::{debug, trace};
+
+use crate::network::network_error::NetworkError;
+use crate::network::CraftConnection;
+use crate::protocol::game::info::registry::known_packs::omit_known_entries;
+use crate::protocol::game::info::registry::registry_generator;
+use crate::protocol::game::info::registry::RegistryDataPacketInternal;
+use crate::protocol::packets::packet_definer::PacketState;
+use crate::protocol::packets::packet_parts::{ResourcePackEntry, Tag};
+use crate::protocol::packets::{
+	AcknowledgeConfigurationPacket, AcknowledgeFinishConfigurationPacket, ClientInformationPacket, ClientboundKnownPacksPacket, ClientboundPluginMessagePacket, FeatureFlagsPacket,
+	FinishConfigurationPacket, Packet, PongPacket, RegistryDataPacket, ServerboundKeepAlivePacket, ServerboundKnownPacksPacket, ServerboundPluginMessagePacket,
+	StartConfigurationPacket, UpdateTagsPacket,
+};
+use crate::protocol::plugin::{Brand, PluginChannel};
+use crate::protocol::serialization::serializer_types::{BoundedString, PrefixedArray};
+use crate::protocol_types::datatypes::internal_types::Mapping;
+use crate::protocol_types::datatypes::var_types::RangedVarInt;
+
+/// The brand sent by default.
+pub const DEFAULT_BRAND: &str = "sandstone";
+/// The feature flag enabled on every vanilla server.
+pub const VANILLA_FEATURE_FLAG: &str = "minecraft:vanilla";
+
+/// Everything the server learned about the client during configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientConfiguration {
+	/// The settings of the client, such as locale and view distance. The client should always send this, but
+	/// it is not required to.
+	pub information: Option<ClientInformationPacket>,
+	/// The brand of the client, such as "vanilla" or "fabric".
+	pub brand: Option<String>,
+	/// The resource packs the client reported sharing with the server.
+	pub known_packs: Vec<ResourcePackEntry>,
+}
+
+/// Everything the client learned about the server during configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerConfiguration {
+	/// The brand of the server, such as "vanilla" or "Paper".
+	pub brand: Option<String>,
+	pub feature_flags: Vec<String>,
+	/// The resource packs offered by the server.
+	pub known_packs: Vec<ResourcePackEntry>,
+	/// The registries as received, so entries omitted because of a shared pack have no body.
+	pub registries: Vec<RegistryDataPacketInternal>,
+	pub tags: Vec<Mapping<PrefixedArray<Tag>>>,
+}
+
+/// Describes what is sent during the configuration phase. Configure it once with the setters and reuse it
+/// for every connection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigurationPhase {
+	brand: String,
+	feature_flags: Vec<String>,
+	known_packs: Vec<ResourcePackEntry>,
+	registries: Vec<RegistryDataPacketInternal>,
+	tags: Vec<Mapping<PrefixedArray<Tag>>>,
+	client_information: ClientInformationPacket,
+}
+
+impl ConfigurationPhase {
+	/// Create a configuration with the [DEFAULT_BRAND], the vanilla feature flag, no known packs, no tags and
+	/// the registries from [registry_generator::default_registries].
+	pub fn new() -> Self {
+		Self {
+			brand: DEFAULT_BRAND.to_string(),
+			feature_flags: vec![VANILLA_FEATURE_FLAG.to_string()],
+			known_packs: vec![],
+			registries: registry_generator::default_registries(),
+			tags: vec![],
+			client_information: default_client_information(),
+		}
+	}
+
+	/// Set the brand sent on the [Brand] channel.
+	pub fn set_brand<S: Into<String>>(&mut self, brand: S) {
+		self.brand = brand.into();
+	}
+
+	/// Set the feature flags sent by the server, such as `minecraft:vanilla` or `minecraft:trade_rebalance`.
+	pub fn set_feature_flags(&mut self, flags: Vec<String>) {
+		self.feature_flags = flags;
+	}
+
+	/// Set the resource packs this side knows. The server offers these to the client, while the client
+	/// responds with the ones it shares with the server. When both sides share `minecraft:core`, registry
+	/// entries that match the embedded data are sent without their body.
+	///
+	/// The embedded data is not vanilla's, see [crate::protocol::game::info::registry::known_packs], so only add
+	/// [core_pack](crate::protocol::game::info::registry::known_packs::core_pack) when the other side is known to
+	/// use the same data, like another sandstone client or server.
+	pub fn set_known_packs(&mut self, packs: Vec<ResourcePackEntry>) {
+		self.known_packs = packs;
+	}
+
+	/// Replace the registries sent by the server.
+	pub fn set_registries(&mut self, registries: Vec<RegistryDataPacketInternal>) {
+		self.registries = registries;
+	}
+
+	/// Add the tags for a registry, such as `minecraft:block`. Tags are sent together in one `UpdateTags` packet.
+	pub fn add_tags<S: Into<String>>(&mut self, registry: S, tags: Vec<Tag>) {
+		self.tags.push(Mapping {
+			key: registry.into(),
+			value: PrefixedArray::new(tags),
+		});
+	}
+
+	/// Set the client settings sent by the client.
+	pub fn set_client_information(&mut self, information: ClientInformationPacket) {
+		self.client_information = information;
+	}
+
+	pub fn get_brand(&self) -> &str {
+		&self.brand
+	}
+
+	pub fn get_known_packs(&self) -> &[ResourcePackEntry] {
+		&self.known_packs
+	}
+
+	pub fn get_registries(&self) -> &[RegistryDataPacketInternal] {
+		&self.registries
+	}
+
+	/// Run the configuration phase as the server. The connection must be in the CONFIGURATION state, or in the
+	/// PLAY state in which case the client is first sent back to configuration with `StartConfiguration`.
+	///
+	/// Returns the details the client sent, and leaves the connection in the PLAY state.
+	pub async fn run_server(&self, connection: &mut CraftConnection) -> Result<ClientConfiguration, NetworkError> {
+		if connection.packet_state == PacketState::PLAY {
+			Self::reenter_server(connection).await?;
+		}
+
+		if connection.packet_state != PacketState::CONFIGURATION {
+			return Err(NetworkError::InvalidPacketState);
+		}
+
+		debug!("Configuring {}", connection);
+
+		let mut client = ClientConfiguration {
+			information: None,
+			brand: None,
+			known_packs: vec![],
+		};
+
+		connection.send_packet(Packet::ClientboundPluginMessage(ClientboundPluginMessagePacket::new(BoundedString::new(Brand::CHANNEL)?, self.brand_payload()?))).await?;
+		connection.send_packet(Packet::FeatureFlags(FeatureFlagsPacket::new(self.feature_flags.clone()))).await?;
+		connection.send_packet(Packet::ClientboundKnownPacks(ClientboundKnownPacksPacket::new(PrefixedArray::new(self.known_packs.clone())))).await?;
+
+		loop {
+			match connection.receive_packet().await? {
+				Packet::ServerboundKnownPacks(packs) => {
+					client.known_packs = packs.entries.vec;
+					break;
+				}
+				other => record_client_packet(&mut client, other)?,
+			}
+		}
+
+		trace!("Client {} shares {} known packs", connection, client.known_packs.len());
+
+		// only packs that were offered count, the client could claim to know anything
+		let shared: Vec<ResourcePackEntry> = client.known_packs.iter().filter(|p| self.known_packs.contains(p)).cloned().collect();
+
+		for registry in &self.registries {
+			connection.send_packet(Packet::RegistryData(RegistryDataPacket::new(omit_known_entries(registry, &shared)))).await?;
+		}
+
+		if !self.tags.is_empty() {
+			connection.send_packet(Packet::UpdateTags(UpdateTagsPacket::new(PrefixedArray::new(self.tags.clone())))).await?;
+		}
+
+		connection.send_packet(Packet::FinishConfiguration(FinishConfigurationPacket::new())).await?;
+
+		loop {
+			match connection.receive_packet().await? {
+				Packet::AcknowledgeFinishConfiguration(_) => break,
+				other => record_client_packet(&mut client, other)?,
+			}
+		}
+
+		connection.change_state(PacketState::PLAY);
+
+		debug!("Finished configuring {}", connection);
+
+		Ok(client)
+	}
+
+	/// Run the configuration phase as the client. The connection must be in the CONFIGURATION state, or in the
+	/// PLAY state after `StartConfiguration` has been received, in which case it is acknowledged first.
+	///
+	/// Keep alives and pings from the server are answered. Registry entries the server sent without a body
+	/// are filled in from the embedded vanilla data. Returns what the server sent, and leaves the connection
+	/// in the PLAY state.
+	pub async fn run_client(&self, connection: &mut CraftConnection) -> Result<ServerConfiguration, NetworkError> {
+		if connection.packet_state == PacketState::PLAY {
+			connection.send_packet(Packet::AcknowledgeConfiguration(AcknowledgeConfigurationPacket::new())).await?;
+			connection.change_state(PacketState::CONFIGURATION);
+		}
+
+		if connection.packet_state != PacketState::CONFIGURATION {
+			return Err(NetworkError::InvalidPacketState);
+		}
+
+		debug!("Configuring with {}", connection);
+
+		let mut server = ServerConfiguration {
+			brand: None,
+			feature_flags: vec![],
+			known_packs: vec![],
+			registries: vec![],
+			tags: vec![],
+		};
+
+		connection.send_packet(Packet::ServerboundPluginMessage(ServerboundPluginMessagePacket::new(BoundedString::new(Brand::CHANNEL)?, self.brand_payload()?))).await?;
+		connection.send_packet(Packet::ClientInformation(self.client_information.clone())).await?;
+
+		loop {
+			match connection.receive_packet().await? {
+				Packet::ClientboundPluginMessage(message) => {
+					if message.channel == Brand::CHANNEL {
+						server.brand = Some(Brand::from_payload(&message.data)?.brand);
+					}
+				}
+				Packet::FeatureFlags(flags) => server.feature_flags = flags.flags,
+				Packet::ClientboundKnownPacks(packs) => {
+					server.known_packs = packs.entries.vec;
+
+					let shared: Vec<ResourcePackEntry> = server.known_packs.iter().filter(|p| self.known_packs.contains(p)).cloned().collect();
+					connection.send_packet(Packet::ServerboundKnownPacks(ServerboundKnownPacksPacket::new(PrefixedArray::new(shared)))).await?;
+				}
+				Packet::RegistryData(registry) => server.registries.push(registry.packet),
+				Packet::UpdateTags(tags) => server.tags.extend(tags.tags.vec),
+				Packet::KeepAlive(keep_alive) => {
+					connection.send_packet(Packet::ServerboundKeepAlive(ServerboundKeepAlivePacket::new(keep_alive.keep_alive_id))).await?;
+				}
+				Packet::Ping(ping) => {
+					connection.send_packet(Packet::Pong(PongPacket::new(ping.id))).await?;
+				}
+				Packet::Disconnect(disconnect) => {
+					return Err(NetworkError::Disconnected(format!("{:?}", disconnect.reason)));
+				}
+				Packet::FinishConfiguration(_) => break,
+				other => trace!("Ignoring {:?} during configuration with {}", other.packet_id(), connection),
+			}
+		}
+
+		connection.send_packet(Packet::AcknowledgeFinishConfiguration(AcknowledgeFinishConfigurationPacket::new())).await?;
+		connection.change_state(PacketState::PLAY);
+
+		debug!("Finished configuring with {}", connection);
+
+		Ok(server)
+	}
+
+	fn brand_payload(&self) -> Result<Vec<u8>, NetworkError> {
+		let brand = Brand { brand: self.brand.clone() };
+		Ok(brand.to_payload()?)
+	}
+
+	/// Send a client in the PLAY state back to configuration and wait for it to acknowledge.
+	async fn reenter_server(connection: &mut CraftConnection) -> Result<(), NetworkError> {
+		connection.send_packet(Packet::StartConfiguration(StartConfigurationPacket::new())).await?;
+
+		// the client may still send play packets until it has processed StartConfiguration
+		loop {
+			match connection.receive_packet().await? {
+				Packet::AcknowledgeConfiguration(_) => break,
+				other => trace!("Ignoring {:?} while waiting for configuration acknowledgement from {}", other.packet_id(), connection),
+			}
+		}
+
+		connection.change_state(PacketState::CONFIGURATION);
+
+		Ok(())
+	}
+}
+
+impl Default for ConfigurationPhase {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// The client settings used by the Notchian client on a fresh install.
+fn default_client_information() -> ClientInformationPacket {
+	ClientInformationPacket::new(BoundedString { string: "en_us".to_string() }, 10, RangedVarInt(0), true, 0x7F, RangedVarInt(1), false, true, RangedVarInt(0))
+}
+
+/// Record the optional packets a client may send at any point during configuration.
+fn record_client_packet(client: &mut ClientConfiguration, packet: Packet) -> Result<(), NetworkError> {
+	match packet {
+		Packet::ClientInformation(information) => client.information = Some(information),
+		Packet::ServerboundPluginMessage(message) => {
+			if message.channel == Brand::CHANNEL {
+				client.brand = Some(Brand::from_payload(&message.data)?.brand);
+			}
+		}
+		Packet::ServerboundKeepAlive(_) | Packet::Pong(_) => {}
+		other => trace!("Ignoring {:?} during configuration", other.packet_id()),
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod test {
+	use crate::protocol::configuration::ConfigurationPhase;
+	use crate::protocol::game::info::registry::known_packs::omit_known_entries;
+	use crate::protocol::packets::packet_definer::PacketState;
+	use crate::protocol::packets::packet_parts::{ResourcePackEntry, Tag};
+	use crate::protocol::serialization::serializer_types::PrefixedArray;
+	use crate::protocol::testing::connection_pair;
+	use crate::protocol_types::datatypes::var_types::VarInt;
+
+	fn pack(id: &str) -> ResourcePackEntry {
+		ResourcePackEntry {
+			namespace: "minecraft".to_string(),
+			id: id.to_string(),
+			version: "1.21.11".to_string(),
+		}
+	}
+
+	#[tokio::test]
+	async fn test_configuration_round_trip() {
+		let (mut client, mut server) = connection_pair(PacketState::CONFIGURATION).await;
+
+		let mut server_phase = ConfigurationPhase::new();
+		// the embedded registries are not vanilla's, so the core pack is not claimed unless it is set
+		assert!(server_phase.get_known_packs().is_empty());
+		server_phase.set_brand("test-server");
+		server_phase.set_known_packs(vec![pack("core"), pack("extra")]);
+		server_phase.add_tags(
+			"minecraft:timeline",
+			vec![Tag {
+				identifier: "minecraft:in_overworld".to_string(),
+				entries: PrefixedArray::new(vec![VarInt(0)]),
+			}],
+		);
+
+		let mut client_phase = ConfigurationPhase::new();
+		client_phase.set_brand("test-client");
+		client_phase.set_known_packs(vec![pack("core")]);
+
+		let expected_registries = server_phase.get_registries().to_vec();
+		let handle = tokio::spawn(async move { server_phase.run_server(&mut server).await.map(|c| (c, server.packet_state)) });
+
+		let from_server = client_phase.run_client(&mut client).await.unwrap();
+		let (from_client, server_state) = handle.await.unwrap().unwrap();
+
+		assert_eq!(from_server.brand, Some("test-server".to_string()));
+		assert_eq!(from_server.feature_flags, vec!["minecraft:vanilla".to_string()]);
+		assert_eq!(from_server.known_packs, vec![pack("core"), pack("extra")]);
+		// both sides share the core pack, so the bodies of the embedded entries were omitted
+		let shared = [pack("core")];
+		assert_eq!(from_server.registries, expected_registries.iter().map(|r| omit_known_entries(r, &shared)).collect::<Vec<_>>());
+		assert!(from_server.registries.iter().flat_map(|r| &r.entries).all(|e| e.data.is_none()));
+		assert_eq!(from_server.tags.len(), 1);
+
+		assert_eq!(from_client.brand, Some("test-client".to_string()));
+		assert_eq!(from_client.known_packs, vec![pack("core")]);
+		assert_eq!(from_client.information.unwrap().locale, "en_us");
+
+		assert_eq!(client.packet_state, PacketState::PLAY);
+		assert_eq!(server_state, PacketState::PLAY);
+	}
+
+	#[tokio::test]
+	async fn test_reenter_from_play() {
+		let (mut client, mut server) = connection_pair(PacketState::PLAY).await;
+
+		let handle = tokio::spawn(async move { ConfigurationPhase::new().run_server(&mut server).await });
+
+		match client.receive_packet().await.unwrap() {
+			crate::protocol::packets::Packet::StartConfiguration(_) => {}
+			p => panic!("Expected start configuration, got {p:?}"),
+		}
+
+		ConfigurationPhase::new().run_client(&mut client).await.unwrap();
+		handle.await.unwrap().unwrap();
+
+		assert_eq!(client.packet_state, PacketState::PLAY);
+	}
+}
@@ -4,16 +4,17 @@ use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize,
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::game_types::Position;
 use crate::protocol_types::datatypes::var_types::VarInt;
-use sandstone_derive::VarIntEnum;
+use sandstone_derive::{McArbitrary, VarIntEnum};
+use serde::Serialize;
 
-#[derive(VarIntEnum, Debug, Copy, Clone, PartialEq)]
+#[derive(VarIntEnum, McArbitrary, Serialize, Debug, Copy, Clone, PartialEq)]
 #[repr(i32)]
 pub enum VibrationSource {
 	Block(Position) = 0,
 	Entity(VarInt, f32) = 1,
 }
 
-#[derive(VarIntEnum, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 #[repr(i32)]
 pub enum Particle {
 	AngryVillager = 0,
@@ -1,18 +1,21 @@
+use crate::prelude::*;
 use crate::protocol::serialization::serializer_error::SerializingErr;
 use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, SerializingResult};
 use crate::protocol::testing::McDefault;
-use sandstone_derive::{McDefault, McDeserialize, McSerialize};
+use crate::protocol_types::datatypes::var_types::VarInt;
+use sandstone_derive::{McArbitrary, McDefault, McDeserialize, McSerialize};
+use serde::Serialize;
 
 /// [Doc link](https://minecraft.wiki/w/Java_Edition_protocol/Packets#Sound_Event)
-#[derive(McDefault, McSerialize, McDeserialize, Debug, PartialOrd, PartialEq, Clone)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, PartialOrd, PartialEq, Clone)]
 pub struct SoundEvent {
 	pub name: String,
 	pub has_fixed_range: bool,
-	#[mc(deserialize_if = has_fixed_range)]
+	#[mc(include_if = has_fixed_range)]
 	pub fixed_range: Option<f32>,
 }
 
-#[derive(McDefault, Debug, PartialOrd, PartialEq, Clone)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, PartialOrd, PartialEq, Clone)]
 pub enum SoundCategory {
 	Master,
 	Music,
@@ -40,7 +43,7 @@ impl McSerialize for SoundCategory {
 			SoundCategory::Ambient => 8,
 			SoundCategory::Voice => 9,
 		};
-		value.mc_serialize(serializer)
+		VarInt(value).mc_serialize(serializer)
 	}
 }
 
@@ -49,7 +52,7 @@ impl McDeserialize for SoundCategory {
 	where
 		Self: Sized,
 	{
-		let id = u8::mc_deserialize(deserializer)?;
+		let id = VarInt::mc_deserialize(deserializer)?.0;
 		match id {
 			0 => Ok(SoundCategory::Master),
 			1 => Ok(SoundCategory::Music),
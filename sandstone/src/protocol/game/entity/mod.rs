@@ -1,17 +1,23 @@
+use crate::prelude::*;
 use crate::protocol::game::effects::particle::Particle;
 use crate::protocol::game::player::inventory::slotdata::SlotData;
 use crate::protocol::packets::packet_parts::item::IdOrPaintingVariant;
 use crate::protocol::serialization::serializer_error::SerializingErr;
 use crate::protocol::serialization::serializer_types::{PrefixedArray, PrefixedOptional};
 use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, SerializingResult};
+#[cfg(any(test, feature = "proptest"))]
+use crate::protocol::testing::arbitrary::McArbitrary;
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::chat::TextComponent;
 use crate::protocol_types::datatypes::game_types::Position;
 use crate::protocol_types::datatypes::var_types::{VarInt, VarLong};
-use sandstone_derive::{McDefault, McDeserialize, McSerialize, VarIntEnum};
+use sandstone_derive::{McArbitrary, McDefault, McDeserialize, McSerialize, VarIntEnum};
+use serde::Serialize;
+#[cfg(any(test, feature = "proptest"))]
+use proptest::prelude::{BoxedStrategy, Strategy};
 use uuid::Uuid;
 
-#[derive(VarIntEnum, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 #[repr(i32)]
 pub enum EntityMetadataValue {
 	Byte(u8) = 0,
@@ -62,13 +68,13 @@ impl McDefault for EntityMetadataValue {
 }
 
 /// The dimension/position pair carried by [EntityMetadataValue::OptionalGlobalPosition].
-#[derive(McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McSerialize, McDeserialize, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub struct GlobalPosition {
 	pub dimension: String,
 	pub position: Position,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct EntityMetadataEntry {
 	pub index: u8,
 	pub value: EntityMetadataValue,
@@ -83,7 +89,20 @@ impl McDefault for EntityMetadataEntry {
 	}
 }
 
-#[derive(McDefault, Debug, Clone, PartialEq)]
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for EntityMetadataEntry {
+	/// An index of 0xFF ends the metadata, so it is never generated.
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		(0..0xFFu8, EntityMetadataValue::mc_arbitrary())
+			.prop_map(|(index, value)| Self {
+				index,
+				value,
+			})
+			.boxed()
+	}
+}
+
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub struct EntityMetadata {
 	pub entries: Vec<EntityMetadataEntry>,
 }
@@ -122,14 +141,14 @@ impl McDeserialize for EntityMetadata {
 	}
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct ProfileProperty {
 	pub name: String,
 	pub value: String,
 	pub signature: PrefixedOptional<String>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct ResolvableProfile {
 	pub name: PrefixedOptional<String>,
 	pub uuid: PrefixedOptional<Uuid>,
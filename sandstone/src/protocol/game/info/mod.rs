@@ -1,3 +1,4 @@
 pub mod registry;
 pub mod stats;
+#[cfg(feature = "std")]
 pub mod content;
\ No newline at end of file
@@ -0,0 +1,134 @@
+//! Decide when registry entries may be sent without their body.
+//!
+//! During configuration the server lists the data packs it knows in `ClientboundKnownPacks`, and the client
+//! responds with the ones it has locally. If both sides share the `minecraft:core` pack for the same version,
+//! the server may omit the NBT body of any vanilla registry entry that is unchanged, and the client fills it
+//! in from its own copy of the vanilla data.
+//!
+//! The embedded data used here is [registry_generator::default_registries], which is sandstone's own minimal set of
+//! registries and not the full vanilla data. A vanilla server omits entries that are not part of it, and a vanilla
+//! client fills in omitted entries with bodies that may differ from it. So the core pack is not known by default,
+//! and should only be shared between peers that both use this data.
+//!
+//! Only the server side is handled here. Entries received without a body are kept that way, since the library
+//! does not have the vanilla data to fill them in.
+//!
+//! https://minecraft.wiki/w/Java_Edition_protocol/Registry_data
+
+use std::collections::HashMap;
+use std::sync::LazyLock;
+
+use crate::protocol::game::info::registry::{registry_generator, RegistryDataPacketInternal, RegistryEntry, RegistryType};
+use crate::protocol::packets::packet_parts::ResourcePackEntry;
+use crate::protocol_types::protocol_verison::ProtocolVerison;
+
+/// The namespace of the vanilla data pack.
+pub const CORE_PACK_NAMESPACE: &str = "minecraft";
+/// The id of the vanilla data pack.
+pub const CORE_PACK_ID: &str = "core";
+
+/// The embedded registry entries keyed by (registry id, entry id).
+static EMBEDDED_ENTRIES: LazyLock<HashMap<(String, String), RegistryType>> = LazyLock::new(|| {
+	registry_generator::default_registries()
+		.into_iter()
+		.flat_map(|registry| {
+			let registry_id = registry.registry_id;
+			registry.entries.into_iter().filter_map(move |entry| Some(((registry_id.clone(), entry.id), entry.data?)))
+		})
+		.collect()
+});
+
+/// The `minecraft:core` pack for the latest supported version, as sent by a vanilla server.
+pub fn core_pack() -> ResourcePackEntry {
+	ResourcePackEntry {
+		namespace: CORE_PACK_NAMESPACE.to_string(),
+		id: CORE_PACK_ID.to_string(),
+		version: ProtocolVerison::latest().get_fancy_name(),
+	}
+}
+
+/// Whether the shared packs include the `minecraft:core` pack with the version of the embedded data.
+/// A core pack for any other version can not be used, since its entries may differ.
+pub fn shares_core_pack(shared_packs: &[ResourcePackEntry]) -> bool {
+	shared_packs.contains(&core_pack())
+}
+
+/// Look up the embedded body of a registry entry, such as `minecraft:dimension_type` / `minecraft:overworld`.
+fn embedded_entry(registry_id: &str, entry_id: &str) -> Option<&'static RegistryType> {
+	EMBEDDED_ENTRIES.get(&(registry_id.to_string(), entry_id.to_string()))
+}
+
+/// Prepare a registry to be sent to a client that shares `shared_packs` with the server. Entries are sent
+/// without a body only if the core pack is shared and the body is identical to the embedded data,
+/// so custom or modified entries are always sent in full.
+pub fn omit_known_entries(registry: &RegistryDataPacketInternal, shared_packs: &[ResourcePackEntry]) -> RegistryDataPacketInternal {
+	if !shares_core_pack(shared_packs) {
+		return registry.clone();
+	}
+
+	let entries = registry
+		.entries
+		.iter()
+		.map(|entry| match &entry.data {
+			Some(data) if embedded_entry(&registry.registry_id, &entry.id) == Some(data) => RegistryEntry::new(entry.id.clone(), None),
+			_ => entry.clone(),
+		})
+		.collect();
+
+	RegistryDataPacketInternal {
+		registry_id: registry.registry_id.clone(),
+		num_entries: registry.num_entries,
+		entries,
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::protocol::game::info::registry::known_packs::{core_pack, omit_known_entries, shares_core_pack};
+	use crate::protocol::game::info::registry::{DimensionType, RegistryDataPacketInternal, RegistryEntry, RegistryType};
+	use crate::protocol::packets::packet_parts::ResourcePackEntry;
+
+	fn dimension_registry(entries: Vec<RegistryEntry>) -> RegistryDataPacketInternal {
+		RegistryDataPacketInternal {
+			registry_id: "minecraft:dimension_type".to_string(),
+			num_entries: (entries.len() as i32).into(),
+			entries,
+		}
+	}
+
+	#[test]
+	fn test_shares_core_pack() {
+		let mut outdated = core_pack();
+		outdated.version = "1.20.4".to_string();
+
+		assert!(shares_core_pack(&[core_pack()]));
+		assert!(!shares_core_pack(&[outdated]));
+		assert!(!shares_core_pack(&[]));
+	}
+
+	#[test]
+	fn test_omit_only_unchanged_vanilla_entries() {
+		let modified = DimensionType {
+			height: 512,
+			..Default::default()
+		};
+
+		let registry = dimension_registry(vec![
+			RegistryEntry::new("minecraft:overworld".to_string(), Some(RegistryType::DimensionType(DimensionType::default()))),
+			RegistryEntry::new("custom:tall".to_string(), Some(RegistryType::DimensionType(modified))),
+		]);
+
+		let omitted = omit_known_entries(&registry, &[core_pack()]);
+		assert_eq!(omitted.entries[0], RegistryEntry::new("minecraft:overworld".to_string(), None));
+		assert!(!omitted.entries[0].is_present);
+		assert_eq!(omitted.entries[1], registry.entries[1]);
+
+		// without the core pack nothing may be omitted
+		let unknown = ResourcePackEntry {
+			namespace: "custom".to_string(),
+			id: "pack".to_string(),
+			version: "1".to_string(),
+		};
+		assert_eq!(omit_known_entries(&registry, &[unknown]), registry);
+	}
+}
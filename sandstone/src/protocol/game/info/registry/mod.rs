@@ -9,6 +9,7 @@
 //!
 //! https://minecraft.wiki/w/Java_Edition_protocol/Registry_data
 
+use crate::prelude::*;
 use crate::protocol::game::info::registry::registry_components::{CatSounds, ChickenSounds, EnchantmentCost, ExitAction, MonsterSpawnLightLevel, NbtTranslateColor, PigSounds, WolfVariantAssets};
 use crate::protocol::serialization::serializer_error::SerializingErr;
 use crate::protocol::serialization::McDeserialize;
@@ -16,19 +17,26 @@ use crate::protocol::serialization::McDeserializer;
 use crate::protocol::serialization::McSerialize;
 use crate::protocol::serialization::McSerializer;
 use crate::protocol::serialization::SerializingResult;
+#[cfg(any(test, feature = "proptest"))]
+use crate::protocol::testing::arbitrary::{McArbitrary, MAX_ARBITRARY_ITEMS};
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::nbt::nbt_error::NbtError;
 use crate::protocol_types::datatypes::nbt::NbtCompound;
 use crate::protocol_types::datatypes::nbt::NbtTag;
 use crate::protocol_types::datatypes::var_types::VarInt;
 use sandstone_derive::AsNbt;
-use sandstone_derive::{FromNbt, McDefault, McSerialize};
+use sandstone_derive::{FromNbt, McArbitrary, McDefault, McSerialize};
+use serde::Serialize;
+#[cfg(any(test, feature = "proptest"))]
+use proptest::prelude::{BoxedStrategy, Strategy};
 
+#[cfg(feature = "std")]
+pub mod known_packs;
 pub mod registry_components;
 pub mod registry_default;
 pub mod registry_generator;
 
-#[derive(McDefault, Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct RegistryDataPacketInternal {
 	/// The registry type this data is for, e.g. "minecraft:dimension_type"
 	pub registry_id: String,
@@ -36,6 +44,46 @@ pub struct RegistryDataPacketInternal {
 	pub entries: Vec<RegistryEntry>,
 }
 
+impl McDefault for RegistryDataPacketInternal {
+	/// Entries of the default registry type, with the registry id and entry count to match them.
+	fn mc_default() -> Self {
+		let entries: Vec<RegistryEntry> = McDefault::mc_default();
+
+		Self {
+			registry_id: RegistryType::mc_default().registry_id().to_string(),
+			num_entries: VarInt(entries.len() as i32),
+			entries,
+		}
+	}
+}
+
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for RegistryDataPacketInternal {
+	/// Every entry is of the same registry type, with the entry count to match them.
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		let entries = proptest::collection::vec(RegistryEntry::mc_arbitrary(), 0..=MAX_ARBITRARY_ITEMS);
+
+		(RegistryType::mc_arbitrary(), entries)
+			.prop_map(|(first, entries)| {
+				let registry_id = first.registry_id();
+				let entries: Vec<RegistryEntry> = entries
+					.into_iter()
+					.map(|entry| match entry.data {
+						Some(data) if data.registry_id() != registry_id => RegistryEntry::new(entry.id, Some(first.clone())),
+						_ => entry,
+					})
+					.collect();
+
+				Self {
+					registry_id: registry_id.to_string(),
+					num_entries: VarInt(entries.len() as i32),
+					entries,
+				}
+			})
+			.boxed()
+	}
+}
+
 impl McSerialize for RegistryDataPacketInternal {
 	fn mc_serialize(&'_ self, serializer: &mut McSerializer) -> SerializingResult<'_, ()> {
 		self.registry_id.mc_serialize(serializer)?;
@@ -54,14 +102,17 @@ impl McDeserialize for RegistryDataPacketInternal {
 		let id = String::mc_deserialize(deserializer)?;
 		let num_entries = VarInt::mc_deserialize(deserializer)?;
 
-		let mut entries = Vec::with_capacity(num_entries.0 as usize);
+		let mut entries = Vec::with_capacity(deserializer.capacity_for(num_entries.0)?);
 
-		for _ in 0..num_entries.0 {
-			// We need to deserialize each entry, but we don't know the type yet.
-			// So we will deserialize it as a RegistryEntry and then convert it to the correct type.
-			let entry = RegistryEntry::mc_deserialize(deserializer, id.clone())?;
-			entries.push(entry);
-		}
+		deserializer.read_field("entries", |deserializer| {
+			for i in 0..num_entries.0 as usize {
+				// We need to deserialize each entry, but we don't know the type yet.
+				// So we will deserialize it as a RegistryEntry and then convert it to the correct type.
+				let entry = deserializer.read_item(i, |deserializer| RegistryEntry::mc_deserialize(deserializer, id.clone()))?;
+				entries.push(entry);
+			}
+			Ok(())
+		})?;
 
 		Ok(Self {
 			registry_id: id,
@@ -71,7 +122,7 @@ impl McDeserialize for RegistryDataPacketInternal {
 	}
 }
 
-#[derive(McDefault, McSerialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, Serialize, McSerialize, Debug, Clone, PartialEq)]
 pub struct RegistryEntry {
 	/// The ID of the registry entry, e.g. "minecraft:overworld"
 	pub id: String,
@@ -109,6 +160,15 @@ impl RegistryEntry {
 	}
 }
 
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for RegistryEntry {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		(String::mc_arbitrary(), proptest::option::of(RegistryType::mc_arbitrary()))
+			.prop_map(|(id, data)| RegistryEntry::new(id, data))
+			.boxed()
+	}
+}
+
 /// Define a registry data group sent by a RegistryDataPacket.
 #[macro_export]
 macro_rules! registry_entry {
@@ -121,7 +181,7 @@ macro_rules! registry_entry {
 	) => {
 		$(
 			/// Automatically generated registry entry body packet.
-			#[derive(McDefault, Debug, Clone, PartialEq, FromNbt, AsNbt)]
+			#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, FromNbt, AsNbt)]
 			pub struct $lib_name {
 				$(
 					pub $field_name: $field_type,
@@ -154,12 +214,19 @@ macro_rules! registry_entry {
 			}
 		)*
 
-		#[derive(McDefault, Debug, Clone, PartialEq)]
+		#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 		pub enum RegistryType {
 			$($lib_name($lib_name)),*
 		}
 
 		impl RegistryType {
+			/// The id of the registry this entry belongs to, such as "minecraft:dimension_type".
+			pub fn registry_id(&self) -> &'static str {
+				match self {
+					$(RegistryType::$lib_name(_) => $mc_name),*
+				}
+			}
+
 			/// Deserialize the registry type according to the given registry type string. Such as "minecraft:dimension_type".
 			pub fn deserialize<'a>(deserializer: &'a mut McDeserializer, registry_type: String) -> SerializingResult<'a, Self> {
 				match registry_type.as_str() {
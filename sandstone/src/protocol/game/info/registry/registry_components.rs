@@ -1,18 +1,23 @@
 //! Complex and nested registry components. Like biome effects, music data, etc.
 
+use crate::prelude::*;
 use crate::protocol::game::info::registry::McDeserialize;
 use crate::protocol::game::info::registry::McDeserializer;
 use crate::protocol::game::info::registry::McSerialize;
 use crate::protocol::game::info::registry::McSerializer;
 use crate::protocol::game::info::registry::SerializingErr;
 use crate::protocol::game::info::registry::SerializingResult;
+#[cfg(any(test, feature = "proptest"))]
+use crate::protocol::testing::arbitrary::McArbitrary;
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::nbt::nbt_error::NbtError;
 use crate::protocol_types::datatypes::nbt::{NbtCompound, NbtTag};
-use sandstone_derive::{AsNbt, FromNbt, McDefault, McDeserialize};
+use sandstone_derive::{AsNbt, FromNbt, McArbitrary, McDefault, McDeserialize};
+#[cfg(any(test, feature = "proptest"))]
+use proptest::prelude::{any, prop_oneof, BoxedStrategy, Strategy};
 use serde::{Deserialize, Serialize};
 
-#[derive(McDefault, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
+#[derive(McDefault, McArbitrary, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
 pub struct PigSounds {
 	pub ambient_sound: String,
 	pub death_sound: String,
@@ -21,7 +26,7 @@ pub struct PigSounds {
 	pub eat_sound: String,
 }
 
-#[derive(McDefault, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
+#[derive(McDefault, McArbitrary, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
 pub struct CatSounds {
 	pub hurt_sound: String,
 	pub purr_sound: String,
@@ -34,7 +39,7 @@ pub struct CatSounds {
 	pub stray_ambient_sound: String,
 }
 
-#[derive(McDefault, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
+#[derive(McDefault, McArbitrary, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
 pub struct ChickenSounds {
 	pub ambient_sound: String,
 	pub death_sound: String,
@@ -43,7 +48,7 @@ pub struct ChickenSounds {
 }
 
 /// Used for some sections of registry components such as painting_variant
-#[derive(McDefault, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
+#[derive(McDefault, McArbitrary, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
 pub struct NbtTranslateColor {
 	pub color: Option<String>,
 	pub translate: String,
@@ -56,7 +61,7 @@ impl From<NbtTag> for Option<NbtTranslateColor> {
 }
 
 /// Used for "minecraft:worldgen/biome" registry component
-#[derive(McDefault, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
+#[derive(McDefault, McArbitrary, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
 pub struct BiomeEffects {
 	pub fog_color: i32,
 	pub foliage_color: i32,
@@ -69,7 +74,7 @@ pub struct BiomeEffects {
 	pub water_fog_color: i32,
 }
 
-#[derive(McDefault, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
+#[derive(McDefault, McArbitrary, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
 pub struct BiomeMood {
 	pub block_search_extent: i32,
 	pub offset: f64,
@@ -77,13 +82,13 @@ pub struct BiomeMood {
 	pub tick_delay: i32,
 }
 
-#[derive(McDefault, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
+#[derive(McDefault, McArbitrary, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
 pub struct BiomeMusicData {
 	pub data: BiomeMusic,
 	pub weight: i32,
 }
 
-#[derive(McDefault, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
+#[derive(McDefault, McArbitrary, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
 pub struct BiomeMusic {
 	pub max_delay: i32,
 	pub min_delay: i32,
@@ -91,25 +96,25 @@ pub struct BiomeMusic {
 	pub sound: String,
 }
 
-#[derive(McDefault, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
+#[derive(McDefault, McArbitrary, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
 pub struct ChatTypePart {
 	pub translation_key: String,
 	pub parameters: Vec<String>,
 }
 
-#[derive(McDefault, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
+#[derive(McDefault, McArbitrary, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
 pub struct ExitAction {
 	pub label: NbtTranslateColor,
 	pub width: i32,
 }
 
-#[derive(McDefault, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
+#[derive(McDefault, McArbitrary, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
 pub struct EnchantmentCost {
 	pub per_level_above_first: i32,
 	pub base: i32,
 }
 
-#[derive(McDefault, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
+#[derive(McDefault, McArbitrary, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
 pub struct Effects {
 	#[nbt(rename = "minecraft:attributes")]
 	pub attributes: Vec<EnchantmentAttribute>,
@@ -119,7 +124,7 @@ pub struct Effects {
 	pub post_attack: Vec<EffectsAttribute>,
 }
 
-#[derive(McDefault, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
+#[derive(McDefault, McArbitrary, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
 pub struct EnchantmentAttribute {
 	pub amount: AttributeModifier,
 	pub attribute: String,
@@ -127,7 +132,7 @@ pub struct EnchantmentAttribute {
 	pub operation: String,
 }
 
-#[derive(McDefault, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
+#[derive(McDefault, McArbitrary, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
 pub struct AttributeModifierValue {
 	pub min_duration: Option<f32>,
 	pub max_amplifier: Option<f32>,
@@ -139,7 +144,7 @@ pub struct AttributeModifierValue {
 	//pub value: Option<AttributeModifier>,
 }
 
-#[derive(McDefault, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
+#[derive(McDefault, McArbitrary, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
 pub struct AttributeModifier {
 	pub base: f32,
 	pub per_level_above_first: f32,
@@ -147,7 +152,7 @@ pub struct AttributeModifier {
 	pub typ: String,
 }
 
-#[derive(McDefault, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
+#[derive(McDefault, McArbitrary, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
 pub struct EffectsAttribute {
 	pub affected: Option<String>,
 	pub effect: AttributeModifierValue,
@@ -155,21 +160,21 @@ pub struct EffectsAttribute {
 	pub requirements: EffectRequirements,
 }
 
-#[derive(McDefault, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
+#[derive(McDefault, McArbitrary, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
 pub struct EffectRequirements {
 	pub condition: String,
 	pub entity: String,
 	pub predicate: EffectPredicate,
 }
 
-#[derive(McDefault, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
+#[derive(McDefault, McArbitrary, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
 pub struct EffectPredicate {
 	#[nbt(rename = "type")]
 	pub typ: String,
 	pub tags: Vec<DamageTag>,
 }
 
-#[derive(McDefault, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
+#[derive(McDefault, McArbitrary, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
 pub struct DamageTag {
 	pub expected: bool,
 	pub id: String,
@@ -177,7 +182,7 @@ pub struct DamageTag {
 
 /// Monster spawn light level can either be a single integer value or a range. This handles this disambiguation basically
 /// like a union.
-#[derive(McDefault, Debug, Clone, PartialEq, McSerialize, McDeserialize)]
+#[derive(McDefault, Serialize, Debug, Clone, PartialEq, McSerialize, McDeserialize)]
 pub struct MonsterSpawnLightLevel {
 	pub isRange: bool,
 	pub level: Option<i32>,
@@ -195,6 +200,26 @@ impl From<MonsterSpawnLightLevel> for NbtCompound {
 	}
 }
 
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for MonsterSpawnLightLevel {
+	/// Either a level or a range, never both.
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		prop_oneof![
+			any::<i32>().prop_map(|level| MonsterSpawnLightLevel {
+				isRange: false,
+				level: Some(level),
+				range: None,
+			}),
+			MonsterSpawnLightLevelRange::mc_arbitrary().prop_map(|range| MonsterSpawnLightLevel {
+				isRange: true,
+				level: None,
+				range: Some(range),
+			}),
+		]
+		.boxed()
+	}
+}
+
 impl From<MonsterSpawnLightLevel> for NbtTag {
 	fn from(value: MonsterSpawnLightLevel) -> Self {
 		if value.isRange {
@@ -225,7 +250,7 @@ impl TryFrom<NbtTag> for MonsterSpawnLightLevel {
 	}
 }
 
-#[derive(McDefault, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
+#[derive(McDefault, McArbitrary, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
 pub struct MonsterSpawnLightLevelRange {
 	pub min_inclusive: i32,
 	pub max_inclusive: i32,
@@ -233,7 +258,7 @@ pub struct MonsterSpawnLightLevelRange {
 	pub typ: String,
 }
 
-#[derive(McDefault, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
+#[derive(McDefault, McArbitrary, Debug, Clone, PartialEq, Deserialize, Serialize, AsNbt, FromNbt, McSerialize, McDeserialize)]
 pub struct WolfVariantAssets {
 	pub angry: String,
 	pub tame: String,
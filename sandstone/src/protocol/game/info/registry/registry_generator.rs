@@ -1,5 +1,6 @@
 //! Generate registry packets necessary for a successful login.
 
+use crate::prelude::*;
 use crate::protocol::game::info::registry::{Biome, CatVariant, ChickenVariant, CowVariant, DamageType, DimensionType, PaintingVariant, RegistryDataPacketInternal, RegistryType, ZombieNautilusVariant};
 use crate::protocol::game::info::registry::{FrogVariant, PigVariant, RegistryEntry, WolfSoundVariant, WolfVariant};
 use crate::protocol::packets::Packet;
@@ -38,6 +39,18 @@ macro_rules! create_registry_packets {
 	};
 }
 
+/// The registries from [default] without the packet wrapper. These are also the embedded data that decides which
+/// entries are sent without a body, see [crate::protocol::game::info::registry::known_packs].
+pub fn default_registries() -> Vec<RegistryDataPacketInternal> {
+	default()
+		.into_iter()
+		.filter_map(|p| match p {
+			Packet::RegistryData(p) => Some(p.packet),
+			_ => None,
+		})
+		.collect()
+}
+
 /// Generate the bare minimum registry packets needed for a successful login.
 ///
 /// Send these packets during the Registry Data phase of the login sequence.
@@ -1,3 +1,4 @@
+use crate::prelude::*;
 use crate::bitflag;
 use crate::protocol::game::player::inventory::slotdata::SlotData;
 use crate::protocol::serialization::serializer_error::SerializingErr;
@@ -9,15 +10,16 @@ use crate::protocol::serialization::{McDeserialize, McSerialize};
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::chat::TextComponent;
 use crate::protocol_types::datatypes::var_types::VarInt;
-use sandstone_derive::{McDefault, VarIntEnum};
+use sandstone_derive::{McArbitrary, McDefault, VarIntEnum};
 use sandstone_derive::{McDeserialize, McSerialize};
-use std::fmt::Debug;
+use serde::Serialize;
+use core::fmt::Debug;
 
 bitflag!(AdvancementFlags: i32 {
 	has_background_texture, show_toast, hidden
 });
 
-#[derive(McSerialize, McDeserialize, McDefault, Debug, Clone, PartialEq)]
+#[derive(McSerialize, McDeserialize, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub struct Advancement {
 	pub parent_id: PrefixedOptional<String>,
 	pub display_data: PrefixedOptional<AdvancementDisplay>,
@@ -25,20 +27,20 @@ pub struct Advancement {
 	pub sends_telemetry_data: bool,
 }
 
-#[derive(McSerialize, McDeserialize, McDefault, Debug, Clone, PartialEq)]
+#[derive(McSerialize, McDeserialize, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub struct AdvancementDisplay {
 	pub title: TextComponent,
 	pub description: TextComponent,
 	pub icon: SlotData,
 	pub frame_type: AdvancementFrameType,
 	pub flags: AdvancementFlags,
-	#[mc(deserialize_if = flags.has_background_texture())]
+	#[mc(include_if = flags.has_background_texture())]
 	pub background_texture: Option<String>,
 	pub x: f32,
 	pub y: f32,
 }
 
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum AdvancementFrameType {
 	Task = 0,
 	Challenge = 1,
@@ -2,16 +2,22 @@
 //!
 //! See <https://minecraft.wiki/w/Dialog#Dialog_format>.
 
+use crate::prelude::*;
 use crate::protocol::serialization::serializer_error::SerializingErr;
 use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, SerializingResult};
+#[cfg(any(test, feature = "proptest"))]
+use crate::protocol::testing::arbitrary::McArbitrary;
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::chat::TextComponent;
 use crate::protocol_types::datatypes::nbt::nbt_error::NbtError;
 use crate::protocol_types::datatypes::nbt::{NbtCompound, NbtTag};
-use sandstone_derive::{AsNbt, FromNbt, McDefault};
+use sandstone_derive::{AsNbt, FromNbt, McArbitrary, McDefault};
+use serde::Serialize;
+#[cfg(any(test, feature = "proptest"))]
+use proptest::prelude::{prop_oneof, BoxedStrategy, Just, Strategy};
 
 /// The root of a dialog. Holds the fields common to every dialog type.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct Dialog {
 	/// Screen title, always visible regardless of dialog type.
 	pub title: TextComponent,
@@ -33,7 +39,7 @@ pub struct Dialog {
 }
 
 /// The dialog kind and its type-specific fields, keyed by the `type` discriminant.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 #[nbt(tag = "type")]
 pub enum DialogType {
 	#[nbt(rename = "minecraft:notice")]
@@ -49,14 +55,14 @@ pub enum DialogType {
 }
 
 /// A dialog with a single action button in the footer.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct NoticeDialog {
 	/// Click action. Defaults to a `gui.ok` button when absent.
 	pub action: Option<ActionButton>,
 }
 
 /// A dialog with two footer buttons for a positive/negative outcome.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct ConfirmationDialog {
 	/// Click action for the positive outcome.
 	pub yes: ActionButton,
@@ -65,7 +71,7 @@ pub struct ConfirmationDialog {
 }
 
 /// A dialog with a scrollable grid of action buttons.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct MultiActionDialog {
 	/// Non-empty list of click actions.
 	pub actions: Vec<ActionButton>,
@@ -76,7 +82,7 @@ pub struct MultiActionDialog {
 }
 
 /// A dialog with a scrollable grid of the server's links.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct ServerLinksDialog {
 	/// Footer/Escape action. The footer is hidden when absent.
 	pub exit_action: Option<ActionButton>,
@@ -87,7 +93,7 @@ pub struct ServerLinksDialog {
 }
 
 /// A dialog with a scrollable grid of buttons leading to other dialogs.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct DialogListDialog {
 	/// The dialogs to list (IDs, tags, or inline definitions).
 	pub dialogs: DialogReferences,
@@ -100,7 +106,7 @@ pub struct DialogListDialog {
 }
 
 /// A button with a label, optional tooltip, and an optional action to run when clicked.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct ActionButton {
 	/// Text shown on the button.
 	pub label: TextComponent,
@@ -115,7 +121,7 @@ pub struct ActionButton {
 /// A click action, keyed by the `type` discriminant. Covers both the static action types (which
 /// mirror text-component click events) and the dynamic ones that build their event from input
 /// control values.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 #[nbt(tag = "type")]
 pub enum Action {
 	#[nbt(rename = "open_url")]
@@ -139,57 +145,57 @@ pub enum Action {
 }
 
 /// Open a URL in the player's browser.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct OpenUrlAction {
 	pub url: String,
 }
 
 /// Run a command as if typed in chat.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct RunCommandAction {
 	pub command: String,
 }
 
 /// Fill the chat box with the given text/command.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct SuggestCommandAction {
 	pub command: String,
 }
 
 /// Change to a page in a written book.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct ChangePageAction {
 	pub page: i32,
 }
 
 /// Copy text to the clipboard.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct CopyToClipboardAction {
 	pub value: String,
 }
 
 /// Open another dialog.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct ShowDialogAction {
 	/// The dialog to show: an ID or an inline definition.
 	pub dialog: DialogReference,
 }
 
 /// Send a custom event to the server (no effect on vanilla servers).
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct CustomAction {
 	pub id: String,
 	pub payload: Option<String>,
 }
 
 /// Build a `run_command` event from a macro template substituted with input values.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct DynamicRunCommandAction {
 	pub template: String,
 }
 
 /// Build a `minecraft:custom` event from all input values, plus optional static additions.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct DynamicCustomAction {
 	/// Static fields added to the payload.
 	pub additions: Option<NbtCompound>,
@@ -199,7 +205,7 @@ pub struct DynamicCustomAction {
 
 /// An input control, keyed by the `type` discriminant. Each variant carries the common `key` +
 /// `label` plus its type-specific fields.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 #[nbt(tag = "type")]
 pub enum InputControl {
 	#[nbt(rename = "minecraft:text")]
@@ -213,7 +219,7 @@ pub enum InputControl {
 }
 
 /// A single-line (or, with `multiline`, multi-line) text input.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct TextInput {
 	/// Identifier used when submitting this input's value.
 	pub key: String,
@@ -232,7 +238,7 @@ pub struct TextInput {
 }
 
 /// Multi-line options for a [`TextInput`].
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct TextInputMultiline {
 	/// Maximum number of lines, if limited.
 	pub max_lines: Option<i32>,
@@ -241,7 +247,7 @@ pub struct TextInputMultiline {
 }
 
 /// A checkbox input.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct BooleanInput {
 	/// Identifier used when submitting this input's value.
 	pub key: String,
@@ -256,7 +262,7 @@ pub struct BooleanInput {
 }
 
 /// A preset option selection.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct SingleOptionInput {
 	/// Identifier used when submitting this input's value.
 	pub key: String,
@@ -271,7 +277,7 @@ pub struct SingleOptionInput {
 }
 
 /// One option of a [`SingleOptionInput`].
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct SingleOption {
 	/// Value sent on submit.
 	pub id: String,
@@ -282,7 +288,7 @@ pub struct SingleOption {
 }
 
 /// A numeric slider input.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct NumberRangeInput {
 	/// Identifier used when submitting this input's value.
 	pub key: String,
@@ -303,7 +309,7 @@ pub struct NumberRangeInput {
 }
 
 /// A body element, keyed by the `type` discriminant.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 #[nbt(tag = "type")]
 pub enum BodyElement {
 	#[nbt(rename = "minecraft:plain_message")]
@@ -313,7 +319,7 @@ pub enum BodyElement {
 }
 
 /// A multiline text label.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct PlainMessageBody {
 	/// The message text.
 	pub contents: TextComponent,
@@ -322,7 +328,7 @@ pub struct PlainMessageBody {
 }
 
 /// An item with an optional description.
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct ItemBody {
 	/// The item stack to display.
 	pub item: DialogItemStack,
@@ -339,7 +345,7 @@ pub struct ItemBody {
 }
 
 /// An item stack as embedded in an [`ItemBody`].
-#[derive(McDefault, Debug, Clone, PartialEq, AsNbt, FromNbt)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, AsNbt, FromNbt)]
 pub struct DialogItemStack {
 	/// Item identifier.
 	pub id: String,
@@ -350,7 +356,7 @@ pub struct DialogItemStack {
 }
 
 /// A dialog's `body`: either a single element or a list of elements.
-#[derive(McDefault, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum DialogBody {
 	Single(Box<BodyElement>),
 	Multiple(Vec<BodyElement>),
@@ -379,7 +385,7 @@ impl TryFrom<NbtTag> for DialogBody {
 
 /// A reference to a single dialog in `show_dialog` / `dialog_list`: either an ID (or `#tag`) string,
 /// or an inline dialog definition.
-#[derive(McDefault, Debug, Clone, PartialEq)]
+#[derive(McDefault, Serialize, Debug, Clone, PartialEq)]
 pub enum DialogReference {
 	Id(String),
 	Inline(Box<Dialog>),
@@ -394,6 +400,19 @@ impl From<DialogReference> for NbtTag {
 	}
 }
 
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for DialogReference {
+	/// Inline dialogs can reference dialogs themselves, so they are only generated once a value is drawn, which
+	/// keeps the strategy from recursing forever.
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		prop_oneof![
+			String::mc_arbitrary().prop_map(DialogReference::Id),
+			Just(()).prop_flat_map(|_| Dialog::mc_arbitrary()).prop_map(|dialog| DialogReference::Inline(Box::new(dialog))),
+		]
+		.boxed()
+	}
+}
+
 impl TryFrom<NbtTag> for DialogReference {
 	type Error = NbtError;
 
@@ -407,7 +426,7 @@ impl TryFrom<NbtTag> for DialogReference {
 }
 
 /// The `dialogs` field of a `dialog_list`: a single reference or a list of references.
-#[derive(McDefault, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum DialogReferences {
 	Single(DialogReference),
 	Multiple(Vec<DialogReference>),
@@ -583,6 +602,31 @@ mod tests {
 		}));
 	}
 
+	/// A list mixing ids and inline dialogs can't be a plain NBT list, so the ids are wrapped in compounds
+	/// and must come back out as ids.
+	#[test]
+	fn dialog_list_with_inline_dialog_round_trips() {
+		let inline = Dialog {
+			title: TextComponent::new("Inline"),
+			external_title: None,
+			body: None,
+			inputs: None,
+			can_close_with_escape: None,
+			pause: None,
+			after_action: None,
+			dialog_type: DialogType::Notice(NoticeDialog {
+				action: Some(button("OK")),
+			}),
+		};
+
+		assert_nbt_round_trip(DialogType::DialogList(DialogListDialog {
+			dialogs: DialogReferences::Multiple(vec![DialogReference::Id("custom:other".to_string()), DialogReference::Inline(Box::new(inline))]),
+			exit_action: None,
+			columns: None,
+			button_width: None,
+		}));
+	}
+
 	/// The `type` discriminant must actually be written to NBT under the `type` key — without it a
 	/// client cannot tell a notice from a confirmation.
 	#[test]
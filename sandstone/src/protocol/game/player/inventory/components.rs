@@ -1,4 +1,5 @@
 ﻿use crate::protocol::game::effects::sound::SoundEvent;
+use crate::prelude::*;
 use crate::protocol::game::entity::ResolvableProfile;
 use crate::protocol::game::player::inventory::slotdata::SlotData;
 use crate::protocol::packets::packet_parts::block::BlockPredicate;
@@ -9,26 +10,31 @@ use crate::protocol::packets::packet_parts::sound::IdOrJukeboxSong;
 use crate::protocol::serialization::serializer_error::SerializingErr;
 use crate::protocol::serialization::serializer_types::{PrefixedArray, PrefixedOptional};
 use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, SerializingResult};
+#[cfg(any(test, feature = "proptest"))]
+use crate::protocol::testing::arbitrary::{registry_id, McArbitrary};
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::chat::TextComponent;
 use crate::protocol_types::datatypes::game_types::Position;
 use crate::protocol_types::datatypes::internal_types::{IDSet, IDorX};
 use crate::protocol_types::datatypes::nbt::{NbtCompound, NbtTag};
 use crate::protocol_types::datatypes::var_types::VarInt;
-use sandstone_derive::{McDefault, McDeserialize, McSerialize, VarIntEnum};
+use sandstone_derive::{McArbitrary, McDefault, McDeserialize, McSerialize, VarIntEnum};
+use serde::Serialize;
+#[cfg(any(test, feature = "proptest"))]
+use proptest::prelude::{any, prop_oneof, BoxedStrategy, Strategy};
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct EnchantmentEntry {
 	pub type_id: VarInt,
 	pub level: VarInt,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct EnchantmentList {
 	pub entries: PrefixedArray<EnchantmentEntry>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct AttributeModifierEntry {
 	pub attribute_id: VarInt,
 	pub modifier_id: String,
@@ -37,12 +43,12 @@ pub struct AttributeModifierEntry {
 	pub slot: VarInt,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct AttributeModifierList {
 	pub entries: PrefixedArray<AttributeModifierEntry>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct CustomModelDataComponent {
 	pub floats: PrefixedArray<f32>,
 	pub flags: PrefixedArray<bool>,
@@ -50,20 +56,20 @@ pub struct CustomModelDataComponent {
 	pub colors: PrefixedArray<i32>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct TooltipDisplayComponent {
 	pub hide_tooltip: bool,
 	pub hidden_components: PrefixedArray<VarInt>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct FoodComponent {
 	pub nutrition: VarInt,
 	pub saturation: f32,
 	pub can_always_eat: bool,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct ConsumableComponent {
 	pub consume_seconds: f32,
 	pub animation: VarInt,
@@ -72,20 +78,20 @@ pub struct ConsumableComponent {
 	pub effects: PrefixedArray<ConsumeEffect>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct UseCooldownComponent {
 	pub seconds: f32,
 	pub cooldown_group: PrefixedOptional<String>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct ToolRule {
 	pub blocks: IDSet,
 	pub speed: PrefixedOptional<f32>,
 	pub correct_drop: PrefixedOptional<bool>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct ToolComponent {
 	pub rules: PrefixedArray<ToolRule>,
 	pub default_speed: f32,
@@ -93,13 +99,13 @@ pub struct ToolComponent {
 	pub can_destroy_in_creative: bool,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct WeaponComponent {
 	pub damage: VarInt,
 	pub disable_blocking_for: f32,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct EquippableComponent {
 	pub slot: VarInt,
 	pub equip_sound: IDorX<SoundEvent>,
@@ -111,7 +117,7 @@ pub struct EquippableComponent {
 	pub damage_on_hurt: bool,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct DamageReduction {
 	pub horizontal_angle: f32,
 	pub typ: PrefixedOptional<IDSet>,
@@ -119,7 +125,7 @@ pub struct DamageReduction {
 	pub factor: f32,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct BlocksAttacksComponent {
 	pub block_delay: f32,
 	pub disable_cooldown_scale: f32,
@@ -132,7 +138,7 @@ pub struct BlocksAttacksComponent {
 	pub disable_sound: PrefixedOptional<IDorX<SoundEvent>>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct PotionContentsComponent {
 	pub potion_id: PrefixedOptional<VarInt>,
 	pub custom_color: PrefixedOptional<i32>,
@@ -140,35 +146,35 @@ pub struct PotionContentsComponent {
 	pub custom_name: String,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct SuspiciousStewEntry {
 	pub type_id: VarInt,
 	pub duration: VarInt,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct SuspiciousStewList {
 	pub entries: PrefixedArray<SuspiciousStewEntry>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct WritableBookPage {
 	pub raw: String,
 	pub filtered: PrefixedOptional<String>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct WritableBookComponent {
 	pub pages: PrefixedArray<WritableBookPage>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct WrittenBookPage {
 	pub raw: TextComponent,
 	pub filtered: PrefixedOptional<TextComponent>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct WrittenBookComponent {
 	pub raw_title: String,
 	pub filtered_title: PrefixedOptional<String>,
@@ -178,25 +184,25 @@ pub struct WrittenBookComponent {
 	pub resolved: bool,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct TrimComponent {
 	pub material: IdOrTrimMaterial,
 	pub pattern: IdOrTrimPattern,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct EntityDataComponent {
 	pub entity_type: VarInt,
 	pub data: NbtCompound,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct BlockEntityDataComponent {
 	pub block_entity_type: VarInt,
 	pub data: NbtCompound,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum ProvidesTrimMaterialComponent {
 	ByName(String),
 	Inline(Box<IdOrTrimMaterial>),
@@ -238,7 +244,7 @@ impl McDefault for ProvidesTrimMaterialComponent {
 	}
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum JukeboxPlayableComponent {
 	ByName(String),
 	Inline(Box<IdOrJukeboxSong>),
@@ -280,7 +286,7 @@ impl McDefault for JukeboxPlayableComponent {
 	}
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct LodestoneTrackerComponent {
 	pub has_global_pos: bool,
 	pub dimension: Option<String>,
@@ -332,35 +338,54 @@ impl McDefault for LodestoneTrackerComponent {
 	}
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for LodestoneTrackerComponent {
+	/// The dimension and position are only present with a global position.
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		(proptest::option::of((String::mc_arbitrary(), Position::mc_arbitrary())), any::<bool>())
+			.prop_map(|(global_pos, tracked)| {
+				let (dimension, position) = global_pos.unzip();
+
+				Self {
+					has_global_pos: dimension.is_some(),
+					dimension,
+					position,
+					tracked,
+				}
+			})
+			.boxed()
+	}
+}
+
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct FireworksComponent {
 	pub flight_duration: VarInt,
 	pub explosions: PrefixedArray<FireworkExplosion>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct BannerLayer {
 	pub pattern: IdOrBannerPattern,
 	pub color: DyeColor,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct BannerPatternsComponent {
 	pub layers: PrefixedArray<BannerLayer>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct BlockStateProperty {
 	pub name: String,
 	pub value: String,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct BlockStateComponent {
 	pub properties: PrefixedArray<BlockStateProperty>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct BeeData {
 	pub entity_type: VarInt,
 	pub data: NbtCompound,
@@ -368,7 +393,7 @@ pub struct BeeData {
 	pub min_ticks_in_hive: VarInt,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum ChickenVariantComponent {
 	ByName(String),
 	Registry(VarInt),
@@ -410,7 +435,7 @@ impl McDefault for ChickenVariantComponent {
 	}
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum PaintingVariantComponent {
 	Registry(VarInt),
 	Inline(Box<PaintingVariant>),
@@ -451,11 +476,22 @@ impl McDefault for PaintingVariantComponent {
 	}
 }
 
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for PaintingVariantComponent {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		prop_oneof![
+			registry_id().prop_map(PaintingVariantComponent::Registry),
+			PaintingVariant::mc_arbitrary().prop_map(|variant| PaintingVariantComponent::Inline(Box::new(variant))),
+		]
+		.boxed()
+	}
+}
+
 /// # Structured Component (Packet Part)
 /// Serializes the enum ID as a VarInt first then the body of the StructuredComponent entry
 ///
 /// https://minecraft.wiki/w/Java_Edition_protocol/Slot_data#Structured_components
-#[derive(VarIntEnum, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 #[repr(i32)]
 pub enum StructuredComponent {
 	CustomData(NbtCompound) = 0,
@@ -1,10 +1,16 @@
+use crate::prelude::*;
 use crate::protocol::game::player::inventory::components::StructuredComponent;
 use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, SerializingResult};
+#[cfg(any(test, feature = "proptest"))]
+use crate::protocol::testing::arbitrary::McArbitrary;
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::var_types::VarInt;
+use serde::Serialize;
+#[cfg(any(test, feature = "proptest"))]
+use proptest::prelude::{prop_oneof, BoxedStrategy, Just, Strategy};
 
 /// https://minecraft.wiki/w/Java_Edition_protocol/Slot_data
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct SlotData {
 	pub item_count: VarInt,
 	pub item_id: Option<VarInt>,
@@ -23,6 +29,34 @@ impl McDefault for SlotData {
 	}
 }
 
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for SlotData {
+	/// Either an empty slot, or an item with components. Components can hold slots themselves, so they are only
+	/// generated once a value is drawn, which keeps the strategy from recursing forever.
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		let components = Just(()).prop_flat_map(|_| Vec::<StructuredComponent>::mc_arbitrary());
+		let item = (1..=i32::MAX, VarInt::mc_arbitrary(), components, Vec::<VarInt>::mc_arbitrary()).prop_map(
+			|(item_count, item_id, components_to_add, components_to_remove)| Self {
+				item_count: VarInt(item_count),
+				item_id: Some(item_id),
+				components_to_add,
+				components_to_remove,
+			},
+		);
+
+		prop_oneof![
+			Just(Self {
+				item_count: VarInt(0),
+				item_id: None,
+				components_to_add: vec![],
+				components_to_remove: vec![],
+			}),
+			item,
+		]
+		.boxed()
+	}
+}
+
 impl McSerialize for SlotData {
 	fn mc_serialize(&self, serializer: &mut McSerializer) -> SerializingResult<()> {
 		self.item_count.mc_serialize(serializer)?;
@@ -61,15 +95,21 @@ impl McDeserialize for SlotData {
 		let num_add = VarInt::mc_deserialize(deserializer)?;
 		let num_remove = VarInt::mc_deserialize(deserializer)?;
 
-		let mut components_to_add = Vec::with_capacity(num_add.0 as usize);
-		for _ in 0..num_add.0 {
-			components_to_add.push(StructuredComponent::mc_deserialize(deserializer)?);
-		}
+		let components_to_add = deserializer.read_field("components_to_add", |deserializer| {
+			let mut components = Vec::with_capacity(deserializer.capacity_for(num_add.0)?);
+			for i in 0..num_add.0 as usize {
+				components.push(deserializer.read_item(i, StructuredComponent::mc_deserialize)?);
+			}
+			Ok(components)
+		})?;
 
-		let mut components_to_remove = Vec::with_capacity(num_remove.0 as usize);
-		for _ in 0..num_remove.0 {
-			components_to_remove.push(VarInt::mc_deserialize(deserializer)?);
-		}
+		let components_to_remove = deserializer.read_field("components_to_remove", |deserializer| {
+			let mut ids = Vec::with_capacity(deserializer.capacity_for(num_remove.0)?);
+			for i in 0..num_remove.0 as usize {
+				ids.push(deserializer.read_item(i, VarInt::mc_deserialize)?);
+			}
+			Ok(ids)
+		})?;
 
 		Ok(Self {
 			item_count,
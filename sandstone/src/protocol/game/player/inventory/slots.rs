@@ -1,3 +1,4 @@
+use crate::prelude::*;
 use crate::protocol::game::player::inventory::components::StructuredComponent;
 use crate::protocol::game::player::inventory::slotdata::SlotData;
 use crate::protocol::serialization::serializer_error::SerializingErr;
@@ -7,14 +8,19 @@ use crate::protocol::serialization::McDeserializer;
 use crate::protocol::serialization::McSerialize;
 use crate::protocol::serialization::McSerializer;
 use crate::protocol::serialization::SerializingResult;
+#[cfg(any(test, feature = "proptest"))]
+use crate::protocol::testing::arbitrary::{McArbitrary, MAX_ARBITRARY_ITEMS};
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::var_types::VarInt;
-use sandstone_derive::{McDefault, McDeserialize, McSerialize, VarIntEnum};
+use sandstone_derive::{McArbitrary, McDefault, McDeserialize, McSerialize, VarIntEnum};
+use serde::Serialize;
+#[cfg(any(test, feature = "proptest"))]
+use proptest::prelude::{prop_oneof, BoxedStrategy, Just, Strategy};
 
 /// Description of a recipe ingredient slot for use for use by the client.
 ///
 /// https://minecraft.wiki/w/Java_Edition_protocol/Recipes#Slot_Display_structure
-#[derive(VarIntEnum, McDefault, Debug, PartialEq, Clone)]
+#[derive(VarIntEnum, McDefault, Serialize, Debug, PartialEq, Clone)]
 #[repr(i32)]
 pub enum SlotDisplay {
 	Empty = 0,
@@ -27,26 +33,64 @@ pub enum SlotDisplay {
 	Composite(Box<CompositeSlotData>) = 7,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, PartialEq, Clone)]
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for SlotDisplay {
+	/// Displays nested a few levels deep, since the trim, remainder and composite displays hold other displays.
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		let leaf = prop_oneof![
+			Just(SlotDisplay::Empty),
+			Just(SlotDisplay::AnyFuel),
+			VarInt::mc_arbitrary().prop_map(SlotDisplay::Item),
+			SlotData::mc_arbitrary().prop_map(SlotDisplay::ItemStack),
+			String::mc_arbitrary().prop_map(SlotDisplay::Tag),
+		];
+
+		leaf.prop_recursive(3, 16, MAX_ARBITRARY_ITEMS as u32, |inner| {
+			prop_oneof![
+				(inner.clone(), inner.clone(), VarInt::mc_arbitrary()).prop_map(|(base, material, pattern)| {
+					SlotDisplay::SmithingingTrim(Box::new(SmithingTrimSlotData {
+						base,
+						material,
+						pattern,
+					}))
+				}),
+				(inner.clone(), inner.clone()).prop_map(|(ingredient, remainder)| {
+					SlotDisplay::WithRemainder(Box::new(WithRemainderSlotData {
+						ingredient,
+						remainder,
+					}))
+				}),
+				proptest::collection::vec(inner, 0..=MAX_ARBITRARY_ITEMS).prop_map(|options| {
+					SlotDisplay::Composite(Box::new(CompositeSlotData {
+						options: PrefixedArray::new(options),
+					}))
+				}),
+			]
+		})
+		.boxed()
+	}
+}
+
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, PartialEq, Clone)]
 pub struct SmithingTrimSlotData {
 	pub base: SlotDisplay,
 	pub material: SlotDisplay,
 	pub pattern: VarInt,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, PartialEq, Clone)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, PartialEq, Clone)]
 pub struct WithRemainderSlotData {
 	pub ingredient: SlotDisplay,
 	pub remainder: SlotDisplay,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, PartialEq, Clone)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, PartialEq, Clone)]
 pub struct CompositeSlotData {
 	pub options: PrefixedArray<SlotDisplay>,
 }
 
 /// https://minecraft.wiki/w/Java_Edition_protocol/Recipes#Recipe_Display
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 #[repr(i32)]
 pub enum RecipeDisplay {
 	CraftingShapeless(CraftingShapelessDisplay) = 0,
@@ -56,14 +100,14 @@ pub enum RecipeDisplay {
 	Smithing(SmithingDisplay) = 4,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct CraftingShapelessDisplay {
 	pub ingredients: PrefixedArray<SlotDisplay>,
 	pub result: SlotDisplay,
 	pub crafting_station: SlotDisplay,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct CraftingShapedDisplay {
 	pub width: VarInt,
 	pub height: VarInt,
@@ -72,7 +116,7 @@ pub struct CraftingShapedDisplay {
 	pub crafting_station: SlotDisplay,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct FurnaceDisplay {
 	pub ingredient: SlotDisplay,
 	pub fuel: SlotDisplay,
@@ -82,14 +126,14 @@ pub struct FurnaceDisplay {
 	pub crafting_station: SlotDisplay,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct StonecutterDisplay {
 	pub input: SlotDisplay,
 	pub result: SlotDisplay,
 	pub crafting_station: SlotDisplay,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct SmithingDisplay {
 	pub template: SlotDisplay,
 	pub base: SlotDisplay,
@@ -100,7 +144,7 @@ pub struct SmithingDisplay {
 
 /// # Changed Slot (Packet Part)
 /// New data for a slot that the client wants to inform the server about.
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct ChangedSlot {
 	pub slot: i16,
 	/// New data for this slot, in the client's opinion. Server verifies this data.
@@ -111,22 +155,22 @@ pub struct ChangedSlot {
 /// Used to communicate slot changes in an inventory.
 ///
 /// https://minecraft.wiki/w/Java_Edition_protocol/Slot_data#Hashed_Format
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct HashedSlot {
 	pub has_item: bool,
-	#[mc(deserialize_if = has_item)]
+	#[mc(include_if = has_item)]
 	pub item_id: Option<VarInt>,
-	#[mc(deserialize_if = has_item)]
+	#[mc(include_if = has_item)]
 	pub item_count: Option<VarInt>,
-	#[mc(deserialize_if = has_item)]
+	#[mc(include_if = has_item)]
 	pub components: Option<PrefixedArray<ComponentHashed>>,
-	#[mc(deserialize_if = has_item)]
+	#[mc(include_if = has_item)]
 	pub components_to_remove: Option<PrefixedArray<StructuredComponent>>
 }
 
 /// # Slot Data Hash (Packet Part)
 /// Used for inventory updates.
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct ComponentHashed {
 	pub component_type: StructuredComponent,
 	/// A CRC32C (note: CRC32C is not the same thing as CRC32) checksum of the component data. Currently undocumented
@@ -136,7 +180,7 @@ pub struct ComponentHashed {
 // TODO: Map valid mode and button combinations?
 /// # Inventory Operation Mode (Packet Part)
 /// Used to determine valid buttons and operations for inventory actions.
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum InventoryOperationMode {
 	Pickup = 0,
 	ShiftClick = 1,
@@ -2,13 +2,14 @@ use crate::protocol::serialization::serializer_error::SerializingErr;
 use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, SerializingResult};
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::var_types::VarInt;
-use sandstone_derive::{McDefault, VarIntEnum};
+use sandstone_derive::{McArbitrary, McDefault, VarIntEnum};
+use serde::Serialize;
 
 pub mod player_action;
 pub mod interface;
 pub mod inventory;
 
-#[derive(McDefault, VarIntEnum, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, VarIntEnum, Debug, Clone, PartialEq)]
 pub enum ClientStatusAction {
 	PerformRespawn = 0,
 	RequestStats = 1,
@@ -17,7 +18,7 @@ pub enum ClientStatusAction {
 /// Data kept after a respawn.
 ///
 /// [Doc link](https://minecraft.wiki/w/Java_Edition_protocol/Packets#Respawn)
-#[derive(McDefault, Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct RespawnKeptData {
 	pub keep_attributes: bool,
 	pub keep_metadata: bool,
@@ -1,16 +1,23 @@
+use crate::prelude::*;
 use crate::protocol::packets::packet_parts::ProtocolPropertyElement;
 use crate::protocol::serialization::serializer_error::SerializingErr;
 use crate::protocol::serialization::serializer_types::{PrefixedArray, PrefixedOptional};
 use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, SerializingResult};
+#[cfg(any(test, feature = "proptest"))]
+use crate::protocol::testing::arbitrary::{McArbitrary, MAX_ARBITRARY_ITEMS};
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::chat::TextComponent;
 use crate::protocol_types::datatypes::var_types::VarInt;
 use crate::util::java::bitfield::BitField;
-use sandstone_derive::{McDefault, McDeserialize, McSerialize};
+use sandstone_derive::{McArbitrary, McDefault, McDeserialize, McSerialize};
+use serde::Serialize;
+#[cfg(any(test, feature = "proptest"))]
+use proptest::prelude::{any, BoxedStrategy, Just, Strategy};
 use uuid::Uuid;
 
 /// Only used for Player Info Updates.
-#[derive(Default, Debug, Clone, PartialEq)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize)]
+#[serde(transparent)]
 pub struct EnumSet {
 	field: BitField<u8>,
 }
@@ -62,13 +69,13 @@ impl McDefault for EnumSet {
 	}
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct AddPlayerData {
 	pub name: String,
 	pub properties: PrefixedArray<ProtocolPropertyElement>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct ChatSessionData {
 	pub session_id: Uuid,
 	pub key_expiry: i64,
@@ -77,7 +84,7 @@ pub struct ChatSessionData {
 }
 
 /// https://minecraft.wiki/w/Java_Edition_protocol/Packets#player-info:player-actions
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct PlayerAction {
 	pub add_player: Option<AddPlayerData>,
 	pub initialize_chat: Option<PrefixedOptional<ChatSessionData>>,
@@ -134,30 +141,62 @@ impl PlayerAction {
 		Ok(())
 	}
 
+	/// Random actions with exactly the fields in `mask` present, see [McArbitrary].
+	#[cfg(any(test, feature = "proptest"))]
+	pub(crate) fn arbitrary_with(mask: u8) -> BoxedStrategy<Self> {
+		fn present<T: McArbitrary + Clone + 'static>(mask: u8, bit: u8) -> BoxedStrategy<Option<T>> {
+			if mask & bit != 0 { T::mc_arbitrary().prop_map(Some).boxed() } else { Just(None).boxed() }
+		}
+
+		(
+			present(mask, 0x01),
+			present(mask, 0x02),
+			present(mask, 0x04),
+			present(mask, 0x08),
+			present(mask, 0x10),
+			present(mask, 0x20),
+			present(mask, 0x40),
+			present(mask, 0x80),
+		)
+			.prop_map(
+				|(add_player, initialize_chat, update_game_mode, update_listed, update_latency, update_display_name, update_list_priority, update_hat)| Self {
+					add_player,
+					initialize_chat,
+					update_game_mode,
+					update_listed,
+					update_latency,
+					update_display_name,
+					update_list_priority,
+					update_hat,
+				},
+			)
+			.boxed()
+	}
+
 	pub fn deserialize_with_mask<'a>(mask: u8, deserializer: &'a mut McDeserializer) -> SerializingResult<'a, Self> {
-		let add_player = if mask & 0x01 != 0 { Some(AddPlayerData::mc_deserialize(deserializer)?) } else { None };
+		let add_player = if mask & 0x01 != 0 { Some(deserializer.read_field("add_player", AddPlayerData::mc_deserialize)?) } else { None };
 
 		let initialize_chat = if mask & 0x02 != 0 {
-			Some(PrefixedOptional::<ChatSessionData>::mc_deserialize(deserializer)?)
+			Some(deserializer.read_field("initialize_chat", PrefixedOptional::<ChatSessionData>::mc_deserialize)?)
 		} else {
 			None
 		};
 
-		let update_game_mode = if mask & 0x04 != 0 { Some(VarInt::mc_deserialize(deserializer)?) } else { None };
+		let update_game_mode = if mask & 0x04 != 0 { Some(deserializer.read_field("update_game_mode", VarInt::mc_deserialize)?) } else { None };
 
-		let update_listed = if mask & 0x08 != 0 { Some(bool::mc_deserialize(deserializer)?) } else { None };
+		let update_listed = if mask & 0x08 != 0 { Some(deserializer.read_field("update_listed", bool::mc_deserialize)?) } else { None };
 
-		let update_latency = if mask & 0x10 != 0 { Some(VarInt::mc_deserialize(deserializer)?) } else { None };
+		let update_latency = if mask & 0x10 != 0 { Some(deserializer.read_field("update_latency", VarInt::mc_deserialize)?) } else { None };
 
 		let update_display_name = if mask & 0x20 != 0 {
-			Some(PrefixedOptional::<TextComponent>::mc_deserialize(deserializer)?)
+			Some(deserializer.read_field("update_display_name", PrefixedOptional::<TextComponent>::mc_deserialize)?)
 		} else {
 			None
 		};
 
-		let update_list_priority = if mask & 0x40 != 0 { Some(VarInt::mc_deserialize(deserializer)?) } else { None };
+		let update_list_priority = if mask & 0x40 != 0 { Some(deserializer.read_field("update_list_priority", VarInt::mc_deserialize)?) } else { None };
 
-		let update_hat = if mask & 0x80 != 0 { Some(bool::mc_deserialize(deserializer)?) } else { None };
+		let update_hat = if mask & 0x80 != 0 { Some(deserializer.read_field("update_hat", bool::mc_deserialize)?) } else { None };
 
 		Ok(Self {
 			add_player,
@@ -187,14 +226,14 @@ impl McDefault for PlayerAction {
 	}
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct PlayerInfoEntry {
 	pub uuid: Uuid,
 	pub actions: PlayerAction,
 }
 
 // https://minecraft.wiki/w/Java_Edition_protocol/Packets#player-info:player-actions
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct PlayerInfoUpdateData {
 	pub actions: EnumSet,
 	pub entries: Vec<PlayerInfoEntry>,
@@ -218,17 +257,19 @@ impl McDeserialize for PlayerInfoUpdateData {
 	where
 		Self: Sized,
 	{
-		let actions = EnumSet::mc_deserialize(deserializer)?;
+		let actions = deserializer.read_field("actions", EnumSet::mc_deserialize)?;
 		let mask = actions.raw();
 		let count = VarInt::mc_deserialize(deserializer)?.0;
-		let mut entries = Vec::with_capacity(count as usize);
-		for _ in 0..count {
-			let uuid = Uuid::mc_deserialize(deserializer)?;
-			let player_actions = PlayerAction::deserialize_with_mask(mask, deserializer)?;
-			entries.push(PlayerInfoEntry {
-				uuid,
-				actions: player_actions,
-			});
+		let mut entries = Vec::with_capacity(deserializer.capacity_for(count)?);
+		// the entries are items of the packet field, like `players[3].actions.add_player`
+		for i in 0..count as usize {
+			let entry = deserializer.read_item(i, |deserializer| {
+				Ok(PlayerInfoEntry {
+					uuid: deserializer.read_field("uuid", Uuid::mc_deserialize)?,
+					actions: deserializer.read_field("actions", |deserializer| PlayerAction::deserialize_with_mask(mask, deserializer))?,
+				})
+			})?;
+			entries.push(entry);
 		}
 		Ok(Self {
 			actions,
@@ -245,3 +286,23 @@ impl McDefault for PlayerInfoUpdateData {
 		}
 	}
 }
+
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for PlayerInfoUpdateData {
+	/// Every entry has the actions in the set, and only those.
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		any::<u8>()
+			.prop_flat_map(|mask| {
+				let entry = (Uuid::mc_arbitrary(), PlayerAction::arbitrary_with(mask)).prop_map(|(uuid, actions)| PlayerInfoEntry {
+					uuid,
+					actions,
+				});
+
+				proptest::collection::vec(entry, 0..=MAX_ARBITRARY_ITEMS).prop_map(move |entries| Self {
+					actions: EnumSet::from_raw(mask),
+					entries,
+				})
+			})
+			.boxed()
+	}
+}
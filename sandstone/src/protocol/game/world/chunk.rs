@@ -2,6 +2,7 @@
 //!
 //! https://minecraft.wiki/w/Java_Edition_protocol/Chunk_format
 
+use crate::prelude::*;
 use crate::protocol::game::world::chunk::PaletteFormatType::{BIOMES, BLOCKS};
 use crate::protocol::serialization::serializer_error::SerializingErr;
 use crate::protocol::serialization::serializer_types::PrefixedArray;
@@ -10,22 +11,27 @@ use crate::protocol::serialization::McDeserializer;
 use crate::protocol::serialization::McSerialize;
 use crate::protocol::serialization::McSerializer;
 use crate::protocol::serialization::SerializingResult;
+#[cfg(any(test, feature = "proptest"))]
+use crate::protocol::testing::arbitrary::{McArbitrary, MAX_ARBITRARY_ITEMS};
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::internal_types::PackedEntries;
 use crate::protocol_types::datatypes::nbt::NbtCompound;
 use crate::protocol_types::datatypes::var_types::VarInt;
 use crate::util::java::bitset::BitSet;
-use sandstone_derive::{McDefault, McDeserialize, McSerialize};
+use sandstone_derive::{McArbitrary, McDefault, McDeserialize, McSerialize};
+use serde::Serialize;
+#[cfg(any(test, feature = "proptest"))]
+use proptest::prelude::{any, prop_oneof, BoxedStrategy, Just, Strategy};
 
 /// Chunk Data field as defined in https://minecraft.wiki/w/Java_Edition_protocol/Packets#Chunk_Data
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct ChunkData {
 	pub heightmaps: PrefixedArray<Heightmap>,
 	pub data: ChunkByteData,
 	pub block_entities: PrefixedArray<BlockEntity>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct LightData {
 	pub sky_light_mask: BitSet,
 	pub block_light_mask: BitSet,
@@ -38,7 +44,7 @@ pub struct LightData {
 /// The length of the inner array is always 2048; There is 1 array for each bit set to true in the block
 /// light mask, starting with the lowest value. Half a byte per light value. Acceptable light values are
 /// 0-15
-#[derive(McDefault, McSerialize, McDeserialize, Default, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Default, Debug, Clone, PartialEq)]
 pub struct LightArray {
 	pub data: PrefixedArray<u8>,
 }
@@ -95,7 +101,7 @@ impl LightArray {
 
 /// An array of 24 chunk sections, containing the block data for a single chunk. This is serialized to/from
 /// a byte array.
-#[derive(McDefault, Debug, Clone, Hash, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, Hash, PartialEq)]
 pub struct ChunkByteData {
 	/// This array is NOT length-prefixed. The number of elements in the array is calculated based on the world's height.
 	/// Sections are sent bottom-to-top. The world height changes based on the dimension.
@@ -104,17 +110,10 @@ pub struct ChunkByteData {
 	pub data: Vec<ChunkSection>,
 }
 
-// convert the section data into a PrefixedArray<u8>
+// the section data is prefixed with its size in bytes
 impl McSerialize for ChunkByteData {
 	fn mc_serialize(&self, serializer: &mut McSerializer) -> SerializingResult<()> {
-		let mut small_serializer = McSerializer::new();
-
-		self.data.mc_serialize(&mut small_serializer)?;
-
-		let prefixed_array = PrefixedArray::new(small_serializer.output);
-		prefixed_array.mc_serialize(serializer)?;
-
-		Ok(())
+		serializer.serialize_length_prefixed(|s| self.data.mc_serialize(s))
 	}
 }
 
@@ -123,9 +122,14 @@ impl McDeserialize for ChunkByteData {
 	where
 		Self: Sized,
 	{
-		let prefixed_array = PrefixedArray::<u8>::mc_deserialize(deserializer)?;
+		let length = VarInt::mc_deserialize(deserializer)?.0;
 
-		let mut small_deserializer = McDeserializer::new(&prefixed_array.vec);
+		if length < 0 {
+			return Err(SerializingErr::OutOfBounds(format!("Negative section data length {}", length)));
+		}
+
+		// parse the sections straight out of the input instead of copying them out first
+		let mut small_deserializer = deserializer.sub_deserializer_length(length as usize)?;
 
 		let data = Vec::mc_deserialize(&mut small_deserializer)?;
 
@@ -135,7 +139,7 @@ impl McDeserialize for ChunkByteData {
 	}
 }
 
-#[derive(McDefault, McSerialize, Debug, Clone, Hash, PartialEq)]
+#[derive(McDefault, Serialize, McSerialize, Debug, Clone, Hash, PartialEq)]
 pub struct ChunkSection {
 	/// Number of non-air blocks present in the chunk section. "Non-air" is defined as any fluid and block other than air, cave air, and void air
 	pub block_count: i16,
@@ -150,9 +154,9 @@ impl McDeserialize for ChunkSection {
 	where
 		Self: Sized,
 	{
-		let block_count = i16::mc_deserialize(deserializer)?;
-		let block_states = PalletedContainer::mc_deserialize(deserializer, 4096, BLOCKS)?;
-		let biomes = PalletedContainer::mc_deserialize(deserializer, 64, BIOMES)?;
+		let block_count = deserializer.read_field("block_count", i16::mc_deserialize)?;
+		let block_states = deserializer.read_field("block_states", |deserializer| PalletedContainer::mc_deserialize(deserializer, 4096, BLOCKS))?;
+		let biomes = deserializer.read_field("biomes", |deserializer| PalletedContainer::mc_deserialize(deserializer, 64, BIOMES))?;
 
 		Ok(Self {
 			block_count,
@@ -162,8 +166,21 @@ impl McDeserialize for ChunkSection {
 	}
 }
 
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for ChunkSection {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		(any::<i16>(), PalletedContainer::arbitrary_with(4096, BLOCKS), PalletedContainer::arbitrary_with(64, BIOMES))
+			.prop_map(|(block_count, block_states, biomes)| Self {
+				block_count,
+				block_states,
+				biomes,
+			})
+			.boxed()
+	}
+}
+
 /// Same as [ChunkSection] but only contains the biome data.
-#[derive(McDefault, McSerialize, Debug, Clone, Hash, PartialEq)]
+#[derive(McDefault, Serialize, McSerialize, Debug, Clone, Hash, PartialEq)]
 pub struct BiomeSection {
 	/// Consists of 64 entries, representing 4×4×4 biome regions in the chunk section
 	pub biomes: PalletedContainer,
@@ -175,31 +192,35 @@ impl McDeserialize for BiomeSection {
 		Self: Sized,
 	{
 		Ok(Self {
-			biomes: PalletedContainer::mc_deserialize(deserializer, 64, BIOMES)?,
+			biomes: deserializer.read_field("biomes", |deserializer| PalletedContainer::mc_deserialize(deserializer, 64, BIOMES))?,
 		})
 	}
 }
 
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for BiomeSection {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		PalletedContainer::arbitrary_with(64, BIOMES)
+			.prop_map(|biomes| Self {
+				biomes,
+			})
+			.boxed()
+	}
+}
+
 /// Same as [ChunkByteData] but the sections contain only the biome data. Used by the Chunk Biomes
 /// packet, which is serialized to/from a length-prefixed byte array.
-#[derive(McDefault, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub struct BiomeByteData {
 	/// This array is NOT length-prefixed. The number of elements matches the number of chunk sections
 	/// in the dimension. Sections are sent bottom-to-top.
 	pub data: Vec<BiomeSection>,
 }
 
-// convert the section data into a PrefixedArray<u8>
+// the section data is prefixed with its size in bytes
 impl McSerialize for BiomeByteData {
 	fn mc_serialize(&self, serializer: &mut McSerializer) -> SerializingResult<()> {
-		let mut small_serializer = McSerializer::new();
-
-		self.data.mc_serialize(&mut small_serializer)?;
-
-		let prefixed_array = PrefixedArray::new(small_serializer.output);
-		prefixed_array.mc_serialize(serializer)?;
-
-		Ok(())
+		serializer.serialize_length_prefixed(|s| self.data.mc_serialize(s))
 	}
 }
 
@@ -208,9 +229,14 @@ impl McDeserialize for BiomeByteData {
 	where
 		Self: Sized,
 	{
-		let prefixed_array = PrefixedArray::<u8>::mc_deserialize(deserializer)?;
+		let length = VarInt::mc_deserialize(deserializer)?.0;
+
+		if length < 0 {
+			return Err(SerializingErr::OutOfBounds(format!("Negative section data length {}", length)));
+		}
 
-		let mut small_deserializer = McDeserializer::new(&prefixed_array.vec);
+		// parse the sections straight out of the input instead of copying them out first
+		let mut small_deserializer = deserializer.sub_deserializer_length(length as usize)?;
 
 		let data = Vec::mc_deserialize(&mut small_deserializer)?;
 
@@ -220,7 +246,7 @@ impl McDeserialize for BiomeByteData {
 	}
 }
 
-#[derive(McDefault, McSerialize, Debug, Clone, Hash, PartialEq)]
+#[derive(McSerialize, Serialize, Debug, Clone, Hash, PartialEq)]
 pub struct PalletedContainer {
 	pub bits_per_entry: u8,
 	pub palette: PalleteFormat,
@@ -279,6 +305,39 @@ impl PalletedContainer {
 		})
 	}
 
+	/// Random containers of any of the three formats, with the bits per entry and the amount of data
+	/// matching `num_entries` and `typ`. A container is only valid for one section type, so
+	/// [ChunkSection] and [BiomeSection] use this instead of a [McArbitrary] implementation.
+	#[cfg(any(test, feature = "proptest"))]
+	pub(crate) fn arbitrary_with(num_entries: u16, typ: PaletteFormatType) -> BoxedStrategy<Self> {
+		let (indirect, direct) = match typ {
+			BLOCKS => (4u8..=8, 15u8),
+			BIOMES => (1u8..=3, 7u8),
+		};
+
+		let data = move |bpe: u8| {
+			let num_i64s = num_entries.div_ceil(entries_per_i64(bpe) as u16) as usize;
+			proptest::collection::vec(PackedEntries::arbitrary_with(bpe), num_i64s)
+		};
+
+		let indirect = (indirect, proptest::collection::vec(VarInt::mc_arbitrary(), 1..=MAX_ARBITRARY_ITEMS))
+			.prop_flat_map(move |(bpe, palette)| (Just(bpe), Just(palette), data(bpe)))
+			.prop_map(|(bits_per_entry, palette, data)| Self {
+				bits_per_entry,
+				palette: PalleteFormat::Indirect(IndirectFormat {
+					palette: PrefixedArray::new(palette),
+				}),
+				data,
+			});
+		let direct = data(direct).prop_map(move |data| Self {
+			bits_per_entry: direct,
+			palette: PalleteFormat::Direct,
+			data,
+		});
+
+		prop_oneof![VarInt::mc_arbitrary().prop_map(Self::single_valued), indirect, direct,].boxed()
+	}
+
 	fn mc_deserialize<'a>(deserializer: &'a mut McDeserializer, num_entries: u16, typ: PaletteFormatType) -> SerializingResult<'a, Self>
 	where
 		Self: Sized,
@@ -289,7 +348,7 @@ impl PalletedContainer {
 		let data = if bpe == 0 {
 			Vec::new()
 		} else {
-			let num_i64s = (num_entries as f32 / entries_per_i64(bpe) as f32).ceil() as u16;
+			let num_i64s = num_entries.div_ceil(entries_per_i64(bpe) as u16);
 			let mut data = Vec::with_capacity(num_i64s as usize);
 			for _ in 0..num_i64s {
 				data.push(PackedEntries::mc_deserialize(deserializer, bpe)?);
@@ -305,14 +364,21 @@ impl PalletedContainer {
 	}
 }
 
+impl McDefault for PalletedContainer {
+	/// A single-valued container, which is valid in both block and biome sections.
+	fn mc_default() -> Self {
+		Self::single_valued(VarInt::mc_default())
+	}
+}
+
 /// Used to determine which palette format to use based on the Bits Per Entry
-#[derive(McDefault, Debug, Clone, Hash, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, Hash, PartialEq)]
 pub enum PaletteFormatType {
 	BLOCKS,
 	BIOMES,
 }
 
-#[derive(McDefault, Debug, Clone, Hash, PartialEq)]
+#[derive(McDefault, Serialize, Debug, Clone, Hash, PartialEq)]
 pub enum PalleteFormat {
 	SingleValued(VarInt),
 	Indirect(IndirectFormat),
@@ -365,12 +431,12 @@ impl PalleteFormat {
 	}
 }
 
-#[derive(McSerialize, McDeserialize, Debug, Clone, Hash, PartialEq)]
+#[derive(McSerialize, Serialize, McDeserialize, Debug, Clone, Hash, PartialEq)]
 pub struct IndirectFormat {
 	pub palette: PrefixedArray<VarInt>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, Hash, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, Hash, PartialEq)]
 pub struct Heightmap {
 	typ: VarInt,
 	data: PrefixedArray<i64>,
@@ -386,7 +452,7 @@ impl Heightmap {
 }
 
 /// A block entity is something like a chest or other block which has NBT.
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct BlockEntity {
 	pub packed_xz: PackedXZ,
 	pub y: i16,
@@ -395,7 +461,7 @@ pub struct BlockEntity {
 }
 
 /// Relative coordinates within a chunk. Each x and z value has valid values 0-15
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct PackedXZ {
 	data: u8,
 }
@@ -433,8 +499,11 @@ fn entries_per_i64(bpe: u8) -> u8 {
 }
 
 #[cfg(test)]
+#[allow(clippy::needless_range_loop, clippy::bool_assert_comparison)]
 mod test {
-	use crate::protocol::game::world::chunk::entries_per_i64;
+	use crate::protocol::game::world::chunk::{entries_per_i64, BiomeSection, ChunkSection};
+	use crate::protocol::testing::arbitrary::{check_round_trip, McArbitrary};
+	use proptest::proptest;
 
 	#[test]
 	fn test_entries_per_i64() {
@@ -499,4 +568,17 @@ mod test {
 
 		assert_eq!(light_array.get(4).is_err(), false);
 	}
+
+	proptest! {
+		/// Block and biome containers of every format, which are only valid inside their sections.
+		#[test]
+		fn chunk_section_round_trip(section in ChunkSection::mc_arbitrary()) {
+			check_round_trip(&section)?;
+		}
+
+		#[test]
+		fn biome_section_round_trip(section in BiomeSection::mc_arbitrary()) {
+			check_round_trip(&section)?;
+		}
+	}
 }
@@ -8,12 +8,14 @@ use crate::protocol::serialization::McSerializer;
 use crate::protocol::serialization::SerializingResult;
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::game_types::Position;
-use sandstone_derive::{McDefault, McDeserialize, McSerialize};
+use sandstone_derive::{McArbitrary, McDefault, McDeserialize, McSerialize};
+use serde::Serialize;
 
 pub mod chunk;
+#[cfg(feature = "std")]
 pub mod generator;
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Copy, Clone)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Copy, Clone)]
 pub struct BlockPos {
 	pub x: i32,
 	pub y: i32,
@@ -40,7 +42,7 @@ impl From<Position> for BlockPos {
 	}
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Copy, Clone)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Copy, Clone)]
 pub struct ChunkPos {
 	pub x: i32,
 	pub z: i32,
@@ -0,0 +1,95 @@
+//! Login protocol components.
+
+use md5::{Digest, Md5};
+use uuid::{Builder, Uuid};
+
+use crate::network::network_error::NetworkError;
+use crate::protocol::packets::packet_parts::ProtocolPropertyElement;
+
+/// The maximum length of a Minecraft username.
+pub const MAX_USERNAME_LENGTH: usize = 16;
+
+/// # Game Profile
+/// The identity of a player once they have logged in. This is what is sent to the client in `LoginSuccess`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameProfile {
+	pub uuid: Uuid,
+	pub username: String,
+	/// Profile properties such as the player's skin (`textures`). Empty for offline mode players.
+	pub properties: Vec<ProtocolPropertyElement>,
+}
+
+impl GameProfile {
+	/// Create a profile for an offline mode player. The UUID is derived from the username in the same
+	/// way as the Notchian server, see [offline_uuid].
+	pub fn offline<S: Into<String>>(username: S) -> Self {
+		let username = username.into();
+
+		Self {
+			uuid: offline_uuid(&username),
+			username,
+			properties: vec![],
+		}
+	}
+}
+
+/// Derive the UUID used for a player on an offline mode server. This matches Java's
+/// `UUID.nameUUIDFromBytes("OfflinePlayer:" + name)`, a version 3 UUID built from the MD5 hash of the
+/// string, so a player keeps the same UUID (and world data) across logins.
+pub fn offline_uuid(username: &str) -> Uuid {
+	let hash: [u8; 16] = Md5::digest(format!("OfflinePlayer:{username}").as_bytes()).into();
+
+	Builder::from_md5_bytes(hash).into_uuid()
+}
+
+/// Check that a username would be accepted by the Notchian server. Usernames must be between 1 and 16
+/// characters long and only contain letters, digits and underscores.
+pub fn validate_username(username: &str) -> Result<(), NetworkError> {
+	if username.is_empty() || username.len() > MAX_USERNAME_LENGTH {
+		return Err(NetworkError::InvalidUsername(format!("Username must be between 1 and {MAX_USERNAME_LENGTH} characters, got {}", username.len())));
+	}
+
+	if let Some(c) = username.chars().find(|c| !(c.is_ascii_alphanumeric() || *c == '_')) {
+		return Err(NetworkError::InvalidUsername(format!("Username contains invalid character '{c}'")));
+	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod test {
+	use std::str::FromStr;
+
+	use uuid::Uuid;
+
+	use crate::protocol::login::login_components::{offline_uuid, validate_username, GameProfile};
+
+	#[test]
+	fn test_offline_uuid_matches_vanilla() {
+		// Values produced by the Notchian server for the same usernames.
+		assert_eq!(offline_uuid("Notch"), Uuid::from_str("b50ad385-829d-3141-a216-7e7d7539ba7f").unwrap());
+		assert_eq!(offline_uuid("dec4234"), offline_uuid("dec4234"));
+		assert_ne!(offline_uuid("dec4234"), offline_uuid("Dec4234"));
+		assert_eq!(offline_uuid("Notch").get_version_num(), 3);
+	}
+
+	#[test]
+	fn test_offline_profile() {
+		let profile = GameProfile::offline("Steve");
+		assert_eq!(profile.username, "Steve");
+		assert_eq!(profile.uuid, offline_uuid("Steve"));
+		assert!(profile.properties.is_empty());
+	}
+
+	#[test]
+	fn test_validate_username() {
+		assert!(validate_username("dec4234").is_ok());
+		assert!(validate_username("_a_").is_ok());
+		assert!(validate_username("sixteen_chars_16").is_ok());
+
+		assert!(validate_username("").is_err());
+		assert!(validate_username("seventeen_chars17").is_err());
+		assert!(validate_username("has space").is_err());
+		assert!(validate_username("§cred").is_err());
+	}
+}
@@ -0,0 +1,178 @@
+//! Correlate login plugin requests with their responses.
+//!
+//! During LOGIN the server may send any number of `LoginPluginRequest` packets, each with a message id, and the
+//! client answers every one of them with a `LoginPluginResponse` carrying the same id. This is used by proxies
+//! such as Velocity to forward player information, and by mod loaders to negotiate their handshake.
+//!
+//! The server side is available on the connection through [CraftConnection::login_query]. The client side is
+//! [LoginQueryResponder], which answers requests for channels it does not know with `success = false`.
+//!
+//! https://minecraft.wiki/w/Java_Edition_protocol/Packets#Login_Plugin_Request
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use log::trace;
+
+use crate::network::network_error::NetworkError;
+use crate::network::CraftConnection;
+use crate::protocol::packets::packet_parts::LoginPluginSpec;
+use crate::protocol::packets::{LoginPluginResponsePacket, Packet};
+
+/// The default time the server waits for the answer to a login query.
+pub const DEFAULT_LOGIN_QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The login queries of a single connection that were sent but not yet awaited.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct LoginQueries {
+	next_id: i32,
+	/// Queries that were sent and have not been answered yet.
+	pending: HashSet<i32>,
+	/// Answers received for queries that have not been awaited yet.
+	answered: HashMap<i32, Option<Vec<u8>>>,
+	pub(crate) timeout: Duration,
+}
+
+impl LoginQueries {
+	pub(crate) fn new() -> Self {
+		Self {
+			next_id: 0,
+			pending: HashSet::new(),
+			answered: HashMap::new(),
+			timeout: DEFAULT_LOGIN_QUERY_TIMEOUT,
+		}
+	}
+
+	/// Allocate the id for a new query.
+	pub(crate) fn allocate(&mut self) -> i32 {
+		let id = self.next_id;
+		self.next_id = self.next_id.wrapping_add(1);
+		self.pending.insert(id);
+		id
+	}
+
+	/// Record the answer to a query. Returns false if no query with the id is pending.
+	pub(crate) fn answer(&mut self, response: LoginPluginSpec) -> bool {
+		let id = response.message_id.0;
+
+		if !self.pending.remove(&id) {
+			return false;
+		}
+
+		let data = if response.success { Some(response.data.unwrap_or_default()) } else { None };
+		self.answered.insert(id, data);
+		true
+	}
+
+	/// Take the answer to a query if it has been received.
+	pub(crate) fn take(&mut self, id: i32) -> Option<Option<Vec<u8>>> {
+		self.answered.remove(&id)
+	}
+
+	/// Stop waiting for a query, dropping its answer if it was received.
+	pub(crate) fn forget(&mut self, id: i32) {
+		self.pending.remove(&id);
+		self.answered.remove(&id);
+	}
+
+	pub(crate) fn is_pending(&self, id: i32) -> bool {
+		self.pending.contains(&id)
+	}
+}
+
+type QueryHandler = Box<dyn FnMut(&[u8]) -> Option<Vec<u8>> + Send>;
+
+/// Answers login plugin requests on the client. Handlers are registered per channel, and return the data to
+/// respond with, or `None` to report that the request was not understood.
+pub struct LoginQueryResponder {
+	handlers: HashMap<String, QueryHandler>,
+}
+
+impl LoginQueryResponder {
+	pub fn new() -> Self {
+		Self { handlers: HashMap::new() }
+	}
+
+	/// Answer requests on the channel with the handler. This replaces any earlier handler for the channel.
+	pub fn add_handler<S: Into<String>, F: FnMut(&[u8]) -> Option<Vec<u8>> + Send + 'static>(&mut self, channel: S, handler: F) {
+		self.handlers.insert(channel.into(), Box::new(handler));
+	}
+
+	/// Answer the packet if it is a login plugin request, returning whether it was one. Requests on channels
+	/// without a handler are answered with `success = false`, as the Notchian client does.
+	pub async fn handle_packet(&mut self, connection: &mut CraftConnection, packet: &Packet) -> Result<bool, NetworkError> {
+		let Packet::LoginPluginRequest(request) = packet else {
+			return Ok(false);
+		};
+
+		let data = match self.handlers.get_mut(request.channel.as_str()) {
+			Some(handler) => handler(&request.data),
+			None => {
+				trace!("No handler for login query on {}, declining", request.channel);
+				None
+			}
+		};
+
+		let response = LoginPluginSpec::new(request.message_id, data);
+		connection.send_packet(Packet::LoginPluginResponse(LoginPluginResponsePacket::new(response))).await?;
+
+		Ok(true)
+	}
+}
+
+impl Default for LoginQueryResponder {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::time::Duration;
+
+	use crate::network::network_error::NetworkError;
+	use crate::protocol::login::login_query::LoginQueryResponder;
+	use crate::protocol::packets::packet_definer::PacketState;
+	use crate::protocol::testing::connection_pair;
+
+	#[tokio::test]
+	async fn test_concurrent_login_queries() {
+		let (mut client, mut server) = connection_pair(PacketState::LOGIN).await;
+
+		let handle = tokio::spawn(async move {
+			server
+				.login_queries(vec![
+					("sandstone:echo".to_string(), vec![1, 2]),
+					("sandstone:unknown".to_string(), vec![]),
+					("sandstone:echo".to_string(), vec![3]),
+				])
+				.await
+		});
+
+		let mut responder = LoginQueryResponder::new();
+		responder.add_handler("sandstone:echo", |data| Some(data.to_vec()));
+
+		for _ in 0..3 {
+			let packet = client.receive_packet().await.unwrap();
+			assert!(responder.handle_packet(&mut client, &packet).await.unwrap());
+		}
+
+		assert_eq!(handle.await.unwrap().unwrap(), vec![Some(vec![1, 2]), None, Some(vec![3])]);
+	}
+
+	#[tokio::test]
+	async fn test_login_query_times_out() {
+		let (_client, mut server) = connection_pair(PacketState::LOGIN).await;
+		server.set_login_query_timeout(Duration::from_millis(50));
+
+		match server.login_query("sandstone:silent", vec![]).await {
+			Err(NetworkError::TimedOut(_)) => {}
+			other => panic!("Expected timeout, got {other:?}"),
+		}
+
+		// the timeout may have cut a packet in half, so the connection can not be used anymore
+		assert!(server.is_closed());
+		assert!(matches!(server.await_login_query(0).await, Err(NetworkError::ExpectedDifferentPacket(_))));
+		assert_eq!(server.receive_packet().await, Err(NetworkError::ConnectionAbortedLocally));
+	}
+}
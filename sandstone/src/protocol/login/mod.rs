@@ -0,0 +1,137 @@
+//! The purpose of this file is to provide default implementations for the login handlers.
+//!
+//! [DefaultServerLoginHandler] implements the login sequence for an offline mode server. Online mode servers
+//! need encryption and authentication with Mojang, which is not handled here.
+
+use log::{debug, trace};
+
+use crate::network::client::client_handlers::ServerLoginHandler;
+use crate::network::network_error::NetworkError;
+use crate::network::CraftConnection;
+use crate::protocol::login::login_components::{validate_username, GameProfile};
+use crate::protocol::packets::packet_definer::PacketState;
+use crate::protocol::packets::{LoginDisconnectPacket, LoginSuccessPacket, Packet, SetCompressionPacket};
+use crate::protocol::serialization::serializer_types::{BoundedString, PrefixedArray};
+use crate::protocol_types::datatypes::chat::{JsonTextComponent, TextComponent};
+use crate::protocol_types::datatypes::var_types::VarInt;
+
+pub mod login_components;
+pub mod login_query;
+
+/// The default login handler for an offline mode server. This will:
+/// - Receive `LoginStart` and validate the username, disconnecting the client if it is invalid
+/// - Derive the offline mode UUID of the player from their username
+/// - Send `SetCompression` and enable compression if a threshold is provided
+/// - Send `LoginSuccess` and wait for `LoginAcknowledged`
+///
+/// The connection is switched to the CONFIGURATION state once the client has acknowledged the login.
+pub struct DefaultServerLoginHandler;
+
+impl ServerLoginHandler for DefaultServerLoginHandler {
+	async fn handle_login(connection: &mut CraftConnection, compression_threshold: Option<u32>) -> Result<GameProfile, NetworkError> {
+		// transfers follow the same login sequence as a regular login
+		if connection.packet_state != PacketState::LOGIN && connection.packet_state != PacketState::TRANSFER {
+			return Err(NetworkError::InvalidPacketState);
+		}
+
+		connection.change_state(PacketState::LOGIN);
+
+		debug!("Handling login for {}", connection);
+
+		let login_start = match connection.receive_packet().await? {
+			Packet::LoginStart(login_start) => login_start,
+			_ => return Err(NetworkError::ExpectedDifferentPacket("Invalid packet received, expected login start".to_string())),
+		};
+
+		trace!("Received login start from {} for {}", connection, login_start.username);
+
+		if let Err(e) = validate_username(&login_start.username) {
+			let reason = JsonTextComponent::from(TextComponent::from(e.to_string()));
+			connection.send_packet(Packet::LoginDisconnect(LoginDisconnectPacket::new(reason))).await?;
+			connection.close().await;
+			return Err(e);
+		}
+
+		// the uuid sent by the client is not trusted in offline mode
+		let profile = GameProfile::offline(login_start.username);
+
+		if let Some(threshold) = compression_threshold {
+			connection.send_packet(Packet::SetCompression(SetCompressionPacket::new(VarInt(threshold as i32)))).await?;
+			connection.enable_compression(Some(threshold));
+
+			trace!("Enabled compression for {} with threshold {}", connection, threshold);
+		}
+
+		let login_success = Packet::LoginSuccess(LoginSuccessPacket::new(profile.uuid, BoundedString::new(profile.username.clone())?, PrefixedArray::new(profile.properties.clone())));
+		connection.send_packet(login_success).await?;
+
+		match connection.receive_packet().await? {
+			Packet::LoginAcknowledged(_) => {}
+			_ => return Err(NetworkError::ExpectedDifferentPacket("Invalid packet received, expected login acknowledged".to_string())),
+		}
+
+		connection.change_state(PacketState::CONFIGURATION);
+
+		debug!("Login complete for {} as {} ({})", connection, profile.username, profile.uuid);
+
+		Ok(profile)
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use uuid::Uuid;
+
+	use crate::network::client::client_handlers::ServerLoginHandler;
+	use crate::network::network_error::NetworkError;
+	use crate::protocol::login::login_components::offline_uuid;
+	use crate::protocol::login::DefaultServerLoginHandler;
+	use crate::protocol::packets::packet_definer::PacketState;
+	use crate::protocol::packets::{LoginAcknowledgedPacket, LoginStartPacket, Packet};
+	use crate::protocol::testing::connection_pair;
+
+	#[tokio::test]
+	async fn test_offline_login() {
+		let (mut client, mut server) = connection_pair(PacketState::LOGIN).await;
+
+		let handle = tokio::spawn(async move { DefaultServerLoginHandler::handle_login(&mut server, Some(256)).await.map(|p| (p, server.packet_state)) });
+
+		client.send_packet(Packet::LoginStart(LoginStartPacket::new("dec4234".try_into().unwrap(), Uuid::new_v4()))).await.unwrap();
+
+		match client.receive_packet().await.unwrap() {
+			Packet::SetCompression(p) => client.enable_compression(Some(p.threshold.0 as u32)),
+			p => panic!("Expected set compression, got {p:?}"),
+		}
+
+		match client.receive_packet().await.unwrap() {
+			Packet::LoginSuccess(p) => {
+				assert_eq!(p.username, "dec4234");
+				assert_eq!(p.uuid, offline_uuid("dec4234"));
+			}
+			p => panic!("Expected login success, got {p:?}"),
+		}
+
+		client.send_packet(Packet::LoginAcknowledged(LoginAcknowledgedPacket::new())).await.unwrap();
+
+		let (profile, state) = handle.await.unwrap().unwrap();
+		assert_eq!(profile.username, "dec4234");
+		assert_eq!(profile.uuid, offline_uuid("dec4234"));
+		assert_eq!(state, PacketState::CONFIGURATION);
+	}
+
+	#[tokio::test]
+	async fn test_invalid_username_disconnects() {
+		let (mut client, mut server) = connection_pair(PacketState::LOGIN).await;
+
+		let handle = tokio::spawn(async move { DefaultServerLoginHandler::handle_login(&mut server, None).await });
+
+		client.send_packet(Packet::LoginStart(LoginStartPacket::new("not a valid name".try_into().unwrap(), Uuid::nil()))).await.unwrap();
+
+		match client.receive_packet().await.unwrap() {
+			Packet::LoginDisconnect(_) => {}
+			p => panic!("Expected login disconnect, got {p:?}"),
+		}
+
+		assert!(matches!(handle.await.unwrap(), Err(NetworkError::InvalidUsername(_))));
+	}
+}
@@ -1,4 +1,14 @@
+#[cfg(feature = "network")]
+pub mod configuration;
+#[cfg(feature = "network")]
+pub mod login;
 pub mod packets;
+#[cfg(feature = "network")]
+pub mod plugin;
+#[cfg(feature = "network")]
+pub mod resource_pack;
+#[cfg(feature = "network")]
+pub mod secure_chat;
 pub mod status;
 pub mod serialization;
 pub mod game;
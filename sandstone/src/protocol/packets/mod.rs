@@ -11,6 +11,7 @@
 #![allow(clippy::too_many_arguments)]
 #![allow(clippy::new_without_default)] // todo: maybe needs default?
 
+use crate::prelude::*;
 use crate::game::player::PlayerGamemode;
 use crate::packets;
 use crate::protocol::game::effects::particle::Particle;
@@ -30,15 +31,16 @@ use crate::protocol::packets::packet_parts::block::{
 	BlockFace, BlockParticleAlternative, CommandBlockFlag, CommandBlockMode, SpecialBlockRotation, StructureBlockAction, StructureBlockFlags, StructureBlockMirror, StructureBlockMode, TestBlockMode,
 	TestInstanceBlockActionAction, TestInstanceStatus,
 };
-use crate::protocol::packets::packet_parts::debug::{CustomReportDetail, DebugSampleType, DebugSubscriptionEvent, DebugSubscriptionUpdate};
+use crate::protocol::packets::packet_parts::debug::{DebugSampleType, DebugSubscriptionEvent, DebugSubscriptionUpdate};
 use crate::protocol::packets::packet_parts::entity::{EntityStatusEnum, MinecartMoveStep};
 use crate::protocol::packets::packet_parts::item::{MapColorPatch, MapIcons, Trade};
 use crate::protocol::packets::packet_parts::player::{PlayerActionStatus, SeenAdvancementsAction, TeleportFlags, UseItemHand, WaypointData, WaypointOperation};
 use crate::protocol::packets::packet_parts::scoreboard::{ObjectiveNumberFormat, ObjectiveType, UpdateScoreFormat, UpdateTeamOptions};
 use crate::protocol::packets::packet_parts::sound::StopSoundDetails;
 use crate::protocol::packets::packet_parts::{ChatTypeNetwork, PlayerAbilityFlags, PlayerInputFlags, PlayerPositionFlags};
+use crate::protocol::serialization::serde_format::byte_array;
 use crate::protocol::serialization::serializer_error::SerializingErr;
-use crate::protocol::serialization::serializer_types::{PrefixedArray, PrefixedOptional};
+use crate::protocol::serialization::serializer_types::{BoundedArray, BoundedString, PrefixedArray, PrefixedOptional};
 use crate::protocol::serialization::SerializingResult;
 use crate::protocol::serialization::StateBasedDeserializer;
 use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer};
@@ -49,8 +51,8 @@ use crate::protocol_types::datatypes::command::Node;
 use crate::protocol_types::datatypes::game_types::{ChunkSectionPosition, GameDifficulty, Position, SectionBlockEntry, SourcePosition, WorldEventType};
 use crate::protocol_types::datatypes::internal_types::{Angle, Either, IDorX, LpVec3, Mapping, RgbColor, TripleDouble};
 use crate::protocol_types::datatypes::nbt::{NbtCompound, NbtTag};
-use crate::protocol_types::datatypes::var_types::{VarInt, VarLong};
-use crate::util::java::bitset::BitSet;
+use crate::protocol_types::datatypes::var_types::{RangedVarInt, VarInt, VarLong};
+use crate::util::java::bitset::{BitSet, FixedBitSet};
 use packet_parts::stats::StatisticAward;
 use packet_parts::ProtocolPropertyElement;
 use packet_parts::{
@@ -61,19 +63,21 @@ use uuid::Uuid;
 
 pub mod packet_definer;
 pub mod packet_parts;
+pub mod packet_versions;
+pub mod packet_views;
 
 // https://minecraft.wiki/w/Java_Edition_protocol
-packets!(v1_21 => { // version name is for reference only, has no effect
+packets!(v1_21 => { // ids are those of the latest version, see packet_versions for older versions
 	HANDSHAKING => {
 		SERVER => { // Server is the destination
 			Handshaking, 0x00 #[doc = "Used to switch server into a different connection state. Should be sent shortly after TCP connection is opened."] => {
 				#[doc = "See protocol_version.rs for more context"]
 				protocol_version: VarInt,
 				#[doc = "The server address, in the form of a domain name or IP address"]
-				server_address: String,
+				server_address: BoundedString<255>,
 				port: u16,
 				#[doc = "1 for STATUS, 2 for LOGIN, 3 for TRANSFER"]
-				next_state: VarInt
+				next_state: RangedVarInt<1, 3>
 			}
 		}
 	},
@@ -102,14 +106,15 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 			},
 			EncryptionRequest, 0x01 => {
 				server_id: String,
-				public_key_length: VarInt,
+				#[mc(length_prefixed)]
 				public_key: Vec<u8>,
-				verify_token_length: VarInt, // always 4 for Notchian servers
+				#[doc = "Always 4 bytes for Notchian servers"]
+				#[mc(length_prefixed)]
 				verify_token: Vec<u8>
 			},
 			LoginSuccess, 0x02 => {
 				uuid: Uuid,
-				username: String,
+				username: BoundedString<16>,
 				array: PrefixedArray<ProtocolPropertyElement>
 			},
 			SetCompression, 0x03 => {
@@ -118,22 +123,23 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 			},
 			LoginPluginRequest, 0x04 => {
 				message_id: VarInt,
-				channel: String,
+				channel: BoundedString<32767>,
+				#[mc(rest)]
 				data: Vec<u8>
 			},
-			LoginCookieRequest, 0x05 => {
-				key: String
+			CookieRequest, 0x05 | CONFIGURATION 0x00 | PLAY 0x15 => {
+				key: BoundedString<32767>
 			}
 		},
 		SERVER => {
 			LoginStart, 0x00 #[doc = "Initiate the login procedure for a client."] => {
-				username: String,
+				username: BoundedString<16>,
 				uuid: Uuid
 			},
 			EncryptionResponse, 0x01 => {
-				shared_secret_length: VarInt,
+				#[mc(length_prefixed)]
 				shared_secret: Vec<u8>,
-				verify_token_length: VarInt,
+				#[mc(length_prefixed)]
 				verify_token: Vec<u8>
 			},
 			LoginPluginResponse, 0x02 => {
@@ -149,24 +155,22 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 	},
 	CONFIGURATION => {
 		CLIENT => {
-			ConfigCookieRequest, 0x00 => {
-				key: String
-			},
-			ClientboundPluginMessage, 0x01 => {
-				channel: String,
+			ClientboundPluginMessage, 0x01 | PLAY 0x18 => {
+				channel: BoundedString<32767>,
+				#[mc(rest)]
 				data: Vec<u8>
 			},
-			ConfigDisconnect, 0x02 => {
+			Disconnect, 0x02 | PLAY 0x20 => {
 				reason: TextComponent
 			},
 			FinishConfiguration, 0x03 => {
 				// none
 			},
-			KeepAlive, 0x04 => {
+			KeepAlive, 0x04 | PLAY 0x2B => {
 				keep_alive_id: i64
 			},
-			ConfigurationPing, 0x05 => {
-				payload: i32
+			Ping, 0x05 | PLAY 0x3B => {
+				id: i32
 			},
 			ResetChat, 0x06 => {
 				// none
@@ -174,75 +178,76 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 			RegistryData, 0x07 => {
 				packet: RegistryDataPacketInternal
 			},
-			RemoveResourcePack, 0x08 => {
+			RemoveResourcePack, 0x08 | PLAY 0x4E => {
 				uuid: PrefixedOptional<Uuid>
 			},
-			AddResourcePack, 0x09 => {
+			AddResourcePack, 0x09 | PLAY 0x4F => {
 				spec: AddResourcePackSpec
 			},
-			ConfigStoreCookie, 0x0A => {
-				key: String,
+			StoreCookie, 0x0A | PLAY 0x76 => {
+				key: BoundedString<32767>,
 				payload: PrefixedArray<u8>
 			},
-			ConfigTransfer, 0x0B => {
+			Transfer, 0x0B | PLAY 0x7F => {
 				host: String,
 				port: VarInt
 			},
 			FeatureFlags, 0x0C => {
-				total: VarInt,
+				#[mc(length_prefixed)]
 				flags: Vec<String>
 			},
-			UpdateTags, 0x0D => {
+			UpdateTags, 0x0D | PLAY 0x84 => {
 				tags: PrefixedArray<Mapping<PrefixedArray<Tag>>>
 			},
 			ClientboundKnownPacks, 0x0E => {
 				entries: PrefixedArray<ResourcePackEntry>
 			},
-			ConfigCustomReportDetails, 0x0F => {
+			CustomReportDetails, 0x0F | PLAY 0x86 => {
 				details: PrefixedArray<CustomReportDetails>
 			},
-			ConfigServerLinks, 0x10 => {
+			ServerLinks, 0x10 | PLAY 0x87 => {
 				links: PrefixedArray<ServerLink>
 			}
 		},
 		SERVER => {
-			ClientInformation, 0x00 => {
-				locale: String,
+			ClientInformation, 0x00 | PLAY 0x0D => {
+				locale: BoundedString<16>,
 				view_distance: i8,
-				chat_mode: VarInt,
+				chat_mode: RangedVarInt<0, 2>,
 				chat_colors: bool,
 				displayed_skin_parts: u8,
-				main_hand: VarInt,
+				main_hand: RangedVarInt<0, 1>,
 				enable_text_filtering: bool,
 				allow_server_listing: bool,
-				particle_status: VarInt
+				particle_status: RangedVarInt<0, 2>
 			},
-			CookieResponse, 0x01 => {
-				key: String,
+			CookieResponse, 0x01 | PLAY 0x14 => {
+				key: BoundedString<32767>,
 				payload: PrefixedOptional<PrefixedArray<u8>>
 			},
-			ServerboundPluginMessage, 0x02 => {
-				channel: String,
+			ServerboundPluginMessage, 0x02 | PLAY 0x15 => {
+				channel: BoundedString<32767>,
+				#[mc(rest)]
 				data: Vec<u8>
 			},
 			AcknowledgeFinishConfiguration, 0x03 => {
 				// none
 			},
-			ServerboundKeepAliveConfig, 0x04 => {
+			ServerboundKeepAlive, 0x04 | PLAY 0x1B => {
 				keep_alive_id: i64
 			},
-			ConfigPong, 0x05 => {
+			Pong, 0x05 | PLAY 0x2C => {
 				id: i32
 			},
-			ConfigResourcePackResponse, 0x06 => {
+			ResourcePackResponse, 0x06 | PLAY 0x30 => {
 				uuid: Uuid,
 				result: VarInt
 			},
 			ServerboundKnownPacks, 0x07 => {
 				entries: PrefixedArray<ResourcePackEntry>
 			},
-			ConfigCustomClickAction, 0x08 => {
-				id: String,
+			CustomClickAction, 0x08 | PLAY 0x41 => {
+				id: BoundedString<32767>,
 				payload: NbtCompound
 			}
 		}
@@ -346,9 +351,6 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 				slot: i16,
 				slot_data: SlotData
 			},
-			CookieRequestPlay, 0x15 => {
-				key: String
-			},
 			SetCooldown, 0x16 => {
 				cooldown_group: String,
 				cooldown_ticks: VarInt
@@ -357,10 +359,6 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 				action: VarInt,
 				entries: PrefixedArray<String>
 			},
-			ClientboundPluginMessagePlay, 0x18 => {
-				channel: String,
-				data: Vec<u8>
-			},
 			DamageEvent, 0x19 => {
 				entity_id: VarInt,
 				source_type_id: VarInt,
@@ -391,12 +389,10 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 			},
 			DeleteMessage, 0x1F => {
 				message_id: VarInt,
-				#[mc(deserialize_if = message_id.0 == 0)]
+				#[mc(include_if = message_id.0 == 0)]
+				#[serde(serialize_with = "byte_array::option")]
 				signature: Option<[u8; 256]>
 			},
-			DisconnectPlay, 0x20 => {
-				reason: TextComponent
-			},
 			DisguisedChatMessage, 0x21 #[doc = "Send client a chat message without any signing information"] => {
 				message: TextComponent,
 				chat_type: IDorX<Box<ChatTypeNetwork>>,
@@ -463,9 +459,6 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 				warning_blocks: VarInt,
 				warning_time: VarInt
 			},
-			ClientboundKeepAlive, 0x2B => {
-				keep_alive_id: i64
-			},
 			ChunkDataUpdateLight, 0x2C #[doc = "https://minecraft.wiki/w/Java_Edition_protocol/Packets#Chunk_Data_and_Update_Light"] => {
 				x: i32,
 				z: i32,
@@ -521,9 +514,9 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 				is_flat: bool,
 				#[doc = "When true, saves details about the player's death location."]
 				has_death_location: bool,
-				#[mc(deserialize_if = has_death_location)]
+				#[mc(include_if = has_death_location)]
 				death_dimension_name: Option<String>,
-				#[mc(deserialize_if = has_death_location)]
+				#[mc(include_if = has_death_location)]
 				death_location: Option<Position>,
 				portal_cooldown: VarInt,
 				sea_level: VarInt,
@@ -595,9 +588,6 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 				location: Position,
 				is_front_text: bool
 			},
-			PingPlay, 0x3B => {
-				id: i32
-			},
 			PingResponsePlay, 0x3C => {
 				payload: i64
 			},
@@ -614,6 +604,7 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 				global_index: VarInt,
 				sender: Uuid,
 				index: VarInt,
+				#[serde(serialize_with = "byte_array::prefixed_optional")]
 				message_signature_bytes: PrefixedOptional<[u8; 256]>,
 				message: String,
 				timestamp: i64,
@@ -622,7 +613,7 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 				unsigned_content: PrefixedOptional<Box<TextComponent>>,
 				filter: PlayerChatFilter,
 				#[doc = "Only present if the Filter Type is Partially Filtered. Specifies the indices at which characters in the original message string should be replaced with the # symbol (i.e., filtered) by the vanilla client"]
-				#[mc(deserialize_if = filter == PlayerChatFilter::PartiallyFiltered)]
+				#[mc(include_if = filter == PlayerChatFilter::PartiallyFiltered)]
 				filter_type_bits: Option<BitSet>,
 				#[doc = "Either the type of chat in the minecraft:chat_type registry, defined by the Registry Data packet, or an inline definition."]
 				chat_type: IDorX<ChatType>,
@@ -646,7 +637,7 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 				players: PrefixedArray<Uuid>
 			},
 			PlayerInfoUpdate, 0x44 => {
-				data: PlayerInfoUpdateData
+				players: PlayerInfoUpdateData
 			},
 			LookAt, 0x45 => {
 				feet_eyes: VarInt,
@@ -654,9 +645,9 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 				target_y: f64,
 				target_z: f64,
 				is_entity: bool,
-				#[mc(deserialize_if = is_entity)]
+				#[mc(include_if = is_entity)]
 				entity_id: Option<VarInt>,
-				#[mc(deserialize_if = is_entity)]
+				#[mc(include_if = is_entity)]
 				entity_feet_eyes: Option<VarInt>
 			},
 			SyncPlayerPosition, 0x46 => {
@@ -709,12 +700,6 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 				entity_name: String,
 				objective_name: PrefixedOptional<String>
 			},
-			RemoveResourcePackPlay, 0x4E => {
-				uuid: PrefixedOptional<Uuid>
-			},
-			AddResourcePackPlay, 0x4F => {
-				spec: AddResourcePackSpec
-			},
 			Respawn, 0x50 #[doc = "https://minecraft.wiki/w/Java_Edition_protocol/Packets#Respawn"] => {
 				dimension_type: VarInt,
 				dimension_name: String,
@@ -821,13 +806,13 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 			UpdateObjectives, 0x68 => {
 				objective_name: String,
 				mode: i8,
-				#[mc(deserialize_if = mode == 0 || mode == 2)]
+				#[mc(include_if = mode == 0 || mode == 2)]
 				objective_value: Option<TextComponent>,
-				#[mc(deserialize_if = mode == 0 || mode == 2)]
+				#[mc(include_if = mode == 0 || mode == 2)]
 				typ: Option<ObjectiveType>,
-				#[mc(deserialize_if = mode == 0 || mode == 2)]
+				#[mc(include_if = mode == 0 || mode == 2)]
 				has_number_format: Option<bool>,
-				#[mc(deserialize_if = (mode == 0 || mode == 2) && has_number_format.unwrap())]
+				#[mc(include_if = (mode == 0 || mode == 2) && has_number_format == Some(true))]
 				number_format: Option<ObjectiveNumberFormat>
 			},
 			SetPassengers, 0x69 => {
@@ -890,10 +875,6 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 			StopSound, 0x75 => {
 				details: StopSoundDetails
 			},
-			StoreCookie, 0x76 => {
-				key: String,
-				payload: PrefixedArray<u8>
-			},
 			SystemChatMessage, 0x77 => {
 				content: TextComponent,
 				overlay: bool
@@ -927,11 +908,11 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 			TestInstanceBlockStatus, 0x7C => {
 				status: TextComponent,
 				has_size: bool,
-				#[mc(deserialize_if = has_size)]
+				#[mc(include_if = has_size)]
 				size_x: Option<f64>,
-				#[mc(deserialize_if = has_size)]
+				#[mc(include_if = has_size)]
 				size_y: Option<f64>,
-				#[mc(deserialize_if = has_size)]
+				#[mc(include_if = has_size)]
 				size_z: Option<f64>
 			},
 			SetTickingState, 0x7D => {
@@ -941,10 +922,6 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 			StepTick, 0x7E => {
 				tick_steps: VarInt
 			},
-			Transfer, 0x7F => {
-				host: String,
-				port: VarInt
-			},
 			UpdateAdvancements, 0x80 => {
 				reset: bool,
 				advancement_mapping: PrefixedArray<Mapping<Advancement>>,
@@ -967,19 +944,10 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 				property_sets: PrefixedArray<PropertySet>,
 				stonecutter_recipes: PrefixedArray<StonecutterRecipe>
 			},
-			UpdateTagsPlay, 0x84 => {
-				tags: PrefixedArray<Mapping<PrefixedArray<Tag>>>
-			},
 			ProjectilePower, 0x85 => {
 				entity_id: VarInt,
 				power: f64
 			},
-			CustomReportDetails, 0x86 => {
-				details: PrefixedArray<CustomReportDetail>
-			},
-			ServerLinks, 0x87 => {
-				links: PrefixedArray<ServerLink>
-			},
 			Waypoint, 0x88 => {
 				operation: WaypointOperation,
 				identifier: Either<Uuid, String>,
@@ -1016,25 +984,27 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 				message_count: VarInt
 			},
 			ChatCommand, 0x06 => {
-				command: String
+				command: BoundedString<32767>
 			},
 			SignedChatCommand, 0x07 #[doc = "https://minecraft.wiki/w/Java_Edition_protocol/Packets#Signed_Chat_Command"] => {
 				#[doc = "The command typed by the client excluding the /"]
-				command: String,
+				command: BoundedString<32767>,
 				time: i64,
 				salt: i64,
-				argument_signatures: PrefixedArray<Mapping<Box<[u8; 256]>>>,
+				#[serde(serialize_with = "byte_array::mappings")]
+				argument_signatures: BoundedArray<Mapping<Box<[u8; 256]>>, 20>,
 				message_count: VarInt,
-				acknowledged: BitSet,
+				acknowledged: FixedBitSet<20>,
 				checksum: i8
 			},
 			ChatMessage, 0x08 #[doc = "https://minecraft.wiki/w/Java_Edition_protocol/Packets#Chat_Message"] => {
-				message: String,
+				message: BoundedString<256>,
 				time: i64,
 				salt: i64,
+				#[serde(serialize_with = "byte_array::prefixed_optional")]
 				signature: PrefixedOptional<Box<[u8; 256]>>,
 				message_count: VarInt,
-				acknowledged: BitSet,
+				acknowledged: FixedBitSet<20>,
 				checksum: i8
 			},
 			PlayerSession, 0x09 #[doc = "https://minecraft.wiki/w/Java_Edition_protocol/Packets#Player_Session"] => {
@@ -1051,20 +1021,9 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 			ClientTickEnd, 0x0C => {
 				// none
 			},
-			ClientInformationPlay, 0x0D => {
-				locale: String,
-				view_distance: i8,
-				chat_mode: VarInt,
-				chat_colors: bool,
-				displayed_skin_parts: u8,
-				main_hand: VarInt,
-				enable_text_filtering: bool,
-				allow_server_listing: bool,
-				particle_status: VarInt
-			},
 			CommandSuggestionsRequest, 0x0E => {
 				transaction_id: VarInt,
-				text: String
+				text: BoundedString<32500>
 			},
 			AcknowledgeConfiguration, 0x0F => {
 				// no fields
@@ -1080,7 +1039,7 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 				slot: i16,
 				button: i8,
 				mode: InventoryOperationMode,
-				changed_slots: PrefixedArray<ChangedSlot>,
+				changed_slots: BoundedArray<ChangedSlot, 128>,
 				#[doc = "Item carried by the cursor"]
 				carried_item: HashedSlot
 			},
@@ -1095,24 +1054,16 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 				#[doc = "The new state of the slot. True for enabled, false for disabled."]
 				state: bool
 			},
-			CookieResponsePlay, 0x14 => {
-				key: String,
-				payload: PrefixedOptional<PrefixedArray<u8>>
-			},
-			ServerboundPluginMessagePlay, 0x15 => {
-				channel: String,
-				data: Vec<u8>
-			},
 			DebugSubscriptionRequest, 0x16 => {
 				request_type: VarInt
 			},
 			EditBook, 0x17 => {
 				#[doc = "The hotbar slot where the written book is located"]
 				slot: VarInt,
-				#[doc = "Text from each page. Maximum string length is 1024 chars."]
-				entries: PrefixedArray<String>,
+				#[doc = "Text from each page. Maximum of 100 pages, each at most 1024 chars."]
+				entries: BoundedArray<BoundedString<1024>, 100>,
 				#[doc = "Title of book. Present if book is being signed, not present if book is being edited."]
-				title: PrefixedOptional<String>
+				title: PrefixedOptional<BoundedString<32>>
 			},
 			QueryEntityTag, 0x18 => {
 				transaction_id: VarInt,
@@ -1122,13 +1073,13 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 				#[doc = "The ID of the entity to interact."]
 				entity_id: VarInt,
 				typ: InteractType,
-				#[mc(deserialize_if = typ == InteractType::InteractAt)]
+				#[mc(include_if = typ == InteractType::InteractAt)]
 				target_x: Option<f32>,
-				#[mc(deserialize_if = typ == InteractType::InteractAt)]
+				#[mc(include_if = typ == InteractType::InteractAt)]
 				target_y: Option<f32>,
-				#[mc(deserialize_if = typ == InteractType::InteractAt)]
+				#[mc(include_if = typ == InteractType::InteractAt)]
 				target_z: Option<f32>,
-				#[mc(deserialize_if = typ == InteractType::InteractAt)]
+				#[mc(include_if = typ == InteractType::InteractAt)]
 				hand: Option<InteractHand>,
 				sneak_key_pressed: bool
 			},
@@ -1137,9 +1088,6 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 				levels: VarInt,
 				keep_jigsaws: bool
 			},
-			ServerboundKeepAlive, 0x1B => {
-				keep_alive_id: i64
-			},
 			LockDifficulty, 0x1C => {
 				locked: bool
 			},
@@ -1215,9 +1163,6 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 			PlayerLoaded, 0x2B => {
 				// none
 			},
-			PongPlay, 0x2C => {
-				id: i32
-			},
 			ChangeRecipeBookSettings, 0x2D => {
 				book_id: VarInt,
 				is_open: bool,
@@ -1227,16 +1172,12 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 				recipe_id: VarInt
 			},
 			RenameItem, 0x2F => {
-				item_name: String
-			},
-			ResourcePackResponse, 0x30 => {
-				uuid: Uuid,
-				result: VarInt
+				item_name: BoundedString<32767>
 			},
 			SeenAdvancements, 0x31 => {
 				action: SeenAdvancementsAction,
-				#[mc(deserialize_if = action == SeenAdvancementsAction::OpenedTab)]
-				tab_id: Option<String>
+				#[mc(include_if = action == SeenAdvancementsAction::OpenedTab)]
+				tab_id: Option<BoundedString<32767>>
 			},
 			SelectTrade, 0x32 => {
 				selected_slot: VarInt
@@ -1252,13 +1193,13 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 			},
 			ProgramCommandBlock, 0x35 #[doc = "https://minecraft.wiki/w/Java_Edition_protocol/Packets#Program_Command_Block"] => {
 				location: Position,
-				command: String,
+				command: BoundedString<32767>,
 				mode: CommandBlockMode,
 				flags: CommandBlockFlag
 			},
 			ProgramCommandBlockMinecart, 0x36 => {
 				entity_id: VarInt,
-				command: String,
+				command: BoundedString<32767>,
 				track_output: bool
 			},
 			SetCreativeModeSlot, 0x37 => {
@@ -1267,11 +1208,11 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 			},
 			ProgramJigsawBlock, 0x38 => {
 				location: Position,
-				name: String,
-				target: String,
-				pool: String,
-				final_state: String,
-				joint_type: String,
+				name: BoundedString<32767>,
+				target: BoundedString<32767>,
+				pool: BoundedString<32767>,
+				final_state: BoundedString<32767>,
+				joint_type: BoundedString<32767>,
 				selection_priority: VarInt,
 				placement_priority: VarInt
 			},
@@ -1279,7 +1220,7 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 				location: Position,
 				action: StructureBlockAction,
 				mode: StructureBlockMode,
-				name: String,
+				name: BoundedString<32767>,
 				offset_x: i8,
 				offset_y: i8,
 				offset_z: i8,
@@ -1288,7 +1229,7 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 				size_z: i8,
 				mirror: StructureBlockMirror,
 				rotation: SpecialBlockRotation,
-				metadata: String,
+				metadata: BoundedString<128>,
 				#[doc = "Between 0 and 1"]
 				integrity: f32,
 				seed: VarLong,
@@ -1297,15 +1238,15 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 			SetTestBlock, 0x3A => {
 				position: Position,
 				mode: TestBlockMode,
-				message: String
+				message: BoundedString<32767>
 			},
 			UpdateSign, 0x3B => {
 				location: Position,
 				is_front_text: bool,
-				line_1: String,
-				line_2: String,
-				line_3: String,
-				line_4: String
+				line_1: BoundedString<384>,
+				line_2: BoundedString<384>,
+				line_3: BoundedString<384>,
+				line_4: BoundedString<384>
 			},
 			SwingArm, 0x3C => {
 				hand: VarInt
@@ -1316,7 +1257,7 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 			TestInstanceBlockAction, 0x3E => {
 				position: Position,
 				action: TestInstanceBlockActionAction,
-				test: PrefixedOptional<String>,
+				test: PrefixedOptional<BoundedString<32767>>,
 				size_x: VarInt,
 				size_y: VarInt,
 				size_z: VarInt,
@@ -1345,10 +1286,6 @@ packets!(v1_21 => { // version name is for reference only, has no effect
 				sequence: VarInt,
 				yaw: f32,
 				pitch: f32
-			},
-			CustomClickAction, 0x41 => {
-				id: String,
-				payload: NbtCompound
 			}
 		}
 	}
@@ -4,7 +4,7 @@
 ///
 /// In the context of initiating a 'CraftConnection', this is the type of client that is being created.
 /// So if you are creating a client that connects to a server, you would use `PacketDirection::CLIENT`.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Copy, serde::Serialize)]
 pub enum PacketDirection {
 	SERVER,
 	CLIENT,
@@ -12,7 +12,7 @@ pub enum PacketDirection {
 
 /// Used to help discern the type of packet being received. Note that different states could have
 /// packets with the same ids.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Copy, serde::Serialize)]
 pub enum PacketState {
 	STATUS,
 	HANDSHAKING,
@@ -44,21 +44,102 @@ impl PacketState {
 	}
 }
 
+/// Describes a packet for tools that need to list the packets, like docs or proxy filters. Every packet has one in
+/// [PACKET_REGISTRY](crate::protocol::packets::PACKET_REGISTRY), which is generated by the `packets!` macro.
+///
+/// The ids are those of the latest version, see [crate::protocol::packets::packet_versions] for older versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct PacketInfo {
+	/// The name of the packet, which is also its variant of [Packet](crate::protocol::packets::Packet).
+	pub name: &'static str,
+	/// The first state the packet is used in.
+	pub state: PacketState,
+	pub direction: PacketDirection,
+	/// The id of the packet in its first state.
+	pub id: i32,
+	/// Any later states the packet is also used in, with its id in each.
+	pub other_states: &'static [(PacketState, i32)],
+	/// The doc string of the packet, or an empty string if it has none.
+	pub doc: &'static str,
+	pub fields: &'static [PacketField],
+}
+
+/// A field of a packet, see [PacketInfo].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+pub struct PacketField {
+	pub name: &'static str,
+	/// The type of the field as it is written in the packet definition, like `PrefixedArray<u8>`.
+	pub typ: &'static str,
+}
+
+impl PacketInfo {
+	/// Find the packet with the given id in a state and direction, with the ids of the latest version.
+	pub fn by_id(state: PacketState, direction: PacketDirection, id: i32) -> Option<&'static PacketInfo> {
+		crate::protocol::packets::PACKET_REGISTRY.iter().find(|info| info.direction == direction && info.id_in(state) == Some(id))
+	}
+
+	/// Find a packet by its name, like `KeepAlive`.
+	pub fn by_name(name: &str) -> Option<&'static PacketInfo> {
+		crate::protocol::packets::PACKET_REGISTRY.iter().find(|info| info.name == name)
+	}
+
+	/// Every state the packet is used in.
+	pub fn states(&self) -> impl Iterator<Item = PacketState> + '_ {
+		core::iter::once(self.state).chain(self.other_states.iter().map(|(state, _)| *state))
+	}
+
+	/// The id of the packet in the given state, or `None` if it is not used in that state.
+	pub fn id_in(&self, state: PacketState) -> Option<i32> {
+		if state == self.state {
+			return Some(self.id);
+		}
+
+		self.other_states.iter().find(|(other, _)| *other == state).map(|(_, id)| *id)
+	}
+}
+
 #[macro_use]
 mod macros {
+	/// Internal Only. Joins the `doc` attributes out of a list of attributes, like `[doc = "..."] [derive(Eq)]`, with
+	/// newlines. The joined lines are collected in the first brackets.
+	#[doc(hidden)]
+	#[macro_export]
+	macro_rules! packet_doc {
+		([]) => {
+			""
+		};
+		([$first: literal $($docs: literal)*]) => {
+			concat!($first $(, "\n", $docs)*)
+		};
+		([$($docs: literal)*] [doc = $doc: literal] $($rest: tt)*) => {
+			$crate::packet_doc!([$($docs)* $doc] $($rest)*)
+		};
+		([$($docs: literal)*] [$($other: tt)*] $($rest: tt)*) => {
+			$crate::packet_doc!([$($docs)*] $($rest)*)
+		};
+	}
+
 	/// Internal Only. This is the complex macro used to define every packet in the game. First, we it define the packet with all of its fields,
 	/// then it adds it to a central enum. This enum is used to deserialize the raw incoming packets from a connection since otherwise
 	/// we can only determine the packet based on the id and current state of the connection.
 	///
 	/// Generally, this is an internal macro, but you may need to work on it in order to change packets around based on
-	/// different game versions. Needless to say, this packet is only used for a single Minecraft version at a time.
+	/// different game versions. The packets are defined with the ids of the latest version, and the ids of older versions
+	/// are looked up in [crate::protocol::packets::packet_versions] when serializing and deserializing.
+	///
+	/// A packet that is used in more than one state, like `KeepAlive`, is defined once in the first state it is used in,
+	/// followed by the other states and its ids there: `KeepAlive, 0x04 | PLAY 0x2B => { ... }`. It is a single variant of
+	/// the packet enum, and its id is picked by the state of the connection when it is sent.
+	///
+	/// Every packet is also described in the generated `PACKET_REGISTRY`. The attributes that can follow its id are put
+	/// on the packet struct, and its `#[doc = "..."]` attributes are joined into the description.
 	#[macro_export]
 	macro_rules! packets {
         ($ref_ver: ident => {
             // These are split into multiple levels to allow for more efficient deserialization
             $($state: ident => {
                 $($direction: ident => {
-                   $($name: ident, $packetID: literal $(#[$struct_meta: meta])* => {
+                   $($name: ident, $packetID: literal $(| $alt_state: ident $alt_id: literal)* $(#[$($struct_meta: tt)*])* => {
                         $(
                             $(#[$field_meta:meta])*
                             $field: ident: $t: ty
@@ -71,8 +152,8 @@ mod macros {
             $(
                 $(
                     $(
-                        #[derive(Debug, Clone, PartialEq, sandstone_derive::McDeserialize, sandstone_derive::McSerialize, sandstone_derive::McDefault)]
-                        $(#[$struct_meta])*
+                        #[derive(Debug, Clone, PartialEq, sandstone_derive::McDeserialize, sandstone_derive::McSerialize, sandstone_derive::McDefault, sandstone_derive::McArbitrary, serde::Serialize)]
+                        $(#[$($struct_meta)*])*
                         pub struct [<$name Packet>] { // The body struct of the packet
                             $(
                                 $(#[$field_meta])*
@@ -86,6 +167,20 @@ mod macros {
                                     $($field),*
                                 }
                             }
+
+                            /// The description of the packet in [PACKET_REGISTRY].
+                            pub const INFO: $crate::protocol::packets::packet_definer::PacketInfo = $crate::protocol::packets::packet_definer::PacketInfo {
+                                name: stringify!($name),
+                                state: PacketState::$state,
+                                direction: PacketDirection::$direction,
+                                id: $packetID,
+                                other_states: &[$((PacketState::$alt_state, $alt_id)),*],
+                                doc: $crate::packet_doc!([] $([$($struct_meta)*])*),
+                                fields: &[$($crate::protocol::packets::packet_definer::PacketField {
+                                    name: stringify!($field),
+                                    typ: stringify!($t),
+                                }),*],
+                            };
                         }
 
                         impl From<[<$name Packet>]> for Packet {
@@ -94,11 +189,13 @@ mod macros {
                             }
                         }
 
-                        impl From<Packet> for [<$name Packet>] {
-                            fn from(p: Packet) -> Self {
+                        impl TryFrom<Packet> for [<$name Packet>] {
+                            type Error = SerializingErr;
+
+                            fn try_from(p: Packet) -> Result<Self, Self::Error> {
                                 match p {
-                                    Packet::$name(p) => p,
-                                    _ => panic!("Invalid conversion")
+                                    Packet::$name(p) => Ok(p),
+                                    other => Err(SerializingErr::UniqueFailure(format!("Expected a {} packet, but got {:?} in {:?}", stringify!($name), other.packet_id(), other.state()))),
                                 }
                             }
                         }
@@ -106,77 +203,141 @@ mod macros {
                 )*
             )*
 
+            /// The description of every packet, in the order they are defined. See [PacketInfo](crate::protocol::packets::packet_definer::PacketInfo).
+            pub static PACKET_REGISTRY: &[$crate::protocol::packets::packet_definer::PacketInfo] = &[
+                $($($([<$name Packet>]::INFO,)*)*)*
+            ];
+
             $crate::as_item!( // weird workaround from mcproto-rs
-                #[derive(Debug, Clone, PartialEq)]
+                #[derive(Debug, Clone, PartialEq, serde::Serialize)]
                 pub enum Packet {
                     $($($($name([<$name Packet>]),)*)*)*
                 }
             );
 
             impl Packet {
+                /// The id of the packet in the first state it is used in, see [Packet::state].
                 pub fn packet_id(&self) -> VarInt {
                     match self {
                         $($($(Packet::$name(_) => VarInt($packetID as i32),)*)*)*
                     }
                 }
 
+                /// The id of the packet in the given state, or `None` if the packet is not used in that state.
+                pub fn packet_id_in(&self, state: PacketState) -> Option<VarInt> {
+                    match self {
+                        $($($(Packet::$name(_) => match state {
+                            PacketState::$state => Some(VarInt($packetID as i32)),
+                            $(PacketState::$alt_state => Some(VarInt($alt_id as i32)),)*
+                            _ => None,
+                        },)*)*)*
+                    }
+                }
+
+                /// The id of the packet in the given protocol version, or `None` if the packet does not exist in that version.
+                pub fn packet_id_for(&self, version: $crate::protocol_types::protocol_verison::ProtocolVerison) -> Option<VarInt> {
+                    $crate::protocol::packets::packet_versions::wire_id(version, self.state(), self.packet_id().0).map(VarInt)
+                }
+
+                /// The first state the packet is used in. See [Packet::states] for packets used in several states.
                 pub fn state(&self) -> PacketState {
                     match self {
                         $($($(Packet::$name(_) => PacketState::$state,)*)*)*
                     }
                 }
 
+                /// Every state the packet is used in.
+                pub fn states(&self) -> &'static [PacketState] {
+                    match self {
+                        $($($(Packet::$name(_) => &[PacketState::$state $(, PacketState::$alt_state)*],)*)*)*
+                    }
+                }
+
                 pub fn direction(&self) -> PacketDirection {
                     match self {
                         $($($(Packet::$name(_) => PacketDirection::$direction,)*)*)*
                     }
                 }
-            }
 
-            impl McSerialize for Packet {
-                fn mc_serialize(&self, serializer: &mut McSerializer) -> SerializingResult<()> {
-                    let mut length_serializer = McSerializer::new();
+                /// The name of the packet, like `KeepAlive`.
+                pub fn name(&self) -> &'static str {
+                    self.info().name
+                }
+
+                /// The description of the packet in [PACKET_REGISTRY].
+                pub fn info(&self) -> &'static $crate::protocol::packets::packet_definer::PacketInfo {
                     match self {
-                        $($($(Packet::$name(b) => {b.mc_serialize(&mut length_serializer)?}),*)*)*
+                        $($($(Packet::$name(_) => &[<$name Packet>]::INFO,)*)*)*
                     }
+                }
+
+                /// A pretty JSON dump of the packet for logging, like `{"KeepAlive": {"keep_alive_id": 5}}`. Byte arrays
+                /// are summarized in hex, and long arrays are cut short, see [crate::protocol::serialization::dump].
+                pub fn to_json(&self) -> String {
+                    $crate::protocol::serialization::dump::to_json(self)
+                }
+            }
+
+            impl Packet {
+                /// Serialize the packet with its id in the given protocol version, in the first state it is used in.
+                pub fn serialize_version(&self, serializer: &mut McSerializer, version: $crate::protocol_types::protocol_verison::ProtocolVerison) -> SerializingResult<()> {
+                    self.serialize_state_version(serializer, self.state(), version)
+                }
 
-                    let packet_id = self.packet_id();
+                /// Serialize the packet with its id in the given state and protocol version. Returns
+                /// [SerializingErr::InvalidPacketState] if the packet is not used in the state.
+                pub fn serialize_state_version(&self, serializer: &mut McSerializer, state: PacketState, version: $crate::protocol_types::protocol_verison::ProtocolVerison) -> SerializingResult<()> {
+                    serializer.serialize_length_prefixed(|s| self.serialize_body_state_version(s, state, version))
+                }
 
-                    let bytes = packet_id.to_bytes(); // getting the bytes is kind of expensive, so cache it
+                /// Like [Packet::serialize_state_version], but without the length prefix, for framing the packet yourself.
+                pub fn serialize_body_state_version(&self, serializer: &mut McSerializer, state: PacketState, version: $crate::protocol_types::protocol_verison::ProtocolVerison) -> SerializingResult<()> {
+                    let Some(latest_id) = self.packet_id_in(state) else {
+                        return Err(SerializingErr::InvalidPacketState);
+                    };
 
-                    VarInt(length_serializer.output.len() as i32 + bytes.len() as i32).mc_serialize(serializer)?;
-                    bytes.mc_serialize(serializer)?;
-                    serializer.merge(length_serializer);
+                    let Some(packet_id) = $crate::protocol::packets::packet_versions::wire_id(version, state, latest_id.0).map(VarInt) else {
+                        return Err(SerializingErr::NoKnownPacket(format!("Packet {:?} does not exist in version {}", latest_id, version)));
+                    };
 
+                    packet_id.mc_serialize(serializer)?;
+
+                    match self {
+                        $($($(Packet::$name(b) => {b.mc_serialize(serializer)?}),*)*)*
+                    }
 
                     Ok(())
                 }
             }
 
+            impl McSerialize for Packet {
+                fn mc_serialize(&self, serializer: &mut McSerializer) -> SerializingResult<()> {
+                    self.serialize_version(serializer, $crate::protocol_types::protocol_verison::ProtocolVerison::latest())
+                }
+            }
+
             impl StateBasedDeserializer for Packet {
-                /// Deserialize a packet from a byte buffer, given the state and direction of the packet.
-                /// The byte buffer should include the raw packet details such as the packet length and id.
-                fn deserialize_state<'a>(deserializer: &'a mut McDeserializer, state: PacketState, packet_direction: PacketDirection) -> SerializingResult<'a, Self> {
+                /// Deserialize a packet from a byte buffer, given the state and direction of the packet and the protocol
+                /// version it was sent in. The byte buffer should include the raw packet details such as the packet length and id.
+                fn deserialize_state_version<'a>(deserializer: &'a mut McDeserializer, state: PacketState, packet_direction: PacketDirection, version: $crate::protocol_types::protocol_verison::ProtocolVerison) -> SerializingResult<'a, Self> {
                     let length = VarInt::mc_deserialize(deserializer)?;
 
                     let mut sub = deserializer.sub_deserializer_length(length.0 as usize)?;
 
                     let packet_id = VarInt::mc_deserialize(&mut sub)?;
 
+                    let Some(latest_id) = $crate::protocol::packets::packet_versions::latest_id(version, state, packet_id.0) else {
+                        return Err(SerializingErr::NoKnownPacket(format!("Could not find matching packet for destination {:?} and state {:?} with packet id '0x{:X}' in version {}", packet_direction, state, packet_id.0, version)));
+                    };
+
                     $(
                         if state == PacketState::$state {
                             $(
                                 if packet_direction == PacketDirection::$direction {
-                                    match packet_id.0 {
+                                    match latest_id {
                                         $(
                                             $packetID => {
-                                                let a = [<$name Packet>]::mc_deserialize(&mut sub);
-
-                                                if let Ok(a) = a {
-                                                    return Ok(Packet::$name(a));
-                                                } else if let Err(e) = a {
-                                                    return Err(SerializingErr::DeserializationError(format!("Failed to deserialize packet {:?} with id '0x{:X}' with message: '{}'", stringify!($name), packet_id.0, e)));
-                                                }
+                                                return [<$name Packet>]::mc_deserialize(&mut sub).map(Packet::$name).map_err(|e| e.in_field(stringify!($name), deserializer));
                                             }
                                         )*
 
@@ -187,9 +348,54 @@ mod macros {
                         }
                     )*
 
+                    // packets that are also used in a later state
+                    $($($(
+                        $(
+                            if state == PacketState::$alt_state && packet_direction == PacketDirection::$direction && latest_id == $alt_id {
+                                return [<$name Packet>]::mc_deserialize(&mut sub).map(Packet::$name).map_err(|e| e.in_field(stringify!($name), deserializer));
+                            }
+                        )*
+                    )*)*)*
+
                     return Err(SerializingErr::NoKnownPacket(format!("Could not find matching packet for destination {:?} and state {:?} with packet id '0x{:X}'", packet_direction, state, packet_id.0)));
                 }
             }
+
+            /// A round trip test for every packet, built from its [McDefault](crate::protocol::testing::McDefault) value.
+            #[cfg(test)]
+            mod packet_round_trip {
+                $($($(
+                    #[test]
+                    fn [<$name:snake>]() {
+                        let packet = super::Packet::$name(<super::[<$name Packet>] as $crate::protocol::testing::McDefault>::mc_default());
+
+                        assert_eq!(packet.packet_id(), super::VarInt($packetID as i32));
+                        assert_eq!(packet.name(), stringify!($name));
+                        for state in packet.states() {
+                            let info = $crate::protocol::packets::packet_definer::PacketInfo::by_id(*state, packet.direction(), packet.packet_id_in(*state).unwrap().0);
+                            assert_eq!(info, Some(packet.info()), "another packet has the same id in {state:?}");
+                        }
+                        $crate::protocol::testing::packet_testing::assert_round_trip(&packet);
+                    }
+                )*)*)*
+            }
+
+            /// A round trip property for every packet, checked against random
+            /// [McArbitrary](crate::protocol::testing::arbitrary::McArbitrary) values.
+            #[cfg(test)]
+            mod packet_properties {
+                use $crate::protocol::testing::arbitrary::McArbitrary;
+
+                ::proptest::proptest! {
+                    #![proptest_config(::proptest::test_runner::Config::with_cases(64))]
+                    $($($(
+                        #[test]
+                        fn [<$name:snake>](packet in <super::[<$name Packet>] as McArbitrary>::mc_arbitrary()) {
+                            $crate::protocol::testing::packet_testing::assert_round_trip(&super::Packet::$name(packet));
+                        }
+                    )*)*)*
+                }
+            }
             } // end paste
         };
     }
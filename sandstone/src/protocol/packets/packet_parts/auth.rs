@@ -6,11 +6,12 @@ use crate::protocol::serialization::McSerialize;
 use crate::protocol::serialization::McSerializer;
 use crate::protocol::serialization::SerializingResult;
 use crate::protocol::testing::McDefault;
-use sandstone_derive::{McDefault, McDeserialize, McSerialize};
+use sandstone_derive::{McArbitrary, McDefault, McDeserialize, McSerialize};
+use serde::Serialize;
 
 /// # Public Key (Packet Part)
 /// Type used to communicate a public key on network.
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct PublicKeyNetwork {
 	pub expires_at: i64,
 	pub public_key: PrefixedArray<u8>,
@@ -1,3 +1,4 @@
+use crate::prelude::*;
 use crate::bitflag;
 use crate::protocol::game::effects::particle::Particle;
 use crate::protocol::serialization::serializer_error::SerializingErr;
@@ -7,11 +8,16 @@ use crate::protocol::serialization::McDeserializer;
 use crate::protocol::serialization::McSerialize;
 use crate::protocol::serialization::McSerializer;
 use crate::protocol::serialization::SerializingResult;
+#[cfg(any(test, feature = "proptest"))]
+use crate::protocol::testing::arbitrary::McArbitrary;
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::internal_types::IDSet;
 use crate::protocol_types::datatypes::nbt::NbtCompound;
 use crate::protocol_types::datatypes::var_types::VarInt;
-use sandstone_derive::{McDefault, McDeserialize, McSerialize, TypeEnum, VarIntEnum};
+use sandstone_derive::{McArbitrary, McDefault, McDeserialize, McSerialize, TypeEnum, VarIntEnum};
+use serde::Serialize;
+#[cfg(any(test, feature = "proptest"))]
+use proptest::prelude::{any, BoxedStrategy, Strategy};
 
 bitflag!(CommandBlockFlag: u8 {
 	track_output, is_conditional, automatic
@@ -21,7 +27,7 @@ bitflag!(StructureBlockFlags: u8 {
 	ignore_entities, show_air, show_bounding_box, strict_placement
 });
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct BlockParticleAlternative {
 	pub particle_id: VarInt,
 	pub particle_data: Particle,
@@ -30,7 +36,7 @@ pub struct BlockParticleAlternative {
 	pub weight: VarInt,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub struct BlockPredicate {
 	pub blocks: PrefixedOptional<IDSet>,
 	pub properties: PrefixedOptional<PrefixedArray<BlockProperty>>,
@@ -69,7 +75,7 @@ impl McDefault for BlockPredicate {
 	}
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct BlockProperty {
 	pub name: String,
 	pub is_exact: bool,
@@ -131,11 +137,26 @@ impl McDefault for BlockProperty {
 	}
 }
 
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for BlockProperty {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		(String::mc_arbitrary(), any::<bool>(), String::mc_arbitrary(), String::mc_arbitrary())
+			.prop_map(|(name, is_exact, first, second)| Self {
+				name,
+				is_exact,
+				exact_value: is_exact.then(|| first.clone()),
+				min_value: (!is_exact).then_some(first),
+				max_value: (!is_exact).then_some(second),
+			})
+			.boxed()
+	}
+}
+
 /// # Command Block Mode (Packet Part)
 /// The mode for a command block
 ///
 /// https://minecraft.wiki/w/Java_Edition_protocol/Packets#Program_Command_Block
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum CommandBlockMode {
 	Chain = 0,
 	Repeating = 1,
@@ -146,7 +167,7 @@ pub enum CommandBlockMode {
 /// The action being undertaken by the stucture block
 ///
 /// https://minecraft.wiki/w/Java_Edition_protocol/Packets#Program_Structure_Block
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum StructureBlockAction {
 	UpdateData = 0,
 	SaveStructure = 1,
@@ -158,7 +179,7 @@ pub enum StructureBlockAction {
 /// The mode of the structure block
 ///
 /// https://minecraft.wiki/w/Java_Edition_protocol/Packets#Program_Structure_Block
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum StructureBlockMode {
 	Save = 0,
 	Load = 1,
@@ -170,7 +191,7 @@ pub enum StructureBlockMode {
 /// The mirror state of the structure block
 ///
 /// https://minecraft.wiki/w/Java_Edition_protocol/Packets#Program_Structure_Block
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum StructureBlockMirror {
 	None = 0,
 	LeftRight = 1,
@@ -182,7 +203,7 @@ pub enum StructureBlockMirror {
 ///
 /// https://minecraft.wiki/w/Java_Edition_protocol/Packets#Program_Structure_Block <br>
 /// https://minecraft.wiki/w/Java_Edition_protocol/Packets#Test_Instance_Block_Action
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum SpecialBlockRotation {
 	None = 0,
 	Clockwise90 = 1,
@@ -194,7 +215,7 @@ pub enum SpecialBlockRotation {
 /// The mode of the test block
 ///
 /// https://minecraft.wiki/w/Java_Edition_protocol/Packets#Set_Test_Block
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum TestBlockMode {
 	Start = 0,
 	Log = 1,
@@ -206,7 +227,7 @@ pub enum TestBlockMode {
 /// Describes the current attempted action for a Test Instance Block
 ///
 /// https://minecraft.wiki/w/Java_Edition_protocol/Packets#Test_Instance_Block_Action
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum TestInstanceBlockActionAction {
 	Init = 0,
 	Query = 1,
@@ -221,7 +242,7 @@ pub enum TestInstanceBlockActionAction {
 /// The status of the test instance.
 ///
 /// https://minecraft.wiki/w/Java_Edition_protocol/Packets#Test_Instance_Block_Action
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum TestInstanceStatus {
 	Cleared = 0,
 	Running = 1,
@@ -232,7 +253,7 @@ pub enum TestInstanceStatus {
 /// The face of a block
 ///
 /// As seen here: https://minecraft.wiki/w/Java_Edition_protocol/Packets#Player_Action
-#[derive(TypeEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(TypeEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 #[type_enum(i8)]
 pub enum BlockFace {
 	Bottom = 0,
@@ -1,6 +1,7 @@
 //! Debug subscription types. Used in certain packets to help with debugging the game.
 //! https://minecraft.wiki/w/Java_Edition_protocol
 
+use crate::prelude::*;
 use crate::protocol::serialization::serializer_error::SerializingErr;
 use crate::protocol::serialization::serializer_types::{PrefixedArray, PrefixedOptional};
 use crate::protocol::serialization::McDeserialize;
@@ -11,11 +12,12 @@ use crate::protocol::serialization::SerializingResult;
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::game_types::Position;
 use crate::protocol_types::datatypes::var_types::VarInt;
-use sandstone_derive::{McDefault, McDeserialize, McSerialize, VarIntEnum};
+use sandstone_derive::{McArbitrary, McDefault, McDeserialize, McSerialize, VarIntEnum};
+use serde::Serialize;
 
 /// A debug subscription event: a debug subscription type followed by its data. The type tag and
 /// the data are carried together by the tagged [DebugSubscriptionData] enum.
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct DebugSubscriptionEvent {
 	pub data: DebugSubscriptionData,
 }
@@ -23,14 +25,14 @@ pub struct DebugSubscriptionEvent {
 /// A debug subscription update: a debug subscription type followed by its optional data. The type
 /// tag travels with the data inside [DebugSubscriptionData]; when absent the subscription carries
 /// no value.
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct DebugSubscriptionUpdate {
 	pub data: PrefixedOptional<DebugSubscriptionData>,
 }
 
 /// The value of a debug subscription. The VarInt discriminant identifies the subscription type and
 /// selects the associated data.
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 #[repr(i32)]
 pub enum DebugSubscriptionData {
 	DedicatedServerTickTime = 0,
@@ -51,7 +53,7 @@ pub enum DebugSubscriptionData {
 	GameEvent(GameEventDebugData) = 15,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct BeeDebugData {
 	pub hive_position: PrefixedOptional<Position>,
 	pub flower_position: PrefixedOptional<Position>,
@@ -59,7 +61,7 @@ pub struct BeeDebugData {
 	pub blacklisted_hives: PrefixedArray<Position>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct VillagerBrainDebugData {
 	pub name: String,
 	pub profession: String,
@@ -77,20 +79,20 @@ pub struct VillagerBrainDebugData {
 	pub potential_pois: PrefixedArray<Position>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct BreezeDebugData {
 	pub attack_target: PrefixedOptional<VarInt>,
 	pub jump_target: PrefixedOptional<Position>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct GoalSelectorDebugData {
 	pub priority: VarInt,
 	pub is_running: bool,
 	pub name: String,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct EntityPathDebugData {
 	pub reached: bool,
 	pub next_block_index: i32,
@@ -102,14 +104,14 @@ pub struct EntityPathDebugData {
 	pub max_node_distance: f32,
 }
 
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum EntityBlockIntersectionType {
 	InBlock = 0,
 	InFluid = 1,
 	InAir = 2,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct BeeHiveDebugData {
 	/// ID in the `minecraft:block` registry.
 	pub typ: VarInt,
@@ -118,7 +120,7 @@ pub struct BeeHiveDebugData {
 	pub sedated: bool,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct PoiDebugData {
 	pub position: Position,
 	/// ID in the `minecraft:point_of_interest_type` registry.
@@ -126,17 +128,17 @@ pub struct PoiDebugData {
 	pub free_ticket_count: VarInt,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct RaidDebugData {
 	pub positions: PrefixedArray<Position>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct StructureDebugData {
 	pub structures: PrefixedArray<DebugStructureInfo>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct GameEventDebugData {
 	/// ID in the `minecraft:game_event` registry.
 	pub event: VarInt,
@@ -145,7 +147,7 @@ pub struct GameEventDebugData {
 	pub z: f64,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct DebugPathNode {
 	pub x: i32,
 	pub y: i32,
@@ -157,7 +159,7 @@ pub struct DebugPathNode {
 	pub f: f32,
 }
 
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum DebugPathNodeType {
 	Blocked = 0,
 	Open = 1,
@@ -187,14 +189,14 @@ pub enum DebugPathNodeType {
 	DangerTrapdoor = 25,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct DebugStructureInfo {
 	pub bounding_box_min: Position,
 	pub bounding_box_max: Position,
 	pub pieces: PrefixedArray<DebugStructurePiece>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct DebugStructurePiece {
 	pub bounding_box_min: Position,
 	pub bounding_box_max: Position,
@@ -203,15 +205,9 @@ pub struct DebugStructurePiece {
 
 /// The type of a debug sample, determining how the accompanying `Prefixed Array of Long` is
 /// interpreted.
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum DebugSampleType {
 	/// Four tick-related metrics in nanoseconds: full tick time, server tick time, tasks time,
 	/// and idle time.
 	TickTime = 0,
 }
-
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
-pub struct CustomReportDetail {
-	pub title: String,
-	pub description: String,
-}
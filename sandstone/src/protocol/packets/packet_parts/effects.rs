@@ -1,13 +1,19 @@
+use crate::prelude::*;
 use crate::protocol::game::effects::sound::SoundEvent;
 use crate::protocol::serialization::serializer_error::SerializingErr;
 use crate::protocol::serialization::serializer_types::{PrefixedArray, PrefixedOptional};
 use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, SerializingResult};
+#[cfg(any(test, feature = "proptest"))]
+use crate::protocol::testing::arbitrary::McArbitrary;
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::internal_types::{IDSet, IDorX};
 use crate::protocol_types::datatypes::var_types::VarInt;
-use sandstone_derive::{McDefault, McDeserialize, McSerialize, VarIntEnum};
+use sandstone_derive::{McArbitrary, McDefault, McDeserialize, McSerialize, VarIntEnum};
+use serde::Serialize;
+#[cfg(any(test, feature = "proptest"))]
+use proptest::prelude::{any, BoxedStrategy, Strategy};
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct FireworkExplosion {
 	pub shape: VarInt,
 	pub colors: PrefixedArray<i32>,
@@ -16,7 +22,7 @@ pub struct FireworkExplosion {
 	pub has_twinkle: bool,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct PotionEffectDetail {
 	pub amplifier: VarInt,
 	pub duration: VarInt,
@@ -64,13 +70,36 @@ impl McDefault for PotionEffectDetail {
 	}
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for PotionEffectDetail {
+	/// Details with hidden effects nested up to 2 deep.
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		let detail = |hidden_effect: BoxedStrategy<PrefixedOptional<Box<PotionEffectDetail>>>| {
+			(VarInt::mc_arbitrary(), VarInt::mc_arbitrary(), any::<bool>(), any::<bool>(), any::<bool>(), hidden_effect).prop_map(
+				|(amplifier, duration, ambient, show_particles, show_icon, hidden_effect)| Self {
+					amplifier,
+					duration,
+					ambient,
+					show_particles,
+					show_icon,
+					hidden_effect,
+				},
+			)
+		};
+
+		detail(proptest::strategy::Just(PrefixedOptional::new(None)).boxed())
+			.prop_recursive(2, 4, 1, move |inner| detail(proptest::option::of(inner.prop_map(Box::new)).prop_map(PrefixedOptional::new).boxed()))
+			.boxed()
+	}
+}
+
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct PotionEffect {
 	pub type_id: VarInt,
 	pub detail: PotionEffectDetail,
 }
 
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 #[repr(i32)]
 pub enum ConsumeEffect {
 	ApplyEffects(PrefixedArray<PotionEffect>, f32) = 0,
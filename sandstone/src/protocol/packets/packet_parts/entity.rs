@@ -6,7 +6,8 @@ use crate::protocol::serialization::McSerializer;
 use crate::protocol::serialization::SerializingResult;
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::internal_types::Angle;
-use sandstone_derive::{McDefault, McDeserialize, McSerialize};
+use sandstone_derive::{McArbitrary, McDefault, McDeserialize, McSerialize};
+use serde::Serialize;
 
 /// The status code sent in the Entity Event packet, represented as a byte.
 ///
@@ -15,7 +16,7 @@ use sandstone_derive::{McDefault, McDeserialize, McSerialize};
 /// code to keep them unambiguous.
 ///
 /// https://minecraft.wiki/w/Java_Edition_protocol/Entity_statuses
-#[derive(Debug, Clone, Hash, PartialEq)]
+#[derive(McArbitrary, Serialize, Debug, Clone, Hash, PartialEq)]
 #[repr(i8)]
 pub enum EntityStatusEnum {
 	/// Arrow
@@ -237,7 +238,7 @@ impl McDeserialize for EntityStatusEnum {
 	}
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct MinecartMoveStep {
 	pub x: f64,
 	pub y: f64,
@@ -1,3 +1,4 @@
+use crate::prelude::*;
 use crate::protocol::game::effects::sound::SoundEvent;
 use crate::protocol::game::player::inventory::components::StructuredComponent;
 use crate::protocol::game::player::inventory::slotdata::SlotData;
@@ -8,14 +9,19 @@ use crate::protocol::serialization::McDeserializer;
 use crate::protocol::serialization::McSerialize;
 use crate::protocol::serialization::McSerializer;
 use crate::protocol::serialization::SerializingResult;
+#[cfg(any(test, feature = "proptest"))]
+use crate::protocol::testing::arbitrary::{registry_id, McArbitrary};
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::chat::TextComponent;
 use crate::protocol_types::datatypes::internal_types::IDorX;
 use crate::protocol_types::datatypes::var_types::VarInt;
-use sandstone_derive::{McDefault, McDeserialize, McSerialize, VarIntEnum};
+use sandstone_derive::{McArbitrary, McDefault, McDeserialize, McSerialize, VarIntEnum};
+use serde::Serialize;
+#[cfg(any(test, feature = "proptest"))]
+use proptest::prelude::{prop_oneof, BoxedStrategy, Strategy};
 
 /// The icon type displayed on a map for a Map Icon entry.
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum MapIconType {
 	WhiteArrow = 0,
 	GreenArrow = 1,
@@ -54,7 +60,7 @@ pub enum MapIconType {
 	TrialChambers = 34,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct MapIcons {
 	pub typ: MapIconType,
 	pub x: i8,
@@ -63,20 +69,20 @@ pub struct MapIcons {
 	pub display_name: PrefixedOptional<TextComponent>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct MapColorPatch {
 	pub columns: u8,
-	#[mc(deserialize_if = columns == 0)]
+	#[mc(include_if = columns == 0)]
 	pub rows: Option<u8>,
-	#[mc(deserialize_if = columns == 0)]
+	#[mc(include_if = columns == 0)]
 	pub color_x: Option<u8>,
-	#[mc(deserialize_if = columns == 0)]
+	#[mc(include_if = columns == 0)]
 	pub color_z: Option<u8>,
-	#[mc(deserialize_if = columns == 0)]
+	#[mc(include_if = columns == 0)]
 	pub data: Option<PrefixedArray<u8>>
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct Trade {
 	pub input_item_1: TradeItem,
 	pub output_item: SlotData,
@@ -90,27 +96,27 @@ pub struct Trade {
 	pub demand: i32
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct TradeItem {
 	pub item_id: VarInt,
 	pub item_count: VarInt,
 	pub structured_components: PrefixedArray<StructuredComponent>
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct TrimMaterial {
 	pub suffix: String,
 	pub overrides: PrefixedArray<TrimMaterialOverride>,
 	pub description: TextComponent,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct TrimMaterialOverride {
 	pub armor_material: String,
 	pub asset_name: String,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct TrimPattern {
 	pub asset_name: String,
 	pub template_item: VarInt,
@@ -118,7 +124,7 @@ pub struct TrimPattern {
 	pub decal: bool,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum IdOrTrimMaterial {
 	Registry(VarInt),
 	Inline(Box<TrimMaterial>),
@@ -156,7 +162,14 @@ impl McDefault for IdOrTrimMaterial {
 	}
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for IdOrTrimMaterial {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		prop_oneof![registry_id().prop_map(IdOrTrimMaterial::Registry), TrimMaterial::mc_arbitrary().prop_map(|value| IdOrTrimMaterial::Inline(Box::new(value))),].boxed()
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum IdOrTrimPattern {
 	Registry(VarInt),
 	Inline(Box<TrimPattern>),
@@ -194,7 +207,14 @@ impl McDefault for IdOrTrimPattern {
 	}
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for IdOrTrimPattern {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		prop_oneof![registry_id().prop_map(IdOrTrimPattern::Registry), TrimPattern::mc_arbitrary().prop_map(|value| IdOrTrimPattern::Inline(Box::new(value))),].boxed()
+	}
+}
+
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct Instrument {
 	pub sound_event: IDorX<SoundEvent>,
 	pub use_duration: f32,
@@ -202,7 +222,7 @@ pub struct Instrument {
 	pub description: TextComponent,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum IdOrInstrument {
 	Registry(VarInt),
 	Inline(Box<Instrument>),
@@ -240,13 +260,20 @@ impl McDefault for IdOrInstrument {
 	}
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for IdOrInstrument {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		prop_oneof![registry_id().prop_map(IdOrInstrument::Registry), Instrument::mc_arbitrary().prop_map(|value| IdOrInstrument::Inline(Box::new(value))),].boxed()
+	}
+}
+
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct BannerPatternDef {
 	pub asset_id: String,
 	pub translation_key: String,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum IdOrBannerPattern {
 	Registry(VarInt),
 	Inline(BannerPatternDef),
@@ -284,7 +311,14 @@ impl McDefault for IdOrBannerPattern {
 	}
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for IdOrBannerPattern {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		prop_oneof![registry_id().prop_map(IdOrBannerPattern::Registry), BannerPatternDef::mc_arbitrary().prop_map(IdOrBannerPattern::Inline),].boxed()
+	}
+}
+
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct PaintingVariant {
 	pub width: VarInt,
 	pub height: VarInt,
@@ -293,7 +327,7 @@ pub struct PaintingVariant {
 	pub author: PrefixedOptional<TextComponent>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum IdOrPaintingVariant {
 	Registry(VarInt),
 	Inline(Box<PaintingVariant>),
@@ -329,4 +363,11 @@ impl McDefault for IdOrPaintingVariant {
 	fn mc_default() -> Self {
 		IdOrPaintingVariant::Registry(VarInt(0))
 	}
-}
\ No newline at end of file
+}
+
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for IdOrPaintingVariant {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		prop_oneof![registry_id().prop_map(IdOrPaintingVariant::Registry), PaintingVariant::mc_arbitrary().prop_map(|value| IdOrPaintingVariant::Inline(Box::new(value))),].boxed()
+	}
+}
@@ -6,9 +6,10 @@ use crate::protocol::serialization::McSerializer;
 use crate::protocol::serialization::SerializingResult;
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::var_types::VarInt;
-use sandstone_derive::{McDefault, VarIntEnum};
+use sandstone_derive::{McArbitrary, McDefault, VarIntEnum};
+use serde::Serialize;
 
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 #[repr(i32)]
 pub enum DyeColor {
 	White = 0,
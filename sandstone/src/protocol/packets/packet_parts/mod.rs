@@ -12,6 +12,7 @@ pub mod scoreboard;
 pub mod sound;
 pub mod stats;
 
+use crate::prelude::*;
 use crate::bitflag;
 use crate::protocol::game::player::inventory::slotdata::SlotData;
 use crate::protocol::game::player::inventory::slots::{RecipeDisplay, SlotDisplay};
@@ -19,13 +20,18 @@ use crate::protocol::game::world::chunk::BiomeByteData;
 use crate::protocol::serialization::serializer_error::SerializingErr;
 use crate::protocol::serialization::serializer_types::{PrefixedArray, PrefixedOptional};
 use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, SerializingResult};
+#[cfg(any(test, feature = "proptest"))]
+use crate::protocol::testing::arbitrary::{McArbitrary, MAX_ARBITRARY_ITEMS};
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::chat::TextComponent;
 use crate::protocol_types::datatypes::game_types::EquipmentSlot;
 use crate::protocol_types::datatypes::internal_types::IDSet;
 use crate::protocol_types::datatypes::nbt::NbtCompound;
 use crate::protocol_types::datatypes::var_types::VarInt;
-use sandstone_derive::{McDefault, McDeserialize, McSerialize, TypeEnum, VarIntEnum};
+use sandstone_derive::{McArbitrary, McDefault, McDeserialize, McSerialize, TypeEnum, VarIntEnum};
+use serde::Serialize;
+#[cfg(any(test, feature = "proptest"))]
+use proptest::prelude::{BoxedStrategy, Strategy};
 use uuid::Uuid;
 
 bitflag!(PlayerAbilityFlags: u8 {
@@ -44,61 +50,99 @@ bitflag!(BossBarFlags: u8 {
 	should_darken_sky, is_dragon_bar, create_fog
 });
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct LoginPluginSpec {
 	pub(crate) message_id: VarInt,
 	pub(crate) success: bool,
-	#[mc(deserialize_if = success)]
+	#[mc(include_if = success)]
 	pub(crate) data: Option<Vec<u8>>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq, Eq, Hash)]
+impl LoginPluginSpec {
+	/// Create a response to the request with the given id. A response without data is unsuccessful.
+	pub fn new(message_id: VarInt, data: Option<Vec<u8>>) -> Self {
+		Self {
+			message_id,
+			success: data.is_some(),
+			data,
+		}
+	}
+}
+
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct AddResourcePackSpec {
 	pub(crate) uuid: Uuid,
 	pub(crate) url: String,
 	pub(crate) hash: String,
 	pub(crate) forced: bool,
 	pub(crate) has_prompt_message: bool,
-	#[mc(deserialize_if = has_prompt_message)]
+	#[mc(include_if = has_prompt_message)]
 	pub(crate) prompt_message: Option<String>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq, Eq)]
+impl AddResourcePackSpec {
+	/// Describe a resource pack for the client to download. The hash is the hex encoded SHA-1 of the pack,
+	/// which the client uses to cache it. Forced packs can not be declined without disconnecting.
+	pub fn new(uuid: Uuid, url: String, hash: String, forced: bool, prompt_message: Option<String>) -> Self {
+		Self {
+			uuid,
+			url,
+			hash,
+			forced,
+			has_prompt_message: prompt_message.is_some(),
+			prompt_message,
+		}
+	}
+
+	pub fn get_uuid(&self) -> Uuid {
+		self.uuid
+	}
+
+	pub fn get_url(&self) -> &str {
+		&self.url
+	}
+
+	pub fn is_forced(&self) -> bool {
+		self.forced
+	}
+}
+
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq, Eq)]
 pub struct LoginCookieResponseSpec {
 	key: String,
 	has_payload: bool,
 	payload_length: VarInt,
-	#[mc(deserialize_if = has_payload)]
+	#[mc(include_if = has_payload)]
 	payload: Option<Vec<u8>>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq, Eq)]
 pub struct ResourcePackEntry {
 	pub namespace: String,
 	pub id: String,
 	pub version: String,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct Tag {
 	pub identifier: String,
 	pub entries: PrefixedArray<VarInt>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq, Eq)]
 pub struct ProtocolPropertyElement {
 	pub name: String,
 	pub value: String,
 	pub signature: PrefixedOptional<String>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq, Eq)]
 pub struct PropertySet {
 	pub identifier: String,
 	pub items: PrefixedArray<VarInt>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct RecipeBookEntry {
 	pub recipe_id: VarInt,
 	pub display: RecipeDisplay,
@@ -108,7 +152,7 @@ pub struct RecipeBookEntry {
 	pub flags: u8,
 }
 
-#[derive(McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McSerialize, McDeserialize, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub struct StonecutterRecipe {
 	pub id_set: IDSet,
 	pub slot_display: SlotDisplay,
@@ -127,7 +171,7 @@ impl McDefault for StonecutterRecipe {
 	}
 }
 
-#[derive(TypeEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(TypeEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 #[type_enum(u8)]
 pub enum GameEventType {
 	NoRespawnBlockAvailable = 0,
@@ -146,21 +190,21 @@ pub enum GameEventType {
 	StartWaitingForLevelChunks = 13,
 }
 
-#[derive(McSerialize, McDeserialize, McDefault, Debug, Clone, PartialEq)]
+#[derive(McSerialize, McDeserialize, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub struct AttributeProperty {
 	pub id: VarInt,
 	pub value: f64,
 	pub modifiers: PrefixedArray<ModifierData>,
 }
 
-#[derive(McSerialize, McDeserialize, McDefault, Debug, Clone, PartialEq)]
+#[derive(McSerialize, McDeserialize, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub struct ModifierData {
 	pub id: String,
 	pub amount: f64,
 	pub operation: ModifierOperation,
 }
 
-#[derive(TypeEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(TypeEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 #[type_enum(u8)]
 pub enum ModifierOperation {
 	AddSubtractAmount = 0,
@@ -168,19 +212,19 @@ pub enum ModifierOperation {
 	MultiplyPercentage = 2,
 }
 
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum ClientStatusAction {
 	PerformRespawn = 0,
 	RequestStats = 1,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub struct EquipmentEntry {
 	pub slot: EquipmentSlot,
 	pub item: SlotData,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct EquipmentList {
 	pub entries: Vec<EquipmentEntry>,
 }
@@ -239,6 +283,18 @@ impl McDefault for EquipmentList {
 	}
 }
 
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for EquipmentList {
+	/// The list is never empty, since the last entry is the one without the 0x80 bit set.
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		proptest::collection::vec(EquipmentEntry::mc_arbitrary(), 1..=MAX_ARBITRARY_ITEMS)
+			.prop_map(|entries| Self {
+				entries,
+			})
+			.boxed()
+	}
+}
+
 impl McDefault for EquipmentEntry {
 	fn mc_default() -> Self {
 		Self {
@@ -248,7 +304,7 @@ impl McDefault for EquipmentEntry {
 	}
 }
 
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum PlayerCommandAction {
 	LeaveBed = 0,
 	StartSprinting = 1,
@@ -259,20 +315,20 @@ pub enum PlayerCommandAction {
 	StartFlyingWithElytra = 6,
 }
 
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum InteractType {
 	Interact = 0,
 	Attack = 1,
 	InteractAt = 2,
 }
 
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum InteractHand {
 	Main = 0,
 	OffHand = 1,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct CustomReportDetails {
 	pub title: String,
 	pub description: String,
@@ -281,17 +337,17 @@ pub struct CustomReportDetails {
 /// A single entry in the Server Links packet. The label is a discriminated union: the
 /// `is_built_in` boolean selects between a known [ServerLinkStandardLabel] (VarInt enum) and
 /// a custom [TextComponent]. Exactly one of `built_in_label`/`custom_label` is present on the wire.
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct ServerLink {
 	pub is_built_in: bool,
-	#[mc(deserialize_if = is_built_in)]
+	#[mc(include_if = is_built_in)]
 	pub built_in_label: Option<ServerLinkStandardLabel>,
-	#[mc(deserialize_if = !is_built_in)]
+	#[mc(include_if = !is_built_in)]
 	pub custom_label: Option<TextComponent>,
 	pub url: String,
 }
 
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum ServerLinkStandardLabel {
 	#[doc = "Displayed on connection error screen; included as a comment in the disconnection report."]
 	BugReport = 0,
@@ -306,7 +362,7 @@ pub enum ServerLinkStandardLabel {
 	Announcements = 9,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 #[repr(i32)]
 pub enum BossBarUpdateAction {
 	Add {
@@ -332,7 +388,7 @@ pub enum BossBarUpdateAction {
 	} = 5,
 }
 
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum BossBarColor {
 	Pink = 0,
 	Blue = 1,
@@ -343,7 +399,7 @@ pub enum BossBarColor {
 	White = 6,
 }
 
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum BossBarDivisions {
 	NoDivision = 0,
 	SixNotches = 1,
@@ -352,7 +408,7 @@ pub enum BossBarDivisions {
 	TwentyNotches = 4,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct ChunkBiomeData {
 	pub z: i32,
 	pub x: i32,
@@ -360,27 +416,27 @@ pub struct ChunkBiomeData {
 	pub data: BiomeByteData,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct TooltipMatch {
 	pub matc: String,
 	pub tooltip: PrefixedOptional<TextComponent>,
 }
 
 /// Network representation of chat type.
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct ChatTypeNetwork {
 	pub chat: ChatTypeEntry,
 	pub narration: ChatTypeEntry,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct ChatTypeEntry {
 	pub translation_key: String,
 	parameters: PrefixedArray<ChatTypeParemeter>,
 	style: NbtCompound,
 }
 
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum ChatTypeParemeter {
 	Sender = 0,
 	Target = 1,
@@ -7,13 +7,14 @@ use crate::protocol::serialization::McSerializer;
 use crate::protocol::serialization::SerializingResult;
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::var_types::VarInt;
-use sandstone_derive::{McDefault, McDeserialize, McSerialize, VarIntEnum};
+use sandstone_derive::{McArbitrary, McDefault, McDeserialize, McSerialize, VarIntEnum};
+use serde::Serialize;
 
 bitflag!(TeleportFlags: i32 {
 	relative_x, relative_y, relative_z, relative_yaw, relative_pitch, relative_velocity_x, relative_velocity_y, relative_velocity_z, rotate_velocity
 });
 
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 #[repr(i32)]
 pub enum WaypointData {
 	Empty = 0,
@@ -24,7 +25,7 @@ pub enum WaypointData {
 
 /// # Position VarInt (Packet Part)
 /// Represents a block position in the world as 3 VarInts
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct PositionVarInt {
 	pub x: VarInt,
 	pub y: VarInt,
@@ -33,13 +34,13 @@ pub struct PositionVarInt {
 
 /// # Chunk Position VarInt (Packet Part)
 /// Represents a chunk location in the world as 2 VarInts
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct ChunkPositionVarInt {
 	pub x: VarInt,
 	pub z: VarInt,
 }
 
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum WaypointOperation {
 	Track = 0,
 	Untrack = 1,
@@ -50,7 +51,7 @@ pub enum WaypointOperation {
 /// Used to communicate a change in player status.
 ///
 /// https://minecraft.wiki/w/Java_Edition_protocol/Packets#Player_Action
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum PlayerActionStatus {
 	StartedDigging = 0,
 	CancelledDigging = 1,
@@ -65,7 +66,7 @@ pub enum PlayerActionStatus {
 /// Action that occurs when interacting with Advancements tab
 ///
 /// https://minecraft.wiki/w/Java_Edition_protocol/Packets#Seen_Advancements
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum SeenAdvancementsAction {
 	OpenedTab = 0,
 	ClosedScreen = 1,
@@ -75,7 +76,7 @@ pub enum SeenAdvancementsAction {
 /// The hand involved in an item use event
 ///
 /// https://minecraft.wiki/w/Java_Edition_protocol/Packets#Use_Item
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum UseItemHand {
 	MainHand = 0,
 	OffHand = 1
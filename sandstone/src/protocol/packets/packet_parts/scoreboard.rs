@@ -1,3 +1,4 @@
+use crate::prelude::*;
 use crate::bitflag;
 use crate::protocol::serialization::serializer_error::SerializingErr;
 use crate::protocol::serialization::serializer_types::PrefixedArray;
@@ -10,13 +11,14 @@ use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::chat::TextComponent;
 use crate::protocol_types::datatypes::nbt::NbtCompound;
 use crate::protocol_types::datatypes::var_types::VarInt;
-use sandstone_derive::{McDefault, McDeserialize, McSerialize, VarIntEnum};
+use sandstone_derive::{McArbitrary, McDefault, McDeserialize, McSerialize, VarIntEnum};
+use serde::Serialize;
 
 bitflag!(FriendlyFlags: u8 {
 	allow_friendly_fire, can_see_invisble_teammates
 });
 
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 #[repr(i32)]
 pub enum ObjectiveNumberFormat {
 	Blank = 0,
@@ -24,14 +26,14 @@ pub enum ObjectiveNumberFormat {
 	Fixed(TextComponent) = 2,
 }
 
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 #[repr(i32)]
 pub enum ObjectiveType {
 	Integer = 0,
 	Hearts = 1,
 }
 
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 #[repr(i32)]
 pub enum NameTagVisibility {
 	Always = 0,
@@ -40,7 +42,7 @@ pub enum NameTagVisibility {
 	HideForOwnTeams = 3,
 }
 
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 #[repr(i32)]
 pub enum CollisionRule {
 	Always = 0,
@@ -49,7 +51,7 @@ pub enum CollisionRule {
 	PushOwnTeam = 3,
 }
 
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 #[repr(i32)]
 pub enum TeamColor {
 	Black = 0,
@@ -76,7 +78,7 @@ pub enum TeamColor {
 	Reset = 21,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct CreateTeam {
 	pub team_display_name: TextComponent,
 	pub friendly_flags: FriendlyFlags,
@@ -88,7 +90,7 @@ pub struct CreateTeam {
 	pub entities: PrefixedArray<String>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct UpdateTeamInfo {
 	pub team_display_name: TextComponent,
 	pub friendly_flags: FriendlyFlags,
@@ -103,7 +105,7 @@ pub struct UpdateTeamInfo {
 /// the variant and determines the layout of the rest of the packet. The discriminant is encoded as
 /// a single `Byte`, not a VarInt, so this implements `McSerialize`/`McDeserialize` by hand rather
 /// than using the enum derive (which would write a VarInt id).
-#[derive(McDefault, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 #[repr(i8)]
 pub enum UpdateTeamOptions {
 	CreateTeam(CreateTeam) = 0,
@@ -154,7 +156,7 @@ impl McDeserialize for UpdateTeamOptions {
 	}
 }
 
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 #[repr(i32)]
 pub enum UpdateScoreFormat {
 	Blank = 0,
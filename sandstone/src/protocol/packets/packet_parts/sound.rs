@@ -1,3 +1,4 @@
+use crate::prelude::*;
 use crate::bitflag;
 use crate::protocol::game::effects::sound::SoundEvent;
 use crate::protocol::serialization::serializer_error::SerializingErr;
@@ -6,17 +7,22 @@ use crate::protocol::serialization::McDeserializer;
 use crate::protocol::serialization::McSerialize;
 use crate::protocol::serialization::McSerializer;
 use crate::protocol::serialization::SerializingResult;
+#[cfg(any(test, feature = "proptest"))]
+use crate::protocol::testing::arbitrary::{registry_id, McArbitrary};
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::chat::TextComponent;
 use crate::protocol_types::datatypes::internal_types::IDorX;
 use crate::protocol_types::datatypes::var_types::VarInt;
-use sandstone_derive::{McDefault, McDeserialize, McSerialize, VarIntEnum};
+use sandstone_derive::{McArbitrary, McDefault, McDeserialize, McSerialize, VarIntEnum};
+use serde::Serialize;
+#[cfg(any(test, feature = "proptest"))]
+use proptest::prelude::{prop_oneof, BoxedStrategy, Strategy};
 
 bitflag!(SoundControlFlags: u8 {
 	source, sound
 });
 
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 #[repr(i32)]
 pub enum SoundSource {
 	Master = 0,
@@ -31,7 +37,7 @@ pub enum SoundSource {
 	Voice = 9,
 }
 
-#[derive(McDefault, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub struct StopSoundDetails {
 	source: Option<SoundSource>,
 	sound: Option<String>
@@ -77,15 +83,15 @@ impl McDeserialize for StopSoundDetails {
 	}
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct InlineSoundEvent {
 	pub name: String,
 	pub has_fixed_range: bool,
-	#[mc(deserialize_if = has_fixed_range)]
+	#[mc(include_if = has_fixed_range)]
 	pub fixed_range: Option<f32>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct JukeboxSong {
 	pub sound_event: IDorX<SoundEvent>,
 	pub description: TextComponent,
@@ -93,7 +99,7 @@ pub struct JukeboxSong {
 	pub output: VarInt,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum IdOrJukeboxSong {
 	Registry(VarInt),
 	Inline(Box<JukeboxSong>),
@@ -129,4 +135,11 @@ impl McDefault for IdOrJukeboxSong {
 	fn mc_default() -> Self {
 		IdOrJukeboxSong::Registry(VarInt(0))
 	}
-}
\ No newline at end of file
+}
+
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for IdOrJukeboxSong {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		prop_oneof![registry_id().prop_map(IdOrJukeboxSong::Registry), JukeboxSong::mc_arbitrary().prop_map(|song| IdOrJukeboxSong::Inline(Box::new(song))),].boxed()
+	}
+}
@@ -6,9 +6,10 @@ use crate::protocol::serialization::McSerializer;
 use crate::protocol::serialization::SerializingResult;
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::var_types::VarInt;
-use sandstone_derive::{McDefault, McDeserialize, McSerialize, VarIntEnum};
+use sandstone_derive::{McArbitrary, McDefault, McDeserialize, McSerialize, VarIntEnum};
+use serde::Serialize;
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct StatisticAward {
 	pub category: StatCategory,
 	pub stat_id: StatID,
@@ -17,7 +18,7 @@ pub struct StatisticAward {
 
 /// Statistic categories defined in the `minecraft:stat_type` registry. Each category determines
 /// which registry the associated statistic ID refers to (block, item, entity_type, or custom_stat).
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum StatCategory {
 	Mined = 0,
 	Crafted = 1,
@@ -32,7 +33,7 @@ pub enum StatCategory {
 
 /// Custom statistic IDs defined in the `minecraft:custom_stat` registry. Used when the
 /// [StatCategory] is [StatCategory::Custom].
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum StatID {
 	LeaveGame = 0,
 	PlayTime = 1,
@@ -0,0 +1,169 @@
+//! The protocol versions supported besides the latest one.
+//!
+//! Packets are defined once, with the ids and fields of [ProtocolVerison::latest]. An older version is only used
+//! in the states where its packets have the same ids and fields, see [supports_state]. For 1.21.7 and 1.21.9
+//! these are handshaking and status, which lets a server answer their server list pings. Logging in is only
+//! supported with the latest version, and a server disconnects older clients after their handshake.
+//!
+//! https://minecraft.wiki/w/Java_Edition_protocol/Packets
+
+use crate::prelude::*;
+use crate::protocol::packets::packet_definer::PacketState;
+use crate::protocol_types::protocol_verison::ProtocolVerison;
+
+/// A version older than the latest one, and the states it can be used in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct OlderVersion {
+	version: ProtocolVerison,
+	/// The states in which every packet has the same id and fields as in the latest version.
+	states: &'static [PacketState],
+}
+
+/// The states whose packets have not changed since 1.21.7.
+const UNCHANGED_STATES: &[PacketState] = &[PacketState::HANDSHAKING, PacketState::STATUS];
+
+const OLDER_VERSIONS: [OlderVersion; 2] = [
+	OlderVersion {
+		version: ProtocolVerison::V1_21_7,
+		states: UNCHANGED_STATES,
+	},
+	OlderVersion {
+		version: ProtocolVerison::V1_21_9,
+		states: UNCHANGED_STATES,
+	},
+];
+
+fn older_version(version: ProtocolVerison) -> Option<&'static OlderVersion> {
+	OLDER_VERSIONS.iter().find(|v| v.version == version)
+}
+
+/// Every protocol version that packets can be sent and received in, newest first. Older versions are only
+/// supported in some states, see [supports_state].
+pub fn supported_versions() -> Vec<ProtocolVerison> {
+	core::iter::once(ProtocolVerison::latest()).chain(OLDER_VERSIONS.iter().rev().map(|v| v.version)).collect()
+}
+
+/// Whether packets can be sent and received in `version` in at least one state.
+pub fn is_supported(version: ProtocolVerison) -> bool {
+	version == ProtocolVerison::latest() || older_version(version).is_some()
+}
+
+/// Whether packets can be sent and received in `version` in `state`. Every state is supported in the latest
+/// version, while older versions are only supported in the states where their packets are unchanged.
+pub fn supports_state(version: ProtocolVerison, state: PacketState) -> bool {
+	version == ProtocolVerison::latest() || older_version(version).is_some_and(|v| v.states.contains(&state))
+}
+
+/// The id of a packet of the latest version in `version`. Returns `None` if the version is not supported in
+/// the state.
+pub fn wire_id(version: ProtocolVerison, state: PacketState, latest_id: i32) -> Option<i32> {
+	supports_state(version, state).then_some(latest_id)
+}
+
+/// The id in the latest version of a packet received with `wire_id` in `version`. Returns `None` if the
+/// version is not supported in the state.
+pub fn latest_id(version: ProtocolVerison, state: PacketState, wire_id: i32) -> Option<i32> {
+	supports_state(version, state).then_some(wire_id)
+}
+
+#[cfg(test)]
+mod test {
+	use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+	use crate::network::network_error::NetworkError;
+	use crate::network::{ConnectionRole, CraftConnection};
+	use crate::protocol::packets::packet_definer::{PacketDirection, PacketState};
+	use crate::protocol::packets::packet_versions::{is_supported, latest_id, supported_versions, supports_state, wire_id};
+	use crate::protocol::packets::{HandshakingPacket, LoginDisconnectPacket, LoginInfoPacket, Packet, StatusResponsePacket};
+	use crate::protocol::serialization::{McDeserializer, McSerialize, McSerializer, StateBasedDeserializer};
+	use crate::protocol::testing::{connection_pair, McDefault};
+	use crate::protocol_types::datatypes::chat::{JsonTextComponent, TextComponent};
+use crate::protocol_types::datatypes::var_types::VarInt;
+	use crate::protocol_types::protocol_verison::ProtocolVerison;
+
+	fn handshake(version: i32, next_state: i32) -> Packet {
+		Packet::Handshaking(HandshakingPacket::new(VarInt(version), "localhost".try_into().unwrap(), 25565, next_state.try_into().unwrap()))
+	}
+
+	#[test]
+	fn test_supported_states() {
+		for version in supported_versions() {
+			assert!(is_supported(version));
+			assert!(supports_state(version, PacketState::HANDSHAKING));
+			assert!(supports_state(version, PacketState::STATUS));
+		}
+
+		// the ids or fields of these states changed
+		for state in [PacketState::LOGIN, PacketState::CONFIGURATION, PacketState::PLAY] {
+			assert!(supports_state(ProtocolVerison::latest(), state));
+			assert!(!supports_state(ProtocolVerison::V1_21_7, state));
+			assert!(!supports_state(ProtocolVerison::V1_21_9, state));
+		}
+
+		assert_eq!(wire_id(ProtocolVerison::V1_21_7, PacketState::PLAY, 0x30), None);
+		assert_eq!(latest_id(ProtocolVerison::V1_21_7, PacketState::PLAY, 0x2B), None);
+		assert_eq!(wire_id(ProtocolVerison::V1_21_7, PacketState::STATUS, 0x00), Some(0x00));
+		assert_eq!(latest_id(ProtocolVerison::V1_21_9, PacketState::STATUS, 0x01), Some(0x01));
+		assert_eq!(wire_id(ProtocolVerison::V1_8, PacketState::STATUS, 0x00), None);
+	}
+
+	#[test]
+	fn test_versioned_packet_round_trip() {
+		let packet = Packet::StatusResponse(StatusResponsePacket::mc_default());
+
+		let mut serializer = McSerializer::new();
+		packet.serialize_version(&mut serializer, ProtocolVerison::V1_21_7).unwrap();
+
+		let mut deserializer = McDeserializer::new(&serializer.output);
+		let out = Packet::deserialize_state_version(&mut deserializer, PacketState::STATUS, PacketDirection::CLIENT, ProtocolVerison::V1_21_7).unwrap();
+		assert_eq!(out, packet);
+
+		// play packets are not sent in an older version, since their fields may have changed
+		let login_info = Packet::LoginInfo(LoginInfoPacket::mc_default());
+		assert_eq!(login_info.packet_id_for(ProtocolVerison::V1_21_7), None);
+		assert!(login_info.serialize_version(&mut McSerializer::new(), ProtocolVerison::V1_21_7).is_err());
+	}
+
+	#[tokio::test]
+	async fn test_connection_uses_handshake_version() {
+		let (mut client, mut server) = connection_pair(PacketState::HANDSHAKING).await;
+		assert_eq!(server.get_protocol_version(), ProtocolVerison::latest());
+
+		client.send_packet(handshake(772, 1)).await.unwrap();
+		server.receive_packet().await.unwrap();
+
+		assert_eq!(client.get_protocol_version(), ProtocolVerison::V1_21_7);
+		assert_eq!(server.get_protocol_version(), ProtocolVerison::V1_21_7);
+
+		client.change_state(PacketState::STATUS);
+		server.change_state(PacketState::STATUS);
+
+		let packet = Packet::StatusResponse(StatusResponsePacket::mc_default());
+		server.send_packet(packet.clone()).await.unwrap();
+		assert_eq!(client.receive_packet().await.unwrap(), packet);
+	}
+
+	#[tokio::test]
+	async fn test_unsupported_login_is_rejected() {
+		let (mut client, _server) = connection_pair(PacketState::HANDSHAKING).await;
+		assert_eq!(client.send_packet(handshake(772, 2)).await, Err(NetworkError::UnsupportedVersion(772)));
+
+		// a server tells a client that tries to log in with another version why, then closes the connection
+		for (version, reason) in [(773, "Outdated client! Please use 1.21.11"), (776, "Outdated server! I'm still on 1.21.11")] {
+			let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+			let mut client = tokio::net::TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+			let mut server = CraftConnection::from_connection(listener.accept().await.unwrap().0, ConnectionRole::SERVER).unwrap();
+
+			let mut serializer = McSerializer::new();
+			handshake(version, 2).mc_serialize(&mut serializer).unwrap();
+			client.write_all(&serializer.output).await.unwrap();
+
+			assert_eq!(server.receive_packet().await, Err(NetworkError::UnsupportedVersion(version)));
+
+			let mut received = vec![];
+			client.read_to_end(&mut received).await.unwrap();
+			let packet = Packet::deserialize_state(&mut McDeserializer::new(&received), PacketState::LOGIN, PacketDirection::CLIENT).unwrap();
+			assert_eq!(packet, Packet::LoginDisconnect(LoginDisconnectPacket::new(JsonTextComponent::from(TextComponent::from(reason.to_string())))));
+		}
+	}
+}
@@ -0,0 +1,93 @@
+//! Borrowed views of packets that are mostly a payload, for proxies and data collectors that only inspect them.
+//! A view points into the received frame instead of copying the payload out of it like
+//! [crate::protocol::packets::Packet] does.
+
+use sandstone_derive::{McDeserialize, McSerialize};
+use serde::Serialize;
+
+use crate::protocol::packets::packet_definer::{PacketDirection, PacketState};
+use crate::protocol::packets::packet_versions;
+use crate::protocol::packets::{ClientboundPluginMessagePacket, ServerboundPluginMessagePacket};
+use crate::protocol::serialization::serializer_error::SerializingErr;
+use crate::protocol::serialization::serializer_types::BoundedString;
+use crate::protocol::serialization::{McDeserialize, McDeserializeBorrowed, McDeserializer, McSerialize, McSerializer, SerializingResult};
+use crate::protocol_types::datatypes::var_types::VarInt;
+use crate::protocol_types::protocol_verison::ProtocolVerison;
+
+/// A plugin message in either direction, with the channel and data borrowed from the input. Serializes the same
+/// as the body of [ClientboundPluginMessagePacket] and [ServerboundPluginMessagePacket].
+#[derive(McSerialize, McDeserialize, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PluginMessageView<'a> {
+	pub channel: &'a str,
+	#[mc(rest)]
+	pub data: &'a [u8],
+}
+
+impl<'a> PluginMessageView<'a> {
+	/// Read a packet with its length prefix, like [crate::protocol::serialization::StateBasedDeserializer::deserialize_state_version].
+	/// Returns the view if the packet is a plugin message, or `None` if it is another packet. Either way, the
+	/// deserializer is moved past the packet.
+	pub fn from_frame(deserializer: &mut McDeserializer<'a>, state: PacketState, direction: PacketDirection, version: ProtocolVerison) -> SerializingResult<'a, Option<Self>> {
+		let length = VarInt::mc_deserialize(deserializer)?;
+		let mut body = deserializer.sub_deserializer_length(length.0 as usize)?;
+
+		let packet_id = VarInt::mc_deserialize(&mut body)?;
+		let info = match direction {
+			PacketDirection::CLIENT => &ClientboundPluginMessagePacket::INFO,
+			PacketDirection::SERVER => &ServerboundPluginMessagePacket::INFO,
+		};
+
+		if info.id_in(state).is_none() || packet_versions::latest_id(version, state, packet_id.0) != info.id_in(state) {
+			return Ok(None);
+		}
+
+		let view = Self::mc_deserialize_borrowed(&mut body)?;
+		BoundedString::<32767>::check_length(view.channel)?;
+
+		Ok(Some(view))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::protocol::packets::packet_definer::{PacketDirection, PacketState};
+	use crate::protocol::packets::packet_views::PluginMessageView;
+	use crate::protocol::packets::{ClientboundPluginMessagePacket, KeepAlivePacket, Packet, ServerboundPluginMessagePacket};
+	use crate::protocol::serialization::{McDeserializeBorrowed, McDeserializer, McSerializer};
+	use crate::protocol_types::protocol_verison::ProtocolVerison;
+
+	#[test]
+	fn test_plugin_message_view() {
+		let packet = Packet::ClientboundPluginMessage(ClientboundPluginMessagePacket::new("sandstone:test".try_into().unwrap(), vec![1, 2, 3]));
+		let mut serializer = McSerializer::new();
+		packet.serialize_state_version(&mut serializer, PacketState::PLAY, ProtocolVerison::latest()).unwrap();
+		Packet::KeepAlive(KeepAlivePacket::new(5)).serialize_state_version(&mut serializer, PacketState::PLAY, ProtocolVerison::latest()).unwrap();
+
+		let mut deserializer = McDeserializer::new(&serializer.output);
+		let view = PluginMessageView::from_frame(&mut deserializer, PacketState::PLAY, PacketDirection::CLIENT, ProtocolVerison::latest()).unwrap().unwrap();
+		assert_eq!(view.channel, "sandstone:test");
+		assert_eq!(view.data, [1, 2, 3]);
+		assert!(serializer.output.as_ptr_range().contains(&view.data.as_ptr()));
+
+		// other packets are skipped
+		let view = PluginMessageView::from_frame(&mut deserializer, PacketState::PLAY, PacketDirection::CLIENT, ProtocolVerison::latest()).unwrap();
+		assert_eq!(view, None);
+		assert!(deserializer.is_at_end());
+
+		// the id of the serverbound plugin message is another packet in the other direction
+		let packet = Packet::ServerboundPluginMessage(ServerboundPluginMessagePacket::new("sandstone:test".try_into().unwrap(), vec![]));
+		let mut serializer = McSerializer::new();
+		packet.serialize_state_version(&mut serializer, PacketState::CONFIGURATION, ProtocolVerison::latest()).unwrap();
+
+		let mut deserializer = McDeserializer::new(&serializer.output);
+		assert_eq!(PluginMessageView::from_frame(&mut deserializer, PacketState::CONFIGURATION, PacketDirection::CLIENT, ProtocolVerison::latest()).unwrap(), None);
+
+		let mut deserializer = McDeserializer::new(&serializer.output);
+		let view = PluginMessageView::from_frame(&mut deserializer, PacketState::CONFIGURATION, PacketDirection::SERVER, ProtocolVerison::latest()).unwrap().unwrap();
+		assert!(view.data.is_empty());
+
+		// the body is read the same way as the owned packet
+		let mut deserializer = McDeserializer::new(&[3, b'a', b'b', b'c', 9]);
+		assert_eq!(PluginMessageView::mc_deserialize_borrowed(&mut deserializer).unwrap(), PluginMessageView { channel: "abc", data: &[9] });
+	}
+}
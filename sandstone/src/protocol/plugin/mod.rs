@@ -0,0 +1,323 @@
+//! Plugin channels, which let mods and plugins exchange custom data through plugin message packets.
+//!
+//! A channel is a type implementing [PluginChannel], which ties the payload to the name of its channel.
+//! [PluginMessenger] sends these payloads in the packet that matches the side and state of the connection,
+//! routes incoming payloads to handlers by channel name and keeps track of the channels the other side
+//! listens on through `minecraft:register` and `minecraft:unregister`.
+//!
+//! https://minecraft.wiki/w/Java_Edition_protocol/Plugin_channels
+
+use std::collections::{HashMap, HashSet};
+
+use log::trace;
+use sandstone_derive::{McDeserialize, McSerialize};
+use serde::Serialize;
+
+use crate::network::network_error::NetworkError;
+use crate::network::{ConnectionRole, CraftConnection};
+use crate::protocol::packets::packet_definer::PacketState;
+use crate::protocol::packets::packet_views::PluginMessageView;
+use crate::protocol::packets::{ClientboundPluginMessagePacket, Packet, ServerboundPluginMessagePacket};
+use crate::protocol::serialization::serializer_error::SerializingErr;
+use crate::protocol::serialization::serializer_types::BoundedString;
+use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, SerializingResult};
+
+/// A payload sent over a specific plugin channel.
+pub trait PluginChannel: McSerialize + McDeserialize + Sized {
+	/// The name of the channel, such as `minecraft:brand`.
+	const CHANNEL: &'static str;
+
+	/// Serialize the payload into the data of a plugin message.
+	fn to_payload(&self) -> SerializingResult<'_, Vec<u8>> {
+		let mut serializer = McSerializer::new();
+		self.mc_serialize(&mut serializer)?;
+		Ok(serializer.output)
+	}
+
+	/// Deserialize the payload from the data of a plugin message.
+	fn from_payload(data: &[u8]) -> SerializingResult<'_, Self> {
+		let mut deserializer = McDeserializer::new(data);
+		Self::mc_deserialize(&mut deserializer)
+	}
+}
+
+/// `minecraft:brand`, the name of the software running on either side, such as "vanilla" or "Paper".
+#[derive(McSerialize, Serialize, McDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Brand {
+	pub brand: String,
+}
+
+impl PluginChannel for Brand {
+	const CHANNEL: &'static str = "minecraft:brand";
+}
+
+/// `minecraft:register`, the channels the sender starts listening on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisterChannels {
+	pub channels: Vec<String>,
+}
+
+impl PluginChannel for RegisterChannels {
+	const CHANNEL: &'static str = "minecraft:register";
+}
+
+/// `minecraft:unregister`, the channels the sender stops listening on.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnregisterChannels {
+	pub channels: Vec<String>,
+}
+
+impl PluginChannel for UnregisterChannels {
+	const CHANNEL: &'static str = "minecraft:unregister";
+}
+
+/// Implement the null separated channel list used by register and unregister.
+macro_rules! channel_list {
+	($($name:ident),*) => {
+		$(
+			impl McSerialize for $name {
+				fn mc_serialize(&self, serializer: &mut McSerializer) -> SerializingResult<()> {
+					serializer.serialize_str_no_length_prefix(&self.channels.join("\0"));
+					Ok(())
+				}
+			}
+
+			impl McDeserialize for $name {
+				fn mc_deserialize<'a>(deserializer: &'a mut McDeserializer) -> SerializingResult<'a, Self> {
+					let data: Vec<u8> = Vec::mc_deserialize(deserializer)?;
+					let channels = String::from_utf8(data)?.split('\0').filter(|c| !c.is_empty()).map(str::to_string).collect();
+
+					Ok(Self { channels })
+				}
+			}
+		)*
+	};
+}
+
+channel_list!(RegisterChannels, UnregisterChannels);
+
+type RawHandler = Box<dyn FnMut(&[u8]) -> Result<(), NetworkError> + Send>;
+
+/// Sends and receives plugin messages for a single connection. Use one messenger per connection, since it
+/// tracks the channels registered by that peer.
+///
+/// Pass every plugin message received to [PluginMessenger::handle_packet], which keeps the registered channels
+/// and brand of the peer up to date and calls the handler for the channel, if there is one.
+pub struct PluginMessenger {
+	handlers: HashMap<String, RawHandler>,
+	peer_channels: HashSet<String>,
+	peer_brand: Option<String>,
+}
+
+impl PluginMessenger {
+	pub fn new() -> Self {
+		Self {
+			handlers: HashMap::new(),
+			peer_channels: HashSet::new(),
+			peer_brand: None,
+		}
+	}
+
+	/// Call the handler whenever a payload is received on the channel of `C`. This replaces any earlier
+	/// handler for the same channel.
+	pub fn add_handler<C: PluginChannel, F: FnMut(C) -> Result<(), NetworkError> + Send + 'static>(&mut self, mut handler: F) {
+		self.add_raw_handler(C::CHANNEL, move |data| handler(C::from_payload(data)?));
+	}
+
+	/// Call the handler with the raw data of every payload received on the channel.
+	pub fn add_raw_handler<S: Into<String>, F: FnMut(&[u8]) -> Result<(), NetworkError> + Send + 'static>(&mut self, channel: S, handler: F) {
+		self.handlers.insert(channel.into(), Box::new(handler));
+	}
+
+	/// Stop handling a channel. Use [PluginMessenger::unregister] to tell the peer as well.
+	pub fn remove_handler(&mut self, channel: &str) {
+		self.handlers.remove(channel);
+	}
+
+	/// The channels this side has handlers for.
+	pub fn get_channels(&self) -> HashSet<String> {
+		self.handlers.keys().cloned().collect()
+	}
+
+	/// The channels the peer has registered.
+	pub fn get_peer_channels(&self) -> &HashSet<String> {
+		&self.peer_channels
+	}
+
+	/// Whether the peer has registered the channel. Channels in the `minecraft` namespace are understood
+	/// without being registered.
+	pub fn peer_listens(&self, channel: &str) -> bool {
+		channel.starts_with("minecraft:") || self.peer_channels.contains(channel)
+	}
+
+	/// The brand the peer sent, if any.
+	pub fn get_peer_brand(&self) -> Option<&str> {
+		self.peer_brand.as_deref()
+	}
+
+	/// Send `minecraft:register` with every channel this side has a handler for.
+	pub async fn announce(&self, connection: &mut CraftConnection) -> Result<(), NetworkError> {
+		let mut channels: Vec<String> = self.get_channels().into_iter().collect();
+		channels.sort();
+
+		if channels.is_empty() {
+			return Ok(());
+		}
+
+		self.send(connection, &RegisterChannels { channels }).await
+	}
+
+	/// Send `minecraft:unregister` for the channels and stop handling them.
+	pub async fn unregister(&mut self, connection: &mut CraftConnection, channels: Vec<String>) -> Result<(), NetworkError> {
+		for channel in &channels {
+			self.remove_handler(channel);
+		}
+
+		self.send(connection, &UnregisterChannels { channels }).await
+	}
+
+	/// Send a typed payload on its channel.
+	pub async fn send<C: PluginChannel>(&self, connection: &mut CraftConnection, payload: &C) -> Result<(), NetworkError> {
+		self.send_raw(connection, C::CHANNEL, payload.to_payload()?).await
+	}
+
+	/// Send raw data on a channel. The packet used depends on the side of the connection, which must be in the
+	/// CONFIGURATION or PLAY state.
+	pub async fn send_raw(&self, connection: &mut CraftConnection, channel: &str, data: Vec<u8>) -> Result<(), NetworkError> {
+		let channel = BoundedString::new(channel)?;
+
+		if !matches!(connection.packet_state, PacketState::CONFIGURATION | PacketState::PLAY) {
+			return Err(NetworkError::InvalidPacketState);
+		}
+
+		let packet = match connection.client_type {
+			ConnectionRole::SERVER => Packet::ClientboundPluginMessage(ClientboundPluginMessagePacket::new(channel, data)),
+			ConnectionRole::CLIENT => Packet::ServerboundPluginMessage(ServerboundPluginMessagePacket::new(channel, data)),
+		};
+
+		connection.send_packet(packet).await
+	}
+
+	/// Process the packet if it is a plugin message, returning whether it was one. Register, unregister and
+	/// brand payloads are recorded, then the payload is passed to the handler of its channel.
+	pub fn handle_packet(&mut self, packet: &Packet) -> Result<bool, NetworkError> {
+		let view = match packet {
+			Packet::ClientboundPluginMessage(p) => PluginMessageView {
+				channel: p.channel.as_str(),
+				data: &p.data,
+			},
+			Packet::ServerboundPluginMessage(p) => PluginMessageView {
+				channel: p.channel.as_str(),
+				data: &p.data,
+			},
+			_ => return Ok(false),
+		};
+
+		self.handle_view(view)?;
+		Ok(true)
+	}
+
+	/// Like [PluginMessenger::handle_packet], for a plugin message read without copying it, see
+	/// [PluginMessageView::from_frame].
+	pub fn handle_view(&mut self, view: PluginMessageView) -> Result<(), NetworkError> {
+		let PluginMessageView {
+			channel,
+			data,
+		} = view;
+
+		match channel {
+			RegisterChannels::CHANNEL => self.peer_channels.extend(RegisterChannels::from_payload(data)?.channels),
+			UnregisterChannels::CHANNEL => {
+				for channel in UnregisterChannels::from_payload(data)?.channels {
+					self.peer_channels.remove(&channel);
+				}
+			}
+			Brand::CHANNEL => self.peer_brand = Some(Brand::from_payload(data)?.brand),
+			_ => {}
+		}
+
+		match self.handlers.get_mut(channel) {
+			Some(handler) => handler(data)?,
+			None => trace!("No handler for plugin channel {}", channel),
+		}
+
+		Ok(())
+	}
+}
+
+impl Default for PluginMessenger {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::sync::{Arc, Mutex};
+
+	use crate::protocol::packets::packet_definer::{PacketDirection, PacketState};
+	use crate::protocol::packets::packet_views::PluginMessageView;
+	use crate::protocol::packets::{Packet, ServerboundPluginMessagePacket};
+	use crate::protocol::plugin::{Brand, PluginChannel, PluginMessenger, RegisterChannels};
+	use crate::protocol::serialization::{McDeserializer, McSerializer};
+	use crate::protocol::testing::connection_pair;
+	use crate::protocol_types::protocol_verison::ProtocolVerison;
+
+	#[test]
+	fn test_channel_list_round_trip() {
+		let register = RegisterChannels {
+			channels: vec!["sandstone:one".to_string(), "sandstone:two".to_string()],
+		};
+
+		let payload = register.to_payload().unwrap();
+		assert_eq!(payload, b"sandstone:one\0sandstone:two");
+		assert_eq!(RegisterChannels::from_payload(&payload).unwrap(), register);
+	}
+
+	#[test]
+	fn test_handle_view() {
+		let mut serializer = McSerializer::new();
+		let data = Brand { brand: "vanilla".to_string() }.to_payload().unwrap();
+		let packet = Packet::ServerboundPluginMessage(ServerboundPluginMessagePacket::new(Brand::CHANNEL.try_into().unwrap(), data));
+		packet.serialize_state_version(&mut serializer, PacketState::PLAY, ProtocolVerison::latest()).unwrap();
+
+		let mut deserializer = McDeserializer::new(&serializer.output);
+		let view = PluginMessageView::from_frame(&mut deserializer, PacketState::PLAY, PacketDirection::SERVER, ProtocolVerison::latest()).unwrap().unwrap();
+
+		let mut messenger = PluginMessenger::new();
+		messenger.handle_view(view).unwrap();
+		assert_eq!(messenger.get_peer_brand(), Some("vanilla"));
+	}
+
+	#[tokio::test]
+	async fn test_register_and_route() {
+		let (mut client, mut server) = connection_pair(PacketState::PLAY).await;
+
+		let received = Arc::new(Mutex::new(vec![]));
+		let mut server_messenger = PluginMessenger::new();
+		let sink = received.clone();
+		server_messenger.add_raw_handler("sandstone:test", move |data| {
+			sink.lock().unwrap().push(data.to_vec());
+			Ok(())
+		});
+
+		let mut client_messenger = PluginMessenger::new();
+		client_messenger.add_handler(|_: Brand| Ok(()));
+
+		server_messenger.announce(&mut server).await.unwrap();
+		client_messenger.handle_packet(&client.receive_packet().await.unwrap()).unwrap();
+		assert!(client_messenger.peer_listens("sandstone:test"));
+
+		client_messenger.send(&mut client, &Brand { brand: "vanilla".to_string() }).await.unwrap();
+		client_messenger.send_raw(&mut client, "sandstone:test", vec![1, 2, 3]).await.unwrap();
+		client_messenger.unregister(&mut client, vec![Brand::CHANNEL.to_string()]).await.unwrap();
+
+		for _ in 0..3 {
+			assert!(server_messenger.handle_packet(&server.receive_packet().await.unwrap()).unwrap());
+		}
+
+		assert_eq!(server_messenger.get_peer_brand(), Some("vanilla"));
+		assert_eq!(*received.lock().unwrap(), vec![vec![1, 2, 3]]);
+		assert!(client_messenger.get_channels().is_empty());
+	}
+}
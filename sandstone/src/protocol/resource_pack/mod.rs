@@ -0,0 +1,305 @@
+//! Push resource packs to a client and track its responses.
+//!
+//! The server sends `AddResourcePack` for every pack, and the client answers each one with a sequence of
+//! `ResourcePackResponse` packets, such as accepted, downloaded and finally loaded. [ResourcePackManager]
+//! keeps track of these per pack, and disconnects the client if it refuses a pack that is forced.
+//!
+//! Packs can be pushed during CONFIGURATION as well as PLAY, the matching packets are picked based on the
+//! state of the connection.
+//!
+//! https://minecraft.wiki/w/Java_Edition_protocol/Packets#Add_Resource_Pack_(configuration)
+
+use log::{debug, trace};
+use uuid::Uuid;
+
+use crate::network::network_error::NetworkError;
+use crate::network::CraftConnection;
+use crate::protocol::packets::packet_definer::PacketState;
+use crate::protocol::packets::packet_parts::AddResourcePackSpec;
+use crate::protocol::packets::{AddResourcePackPacket, DisconnectPacket, Packet, RemoveResourcePackPacket};
+use crate::protocol::serialization::serializer_error::SerializingErr;
+use crate::protocol::serialization::serializer_types::PrefixedOptional;
+use crate::protocol_types::datatypes::chat::TextComponent;
+use crate::protocol_types::datatypes::var_types::VarInt;
+
+/// The disconnect reason used when a client rejects a forced pack.
+pub const DEFAULT_KICK_MESSAGE: &str = "You must accept the server resource pack to play on this server";
+
+/// The result sent by the client in `ResourcePackResponse`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ResourcePackStatus {
+	/// The pack was downloaded and applied.
+	Loaded,
+	Declined,
+	FailedDownload,
+	Accepted,
+	Downloaded,
+	InvalidUrl,
+	FailedReload,
+	/// The pack was removed by the client, such as when it was replaced by a pack with the same UUID.
+	Discarded,
+}
+
+impl ResourcePackStatus {
+	pub fn from_id(id: i32) -> Option<Self> {
+		match id {
+			0 => Some(Self::Loaded),
+			1 => Some(Self::Declined),
+			2 => Some(Self::FailedDownload),
+			3 => Some(Self::Accepted),
+			4 => Some(Self::Downloaded),
+			5 => Some(Self::InvalidUrl),
+			6 => Some(Self::FailedReload),
+			7 => Some(Self::Discarded),
+			_ => None,
+		}
+	}
+
+	pub fn get_id(&self) -> i32 {
+		match self {
+			Self::Loaded => 0,
+			Self::Declined => 1,
+			Self::FailedDownload => 2,
+			Self::Accepted => 3,
+			Self::Downloaded => 4,
+			Self::InvalidUrl => 5,
+			Self::FailedReload => 6,
+			Self::Discarded => 7,
+		}
+	}
+
+	/// Whether the client will not send any more responses for the pack after this one.
+	pub fn is_final(&self) -> bool {
+		!matches!(self, Self::Accepted | Self::Downloaded)
+	}
+
+	/// Whether the pack did not end up loaded on the client.
+	pub fn is_failure(&self) -> bool {
+		self.is_final() && *self != Self::Loaded
+	}
+}
+
+/// A pushed pack and every status the client reported for it, in order.
+#[derive(Debug, Clone, PartialEq)]
+struct TrackedPack {
+	spec: AddResourcePackSpec,
+	statuses: Vec<ResourcePackStatus>,
+}
+
+/// Pushes resource packs to a single client and tracks the status of each one. Use one manager per connection.
+///
+/// Either wait for every pack with [ResourcePackManager::await_responses], or pass the packets received in
+/// your own loop to [ResourcePackManager::handle_packet].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourcePackManager {
+	packs: Vec<TrackedPack>,
+	kick_message: String,
+}
+
+impl ResourcePackManager {
+	pub fn new() -> Self {
+		Self {
+			packs: vec![],
+			kick_message: DEFAULT_KICK_MESSAGE.to_string(),
+		}
+	}
+
+	/// Set the reason shown to a client that is disconnected for rejecting a forced pack.
+	pub fn set_kick_message<S: Into<String>>(&mut self, message: S) {
+		self.kick_message = message.into();
+	}
+
+	/// Send the packs to the client. Pushing a pack with the UUID of an earlier pack replaces it, on the client
+	/// as well as here. The connection must be in the CONFIGURATION or PLAY state.
+	pub async fn push(&mut self, connection: &mut CraftConnection, packs: Vec<AddResourcePackSpec>) -> Result<(), NetworkError> {
+		Self::check_state(connection)?;
+
+		for spec in packs {
+			connection.send_packet(Packet::AddResourcePack(AddResourcePackPacket::new(spec.clone()))).await?;
+
+			trace!("Pushed resource pack {} to {}", spec.uuid, connection);
+
+			self.packs.retain(|p| p.spec.uuid != spec.uuid);
+			self.packs.push(TrackedPack { spec, statuses: vec![] });
+		}
+
+		Ok(())
+	}
+
+	/// Tell the client to unload a pack, or every pack if no UUID is given. The pack is no longer tracked.
+	pub async fn remove(&mut self, connection: &mut CraftConnection, uuid: Option<Uuid>) -> Result<(), NetworkError> {
+		Self::check_state(connection)?;
+		connection.send_packet(Packet::RemoveResourcePack(RemoveResourcePackPacket::new(PrefixedOptional::new(uuid)))).await?;
+
+		match uuid {
+			Some(uuid) => self.packs.retain(|p| p.spec.uuid != uuid),
+			None => self.packs.clear(),
+		}
+
+		Ok(())
+	}
+
+	/// Record the packet if it is a resource pack response, returning whether it was one. If the client
+	/// declined or failed to load a forced pack it is disconnected and [NetworkError::ResourcePackRejected]
+	/// is returned.
+	pub async fn handle_packet(&mut self, connection: &mut CraftConnection, packet: &Packet) -> Result<bool, NetworkError> {
+		let (uuid, result) = match packet {
+			Packet::ResourcePackResponse(response) => (response.uuid, response.result),
+			_ => return Ok(false),
+		};
+
+		self.record(connection, uuid, result).await?;
+
+		Ok(true)
+	}
+
+	/// Receive packets until the client has sent a final status for every pushed pack. Other packets
+	/// received in the meantime are discarded. Returns the final status of every tracked pack.
+	pub async fn await_responses(&mut self, connection: &mut CraftConnection) -> Result<Vec<(Uuid, ResourcePackStatus)>, NetworkError> {
+		while !self.is_complete() {
+			let packet = connection.receive_packet().await?;
+
+			if !self.handle_packet(connection, &packet).await? {
+				trace!("Ignoring {:?} while waiting for resource packs from {}", packet.packet_id(), connection);
+			}
+		}
+
+		Ok(self.packs.iter().filter_map(|p| Some((p.spec.uuid, *p.statuses.last()?))).collect())
+	}
+
+	/// Whether every pushed pack has reached a final status.
+	pub fn is_complete(&self) -> bool {
+		self.packs.iter().all(|p| p.statuses.last().is_some_and(|s| s.is_final()))
+	}
+
+	/// The latest status reported for a pack, if any.
+	pub fn get_status(&self, uuid: Uuid) -> Option<ResourcePackStatus> {
+		self.get_statuses(uuid).last().copied()
+	}
+
+	/// Every status reported for a pack, in the order the client sent them.
+	pub fn get_statuses(&self, uuid: Uuid) -> &[ResourcePackStatus] {
+		self.packs.iter().find(|p| p.spec.uuid == uuid).map(|p| p.statuses.as_slice()).unwrap_or_default()
+	}
+
+	/// Resource packs can only be sent during CONFIGURATION and PLAY.
+	fn check_state(connection: &CraftConnection) -> Result<(), NetworkError> {
+		match connection.packet_state {
+			PacketState::CONFIGURATION | PacketState::PLAY => Ok(()),
+			_ => Err(NetworkError::InvalidPacketState),
+		}
+	}
+
+	async fn record(&mut self, connection: &mut CraftConnection, uuid: Uuid, result: VarInt) -> Result<(), NetworkError> {
+		let status = ResourcePackStatus::from_id(result.0).ok_or_else(|| SerializingErr::OutOfBounds(format!("Unknown resource pack result {}", result.0)))?;
+
+		let Some(pack) = self.packs.iter_mut().find(|p| p.spec.uuid == uuid) else {
+			trace!("Ignoring status {:?} for unknown resource pack {} from {}", status, uuid, connection);
+			return Ok(());
+		};
+
+		pack.statuses.push(status);
+
+		debug!("Resource pack {} is {:?} for {}", uuid, status, connection);
+
+		if pack.spec.forced && status.is_failure() {
+			let reason = TextComponent::from(self.kick_message.clone());
+			connection.send_packet(Packet::Disconnect(DisconnectPacket::new(reason))).await?;
+			connection.close().await;
+
+			return Err(NetworkError::ResourcePackRejected(format!("forced pack {uuid} was {status:?}")));
+		}
+
+		Ok(())
+	}
+}
+
+impl Default for ResourcePackManager {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use uuid::Uuid;
+
+	use crate::network::network_error::NetworkError;
+	use crate::protocol::packets::packet_definer::PacketState;
+	use crate::protocol::packets::packet_parts::AddResourcePackSpec;
+	use crate::protocol::packets::{DisconnectPacket, Packet, ResourcePackResponsePacket};
+	use crate::protocol::resource_pack::{ResourcePackManager, ResourcePackStatus};
+	use crate::protocol::testing::connection_pair;
+	use crate::protocol_types::datatypes::var_types::VarInt;
+
+	fn spec(uuid: Uuid, forced: bool) -> AddResourcePackSpec {
+		AddResourcePackSpec::new(uuid, "https://example.com/pack.zip".to_string(), "0".repeat(40), forced, Some("Please".to_string()))
+	}
+
+	#[tokio::test]
+	async fn test_push_and_track_configuration() {
+		let (mut client, mut server) = connection_pair(PacketState::CONFIGURATION).await;
+		let (first, second) = (Uuid::new_v4(), Uuid::new_v4());
+
+		let handle = tokio::spawn(async move {
+			let mut manager = ResourcePackManager::new();
+			manager.push(&mut server, vec![spec(first, true), spec(second, false)]).await?;
+			let result = manager.await_responses(&mut server).await?;
+			Ok::<_, NetworkError>((result, manager))
+		});
+
+		for expected in [first, second] {
+			match client.receive_packet().await.unwrap() {
+				Packet::AddResourcePack(p) => assert_eq!(p.spec.get_uuid(), expected),
+				p => panic!("Expected add resource pack, got {p:?}"),
+			}
+		}
+
+		for (uuid, result) in [(first, 3), (second, 1), (first, 4), (first, 0)] {
+			client.send_packet(Packet::ResourcePackResponse(ResourcePackResponsePacket::new(uuid, VarInt(result)))).await.unwrap();
+		}
+
+		let (result, manager) = handle.await.unwrap().unwrap();
+		assert_eq!(result, vec![(first, ResourcePackStatus::Loaded), (second, ResourcePackStatus::Declined)]);
+		assert_eq!(manager.get_statuses(first), &[ResourcePackStatus::Accepted, ResourcePackStatus::Downloaded, ResourcePackStatus::Loaded]);
+		assert_eq!(manager.get_status(second), Some(ResourcePackStatus::Declined));
+	}
+
+	/// Decline a forced pack in `state`, and check that the client is kicked with the disconnect packet of that state.
+	async fn forced_pack_declined(state: PacketState) {
+		let (mut client, mut server) = connection_pair(state).await;
+		let uuid = Uuid::new_v4();
+
+		let handle = tokio::spawn(async move {
+			let mut manager = ResourcePackManager::new();
+			manager.push(&mut server, vec![spec(uuid, true)]).await?;
+			manager.await_responses(&mut server).await
+		});
+
+		match client.receive_packet().await.unwrap() {
+			Packet::AddResourcePack(p) => assert!(p.spec.is_forced()),
+			p => panic!("Expected add resource pack, got {p:?}"),
+		}
+
+		client.send_packet(Packet::ResourcePackResponse(ResourcePackResponsePacket::new(uuid, VarInt(1)))).await.unwrap();
+
+		// the client reads with the ids of its state, so the disconnect id of another state would not decode to Disconnect
+		match client.receive_packet().await.unwrap() {
+			Packet::Disconnect(_) => {}
+			p => panic!("Expected disconnect, got {p:?}"),
+		}
+
+		assert!(matches!(handle.await.unwrap(), Err(NetworkError::ResourcePackRejected(_))));
+	}
+
+	#[tokio::test]
+	async fn test_forced_pack_declined_in_configuration() {
+		assert_eq!(DisconnectPacket::INFO.id_in(PacketState::CONFIGURATION), Some(0x02));
+		forced_pack_declined(PacketState::CONFIGURATION).await;
+	}
+
+	#[tokio::test]
+	async fn test_forced_pack_declined_in_play() {
+		forced_pack_declined(PacketState::PLAY).await;
+	}
+}
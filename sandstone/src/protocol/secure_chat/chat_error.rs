@@ -0,0 +1,31 @@
+//! Errors that can occur while validating or signing chat messages.
+
+use thiserror::Error;
+
+/// Represents the reasons a chat session or message can be rejected. Each of these should disconnect the
+/// player, since the chain of signed messages can not be continued afterward.
+#[derive(Error, Debug, Clone, Hash, PartialEq, Eq)]
+pub enum ChatError {
+	/// The message was not signed, but the server enforces secure chat or the player has a chat session.
+	#[error("Missing profile public key")]
+	MissingProfileKey,
+	#[error("Invalid profile public key: {0}")]
+	InvalidPublicKey(String),
+	#[error("Expired profile public key")]
+	ExpiredPublicKey,
+	/// The public key of the player was not signed by Mojang.
+	#[error("Invalid signature for profile public key")]
+	InvalidKeySignature,
+	#[error("Invalid message signature")]
+	InvalidSignature,
+	#[error("Chat message received out of order")]
+	OutOfOrder,
+	/// The acknowledgement of previously seen messages did not match what was sent to the player.
+	#[error("Invalid last seen messages: {0}")]
+	InvalidLastSeen(String),
+	/// The player did not acknowledge enough of the messages sent to them.
+	#[error("Too many unacknowledged chat messages")]
+	TooManyPending,
+	#[error("Could not sign message: {0}")]
+	SigningFailed(String),
+}
@@ -0,0 +1,156 @@
+//! Sign chat messages on the client.
+//!
+//! The key pair of a player and the signature Mojang made for it are returned by
+//! `https://api.minecraftservices.com/player/certificates`, which is what [PlayerCertificate] is built from.
+
+use base64::Engine;
+use rsa::pkcs1v15::SigningKey;
+use rsa::pkcs8::{DecodePrivateKey, EncodePublicKey};
+use rsa::signature::{SignatureEncoding, Signer};
+use rsa::RsaPrivateKey;
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::protocol::packets::packet_parts::auth::PublicKeyNetwork;
+use crate::protocol::packets::{AcknowledgeMessagePacket, ChatMessagePacket, Packet, PlayerSessionPacket};
+use crate::protocol::secure_chat::chat_error::ChatError;
+use crate::protocol::secure_chat::last_seen::LastSeenTracker;
+use crate::protocol::secure_chat::{now_millis, MessageSignature, SignedMessageBody};
+use crate::protocol::serialization::serializer_types::{BoundedString, PrefixedArray, PrefixedOptional};
+use crate::protocol_types::datatypes::var_types::VarInt;
+
+/// The number of received messages after which the client acknowledges them without sending a message of its own.
+const ACKNOWLEDGE_THRESHOLD: i32 = 64;
+
+/// The key pair of a player, along with the signature Mojang made for the public key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerCertificate {
+	private_key: RsaPrivateKey,
+	/// The X.509 encoded public key.
+	public_key: Vec<u8>,
+	key_signature: Vec<u8>,
+	/// When the certificate expires, in milliseconds since the epoch.
+	expires_at: i64,
+}
+
+impl PlayerCertificate {
+	pub fn new(private_key: RsaPrivateKey, expires_at: i64, key_signature: Vec<u8>) -> Result<Self, ChatError> {
+		let public_key = private_key.to_public_key().to_public_key_der().map_err(|e| ChatError::InvalidPublicKey(e.to_string()))?;
+
+		Ok(Self {
+			private_key,
+			public_key: public_key.to_vec(),
+			key_signature,
+			expires_at,
+		})
+	}
+
+	/// Read the private key as returned by Mojang. It is labeled as an `RSA PRIVATE KEY`, but the content is PKCS#8.
+	pub fn from_pem(pem: &str, expires_at: i64, key_signature: Vec<u8>) -> Result<Self, ChatError> {
+		let body: String = pem.lines().filter(|line| !line.starts_with("-----")).collect();
+		let der = base64::engine::general_purpose::STANDARD.decode(body.trim()).map_err(|e| ChatError::InvalidPublicKey(e.to_string()))?;
+		let private_key = RsaPrivateKey::from_pkcs8_der(&der).map_err(|e| ChatError::InvalidPublicKey(e.to_string()))?;
+
+		Self::new(private_key, expires_at, key_signature)
+	}
+
+	pub fn get_public_key(&self) -> &[u8] {
+		&self.public_key
+	}
+
+	pub fn get_expires_at(&self) -> i64 {
+		self.expires_at
+	}
+}
+
+/// The chat session of the player on the client side. Announce it with [ClientChatSession::session_packet],
+/// then sign each message with [ClientChatSession::sign_message].
+#[derive(Debug, Clone)]
+pub struct ClientChatSession {
+	player: Uuid,
+	session_id: Uuid,
+	certificate: PlayerCertificate,
+	index: i32,
+	last_seen: LastSeenTracker,
+}
+
+impl ClientChatSession {
+	/// Start a new session with a random id.
+	pub fn new(player: Uuid, certificate: PlayerCertificate) -> Self {
+		Self {
+			player,
+			session_id: Uuid::new_v4(),
+			certificate,
+			index: 0,
+			last_seen: LastSeenTracker::new(),
+		}
+	}
+
+	pub fn get_session_id(&self) -> Uuid {
+		self.session_id
+	}
+
+	/// The `PlayerSession` packet that tells the server which key messages are signed with.
+	pub fn session_packet(&self) -> Packet {
+		let key = PublicKeyNetwork {
+			expires_at: self.certificate.expires_at,
+			public_key: PrefixedArray::new(self.certificate.public_key.clone()),
+			key_signature: PrefixedArray::new(self.certificate.key_signature.clone()),
+		};
+
+		Packet::PlayerSession(PlayerSessionPacket::new(self.session_id, Box::new(key)))
+	}
+
+	/// Sign a message, acknowledging every message received so far.
+	pub fn sign_message(&mut self, message: &str) -> Result<Packet, ChatError> {
+		let message = BoundedString::new(message).map_err(|e| ChatError::SigningFailed(e.to_string()))?;
+		let update = self.last_seen.generate_update();
+
+		let body = SignedMessageBody {
+			sender: self.player,
+			session_id: self.session_id,
+			index: self.index,
+			salt: rand::random(),
+			timestamp: now_millis(),
+			message: message.to_string(),
+			last_seen: update.signatures,
+		};
+
+		let signature = self.sign(&body)?;
+		self.index += 1;
+
+		Ok(Packet::ChatMessage(ChatMessagePacket::new(
+			message,
+			body.timestamp,
+			body.salt,
+			PrefixedOptional::new(Some(Box::new(signature))),
+			VarInt(update.offset),
+			update.acknowledged,
+			update.checksum,
+		)))
+	}
+
+	/// Track the messages received from the server. When many messages were received without sending one, this
+	/// returns the `AcknowledgeMessage` packet to send, so that the server does not disconnect the player.
+	pub fn handle_packet(&mut self, packet: &Packet) -> Option<Packet> {
+		let Packet::PlayerChatMessage(chat) = packet else {
+			return None;
+		};
+
+		self.last_seen.add(*chat.message_signature_bytes.value()?);
+
+		if self.last_seen.get_offset() > ACKNOWLEDGE_THRESHOLD {
+			let offset = self.last_seen.take_offset();
+			return Some(Packet::AcknowledgeMessage(AcknowledgeMessagePacket::new(VarInt(offset))));
+		}
+
+		None
+	}
+
+	fn sign(&self, body: &SignedMessageBody) -> Result<MessageSignature, ChatError> {
+		let signing_key = SigningKey::<Sha256>::new(self.certificate.private_key.clone());
+		let signature = signing_key.try_sign(&body.signing_payload()).map_err(|e| ChatError::SigningFailed(e.to_string()))?;
+
+		signature.to_bytes().as_ref().try_into().map_err(|_| ChatError::SigningFailed("signature is not 256 bytes".to_string()))
+	}
+}
@@ -0,0 +1,236 @@
+//! Track which chat messages have been seen by a player.
+//!
+//! Each signed message includes the signatures of up to [LAST_SEEN_WINDOW] messages the sender has seen. The
+//! client sends which messages it acknowledges as a bit set over a sliding window, along with how far the window
+//! moved since its last update. The server keeps the same window for every player to check that the client only
+//! acknowledges messages that were actually sent to it.
+
+use crate::protocol::secure_chat::chat_error::ChatError;
+use crate::protocol::secure_chat::{last_seen_checksum, MessageSignature, LAST_SEEN_WINDOW};
+use crate::util::java::bitset::FixedBitSet;
+
+/// The maximum number of messages a player may leave unacknowledged before they are disconnected.
+pub const MAX_PENDING_MESSAGES: usize = 4096;
+
+/// A message in the last seen window, which is pending until it was included in an update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TrackedEntry {
+	signature: MessageSignature,
+	pending: bool,
+}
+
+/// The part of a chat message that describes the last seen window of the sender.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LastSeenUpdate {
+	/// How many messages were added to the window since the last update.
+	pub offset: i32,
+	pub acknowledged: FixedBitSet<LAST_SEEN_WINDOW>,
+	pub checksum: i8,
+	/// The acknowledged signatures, oldest first. These are covered by the signature of the message.
+	pub signatures: Vec<MessageSignature>,
+}
+
+/// Client side tracking of the messages received, used to build the update sent with each chat message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LastSeenTracker {
+	/// A ring of the last messages, where `tail` is the oldest.
+	entries: Vec<Option<TrackedEntry>>,
+	tail: usize,
+	offset: i32,
+	last: Option<MessageSignature>,
+}
+
+impl LastSeenTracker {
+	pub fn new() -> Self {
+		Self {
+			entries: vec![None; LAST_SEEN_WINDOW],
+			tail: 0,
+			offset: 0,
+			last: None,
+		}
+	}
+
+	/// Record a signed message that was received.
+	pub fn add(&mut self, signature: MessageSignature) {
+		if self.last == Some(signature) {
+			return;
+		}
+
+		self.entries[self.tail] = Some(TrackedEntry { signature, pending: true });
+		self.tail = (self.tail + 1) % LAST_SEEN_WINDOW;
+		self.offset += 1;
+		self.last = Some(signature);
+	}
+
+	/// How many messages were received since the last update.
+	pub fn get_offset(&self) -> i32 {
+		self.offset
+	}
+
+	/// Reset the offset, returning what it was. The returned value is sent in `AcknowledgeMessage`.
+	pub fn take_offset(&mut self) -> i32 {
+		std::mem::take(&mut self.offset)
+	}
+
+	/// Acknowledge every message in the window, building the update to send with the next chat message.
+	pub fn generate_update(&mut self) -> LastSeenUpdate {
+		let offset = self.take_offset();
+		let mut acknowledged = FixedBitSet::new();
+		let mut signatures = vec![];
+
+		for i in 0..LAST_SEEN_WINDOW {
+			let index = (self.tail + i) % LAST_SEEN_WINDOW;
+
+			if let Some(entry) = &mut self.entries[index] {
+				acknowledged.set_bit(i, true);
+				signatures.push(entry.signature);
+				entry.pending = false;
+			}
+		}
+
+		LastSeenUpdate {
+			offset,
+			acknowledged,
+			checksum: last_seen_checksum(&signatures),
+			signatures,
+		}
+	}
+}
+
+impl Default for LastSeenTracker {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+/// Server side tracking of the messages sent to a player, used to validate the updates the player sends.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LastSeenValidator {
+	/// Every message that may still be acknowledged. The first [LAST_SEEN_WINDOW] entries are the current window.
+	tracked: Vec<Option<TrackedEntry>>,
+	last_pending: Option<MessageSignature>,
+}
+
+impl LastSeenValidator {
+	pub fn new() -> Self {
+		Self {
+			tracked: vec![None; LAST_SEEN_WINDOW],
+			last_pending: None,
+		}
+	}
+
+	/// Record a signed message that was sent to the player.
+	pub fn add_pending(&mut self, signature: MessageSignature) -> Result<(), ChatError> {
+		if self.last_pending == Some(signature) {
+			return Ok(());
+		}
+
+		self.tracked.push(Some(TrackedEntry { signature, pending: true }));
+		self.last_pending = Some(signature);
+
+		if self.pending_count() > MAX_PENDING_MESSAGES {
+			return Err(ChatError::TooManyPending);
+		}
+
+		Ok(())
+	}
+
+	/// The number of messages outside of the current window.
+	pub fn pending_count(&self) -> usize {
+		self.tracked.len() - LAST_SEEN_WINDOW
+	}
+
+	/// Move the window forward, as sent by the player in `AcknowledgeMessage` or with a chat message.
+	pub fn apply_offset(&mut self, offset: i32) -> Result<(), ChatError> {
+		let max = self.pending_count();
+
+		if offset < 0 || offset as usize > max {
+			return Err(ChatError::InvalidLastSeen(format!("advanced window by {offset} messages, but expected at most {max}")));
+		}
+
+		self.tracked.drain(..offset as usize);
+
+		Ok(())
+	}
+
+	/// Apply the update sent with a chat message, returning the signatures it acknowledges.
+	pub fn apply_update(&mut self, offset: i32, acknowledged: &FixedBitSet<LAST_SEEN_WINDOW>, checksum: i8) -> Result<Vec<MessageSignature>, ChatError> {
+		self.apply_offset(offset)?;
+
+		let mut signatures = Vec::with_capacity(acknowledged.cardinality());
+
+		for i in 0..LAST_SEEN_WINDOW {
+			let entry = &mut self.tracked[i];
+
+			if acknowledged.get_bit(i) {
+				let Some(entry) = entry else {
+					return Err(ChatError::InvalidLastSeen(format!("acknowledged unknown or previously ignored message at index {i}")));
+				};
+
+				entry.pending = false;
+				signatures.push(entry.signature);
+			} else {
+				if entry.is_some_and(|e| !e.pending) {
+					return Err(ChatError::InvalidLastSeen(format!("ignored previously acknowledged message at index {i}")));
+				}
+
+				*entry = None;
+			}
+		}
+
+		// a checksum of 0 is sent by clients that do not compute it
+		if checksum != 0 && checksum != last_seen_checksum(&signatures) {
+			return Err(ChatError::InvalidLastSeen("checksum mismatch".to_string()));
+		}
+
+		Ok(signatures)
+	}
+}
+
+impl Default for LastSeenValidator {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use crate::protocol::secure_chat::last_seen::{LastSeenTracker, LastSeenValidator};
+
+	#[test]
+	fn test_tracker_and_validator_agree() {
+		let mut tracker = LastSeenTracker::new();
+		let mut validator = LastSeenValidator::new();
+
+		for i in 0..25u8 {
+			validator.add_pending([i; 256]).unwrap();
+			tracker.add([i; 256]);
+		}
+
+		let update = tracker.generate_update();
+		assert_eq!(update.offset, 25);
+		assert_eq!(update.signatures.len(), 20);
+		assert_eq!(update.signatures[0], [5; 256]);
+
+		let acknowledged = validator.apply_update(update.offset, &update.acknowledged, update.checksum).unwrap();
+		assert_eq!(acknowledged, update.signatures);
+
+		// nothing new was received, so the same window is acknowledged again
+		let update = tracker.generate_update();
+		assert_eq!(validator.apply_update(update.offset, &update.acknowledged, update.checksum).unwrap(), acknowledged);
+	}
+
+	#[test]
+	fn test_validator_rejects_bad_updates() {
+		let mut tracker = LastSeenTracker::new();
+		let mut validator = LastSeenValidator::new();
+
+		validator.add_pending([1; 256]).unwrap();
+		tracker.add([1; 256]);
+		tracker.add([2; 256]); // never sent by the server
+
+		let update = tracker.generate_update();
+		assert!(validator.clone().apply_update(update.offset, &update.acknowledged, update.checksum).is_err());
+		assert!(validator.apply_update(1, &update.acknowledged, update.checksum.wrapping_add(1)).is_err());
+	}
+}
@@ -0,0 +1,217 @@
+//! Secure chat, where every chat message is signed by the key of the player that sent it.
+//!
+//! A player first sends `PlayerSession` with a public key that was signed by Mojang. Every following chat
+//! message is signed with the matching private key. The signature covers the message, its position in the
+//! chain of messages sent by the player and the signatures of the last messages the player has seen, so
+//! messages can not be reordered, replayed or taken out of context.
+//!
+//! - [client_session::ClientChatSession] signs messages on the client, given a [client_session::PlayerCertificate]
+//! - [server_session::ServerChatSession] validates sessions and messages on the server, and relays them to other players
+//! - [last_seen] tracks which messages each side has seen
+//!
+//! https://minecraft.wiki/w/Java_Edition_protocol/Chat
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::Engine;
+use rsa::pkcs1v15::{Signature, VerifyingKey};
+use rsa::pkcs8::DecodePublicKey;
+use rsa::signature::Verifier;
+use rsa::RsaPublicKey;
+use sha1::Sha1;
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::protocol::packets::packet_parts::auth::PublicKeyNetwork;
+use crate::protocol::secure_chat::chat_error::ChatError;
+
+pub mod chat_error;
+pub mod client_session;
+pub mod last_seen;
+pub mod server_session;
+
+/// The number of previously seen messages that are included in each signed message.
+pub const LAST_SEEN_WINDOW: usize = 20;
+/// The version of the message signature format.
+const SIGNATURE_VERSION: i32 = 1;
+
+/// A SHA256withRSA signature made by a 2048 bit player key.
+pub type MessageSignature = [u8; 256];
+
+/// The public key a player signs their messages with, sent in `PlayerSession`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlayerPublicKey {
+	pub session_id: Uuid,
+	/// When the key expires, in milliseconds since the epoch.
+	pub expires_at: i64,
+	/// The X.509 encoded key, as sent on the network.
+	pub key_bytes: Vec<u8>,
+	/// The signature of the key made by Mojang, see [key_signature_payload].
+	pub key_signature: Vec<u8>,
+	key: RsaPublicKey,
+}
+
+impl PlayerPublicKey {
+	/// Parse the key sent in `PlayerSession`. This does not check that the key is valid, see [PlayerPublicKey::validate].
+	pub fn from_network(session_id: Uuid, key: &PublicKeyNetwork) -> Result<Self, ChatError> {
+		let key_bytes = key.public_key.vec.clone();
+		let parsed = RsaPublicKey::from_public_key_der(&key_bytes).map_err(|e| ChatError::InvalidPublicKey(e.to_string()))?;
+
+		Ok(Self {
+			session_id,
+			expires_at: key.expires_at,
+			key_bytes,
+			key_signature: key.key_signature.vec.clone(),
+			key: parsed,
+		})
+	}
+
+	/// Check that the key has not expired and was signed for the player by one of Mojang's player certificate keys.
+	pub fn validate(&self, player: Uuid, mojang_keys: &[RsaPublicKey]) -> Result<(), ChatError> {
+		if self.is_expired() {
+			return Err(ChatError::ExpiredPublicKey);
+		}
+
+		let payload = key_signature_payload(player, self.expires_at, &self.key_bytes);
+		let signature = Signature::try_from(self.key_signature.as_slice()).map_err(|_| ChatError::InvalidKeySignature)?;
+
+		if mojang_keys.iter().any(|key| VerifyingKey::<Sha1>::new(key.clone()).verify(&payload, &signature).is_ok()) {
+			Ok(())
+		} else {
+			Err(ChatError::InvalidKeySignature)
+		}
+	}
+
+	pub fn is_expired(&self) -> bool {
+		self.expires_at < now_millis()
+	}
+
+	/// Check a message signature made with the private key of the player.
+	pub fn verify(&self, body: &SignedMessageBody, signature: &MessageSignature) -> bool {
+		let Ok(signature) = Signature::try_from(signature.as_slice()) else {
+			return false;
+		};
+
+		VerifyingKey::<Sha256>::new(self.key.clone()).verify(&body.signing_payload(), &signature).is_ok()
+	}
+}
+
+/// Everything covered by the signature of a chat message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignedMessageBody {
+	pub sender: Uuid,
+	pub session_id: Uuid,
+	/// The position of the message in the chain of messages sent during the session, starting at 0.
+	pub index: i32,
+	pub salt: i64,
+	/// When the message was sent, in milliseconds since the epoch.
+	pub timestamp: i64,
+	pub message: String,
+	/// The signatures of the messages the sender acknowledged, oldest first.
+	pub last_seen: Vec<MessageSignature>,
+}
+
+impl SignedMessageBody {
+	/// The bytes that are signed, in the same layout as the Notchian client.
+	pub fn signing_payload(&self) -> Vec<u8> {
+		let message = self.message.as_bytes();
+		let mut payload = Vec::with_capacity(64 + message.len() + self.last_seen.len() * 256);
+
+		payload.extend_from_slice(&SIGNATURE_VERSION.to_be_bytes());
+		payload.extend_from_slice(self.sender.as_bytes());
+		payload.extend_from_slice(self.session_id.as_bytes());
+		payload.extend_from_slice(&self.index.to_be_bytes());
+		payload.extend_from_slice(&self.salt.to_be_bytes());
+		// the timestamp is signed in seconds, even though it is sent in milliseconds
+		payload.extend_from_slice(&(self.timestamp / 1000).to_be_bytes());
+		payload.extend_from_slice(&(message.len() as i32).to_be_bytes());
+		payload.extend_from_slice(message);
+		payload.extend_from_slice(&(self.last_seen.len() as i32).to_be_bytes());
+
+		for signature in &self.last_seen {
+			payload.extend_from_slice(signature);
+		}
+
+		payload
+	}
+}
+
+/// The bytes Mojang signs to certify the public key of a player.
+pub fn key_signature_payload(player: Uuid, expires_at: i64, key_bytes: &[u8]) -> Vec<u8> {
+	let mut payload = Vec::with_capacity(24 + key_bytes.len());
+	payload.extend_from_slice(player.as_bytes());
+	payload.extend_from_slice(&expires_at.to_be_bytes());
+	payload.extend_from_slice(key_bytes);
+	payload
+}
+
+/// The checksum of the acknowledged signatures sent along with a chat message. This matches Java's
+/// `Arrays.hashCode` of each signature folded together, and is never 0 since 0 means "not checked".
+pub fn last_seen_checksum(signatures: &[MessageSignature]) -> i8 {
+	let mut checksum: i32 = 1;
+
+	for signature in signatures {
+		let hash = signature.iter().fold(1i32, |hash, b| hash.wrapping_mul(31).wrapping_add(*b as i8 as i32));
+		checksum = checksum.wrapping_mul(31).wrapping_add(hash);
+	}
+
+	match checksum as i8 {
+		0 => 1,
+		c => c,
+	}
+}
+
+/// Parse one of the player certificate keys returned by `https://api.minecraftservices.com/publickeys`,
+/// which are base64 encoded X.509 keys.
+pub fn parse_mojang_key(key: &str) -> Result<RsaPublicKey, ChatError> {
+	let der = base64::engine::general_purpose::STANDARD.decode(key.trim()).map_err(|e| ChatError::InvalidPublicKey(e.to_string()))?;
+	RsaPublicKey::from_public_key_der(&der).map_err(|e| ChatError::InvalidPublicKey(e.to_string()))
+}
+
+pub(crate) fn now_millis() -> i64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as i64).unwrap_or_default()
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+	use rsa::pkcs8::DecodePrivateKey;
+	use rsa::RsaPrivateKey;
+
+	use crate::protocol::secure_chat::{last_seen_checksum, SignedMessageBody};
+
+	/// A fixed 2048 bit key, since generating one is slow in debug builds.
+	pub(crate) fn test_key() -> RsaPrivateKey {
+		RsaPrivateKey::from_pkcs8_pem(include_str!("test_key.pem")).unwrap()
+	}
+
+	#[test]
+	fn test_checksum() {
+		assert_eq!(last_seen_checksum(&[]), 1);
+
+		// (byte) (31 * 1 + Arrays.hashCode(new byte[256])) in Java
+		assert_eq!(last_seen_checksum(&[[0; 256]]), 32);
+
+		let mut other = [0; 256];
+		other[255] = 1;
+		assert_ne!(last_seen_checksum(&[[0; 256]]), last_seen_checksum(&[other]));
+	}
+
+	#[test]
+	fn test_signing_payload_layout() {
+		let body = SignedMessageBody {
+			sender: uuid::Uuid::from_u128(1),
+			session_id: uuid::Uuid::from_u128(2),
+			index: 3,
+			salt: 4,
+			timestamp: 5_000,
+			message: "hi".to_string(),
+			last_seen: vec![[7; 256]],
+		};
+
+		let payload = body.signing_payload();
+		assert_eq!(payload.len(), 4 + 16 + 16 + 4 + 8 + 8 + 4 + 2 + 4 + 256);
+		assert_eq!(&payload[0..4], &[0, 0, 0, 1]);
+		assert_eq!(&payload[48..56], &5i64.to_be_bytes());
+		assert_eq!(&payload[60..62], b"hi");
+	}
+}
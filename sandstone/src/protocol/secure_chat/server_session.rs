@@ -0,0 +1,254 @@
+//! Validate chat sessions and signed messages on the server, and relay them to other players.
+//!
+//! Each player has one [ServerChatSession]. It checks the messages the player sends, and keeps track of the
+//! messages relayed to the player so that the acknowledgements the player sends back can be checked.
+
+use rsa::RsaPublicKey;
+use uuid::Uuid;
+
+use crate::protocol::game::info::registry::ChatType;
+use crate::protocol::packets::{AcknowledgeMessagePacket, ChatMessagePacket, Packet, PlayerChatMessagePacket, PlayerSessionPacket};
+use crate::protocol::secure_chat::chat_error::ChatError;
+use crate::protocol::secure_chat::last_seen::LastSeenValidator;
+use crate::protocol::secure_chat::{MessageSignature, PlayerPublicKey, SignedMessageBody};
+use crate::protocol::serialization::serializer_types::{PrefixedArray, PrefixedOptional};
+use crate::protocol_types::datatypes::chat::{PlayerChatFilter, PlayerChatSignature, TextComponent};
+use crate::protocol_types::datatypes::internal_types::IDorX;
+use crate::protocol_types::datatypes::var_types::VarInt;
+
+/// A chat message that was accepted from a player, ready to be relayed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignedMessage {
+	pub body: SignedMessageBody,
+	/// The signature of the message, or `None` if the player sent it unsigned.
+	pub signature: Option<MessageSignature>,
+}
+
+/// The chat session of a single player on the server side.
+#[derive(Debug, Clone)]
+pub struct ServerChatSession {
+	player: Uuid,
+	public_key: Option<PlayerPublicKey>,
+	next_index: i32,
+	last_timestamp: i64,
+	/// Set when a message failed validation. The chain can not continue until a new session is started.
+	chain_broken: bool,
+	last_seen: LastSeenValidator,
+	/// The index of the next message relayed to the player, counting every message sent to them.
+	next_global_index: i32,
+	enforce_secure_chat: bool,
+}
+
+impl ServerChatSession {
+	pub fn new(player: Uuid) -> Self {
+		Self {
+			player,
+			public_key: None,
+			next_index: 0,
+			last_timestamp: i64::MIN,
+			chain_broken: false,
+			last_seen: LastSeenValidator::new(),
+			next_global_index: 0,
+			enforce_secure_chat: true,
+		}
+	}
+
+	/// Whether every message must be signed, like `enforce-secure-profile` in `server.properties`. Defaults to true.
+	pub fn set_enforce_secure_chat(&mut self, enforce: bool) {
+		self.enforce_secure_chat = enforce;
+	}
+
+	pub fn get_public_key(&self) -> Option<&PlayerPublicKey> {
+		self.public_key.as_ref()
+	}
+
+	/// Start a new session from `PlayerSession`, checking that the key was signed by one of `mojang_keys`.
+	pub fn handle_session(&mut self, packet: &PlayerSessionPacket, mojang_keys: &[RsaPublicKey]) -> Result<(), ChatError> {
+		let key = PlayerPublicKey::from_network(packet.session_id, &packet.public_key)?;
+		key.validate(self.player, mojang_keys)?;
+
+		self.public_key = Some(key);
+		self.next_index = 0;
+		self.last_timestamp = i64::MIN;
+		self.chain_broken = false;
+
+		Ok(())
+	}
+
+	/// Validate a chat message sent by the player. Any error should disconnect the player.
+	pub fn handle_chat(&mut self, packet: &ChatMessagePacket) -> Result<SignedMessage, ChatError> {
+		let last_seen = self.last_seen.apply_update(packet.message_count.0, &packet.acknowledged, packet.checksum)?;
+
+		let Some(signature) = packet.signature.value().map(|s| **s) else {
+			if self.public_key.is_some() || self.enforce_secure_chat {
+				return Err(ChatError::MissingProfileKey);
+			}
+
+			let body = SignedMessageBody {
+				sender: self.player,
+				session_id: Uuid::nil(),
+				index: 0,
+				salt: packet.salt,
+				timestamp: packet.time,
+				message: packet.message.to_string(),
+				last_seen,
+			};
+
+			return Ok(SignedMessage { body, signature: None });
+		};
+
+		let key = self.public_key.as_ref().ok_or(ChatError::MissingProfileKey)?;
+
+		if key.is_expired() {
+			return Err(ChatError::ExpiredPublicKey);
+		}
+
+		if self.chain_broken {
+			return Err(ChatError::InvalidSignature);
+		}
+
+		if packet.time < self.last_timestamp {
+			return Err(ChatError::OutOfOrder);
+		}
+
+		let body = SignedMessageBody {
+			sender: self.player,
+			session_id: key.session_id,
+			index: self.next_index,
+			salt: packet.salt,
+			timestamp: packet.time,
+			message: packet.message.to_string(),
+			last_seen,
+		};
+
+		if !key.verify(&body, &signature) {
+			self.chain_broken = true;
+			return Err(ChatError::InvalidSignature);
+		}
+
+		self.next_index += 1;
+		self.last_timestamp = packet.time;
+
+		Ok(SignedMessage { body, signature: Some(signature) })
+	}
+
+	/// Move the last seen window forward, as sent by the player in `AcknowledgeMessage`.
+	pub fn handle_acknowledgement(&mut self, packet: &AcknowledgeMessagePacket) -> Result<(), ChatError> {
+		self.last_seen.apply_offset(packet.message_count.0)
+	}
+
+	/// Build the `PlayerChatMessage` packet that relays a message to this player, tracking it so that the
+	/// player is expected to acknowledge it.
+	pub fn relay(&mut self, message: &SignedMessage, chat_type: IDorX<ChatType>, sender_name: TextComponent) -> Result<Packet, ChatError> {
+		if let Some(signature) = message.signature {
+			self.last_seen.add_pending(signature)?;
+		}
+
+		let global_index = self.next_global_index;
+		self.next_global_index += 1;
+
+		let previous = message.body.last_seen.iter().map(|s| PlayerChatSignature::full(*s)).collect();
+
+		Ok(Packet::PlayerChatMessage(PlayerChatMessagePacket::new(
+			VarInt(global_index),
+			message.body.sender,
+			VarInt(message.body.index),
+			PrefixedOptional::new(message.signature),
+			message.body.message.clone(),
+			message.body.timestamp,
+			message.body.salt,
+			Box::new(PrefixedArray::new(previous)),
+			PrefixedOptional::new(None),
+			PlayerChatFilter::PassThrough,
+			None,
+			chat_type,
+			Box::new(sender_name),
+			PrefixedOptional::new(None),
+		)))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use rsa::pkcs1v15::SigningKey;
+	use rsa::pkcs8::EncodePublicKey;
+	use rsa::signature::{SignatureEncoding, Signer};
+	use sha1::Sha1;
+	use uuid::Uuid;
+
+	use crate::protocol::packets::Packet;
+	use crate::protocol::secure_chat::chat_error::ChatError;
+	use crate::protocol::secure_chat::client_session::{ClientChatSession, PlayerCertificate};
+	use crate::protocol::secure_chat::server_session::ServerChatSession;
+	use crate::protocol::secure_chat::test::test_key;
+	use crate::protocol::secure_chat::{key_signature_payload, now_millis};
+	use crate::protocol::serialization::serializer_types::PrefixedOptional;
+	use crate::protocol_types::datatypes::chat::TextComponent;
+	use crate::protocol_types::datatypes::internal_types::IDorX;
+	use crate::protocol_types::datatypes::var_types::VarInt;
+
+	/// A client session whose key is signed by the test key, which stands in for Mojang.
+	fn client(player: Uuid) -> ClientChatSession {
+		let key = test_key();
+		let expires_at = now_millis() + 60_000;
+		let public_key = key.to_public_key().to_public_key_der().unwrap();
+		let payload = key_signature_payload(player, expires_at, public_key.as_bytes());
+		let key_signature = SigningKey::<Sha1>::new(key.clone()).sign(&payload).to_vec();
+
+		ClientChatSession::new(player, PlayerCertificate::new(key, expires_at, key_signature).unwrap())
+	}
+
+	fn start(player: Uuid) -> (ClientChatSession, ServerChatSession) {
+		let client = client(player);
+		let mut server = ServerChatSession::new(player);
+
+		let Packet::PlayerSession(session) = client.session_packet() else { unreachable!() };
+		server.handle_session(&session, &[test_key().to_public_key()]).unwrap();
+
+		(client, server)
+	}
+
+	#[test]
+	fn test_signed_chain_and_relay() {
+		let player = Uuid::from_u128(1);
+		let (mut client, mut server) = start(player);
+
+		let Packet::ChatMessage(first) = client.sign_message("hello").unwrap() else { unreachable!() };
+		let message = server.handle_chat(&first).unwrap();
+		assert_eq!(message.body.index, 0);
+
+		// relay the message back to the sender, who then acknowledges it with their next message
+		let relayed = server.relay(&message, IDorX::Registry(VarInt(0)), TextComponent::from("player".to_string())).unwrap();
+		assert!(client.handle_packet(&relayed).is_none());
+
+		let Packet::ChatMessage(second) = client.sign_message("world").unwrap() else { unreachable!() };
+		let message = server.handle_chat(&second).unwrap();
+		assert_eq!(message.body.index, 1);
+		assert_eq!(message.body.last_seen, vec![message_signature(&relayed)]);
+
+		// a replayed message no longer matches the chain
+		assert!(server.handle_chat(&first).is_err());
+	}
+
+	#[test]
+	fn test_rejects_tampered_and_unsigned() {
+		let player = Uuid::from_u128(2);
+		let (mut client, mut server) = start(player);
+
+		let Packet::ChatMessage(mut packet) = client.sign_message("hello").unwrap() else { unreachable!() };
+		packet.message = "goodbye".try_into().unwrap();
+		assert_eq!(server.clone().handle_chat(&packet), Err(ChatError::InvalidSignature));
+
+		packet.signature = PrefixedOptional::new(None);
+		assert_eq!(server.handle_chat(&packet), Err(ChatError::MissingProfileKey));
+
+		let mut unsigned_server = ServerChatSession::new(player);
+		unsigned_server.set_enforce_secure_chat(false);
+		assert!(unsigned_server.handle_chat(&packet).unwrap().signature.is_none());
+	}
+
+	fn message_signature(packet: &Packet) -> [u8; 256] {
+		let Packet::PlayerChatMessage(chat) = packet else { unreachable!() };
+		*chat.message_signature_bytes.value().unwrap()
+	}
+}
@@ -0,0 +1,494 @@
+//! A readable JSON dump of packets and their parts, for logging and debugging. See [Packet::to_json](crate::protocol::packets::Packet::to_json).
+//!
+//! This goes through the `serde::Serialize` impls of the protocol types, with a few changes to keep the output short:
+//! - byte arrays are a hex string of their first bytes and their length, like `"0a1b2c... (300 bytes)"`
+//! - other arrays stop after their first [MAX_DUMP_ITEMS] items, followed by how many were left out
+//! - map keys that aren't strings are written as strings
+//!
+//! Struct fields keep their order. The dump is only meant to be read, it can't be deserialized again.
+
+use serde::ser::{SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple, SerializeTupleStruct, SerializeTupleVariant};
+use serde::{Serialize, Serializer};
+
+use crate::prelude::*;
+use crate::protocol::serialization::serializer_error::SerializingErr;
+
+/// The most items shown for an array, or bytes for a byte array.
+pub const MAX_DUMP_ITEMS: usize = 32;
+
+/// Dump a value as pretty printed JSON. If the value fails to serialize, the error is dumped instead.
+pub fn to_json<T: Serialize + ?Sized>(value: &T) -> String {
+	let node = value.serialize(Dumper).unwrap_or_else(|e| Node::String(format!("failed to dump: {e}")));
+
+	serde_json::to_string_pretty(&node).unwrap_or_else(|e| format!("\"failed to dump: {e}\""))
+}
+
+/// The first [MAX_DUMP_ITEMS] bytes in hex, followed by the total length.
+pub fn hex_summary(bytes: &[u8]) -> String {
+	summarize(&bytes[..bytes.len().min(MAX_DUMP_ITEMS)], bytes.len())
+}
+
+/// The shown bytes in hex, marked as cut short if there are fewer than `len`.
+fn summarize(shown: &[u8], len: usize) -> String {
+	let cut = if shown.len() < len { "..." } else { "" };
+	let unit = if len == 1 { "byte" } else { "bytes" };
+
+	format!("{}{cut} ({len} {unit})", hex::encode(shown))
+}
+
+/// A dumped value. Like a `serde_json::Value`, but maps keep their order, and bytes are told apart from other
+/// integers so that byte arrays can be summarized.
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+	Null,
+	Bool(bool),
+	UByte(u8),
+	IByte(i8),
+	Int(i128),
+	UInt(u128),
+	Float(f64),
+	String(String),
+	Seq(Vec<Node>),
+	Map(Vec<(String, Node)>),
+}
+
+impl Node {
+	fn as_byte(&self) -> Option<u8> {
+		match self {
+			Node::UByte(b) => Some(*b),
+			Node::IByte(b) => Some(*b as u8),
+			_ => None,
+		}
+	}
+
+	/// The node as a map key, where strings are used as is.
+	fn into_key(self) -> String {
+		match self {
+			Node::String(s) => s,
+			Node::Null => "null".to_string(),
+			Node::Bool(b) => b.to_string(),
+			Node::UByte(b) => b.to_string(),
+			Node::IByte(b) => b.to_string(),
+			Node::Int(i) => i.to_string(),
+			Node::UInt(u) => u.to_string(),
+			Node::Float(f) => f.to_string(),
+			other => serde_json::to_string(&other).unwrap_or_default(),
+		}
+	}
+}
+
+impl Serialize for Node {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		match self {
+			Node::Null => serializer.serialize_unit(),
+			Node::Bool(b) => serializer.serialize_bool(*b),
+			Node::UByte(b) => serializer.serialize_u8(*b),
+			Node::IByte(b) => serializer.serialize_i8(*b),
+			Node::Int(i) => serializer.serialize_i128(*i),
+			Node::UInt(u) => serializer.serialize_u128(*u),
+			// JSON has no NaN or infinity, so they are written out instead of becoming null
+			Node::Float(f) if !f.is_finite() => serializer.serialize_str(&f.to_string()),
+			Node::Float(f) => serializer.serialize_f64(*f),
+			Node::String(s) => serializer.serialize_str(s),
+			Node::Seq(items) => serializer.collect_seq(items),
+			Node::Map(entries) => serializer.collect_map(entries.iter().map(|(k, v)| (k, v))),
+		}
+	}
+}
+
+/// Serializes values into [Node]s.
+struct Dumper;
+
+impl Serializer for Dumper {
+	type Ok = Node;
+	type Error = SerializingErr;
+	type SerializeSeq = Collection;
+	type SerializeTuple = Collection;
+	type SerializeTupleStruct = Collection;
+	type SerializeTupleVariant = Variant<Collection>;
+	type SerializeMap = MapNode;
+	type SerializeStruct = MapNode;
+	type SerializeStructVariant = Variant<MapNode>;
+
+	fn serialize_bool(self, v: bool) -> Result<Node, SerializingErr> {
+		Ok(Node::Bool(v))
+	}
+
+	fn serialize_i8(self, v: i8) -> Result<Node, SerializingErr> {
+		Ok(Node::IByte(v))
+	}
+
+	fn serialize_i16(self, v: i16) -> Result<Node, SerializingErr> {
+		Ok(Node::Int(v as i128))
+	}
+
+	fn serialize_i32(self, v: i32) -> Result<Node, SerializingErr> {
+		Ok(Node::Int(v as i128))
+	}
+
+	fn serialize_i64(self, v: i64) -> Result<Node, SerializingErr> {
+		Ok(Node::Int(v as i128))
+	}
+
+	fn serialize_i128(self, v: i128) -> Result<Node, SerializingErr> {
+		Ok(Node::Int(v))
+	}
+
+	fn serialize_u8(self, v: u8) -> Result<Node, SerializingErr> {
+		Ok(Node::UByte(v))
+	}
+
+	fn serialize_u16(self, v: u16) -> Result<Node, SerializingErr> {
+		Ok(Node::UInt(v as u128))
+	}
+
+	fn serialize_u32(self, v: u32) -> Result<Node, SerializingErr> {
+		Ok(Node::UInt(v as u128))
+	}
+
+	fn serialize_u64(self, v: u64) -> Result<Node, SerializingErr> {
+		Ok(Node::UInt(v as u128))
+	}
+
+	fn serialize_u128(self, v: u128) -> Result<Node, SerializingErr> {
+		Ok(Node::UInt(v))
+	}
+
+	fn serialize_f32(self, v: f32) -> Result<Node, SerializingErr> {
+		Ok(Node::Float(v as f64))
+	}
+
+	fn serialize_f64(self, v: f64) -> Result<Node, SerializingErr> {
+		Ok(Node::Float(v))
+	}
+
+	fn serialize_char(self, v: char) -> Result<Node, SerializingErr> {
+		Ok(Node::String(v.to_string()))
+	}
+
+	fn serialize_str(self, v: &str) -> Result<Node, SerializingErr> {
+		Ok(Node::String(v.to_string()))
+	}
+
+	fn serialize_bytes(self, v: &[u8]) -> Result<Node, SerializingErr> {
+		if v.is_empty() {
+			return Ok(Node::Seq(vec![]));
+		}
+
+		Ok(Node::String(hex_summary(v)))
+	}
+
+	fn serialize_none(self) -> Result<Node, SerializingErr> {
+		Ok(Node::Null)
+	}
+
+	fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Node, SerializingErr> {
+		value.serialize(self)
+	}
+
+	fn serialize_unit(self) -> Result<Node, SerializingErr> {
+		Ok(Node::Null)
+	}
+
+	fn serialize_unit_struct(self, _name: &'static str) -> Result<Node, SerializingErr> {
+		Ok(Node::Null)
+	}
+
+	fn serialize_unit_variant(self, _name: &'static str, _index: u32, variant: &'static str) -> Result<Node, SerializingErr> {
+		Ok(Node::String(variant.to_string()))
+	}
+
+	/// Newtypes like VarInt are just their inner value.
+	fn serialize_newtype_struct<T: Serialize + ?Sized>(self, _name: &'static str, value: &T) -> Result<Node, SerializingErr> {
+		value.serialize(self)
+	}
+
+	fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _name: &'static str, _index: u32, variant: &'static str, value: &T) -> Result<Node, SerializingErr> {
+		Ok(Node::Map(vec![(variant.to_string(), value.serialize(self)?)]))
+	}
+
+	fn serialize_seq(self, _len: Option<usize>) -> Result<Collection, SerializingErr> {
+		Ok(Collection::new())
+	}
+
+	fn serialize_tuple(self, _len: usize) -> Result<Collection, SerializingErr> {
+		Ok(Collection::new())
+	}
+
+	fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Collection, SerializingErr> {
+		Ok(Collection::new())
+	}
+
+	fn serialize_tuple_variant(self, _name: &'static str, _index: u32, variant: &'static str, _len: usize) -> Result<Variant<Collection>, SerializingErr> {
+		Ok(Variant {
+			variant,
+			inner: Collection::new(),
+		})
+	}
+
+	fn serialize_map(self, _len: Option<usize>) -> Result<MapNode, SerializingErr> {
+		Ok(MapNode::new())
+	}
+
+	fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<MapNode, SerializingErr> {
+		Ok(MapNode::new())
+	}
+
+	fn serialize_struct_variant(self, _name: &'static str, _index: u32, variant: &'static str, _len: usize) -> Result<Variant<MapNode>, SerializingErr> {
+		Ok(Variant {
+			variant,
+			inner: MapNode::new(),
+		})
+	}
+}
+
+/// An array being dumped. Only the first [MAX_DUMP_ITEMS] items are kept, the rest are just counted, and
+/// checked for whether they are all bytes.
+struct Collection {
+	items: Vec<Node>,
+	len: usize,
+	bytes: bool,
+}
+
+impl Collection {
+	fn new() -> Self {
+		Self {
+			items: vec![],
+			len: 0,
+			bytes: true,
+		}
+	}
+
+	fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerializingErr> {
+		if self.len < MAX_DUMP_ITEMS || self.bytes {
+			let node = value.serialize(Dumper)?;
+			self.bytes &= node.as_byte().is_some();
+
+			if self.len < MAX_DUMP_ITEMS {
+				self.items.push(node);
+			}
+		}
+
+		self.len += 1;
+		Ok(())
+	}
+
+	fn finish(self) -> Node {
+		if self.len > 0 && self.bytes {
+			let shown: Vec<u8> = self.items.iter().filter_map(Node::as_byte).collect();
+			return Node::String(summarize(&shown, self.len));
+		}
+
+		let mut items = self.items;
+		if self.len > MAX_DUMP_ITEMS {
+			items.push(Node::String(format!("... {} more", self.len - MAX_DUMP_ITEMS)));
+		}
+
+		Node::Seq(items)
+	}
+}
+
+impl SerializeSeq for Collection {
+	type Ok = Node;
+	type Error = SerializingErr;
+
+	fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerializingErr> {
+		self.push(value)
+	}
+
+	fn end(self) -> Result<Node, SerializingErr> {
+		Ok(self.finish())
+	}
+}
+
+impl SerializeTuple for Collection {
+	type Ok = Node;
+	type Error = SerializingErr;
+
+	fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerializingErr> {
+		self.push(value)
+	}
+
+	fn end(self) -> Result<Node, SerializingErr> {
+		Ok(self.finish())
+	}
+}
+
+impl SerializeTupleStruct for Collection {
+	type Ok = Node;
+	type Error = SerializingErr;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerializingErr> {
+		self.push(value)
+	}
+
+	fn end(self) -> Result<Node, SerializingErr> {
+		Ok(self.finish())
+	}
+}
+
+/// A map or struct being dumped.
+struct MapNode {
+	entries: Vec<(String, Node)>,
+	key: Option<String>,
+}
+
+impl MapNode {
+	fn new() -> Self {
+		Self {
+			entries: vec![],
+			key: None,
+		}
+	}
+}
+
+impl SerializeMap for MapNode {
+	type Ok = Node;
+	type Error = SerializingErr;
+
+	fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerializingErr> {
+		self.key = Some(key.serialize(Dumper)?.into_key());
+		Ok(())
+	}
+
+	fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerializingErr> {
+		let key = self.key.take().ok_or_else(|| SerializingErr::UniqueFailure("Map value without a key".to_string()))?;
+		self.entries.push((key, value.serialize(Dumper)?));
+		Ok(())
+	}
+
+	fn end(self) -> Result<Node, SerializingErr> {
+		Ok(Node::Map(self.entries))
+	}
+}
+
+impl SerializeStruct for MapNode {
+	type Ok = Node;
+	type Error = SerializingErr;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), SerializingErr> {
+		self.entries.push((key.to_string(), value.serialize(Dumper)?));
+		Ok(())
+	}
+
+	fn end(self) -> Result<Node, SerializingErr> {
+		Ok(Node::Map(self.entries))
+	}
+}
+
+/// A tuple or struct variant being dumped, which is wrapped in its variant name like serde_json does.
+struct Variant<T> {
+	variant: &'static str,
+	inner: T,
+}
+
+impl SerializeTupleVariant for Variant<Collection> {
+	type Ok = Node;
+	type Error = SerializingErr;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerializingErr> {
+		self.inner.push(value)
+	}
+
+	fn end(self) -> Result<Node, SerializingErr> {
+		Ok(Node::Map(vec![(self.variant.to_string(), self.inner.finish())]))
+	}
+}
+
+impl SerializeStructVariant for Variant<MapNode> {
+	type Ok = Node;
+	type Error = SerializingErr;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<(), SerializingErr> {
+		SerializeStruct::serialize_field(&mut self.inner, key, value)
+	}
+
+	fn end(self) -> Result<Node, SerializingErr> {
+		Ok(Node::Map(vec![(self.variant.to_string(), Node::Map(self.inner.entries))]))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use serde_json::{json, Value};
+
+	use crate::protocol::packets::packet_parts::PlayerAbilityFlags;
+	use crate::protocol::packets::{ClientboundPluginMessagePacket, KeepAlivePacket, Packet, PlayerAbilitiesPacket};
+	use crate::protocol::serialization::dump::{hex_summary, to_json, MAX_DUMP_ITEMS};
+	use crate::protocol::serialization::serializer_types::{BoundedString, PrefixedArray};
+	use crate::protocol_types::datatypes::var_types::VarInt;
+
+	fn dump(value: &impl serde::Serialize) -> Value {
+		serde_json::from_str(&to_json(value)).unwrap()
+	}
+
+	#[test]
+	fn packet_to_json() {
+		let packet = Packet::KeepAlive(KeepAlivePacket {
+			keep_alive_id: 5,
+		});
+
+		assert_eq!(serde_json::from_str::<Value>(&packet.to_json()).unwrap(), json!({"KeepAlive": {"keep_alive_id": 5}}));
+	}
+
+	#[test]
+	fn fields_keep_their_order() {
+		let packet = Packet::PlayerAbilities(PlayerAbilitiesPacket {
+			flags: PlayerAbilityFlags::new(false, true, true, false),
+			flying_speed: 0.05,
+			fov_modifier: 0.1,
+		});
+		let json = packet.to_json();
+
+		let flags = json.find("\"flags\"").unwrap();
+		let speed = json.find("\"flying_speed\"").unwrap();
+		let fov = json.find("\"fov_modifier\"").unwrap();
+		assert!(flags < speed && speed < fov, "{json}");
+	}
+
+	#[test]
+	fn bitflags_by_name() {
+		let flags = PlayerAbilityFlags::new(false, true, true, false);
+
+		assert_eq!(dump(&flags), json!({"invulnerable": false, "flying": true, "allow_flying": true, "creative_mode": false}));
+	}
+
+	#[test]
+	fn byte_arrays_are_summarized() {
+		let packet = Packet::ClientboundPluginMessage(ClientboundPluginMessagePacket {
+			channel: BoundedString::new("minecraft:brand").unwrap(),
+			data: (0..100).collect(),
+		});
+
+		let expected = format!("{}... (100 bytes)", hex::encode((0..MAX_DUMP_ITEMS as u8).collect::<Vec<u8>>()));
+		assert_eq!(dump(&packet)["ClientboundPluginMessage"]["data"], json!(expected));
+
+		assert_eq!(dump(&PrefixedArray::new(vec![0xCAu8, 0xFE])), json!("cafe (2 bytes)"));
+		assert_eq!(dump(&vec![-1i8]), json!("ff (1 byte)"));
+		assert_eq!(dump(&Vec::<u8>::new()), json!([]));
+		assert_eq!(hex_summary(&[1, 2, 3]), "010203 (3 bytes)");
+	}
+
+	#[test]
+	fn long_arrays_are_cut_short() {
+		let ids: Vec<VarInt> = (0..40).map(VarInt).collect();
+		let out = dump(&PrefixedArray::new(ids));
+
+		let items = out.as_array().unwrap();
+		assert_eq!(items.len(), MAX_DUMP_ITEMS + 1);
+		assert_eq!(items[0], json!(0));
+		assert_eq!(items[MAX_DUMP_ITEMS - 1], json!(MAX_DUMP_ITEMS - 1));
+		assert_eq!(items[MAX_DUMP_ITEMS], json!("... 8 more"));
+
+		assert_eq!(dump(&vec![1i32, 2, 3]), json!([1, 2, 3]));
+	}
+
+	#[test]
+	fn non_finite_floats_and_keys() {
+		assert_eq!(dump(&f32::NAN), json!("NaN"));
+		assert_eq!(dump(&f64::NEG_INFINITY), json!("-inf"));
+
+		let map: alloc::collections::BTreeMap<i32, bool> = [(1, true)].into();
+		assert_eq!(dump(&map), json!({"1": true}));
+	}
+}
@@ -0,0 +1,111 @@
+//! Records which bytes of the input each field was read from. This is used by tools that annotate raw
+//! packets, like the `sandstone-dissect` CLI. It needs the `field-spans` feature, which adds the recorder to
+//! [McDeserializer] and makes it neither `Send` nor `Sync`.
+
+use core::cell::RefCell;
+use core::hash::{Hash, Hasher};
+use core::ops::Range;
+
+use crate::prelude::*;
+use crate::protocol::serialization::{McDeserializer, PathSegment};
+
+/// A field that was read from the input, see [FieldRecorder].
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize)]
+pub struct FieldSpan {
+	/// The path of the field, like `players[3].properties[0].signature`.
+	pub path: String,
+	/// The number of fields that this one is inside of.
+	pub depth: usize,
+	/// The bytes of the input that the field was read from.
+	pub range: Range<usize>,
+}
+
+/// Records a [FieldSpan] for every field read by a [McDeserializer] created with [McDeserializer::with_recorder],
+/// and by its sub-deserializers. Fields that are read from a copy of the input, like decompressed data, are skipped.
+///
+/// Recorders are only equal to themselves, so that a [McDeserializer] can still be compared and hashed.
+#[derive(Debug)]
+pub struct FieldRecorder {
+	/// The address of the first byte of the input.
+	start: usize,
+	len: usize,
+	spans: RefCell<Vec<FieldSpan>>,
+	/// The indices of the spans that are still being read, innermost last.
+	open: RefCell<Vec<usize>>,
+}
+
+impl FieldRecorder {
+	/// Create a recorder for the fields read from `input`.
+	pub fn new(input: &[u8]) -> Self {
+		Self {
+			start: input.as_ptr() as usize,
+			len: input.len(),
+			spans: RefCell::new(vec![]),
+			open: RefCell::new(vec![]),
+		}
+	}
+
+	/// The fields that were read, in the order they were started in. A field comes right before the fields inside of it.
+	pub fn into_spans(self) -> Vec<FieldSpan> {
+		self.spans.into_inner()
+	}
+
+	/// The offset in the input of the current position of `deserializer`, if it is reading from the input.
+	fn offset(&self, deserializer: &McDeserializer) -> Option<usize> {
+		let address = deserializer.data.as_ptr() as usize + deserializer.index.min(deserializer.data.len());
+
+		if address < self.start || address > self.start + self.len {
+			return None;
+		}
+
+		Some(address - self.start)
+	}
+
+	/// Start a field at the current position of `deserializer`. Returns the index of its span, to be passed to [FieldRecorder::exit].
+	pub(crate) fn enter(&self, segment: PathSegment, deserializer: &McDeserializer) -> Option<usize> {
+		let offset = self.offset(deserializer)?;
+		let mut spans = self.spans.borrow_mut();
+		let mut open = self.open.borrow_mut();
+
+		let parent = open.last().map(|i| spans[*i].path.as_str()).unwrap_or("");
+		let path = match segment {
+			PathSegment::Field(name) if parent.is_empty() => name.to_string(),
+			PathSegment::Field(name) => format!("{parent}.{name}"),
+			PathSegment::Index(index) => format!("{parent}[{index}]"),
+		};
+
+		open.push(spans.len());
+		spans.push(FieldSpan {
+			path,
+			depth: open.len() - 1,
+			range: offset..offset,
+		});
+
+		open.last().copied()
+	}
+
+	/// Finish the span started by [FieldRecorder::enter] at the current position of `deserializer`.
+	pub(crate) fn exit(&self, span: usize, deserializer: &McDeserializer) {
+		let mut spans = self.spans.borrow_mut();
+
+		if let Some(end) = self.offset(deserializer) {
+			spans[span].range.end = end.max(spans[span].range.start);
+		}
+
+		self.open.borrow_mut().pop();
+	}
+}
+
+impl PartialEq for FieldRecorder {
+	fn eq(&self, other: &Self) -> bool {
+		core::ptr::eq(self, other)
+	}
+}
+
+impl Eq for FieldRecorder {}
+
+impl Hash for FieldRecorder {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		core::ptr::hash(self, state);
+	}
+}
@@ -4,11 +4,20 @@
 //! Conversely, information can also be "deserialized" from raw bytes into the original type. This is useful
 //! for reading packets from the network.
 
-use std::cmp::min;
+use core::cmp::min;
 
+use crate::prelude::*;
 use crate::protocol::packets::packet_definer::{PacketDirection, PacketState};
+#[cfg(feature = "field-spans")]
+use crate::protocol::serialization::field_spans::FieldRecorder;
 use crate::protocol::serialization::serializer_error::SerializingErr;
+use crate::protocol_types::datatypes::var_types::VarInt;
+use crate::protocol_types::protocol_verison::ProtocolVerison;
 
+pub mod dump;
+#[cfg(feature = "field-spans")]
+pub mod field_spans;
+pub mod serde_format;
 pub mod serializer_error;
 mod serializer_testing;
 pub mod serializer_types;
@@ -57,16 +66,39 @@ impl McSerializer {
 	}
 
 	/// Add a slice of bytes to the internal buffer. Reallocates the buffer
-	/// for the new amount of space required before pushing.
+	/// for the new amount of space required before copying.
 	pub fn serialize_bytes(&mut self, input: &[u8]) {
-		for b in input {
-			self.output.push(*b);
-		}
+		self.output.extend_from_slice(input);
 	}
 
 	/// Serialize a Vec of bytes and fix it to the end of the internal buffer.
 	pub fn serialize_vec(&mut self, vec: Vec<u8>) {
-		self.serialize_bytes(&vec);
+		self.output.extend(vec);
+	}
+
+	/// Serialize whatever `f` writes, prefixed with its length in bytes as a VarInt, without writing it twice.
+	/// Space for a 3 byte VarInt, which fits the length of any packet, is reserved before `f` runs and filled in after.
+	/// If the length needs a different number of bytes, the data is moved to fit it.
+	pub fn serialize_length_prefixed<F>(&mut self, f: F) -> SerializingResult<'static, ()>
+	where
+		F: FnOnce(&mut McSerializer) -> SerializingResult<()>,
+	{
+		const RESERVED: usize = 3;
+
+		let start = self.output.len();
+		self.output.extend_from_slice(&[0; RESERVED]);
+		f(self)?;
+
+		let length = self.output.len() - start - RESERVED;
+		let (bytes, len) = VarInt(length as i32).to_byte_array();
+
+		if len == RESERVED {
+			self.output[start..start + RESERVED].copy_from_slice(&bytes[..len]);
+		} else {
+			self.output.splice(start..start + RESERVED, bytes[..len].iter().copied());
+		}
+
+		Ok(())
 	}
 
 	/// Add a single byte to the end of the internal buffer.
@@ -87,7 +119,7 @@ impl McSerializer {
 	/// Merge the contents of the provided McSerializer into this one. The contents of the provided serializer
 	/// are appended to the end of the current serializer's output.
 	pub fn merge(&mut self, serializer: McSerializer) {
-		self.serialize_bytes(&serializer.output);
+		self.output.extend(serializer.output);
 	}
 
 	pub fn as_bytes(&self) -> &[u8] {
@@ -116,6 +148,16 @@ impl From<McSerializer> for Vec<u8> {
 pub struct McDeserializer<'a> {
 	pub data: &'a [u8],
 	pub index: usize,
+	/// Records the bytes that each field is read from, see [McDeserializer::with_recorder].
+	#[cfg(feature = "field-spans")]
+	recorder: Option<&'a FieldRecorder>,
+}
+
+/// Part of the path of a field, see [McDeserializer::read_field] and [McDeserializer::read_item].
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum PathSegment<'s> {
+	Field(&'s str),
+	Index(usize),
 }
 
 impl<'a> McDeserializer<'a> {
@@ -124,17 +166,30 @@ impl<'a> McDeserializer<'a> {
 		Self {
 			data,
 			index: 0,
+			#[cfg(feature = "field-spans")]
+			recorder: None,
+		}
+	}
+
+	/// Create a new McDeserializer that records the bytes each field is read from in `recorder`. The recorder
+	/// should be created for the same slice of bytes.
+	#[cfg(feature = "field-spans")]
+	pub fn with_recorder(data: &'a [u8], recorder: &'a FieldRecorder) -> Self {
+		Self {
+			data,
+			index: 0,
+			recorder: Some(recorder),
 		}
 	}
 
 	/// Collect the remaining data into a sub-slice
-	pub fn collect_remaining(&self) -> &[u8] {
+	pub fn collect_remaining(&self) -> &'a [u8] {
 		&self.data[self.index..]
 	}
 
 	/// Slice the internal buffer, starting at the current index and up to the bound provided. Will
 	/// cut off the subslice at max(data.len, bound + index) to prevent overflow
-	pub fn slice(&mut self, bound: usize) -> &[u8] {
+	pub fn slice(&mut self, bound: usize) -> &'a [u8] {
 		let actual = min(self.data.len(), bound) + self.index;
 		let actual = min(actual, self.data.len());
 
@@ -145,8 +200,8 @@ impl<'a> McDeserializer<'a> {
 
 	/// Slice the internal buffer, starting at the current index and up to the
 	/// bound provided, but only if it is within bounds
-	pub fn slice_option(&mut self, bound: usize) -> Option<&[u8]> {
-		if self.index + bound > self.data.len() {
+	pub fn slice_option(&mut self, bound: usize) -> Option<&'a [u8]> {
+		if bound > self.remaining() {
 			return None;
 		}
 
@@ -184,6 +239,58 @@ impl<'a> McDeserializer<'a> {
 		}
 	}
 
+	/// The number of bytes left to read.
+	pub fn remaining(&self) -> usize {
+		self.data.len().saturating_sub(self.index)
+	}
+
+	/// Check a length that was read from the input, and return how many items to reserve space for.
+	/// Each item takes at least a byte, so the capacity is capped at the remaining input to keep a forged
+	/// length from causing a huge allocation.
+	pub fn capacity_for(&self, length: i32) -> SerializingResult<'static, usize> {
+		if length < 0 {
+			return Err(SerializingErr::OutOfBounds(format!("Negative length {}", length)));
+		}
+
+		Ok((length as usize).min(self.remaining()))
+	}
+
+	/// Read a field with `f`, adding the field to the path of any error it returns. See [SerializingErr::in_field].
+	pub fn read_field<T, F>(&mut self, field: &str, f: F) -> SerializingResult<'a, T>
+	where
+		F: FnOnce(&mut Self) -> SerializingResult<'a, T>,
+	{
+		self.read_segment(PathSegment::Field(field), f)
+	}
+
+	/// Read an item of an array with `f`, adding its index to the path of any error it returns. See [SerializingErr::at_index].
+	pub fn read_item<T, F>(&mut self, index: usize, f: F) -> SerializingResult<'a, T>
+	where
+		F: FnOnce(&mut Self) -> SerializingResult<'a, T>,
+	{
+		self.read_segment(PathSegment::Index(index), f)
+	}
+
+	fn read_segment<T, F>(&mut self, segment: PathSegment, f: F) -> SerializingResult<'a, T>
+	where
+		F: FnOnce(&mut Self) -> SerializingResult<'a, T>,
+	{
+		#[cfg(feature = "field-spans")]
+		let span = self.recorder.and_then(|recorder| recorder.enter(segment, self));
+
+		let result = f(self).map_err(|e| match segment {
+			PathSegment::Field(field) => e.in_field(field, self),
+			PathSegment::Index(index) => e.at_index(index, self),
+		});
+
+		#[cfg(feature = "field-spans")]
+		if let (Some(recorder), Some(span)) = (self.recorder, span) {
+			recorder.exit(span, self);
+		}
+
+		result
+	}
+
 	/// Return true if the current index is at the end of the data buffer.
 	pub fn is_at_end(&self) -> bool {
 		self.index >= self.data.len()
@@ -196,19 +303,27 @@ impl<'a> McDeserializer<'a> {
 
 	/// Creates a new McDeserializer only including the remaining unused data.
 	/// Used in conjunction with reset()
-	pub fn create_sub_deserializer(&self) -> McDeserializer {
-		McDeserializer::new(&self.data[self.index..])
+	pub fn create_sub_deserializer(&self) -> McDeserializer<'a> {
+		McDeserializer {
+			#[cfg(feature = "field-spans")]
+			recorder: self.recorder,
+			..McDeserializer::new(&self.data[self.index..])
+		}
 	}
 
 	/// Create a new McDeserializer with a start at `index` and an end at `index + end`.
 	/// Basically reserves the number of bytes you specify for the sub-deserializer.
 	/// Also increments the parent McDeserializer's index by `end`
-	pub fn sub_deserializer_length(&mut self, end: usize) -> SerializingResult<McDeserializer> {
-		if self.index + end > self.data.len() {
+	pub fn sub_deserializer_length(&mut self, end: usize) -> SerializingResult<'a, McDeserializer<'a>> {
+		if end > self.remaining() {
 			return Err(SerializingErr::UniqueFailure("Sub-deserializer length exceeds data length".to_string()));
 		}
 
-		let ret = Ok(McDeserializer::new(&self.data[self.index..(self.index + end)]));
+		let ret = Ok(McDeserializer {
+			#[cfg(feature = "field-spans")]
+			recorder: self.recorder,
+			..McDeserializer::new(&self.data[self.index..(self.index + end)])
+		});
 
 		self.index += end;
 
@@ -217,7 +332,7 @@ impl<'a> McDeserializer<'a> {
 
 	/// Create a sub-slice of the data, before and after the current index. Useful for debugging.
 	/// Set start rel to the number of bytes before the current index, and end rel to the number of bytes after the current index.
-	pub fn subset(&self, mut start_rel: usize, mut end_rel: usize) -> &[u8] {
+	pub fn subset(&self, mut start_rel: usize, mut end_rel: usize) -> &'a [u8] {
 		start_rel = self.index.saturating_sub(start_rel);
 		end_rel = self.index.saturating_add(end_rel);
 
@@ -236,6 +351,22 @@ pub trait McDeserialize {
 		Self: Sized;
 }
 
+/// Deserializes types that borrow from the input buffer instead of copying out of it, such as `&'de str`,
+/// `&'de [u8]` or `Cow<'de, str>`. The result can live as long as the buffer itself, which lets
+/// large payloads (chunk data, plugin messages) be inspected without allocating.
+///
+/// Every [McDeserialize] type implements this trait, so borrowed and owned fields can be mixed in one struct.
+pub trait McDeserializeBorrowed<'de>: Sized {
+	/// Deserialize the byte buffer into the type that implements this trait, borrowing from the buffer where possible.
+	fn mc_deserialize_borrowed(deserializer: &mut McDeserializer<'de>) -> SerializingResult<'de, Self>;
+}
+
+impl<'de, T: McDeserialize> McDeserializeBorrowed<'de> for T {
+	fn mc_deserialize_borrowed(deserializer: &mut McDeserializer<'de>) -> SerializingResult<'de, Self> {
+		T::mc_deserialize(deserializer)
+	}
+}
+
 /// Deserialize data given the current packet state and the packet id. This is needed since
 /// the packet id is not enough to determine the packet type in some cases.
 /// (ie. Both STATUS and HANDSHAKING states have a packet with ID 0)
@@ -243,6 +374,14 @@ pub trait StateBasedDeserializer {
 	/// Deserialize the byte buffer into a 'Packet'. This takes 2 extra arguments, the packet state and the
 	/// direction of the packet to narrow down the exact packet that should be deserialized.
 	fn deserialize_state<'a>(deserializer: &'a mut McDeserializer, state: PacketState, packet_direction: PacketDirection) -> SerializingResult<'a, Self>
+	where
+		Self: Sized,
+	{
+		Self::deserialize_state_version(deserializer, state, packet_direction, ProtocolVerison::latest())
+	}
+
+	/// Like [StateBasedDeserializer::deserialize_state], for a packet sent in the given protocol version.
+	fn deserialize_state_version<'a>(deserializer: &'a mut McDeserializer, state: PacketState, packet_direction: PacketDirection, version: ProtocolVerison) -> SerializingResult<'a, Self>
 	where
 		Self: Sized;
 }
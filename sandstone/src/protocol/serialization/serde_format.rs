@@ -0,0 +1,918 @@
+//! A serde data format for the binary encoding of the protocol, so that types which derive `serde::Serialize`
+//! and `serde::Deserialize` can be sent without implementing [McSerialize](crate::protocol::serialization::McSerialize).
+//!
+//! The serde data model is mapped onto the protocol types like this:
+//! - integers and floats are big endian, `u128` and `i128` are 16 bytes
+//! - `bool` is a single byte
+//! - strings, byte arrays, sequences and maps have a VarInt length prefix
+//! - `Option` has a bool prefix, like a Prefixed Optional
+//! - structs and tuples are their fields in order, without names or a length
+//! - enums are a VarInt of the variant index, followed by the fields of the variant
+//!
+//! The format is not self-describing, so `deserialize_any` is not supported. This rules out attributes like
+//! `#[serde(flatten)]`, `#[serde(untagged)]` and `#[serde(skip_serializing_if)]`.
+//!
+//! Fields can use the encodings of the protocol through `#[serde(with = "...")]`, with [varint], [varlong] and
+//! [uuid]. These are the same as the regular encoding in other formats, like JSON.
+//!
+//! https://minecraft.wiki/w/Java_Edition_protocol/Data_types
+
+use core::fmt::Display;
+
+use serde::de::{DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess, SeqAccess, VariantAccess, Visitor};
+use serde::ser::{SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant, SerializeTuple, SerializeTupleStruct, SerializeTupleVariant};
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::*;
+use crate::protocol::serialization::serializer_error::SerializingErr;
+use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, SerializingResult};
+use crate::protocol_types::datatypes::var_types::{VarInt, VarLong};
+
+/// The newtype name that [varint] wraps values in, which tells the format to use a VarInt.
+const VARINT_NAME: &str = "$sandstone::VarInt";
+/// The newtype name that [varlong] wraps values in, which tells the format to use a VarLong.
+const VARLONG_NAME: &str = "$sandstone::VarLong";
+
+impl serde::ser::Error for SerializingErr {
+	fn custom<T: Display>(msg: T) -> Self {
+		SerializingErr::UniqueFailure(msg.to_string())
+	}
+}
+
+impl serde::de::Error for SerializingErr {
+	fn custom<T: Display>(msg: T) -> Self {
+		SerializingErr::DeserializationError(msg.to_string())
+	}
+}
+
+/// Serialize a value to a new buffer.
+pub fn to_bytes<T: Serialize + ?Sized>(value: &T) -> SerializingResult<'static, Vec<u8>> {
+	let mut serializer = McSerializer::new();
+	serialize_into(&mut serializer, value)?;
+
+	Ok(serializer.output)
+}
+
+/// Serialize a value to the end of a serializer. This can be used to implement `McSerialize` for a type that
+/// implements `Serialize`.
+pub fn serialize_into<T: Serialize + ?Sized>(serializer: &mut McSerializer, value: &T) -> SerializingResult<'static, ()> {
+	value.serialize(&mut SerdeSerializer::new(serializer))
+}
+
+/// Deserialize a value that takes up all of `bytes`.
+pub fn from_bytes<'de, T: Deserialize<'de>>(bytes: &'de [u8]) -> SerializingResult<'de, T> {
+	let mut deserializer = McDeserializer::new(bytes);
+	let value = deserialize_from(&mut deserializer)?;
+
+	if !deserializer.is_at_end() {
+		return Err(SerializingErr::LeftoverInput);
+	}
+
+	Ok(value)
+}
+
+/// Deserialize a value from the current position of a deserializer. This can be used to implement
+/// `McDeserialize` for a type that implements `Deserialize`.
+pub fn deserialize_from<'de, T: Deserialize<'de>>(deserializer: &mut McDeserializer<'de>) -> SerializingResult<'de, T> {
+	T::deserialize(&mut SerdeDeserializer::new(deserializer))
+}
+
+/// Which var-type the next integer is written as, set by [varint] and [varlong].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VarEncoding {
+	VarInt,
+	VarLong,
+}
+
+impl VarEncoding {
+	fn from_name(name: &str) -> Option<Self> {
+		match name {
+			VARINT_NAME => Some(VarEncoding::VarInt),
+			VARLONG_NAME => Some(VarEncoding::VarLong),
+			_ => None,
+		}
+	}
+}
+
+/// A `serde::Serializer` that writes the protocol encoding to an [McSerializer].
+pub struct SerdeSerializer<'a> {
+	serializer: &'a mut McSerializer,
+	var_encoding: Option<VarEncoding>,
+}
+
+impl<'a> SerdeSerializer<'a> {
+	pub fn new(serializer: &'a mut McSerializer) -> Self {
+		Self {
+			serializer,
+			var_encoding: None,
+		}
+	}
+
+	/// Write an integer, as a var-type if the field asked for one.
+	fn write_int(&mut self, value: i128, bytes: &[u8]) -> SerializingResult<'static, ()> {
+		match self.var_encoding.take() {
+			Some(VarEncoding::VarInt) => {
+				let value = i32::try_from(value).map_err(|_| SerializingErr::OutOfBounds(format!("{} does not fit in a VarInt", value)))?;
+				VarInt(value).mc_serialize(self.serializer)
+			}
+			Some(VarEncoding::VarLong) => {
+				let value = i64::try_from(value).map_err(|_| SerializingErr::OutOfBounds(format!("{} does not fit in a VarLong", value)))?;
+				VarLong(value).mc_serialize(self.serializer)
+			}
+			None => {
+				self.serializer.serialize_bytes(bytes);
+				Ok(())
+			}
+		}
+	}
+
+	fn write_length(&mut self, len: Option<usize>) -> SerializingResult<'static, ()> {
+		let len = len.ok_or_else(|| SerializingErr::UniqueFailure("Sequences and maps must have a known length".to_string()))?;
+		VarInt(len as i32).mc_serialize(self.serializer)
+	}
+
+	fn not_an_integer(&mut self) -> SerializingResult<'static, ()> {
+		match self.var_encoding {
+			Some(_) => Err(SerializingErr::UniqueFailure("Only integers can be written as a VarInt or VarLong".to_string())),
+			None => Ok(()),
+		}
+	}
+}
+
+impl<'a> serde::Serializer for &mut SerdeSerializer<'a> {
+	type Ok = ();
+	type Error = SerializingErr;
+	type SerializeSeq = Self;
+	type SerializeTuple = Self;
+	type SerializeTupleStruct = Self;
+	type SerializeTupleVariant = Self;
+	type SerializeMap = Self;
+	type SerializeStruct = Self;
+	type SerializeStructVariant = Self;
+
+	fn serialize_bool(self, v: bool) -> Result<(), SerializingErr> {
+		self.not_an_integer()?;
+		self.serializer.serialize_u8(v as u8);
+		Ok(())
+	}
+
+	fn serialize_i8(self, v: i8) -> Result<(), SerializingErr> {
+		self.write_int(v as i128, &v.to_be_bytes())
+	}
+
+	fn serialize_i16(self, v: i16) -> Result<(), SerializingErr> {
+		self.write_int(v as i128, &v.to_be_bytes())
+	}
+
+	fn serialize_i32(self, v: i32) -> Result<(), SerializingErr> {
+		self.write_int(v as i128, &v.to_be_bytes())
+	}
+
+	fn serialize_i64(self, v: i64) -> Result<(), SerializingErr> {
+		self.write_int(v as i128, &v.to_be_bytes())
+	}
+
+	fn serialize_i128(self, v: i128) -> Result<(), SerializingErr> {
+		self.write_int(v, &v.to_be_bytes())
+	}
+
+	fn serialize_u8(self, v: u8) -> Result<(), SerializingErr> {
+		self.write_int(v as i128, &v.to_be_bytes())
+	}
+
+	fn serialize_u16(self, v: u16) -> Result<(), SerializingErr> {
+		self.write_int(v as i128, &v.to_be_bytes())
+	}
+
+	fn serialize_u32(self, v: u32) -> Result<(), SerializingErr> {
+		self.write_int(v as i128, &v.to_be_bytes())
+	}
+
+	fn serialize_u64(self, v: u64) -> Result<(), SerializingErr> {
+		self.write_int(v as i128, &v.to_be_bytes())
+	}
+
+	fn serialize_u128(self, v: u128) -> Result<(), SerializingErr> {
+		// larger than any var-type, so this only fails if a var-type was asked for
+		self.write_int(i128::try_from(v).unwrap_or(i128::MAX), &v.to_be_bytes())
+	}
+
+	fn serialize_f32(self, v: f32) -> Result<(), SerializingErr> {
+		self.not_an_integer()?;
+		self.serializer.serialize_bytes(&v.to_be_bytes());
+		Ok(())
+	}
+
+	fn serialize_f64(self, v: f64) -> Result<(), SerializingErr> {
+		self.not_an_integer()?;
+		self.serializer.serialize_bytes(&v.to_be_bytes());
+		Ok(())
+	}
+
+	fn serialize_char(self, v: char) -> Result<(), SerializingErr> {
+		self.serialize_str(v.encode_utf8(&mut [0; 4]))
+	}
+
+	fn serialize_str(self, v: &str) -> Result<(), SerializingErr> {
+		self.not_an_integer()?;
+		v.mc_serialize(self.serializer)
+	}
+
+	fn serialize_bytes(self, v: &[u8]) -> Result<(), SerializingErr> {
+		self.not_an_integer()?;
+		self.write_length(Some(v.len()))?;
+		self.serializer.serialize_bytes(v);
+		Ok(())
+	}
+
+	fn serialize_none(self) -> Result<(), SerializingErr> {
+		self.var_encoding = None;
+		self.serializer.serialize_u8(0);
+		Ok(())
+	}
+
+	fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), SerializingErr> {
+		// the var-type applies to the value, not the prefix
+		self.serializer.serialize_u8(1);
+		value.serialize(self)
+	}
+
+	fn serialize_unit(self) -> Result<(), SerializingErr> {
+		self.not_an_integer()
+	}
+
+	fn serialize_unit_struct(self, _name: &'static str) -> Result<(), SerializingErr> {
+		self.not_an_integer()
+	}
+
+	fn serialize_unit_variant(self, _name: &'static str, variant_index: u32, _variant: &'static str) -> Result<(), SerializingErr> {
+		self.not_an_integer()?;
+		VarInt(variant_index as i32).mc_serialize(self.serializer)
+	}
+
+	fn serialize_newtype_struct<T: Serialize + ?Sized>(self, name: &'static str, value: &T) -> Result<(), SerializingErr> {
+		if let Some(encoding) = VarEncoding::from_name(name) {
+			self.var_encoding = Some(encoding);
+			let result = value.serialize(&mut *self);
+			self.var_encoding = None;
+			return result;
+		}
+
+		value.serialize(self)
+	}
+
+	fn serialize_newtype_variant<T: Serialize + ?Sized>(self, _name: &'static str, variant_index: u32, _variant: &'static str, value: &T) -> Result<(), SerializingErr> {
+		self.not_an_integer()?;
+		VarInt(variant_index as i32).mc_serialize(self.serializer)?;
+		value.serialize(self)
+	}
+
+	fn serialize_seq(self, len: Option<usize>) -> Result<Self, SerializingErr> {
+		self.not_an_integer()?;
+		self.write_length(len)?;
+		Ok(self)
+	}
+
+	fn serialize_tuple(self, _len: usize) -> Result<Self, SerializingErr> {
+		self.not_an_integer()?;
+		Ok(self)
+	}
+
+	fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self, SerializingErr> {
+		self.not_an_integer()?;
+		Ok(self)
+	}
+
+	fn serialize_tuple_variant(self, _name: &'static str, variant_index: u32, _variant: &'static str, _len: usize) -> Result<Self, SerializingErr> {
+		self.not_an_integer()?;
+		VarInt(variant_index as i32).mc_serialize(self.serializer)?;
+		Ok(self)
+	}
+
+	fn serialize_map(self, len: Option<usize>) -> Result<Self, SerializingErr> {
+		self.not_an_integer()?;
+		self.write_length(len)?;
+		Ok(self)
+	}
+
+	fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self, SerializingErr> {
+		self.not_an_integer()?;
+		Ok(self)
+	}
+
+	fn serialize_struct_variant(self, _name: &'static str, variant_index: u32, _variant: &'static str, _len: usize) -> Result<Self, SerializingErr> {
+		self.not_an_integer()?;
+		VarInt(variant_index as i32).mc_serialize(self.serializer)?;
+		Ok(self)
+	}
+
+	fn is_human_readable(&self) -> bool {
+		false
+	}
+}
+
+impl<'a> SerializeSeq for &mut SerdeSerializer<'a> {
+	type Ok = ();
+	type Error = SerializingErr;
+
+	fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerializingErr> {
+		value.serialize(&mut **self)
+	}
+
+	fn end(self) -> Result<(), SerializingErr> {
+		Ok(())
+	}
+}
+
+impl<'a> SerializeTuple for &mut SerdeSerializer<'a> {
+	type Ok = ();
+	type Error = SerializingErr;
+
+	fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerializingErr> {
+		value.serialize(&mut **self)
+	}
+
+	fn end(self) -> Result<(), SerializingErr> {
+		Ok(())
+	}
+}
+
+impl<'a> SerializeTupleStruct for &mut SerdeSerializer<'a> {
+	type Ok = ();
+	type Error = SerializingErr;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerializingErr> {
+		value.serialize(&mut **self)
+	}
+
+	fn end(self) -> Result<(), SerializingErr> {
+		Ok(())
+	}
+}
+
+impl<'a> SerializeTupleVariant for &mut SerdeSerializer<'a> {
+	type Ok = ();
+	type Error = SerializingErr;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerializingErr> {
+		value.serialize(&mut **self)
+	}
+
+	fn end(self) -> Result<(), SerializingErr> {
+		Ok(())
+	}
+}
+
+impl<'a> SerializeMap for &mut SerdeSerializer<'a> {
+	type Ok = ();
+	type Error = SerializingErr;
+
+	fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerializingErr> {
+		key.serialize(&mut **self)
+	}
+
+	fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerializingErr> {
+		value.serialize(&mut **self)
+	}
+
+	fn end(self) -> Result<(), SerializingErr> {
+		Ok(())
+	}
+}
+
+impl<'a> SerializeStruct for &mut SerdeSerializer<'a> {
+	type Ok = ();
+	type Error = SerializingErr;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, _key: &'static str, value: &T) -> Result<(), SerializingErr> {
+		value.serialize(&mut **self)
+	}
+
+	fn end(self) -> Result<(), SerializingErr> {
+		Ok(())
+	}
+}
+
+impl<'a> SerializeStructVariant for &mut SerdeSerializer<'a> {
+	type Ok = ();
+	type Error = SerializingErr;
+
+	fn serialize_field<T: Serialize + ?Sized>(&mut self, _key: &'static str, value: &T) -> Result<(), SerializingErr> {
+		value.serialize(&mut **self)
+	}
+
+	fn end(self) -> Result<(), SerializingErr> {
+		Ok(())
+	}
+}
+
+/// A `serde::Deserializer` that reads the protocol encoding from an [McDeserializer]. Strings and bytes are
+/// borrowed from the input when the type allows it.
+pub struct SerdeDeserializer<'a, 'de> {
+	deserializer: &'a mut McDeserializer<'de>,
+	var_encoding: Option<VarEncoding>,
+}
+
+impl<'a, 'de> SerdeDeserializer<'a, 'de> {
+	pub fn new(deserializer: &'a mut McDeserializer<'de>) -> Self {
+		Self {
+			deserializer,
+			var_encoding: None,
+		}
+	}
+
+	fn read_bytes(&mut self, len: usize) -> SerializingResult<'de, &'de [u8]> {
+		let data: &'de [u8] = self.deserializer.data;
+		let start = self.deserializer.index;
+
+		if start + len > data.len() {
+			return Err(SerializingErr::InputEnded);
+		}
+
+		self.deserializer.increment(len);
+		Ok(&data[start..start + len])
+	}
+
+	fn read_array<const N: usize>(&mut self) -> SerializingResult<'de, [u8; N]> {
+		Ok(self.read_bytes(N)?.try_into()?)
+	}
+
+	fn read_length(&mut self) -> SerializingResult<'de, usize> {
+		let len = VarInt::mc_deserialize(self.deserializer)?.0;
+
+		if len < 0 {
+			return Err(SerializingErr::OutOfBounds(format!("Negative length {}", len)));
+		}
+
+		Ok(len as usize)
+	}
+
+	/// Read a var-type if the field asked for one, handing its value to the visitor.
+	fn read_var<V: Visitor<'de>>(&mut self, visitor: &mut Option<V>) -> SerializingResult<'de, Option<V::Value>> {
+		let value = match self.var_encoding.take() {
+			Some(VarEncoding::VarInt) => visitor.take().unwrap().visit_i32::<SerializingErr>(VarInt::mc_deserialize(self.deserializer)?.0)?,
+			Some(VarEncoding::VarLong) => visitor.take().unwrap().visit_i64::<SerializingErr>(VarLong::mc_deserialize(self.deserializer)?.0)?,
+			None => return Ok(None),
+		};
+
+		Ok(Some(value))
+	}
+
+	fn not_an_integer(&mut self) -> SerializingResult<'de, ()> {
+		match self.var_encoding {
+			Some(_) => Err(SerializingErr::UniqueFailure("Only integers can be read as a VarInt or VarLong".to_string())),
+			None => Ok(()),
+		}
+	}
+}
+
+/// Read a fixed size integer, or a var-type if the field asked for one.
+macro_rules! deserialize_int {
+	($($method: ident, $visit: ident, $ty: ty);*) => {
+		$(
+			fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerializingErr> {
+				let mut visitor = Some(visitor);
+
+				if let Some(value) = self.read_var(&mut visitor)? {
+					return Ok(value);
+				}
+
+				let value = <$ty>::from_be_bytes(self.read_array()?);
+				visitor.take().unwrap().$visit(value)
+			}
+		)*
+	};
+}
+
+impl<'de> serde::Deserializer<'de> for &mut SerdeDeserializer<'_, 'de> {
+	type Error = SerializingErr;
+
+	deserialize_int!(
+		deserialize_i8, visit_i8, i8;
+		deserialize_i16, visit_i16, i16;
+		deserialize_i32, visit_i32, i32;
+		deserialize_i64, visit_i64, i64;
+		deserialize_i128, visit_i128, i128;
+		deserialize_u8, visit_u8, u8;
+		deserialize_u16, visit_u16, u16;
+		deserialize_u32, visit_u32, u32;
+		deserialize_u64, visit_u64, u64;
+		deserialize_u128, visit_u128, u128
+	);
+
+	fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, SerializingErr> {
+		Err(SerializingErr::UniqueFailure("The protocol format is not self-describing, so the type must be known".to_string()))
+	}
+
+	fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerializingErr> {
+		self.not_an_integer()?;
+		visitor.visit_bool(bool::mc_deserialize(self.deserializer)?)
+	}
+
+	fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerializingErr> {
+		self.not_an_integer()?;
+		visitor.visit_f32(f32::from_be_bytes(self.read_array()?))
+	}
+
+	fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerializingErr> {
+		self.not_an_integer()?;
+		visitor.visit_f64(f64::from_be_bytes(self.read_array()?))
+	}
+
+	fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerializingErr> {
+		self.not_an_integer()?;
+		let len = self.read_length()?;
+		let s = core::str::from_utf8(self.read_bytes(len)?).map_err(|e| SerializingErr::DeserializationError(e.to_string()))?;
+		let mut chars = s.chars();
+
+		match (chars.next(), chars.next()) {
+			(Some(c), None) => visitor.visit_char(c),
+			_ => Err(SerializingErr::DeserializationError(format!("Expected a single character, got \"{}\"", s))),
+		}
+	}
+
+	fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerializingErr> {
+		self.not_an_integer()?;
+		let len = self.read_length()?;
+		let s = core::str::from_utf8(self.read_bytes(len)?).map_err(|e| SerializingErr::DeserializationError(e.to_string()))?;
+		visitor.visit_borrowed_str(s)
+	}
+
+	fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerializingErr> {
+		self.deserialize_str(visitor)
+	}
+
+	fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerializingErr> {
+		self.not_an_integer()?;
+		let len = self.read_length()?;
+		visitor.visit_borrowed_bytes(self.read_bytes(len)?)
+	}
+
+	fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerializingErr> {
+		self.deserialize_bytes(visitor)
+	}
+
+	fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerializingErr> {
+		// the var-type applies to the value, not the prefix
+		match u8::mc_deserialize(self.deserializer)? {
+			0 => {
+				self.var_encoding = None;
+				visitor.visit_none()
+			}
+			1 => visitor.visit_some(self),
+			b => Err(SerializingErr::OutOfBounds(format!("Invalid Option prefix {}", b))),
+		}
+	}
+
+	fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerializingErr> {
+		self.not_an_integer()?;
+		visitor.visit_unit()
+	}
+
+	fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, SerializingErr> {
+		self.deserialize_unit(visitor)
+	}
+
+	fn deserialize_newtype_struct<V: Visitor<'de>>(self, name: &'static str, visitor: V) -> Result<V::Value, SerializingErr> {
+		if let Some(encoding) = VarEncoding::from_name(name) {
+			self.var_encoding = Some(encoding);
+			let result = visitor.visit_newtype_struct(&mut *self);
+			self.var_encoding = None;
+			return result;
+		}
+
+		visitor.visit_newtype_struct(self)
+	}
+
+	fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerializingErr> {
+		self.not_an_integer()?;
+		let len = self.read_length()?;
+		visitor.visit_seq(Counted { de: self, remaining: len })
+	}
+
+	fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, SerializingErr> {
+		self.not_an_integer()?;
+		visitor.visit_seq(Counted { de: self, remaining: len })
+	}
+
+	fn deserialize_tuple_struct<V: Visitor<'de>>(self, _name: &'static str, len: usize, visitor: V) -> Result<V::Value, SerializingErr> {
+		self.deserialize_tuple(len, visitor)
+	}
+
+	fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerializingErr> {
+		self.not_an_integer()?;
+		let len = self.read_length()?;
+		visitor.visit_map(Counted { de: self, remaining: len })
+	}
+
+	fn deserialize_struct<V: Visitor<'de>>(self, _name: &'static str, fields: &'static [&'static str], visitor: V) -> Result<V::Value, SerializingErr> {
+		self.deserialize_tuple(fields.len(), visitor)
+	}
+
+	fn deserialize_enum<V: Visitor<'de>>(self, _name: &'static str, _variants: &'static [&'static str], visitor: V) -> Result<V::Value, SerializingErr> {
+		self.not_an_integer()?;
+		visitor.visit_enum(self)
+	}
+
+	fn deserialize_identifier<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, SerializingErr> {
+		Err(SerializingErr::UniqueFailure("The protocol format does not include field names".to_string()))
+	}
+
+	fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerializingErr> {
+		self.deserialize_any(visitor)
+	}
+
+	fn is_human_readable(&self) -> bool {
+		false
+	}
+}
+
+/// Gives the elements of a sequence, or the entries of a map, with a known length.
+struct Counted<'a, 'b, 'de> {
+	de: &'a mut SerdeDeserializer<'b, 'de>,
+	remaining: usize,
+}
+
+impl<'de> SeqAccess<'de> for Counted<'_, '_, 'de> {
+	type Error = SerializingErr;
+
+	fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, SerializingErr> {
+		if self.remaining == 0 {
+			return Ok(None);
+		}
+
+		self.remaining -= 1;
+		seed.deserialize(&mut *self.de).map(Some)
+	}
+
+	fn size_hint(&self) -> Option<usize> {
+		// the length is not trusted for allocations, every element takes at least a byte
+		Some(self.remaining.min(self.de.deserializer.data.len().saturating_sub(self.de.deserializer.index)))
+	}
+}
+
+impl<'de> MapAccess<'de> for Counted<'_, '_, 'de> {
+	type Error = SerializingErr;
+
+	fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, SerializingErr> {
+		self.next_element_seed(seed)
+	}
+
+	fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, SerializingErr> {
+		seed.deserialize(&mut *self.de)
+	}
+
+	fn size_hint(&self) -> Option<usize> {
+		SeqAccess::size_hint(self)
+	}
+}
+
+impl<'de> EnumAccess<'de> for &mut SerdeDeserializer<'_, 'de> {
+	type Error = SerializingErr;
+	type Variant = Self;
+
+	fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), SerializingErr> {
+		let index = VarInt::mc_deserialize(self.deserializer)?.0;
+		let index = u32::try_from(index).map_err(|_| SerializingErr::OutOfBounds(format!("Invalid variant index {}", index)))?;
+		let variant = seed.deserialize(IntoDeserializer::<SerializingErr>::into_deserializer(index))?;
+
+		Ok((variant, self))
+	}
+}
+
+impl<'de> VariantAccess<'de> for &mut SerdeDeserializer<'_, 'de> {
+	type Error = SerializingErr;
+
+	fn unit_variant(self) -> Result<(), SerializingErr> {
+		Ok(())
+	}
+
+	fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, SerializingErr> {
+		seed.deserialize(self)
+	}
+
+	fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, SerializingErr> {
+		serde::Deserializer::deserialize_tuple(self, len, visitor)
+	}
+
+	fn struct_variant<V: Visitor<'de>>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value, SerializingErr> {
+		serde::Deserializer::deserialize_tuple(self, fields.len(), visitor)
+	}
+}
+
+/// Passes a newtype through to the inner value, used by [varint] and [varlong] to mark the value.
+struct NewtypeVisitor<T>(core::marker::PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for NewtypeVisitor<T> {
+	type Value = T;
+
+	fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+		formatter.write_str("an integer")
+	}
+
+	fn visit_newtype_struct<D: serde::Deserializer<'de>>(self, deserializer: D) -> Result<T, D::Error> {
+		T::deserialize(deserializer)
+	}
+}
+
+/// Encode an integer field as a VarInt, with `#[serde(with = "varint")]`.
+pub mod varint {
+	use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+	use crate::protocol::serialization::serde_format::{NewtypeVisitor, VARINT_NAME};
+
+	pub fn serialize<T: Serialize, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_newtype_struct(VARINT_NAME, value)
+	}
+
+	pub fn deserialize<'de, T: Deserialize<'de>, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+		deserializer.deserialize_newtype_struct(VARINT_NAME, NewtypeVisitor(core::marker::PhantomData))
+	}
+}
+
+/// Encode an integer field as a VarLong, with `#[serde(with = "varlong")]`.
+pub mod varlong {
+	use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+	use crate::protocol::serialization::serde_format::{NewtypeVisitor, VARLONG_NAME};
+
+	pub fn serialize<T: Serialize, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_newtype_struct(VARLONG_NAME, value)
+	}
+
+	pub fn deserialize<'de, T: Deserialize<'de>, D: Deserializer<'de>>(deserializer: D) -> Result<T, D::Error> {
+		deserializer.deserialize_newtype_struct(VARLONG_NAME, NewtypeVisitor(core::marker::PhantomData))
+	}
+}
+
+/// Encode a [Uuid](uuid::Uuid) field as a 128-bit integer, with `#[serde(with = "uuid")]`.
+pub mod uuid {
+	use serde::{Deserialize, Deserializer, Serializer};
+	use uuid::Uuid;
+
+	pub fn serialize<S: Serializer>(value: &Uuid, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_u128(value.as_u128())
+	}
+
+	pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Uuid, D::Error> {
+		Ok(Uuid::from_u128(u128::deserialize(deserializer)?))
+	}
+}
+
+/// Serialize fixed size byte arrays, like message signatures, as bytes with `#[serde(serialize_with = "...")]`.
+/// serde only implements `Serialize` for arrays of up to 32 items, and writes them as tuples rather than bytes.
+pub mod byte_array {
+	use serde::{Serialize, Serializer};
+
+	use crate::prelude::*;
+	use crate::protocol::serialization::serializer_types::{BoundedArray, PrefixedOptional};
+	use crate::protocol::serialization::{McDeserialize, McSerialize};
+	use crate::protocol_types::datatypes::internal_types::Mapping;
+
+	/// A fixed size byte array, or a box of one.
+	pub trait ByteArray {
+		fn bytes(&self) -> &[u8];
+	}
+
+	impl<const N: usize> ByteArray for [u8; N] {
+		fn bytes(&self) -> &[u8] {
+			self
+		}
+	}
+
+	impl<const N: usize> ByteArray for Box<[u8; N]> {
+		fn bytes(&self) -> &[u8] {
+			self.as_slice()
+		}
+	}
+
+	struct Bytes<'a>(&'a [u8]);
+
+	impl Serialize for Bytes<'_> {
+		fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+			serializer.serialize_bytes(self.0)
+		}
+	}
+
+	#[derive(Serialize)]
+	struct Entry<'a> {
+		key: &'a str,
+		value: Bytes<'a>,
+	}
+
+	pub fn serialize<B: ByteArray, S: Serializer>(value: &B, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_bytes(value.bytes())
+	}
+
+	pub fn option<B: ByteArray, S: Serializer>(value: &Option<B>, serializer: S) -> Result<S::Ok, S::Error> {
+		value.as_ref().map(|b| Bytes(b.bytes())).serialize(serializer)
+	}
+
+	pub fn prefixed_optional<B: ByteArray + McSerialize + McDeserialize, S: Serializer>(value: &PrefixedOptional<B>, serializer: S) -> Result<S::Ok, S::Error> {
+		value.value().map(|b| Bytes(b.bytes())).serialize(serializer)
+	}
+
+	/// Serialized like a [Mapping] with a byte array value.
+	pub fn mappings<B, S, const N: usize>(value: &BoundedArray<Mapping<B>, N>, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		B: ByteArray + McSerialize + McDeserialize + Clone + PartialEq,
+		Mapping<B>: McSerialize + McDeserialize,
+		S: Serializer,
+	{
+		serializer.collect_seq(value.slice().iter().map(|mapping| Entry {
+			key: &mapping.key,
+			value: Bytes(mapping.value.bytes()),
+		}))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use serde::{Deserialize, Serialize};
+	use uuid::Uuid;
+
+	use crate::protocol::serialization::serde_format::{from_bytes, to_bytes};
+	use crate::protocol::serialization::serializer_types::PrefixedOptional;
+	use crate::protocol::serialization::{McSerialize, McSerializer};
+	use crate::protocol_types::datatypes::var_types::VarInt;
+
+	#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+	enum Shape {
+		Point,
+		Circle(f32),
+		Rect { width: u8, height: u8 },
+	}
+
+	#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+	struct Entity<'a> {
+		#[serde(with = "crate::protocol::serialization::serde_format::varint")]
+		id: i32,
+		#[serde(with = "crate::protocol::serialization::serde_format::uuid")]
+		uuid: Uuid,
+		name: String,
+		#[serde(borrow)]
+		tag: &'a str,
+		nickname: Option<String>,
+		#[serde(with = "crate::protocol::serialization::serde_format::varlong")]
+		age: u32,
+		shapes: Vec<Shape>,
+		position: (i16, i16),
+	}
+
+	#[test]
+	fn test_round_trip() {
+		let entity = Entity {
+			id: 300,
+			uuid: Uuid::from_u128(0x0123_4567_89AB_CDEF_0123_4567_89AB_CDEF),
+			name: "zombie".to_string(),
+			tag: "mob",
+			nickname: Some("bob".to_string()),
+			age: 7,
+			shapes: vec![Shape::Point, Shape::Circle(1.5), Shape::Rect { width: 2, height: 3 }],
+			position: (-1, 1),
+		};
+
+		let bytes = to_bytes(&entity).unwrap();
+		assert_eq!(from_bytes::<Entity>(&bytes).unwrap(), entity);
+
+		// the same as the McSerialize implementations of the protocol types
+		let mut serializer = McSerializer::new();
+		VarInt(300).mc_serialize(&mut serializer).unwrap();
+		entity.uuid.mc_serialize(&mut serializer).unwrap();
+		"zombie".mc_serialize(&mut serializer).unwrap();
+		"mob".mc_serialize(&mut serializer).unwrap();
+		PrefixedOptional::new(Some("bob".to_string())).mc_serialize(&mut serializer).unwrap();
+		assert_eq!(&bytes[..serializer.output.len()], serializer.output.as_slice());
+
+		let rest = &bytes[serializer.output.len()..];
+		assert_eq!(rest, &[7, 3, 0, 1, 0x3F, 0xC0, 0, 0, 2, 2, 3, 0xFF, 0xFF, 0, 1]);
+	}
+
+	#[test]
+	fn test_invalid_input() {
+		// the Option prefix must be 0 or 1
+		assert!(from_bytes::<Option<u8>>(&[2, 0]).is_err());
+		assert!(from_bytes::<Shape>(&[5]).is_err());
+		assert!(from_bytes::<String>(&[10, b'a']).is_err());
+		assert!(from_bytes::<u8>(&[1, 2]).is_err());
+		assert!(from_bytes::<serde_json::Value>(&[1]).is_err());
+	}
+
+	#[test]
+	fn test_attributes_are_transparent_in_json() {
+		let entity = Entity {
+			id: 1,
+			uuid: Uuid::nil(),
+			name: String::new(),
+			tag: "",
+			nickname: None,
+			age: 2,
+			shapes: vec![],
+			position: (0, 0),
+		};
+
+		let json = serde_json::to_string(&entity).unwrap();
+		assert!(json.starts_with("{\"id\":1,"));
+		assert_eq!(serde_json::from_str::<Entity>(&json).unwrap(), entity);
+	}
+}
@@ -1,10 +1,13 @@
 //! This file describes the SerializingError type, which is used to represent errors
 //! for serialization and deserialization operations. This is useful for debugging and error handling.
 
-use std::array::TryFromSliceError;
-use std::fmt::Debug;
-use std::string::FromUtf8Error;
+use core::array::TryFromSliceError;
+use core::fmt::{Debug, Display, Formatter};
+use core::str::Utf8Error;
+use alloc::string::FromUtf8Error;
 
+use crate::prelude::*;
+use crate::protocol::serialization::McDeserializer;
 use crate::protocol_types::datatypes::nbt::nbt_error::NbtError;
 use thiserror::Error;
 
@@ -20,6 +23,8 @@ pub enum SerializingErr {
 	#[error(transparent)]
 	CouldNotDeserializeString(#[from] FromUtf8Error),
 	#[error(transparent)]
+	InvalidUtf8(#[from] Utf8Error),
+	#[error(transparent)]
 	StringFromSliceError(#[from] TryFromSliceError),
 	#[error("Input ended prematurely")]
 	InputEnded,
@@ -49,6 +54,146 @@ pub enum SerializingErr {
 	OutOfBounds(String),
 	#[error("Failed to serialize: {0}")]
 	FailedToSerializeJSON(String),
+	#[error("{0}")]
+	Context(Box<ErrorContext>),
+}
+
+impl SerializingErr {
+	/// Add the field that this error happened in to the front of its path. The first time this is called, the error
+	/// is wrapped in an [ErrorContext] at the current position of `deserializer`.
+	///
+	/// This is called for every field by the `McDeserialize` derive, so it is rarely needed by hand.
+	pub fn in_field(self, field: &str, deserializer: &McDeserializer) -> Self {
+		self.with_segment(deserializer, |path| {
+			if !path.is_empty() && !path.starts_with('[') {
+				path.insert(0, '.');
+			}
+
+			path.insert_str(0, field);
+		})
+	}
+
+	/// Add the index of the item that this error happened in to the front of its path, see [SerializingErr::in_field].
+	pub fn at_index(self, index: usize, deserializer: &McDeserializer) -> Self {
+		self.with_segment(deserializer, |path| {
+			if !path.is_empty() && !path.starts_with('[') {
+				path.insert(0, '.');
+			}
+
+			path.insert_str(0, &format!("[{}]", index));
+		})
+	}
+
+	fn with_segment(self, deserializer: &McDeserializer, f: impl FnOnce(&mut String)) -> Self {
+		let mut context = match self {
+			Self::Context(context) => context,
+			other => Box::new(ErrorContext::new(other, deserializer)),
+		};
+
+		context.rebase(deserializer);
+		f(&mut context.path);
+
+		Self::Context(context)
+	}
+
+	/// The [ErrorContext] of this error, if it happened while deserializing a field.
+	pub fn context(&self) -> Option<&ErrorContext> {
+		match self {
+			Self::Context(context) => Some(context),
+			_ => None,
+		}
+	}
+
+	/// The error that caused this one, without any [ErrorContext].
+	pub fn root(&self) -> &SerializingErr {
+		match self {
+			Self::Context(context) => &context.source,
+			other => other,
+		}
+	}
+}
+
+/// Where in the input a deserialization error happened: the byte offset, the path of fields that were being read,
+/// like `PlayerInfoUpdate.players[3].actions.add_player.properties[0].signature`, and the bytes around it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorContext {
+	/// The error that was returned by the innermost field.
+	pub source: SerializingErr,
+	/// The offset of the failure in the outermost buffer that the path was built through.
+	pub offset: usize,
+	pub path: String,
+	/// Up to [ErrorContext::WINDOW] bytes on each side of the failure.
+	pub window: Vec<u8>,
+	/// The offset of the first byte of `window`.
+	pub window_start: usize,
+	/// The address of the failing byte. Sub-deserializers are slices of the same buffer, so this lets the offset be
+	/// moved into the buffer of each parent deserializer as the error is returned through it.
+	address: usize,
+}
+
+impl ErrorContext {
+	/// The number of bytes kept on each side of the failure.
+	pub const WINDOW: usize = 16;
+
+	fn new(source: SerializingErr, deserializer: &McDeserializer) -> Self {
+		let offset = deserializer.index.min(deserializer.data.len());
+
+		let mut context = Self {
+			source,
+			offset,
+			path: String::new(),
+			window: vec![],
+			window_start: 0,
+			address: deserializer.data.as_ptr() as usize + offset,
+		};
+
+		context.rebase(deserializer);
+		context
+	}
+
+	/// Move the offset and window into the buffer of `deserializer`, if the failure is inside of it.
+	fn rebase(&mut self, deserializer: &McDeserializer) {
+		let start = deserializer.data.as_ptr() as usize;
+
+		if self.address < start || self.address > start + deserializer.data.len() {
+			return;
+		}
+
+		self.offset = self.address - start;
+		self.window_start = self.offset.saturating_sub(Self::WINDOW);
+		self.window = deserializer.data[self.window_start..(self.offset + Self::WINDOW).min(deserializer.data.len())].to_vec();
+	}
+}
+
+impl Display for ErrorContext {
+	/// Writes the error, its position and the window, with a `|` before the byte that could not be read.
+	fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+		write!(f, "{} at byte {}", self.source, self.offset)?;
+
+		if !self.path.is_empty() {
+			write!(f, " in {}", self.path)?;
+		}
+
+		write!(f, " [")?;
+
+		for (i, byte) in self.window.iter().enumerate() {
+			if self.window_start + i == self.offset {
+				write!(f, "| ")?;
+			}
+
+			write!(f, "{:02x}", byte)?;
+
+			if i + 1 < self.window.len() {
+				write!(f, " ")?;
+			}
+		}
+
+		if self.window_start + self.window.len() == self.offset {
+			write!(f, "{}|", if self.window.is_empty() { "" } else { " " })?;
+		}
+
+		write!(f, "]")
+	}
 }
 
 impl PartialEq for SerializingErr {
@@ -57,6 +202,7 @@ impl PartialEq for SerializingErr {
 			(Self::InvalidEndOfVarInt, Self::InvalidEndOfVarInt) => true,
 			(Self::VarTypeTooLong(a), Self::VarTypeTooLong(b)) => a == b,
 			(Self::CouldNotDeserializeString(a), Self::CouldNotDeserializeString(b)) => a == b,
+			(Self::InvalidUtf8(a), Self::InvalidUtf8(b)) => a == b,
 			(Self::StringFromSliceError(a), Self::StringFromSliceError(b)) => a.to_string() == b.to_string(),
 			(Self::InputEnded, Self::InputEnded) => true,
 			(Self::LeftoverInput, Self::LeftoverInput) => true,
@@ -65,6 +211,7 @@ impl PartialEq for SerializingErr {
 			(Self::InvalidPacketState, Self::InvalidPacketState) => true,
 			(Self::InconsistentField(a), Self::InconsistentField(b)) => a == b,
 			(Self::OutOfBounds(a), Self::OutOfBounds(b)) => a == b,
+			(Self::Context(a), Self::Context(b)) => a == b,
 			_ => false,
 		}
 	}
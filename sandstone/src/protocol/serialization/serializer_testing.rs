@@ -1,4 +1,5 @@
 //! Test random nesting of enums and structs for serialization and deserialization
+use crate::prelude::*;
 use crate::protocol::serialization::serializer_error::SerializingErr;
 use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, SerializingResult};
 use crate::protocol_types::datatypes::var_types::{VarInt, VarLong};
@@ -139,8 +140,11 @@ mod tests {
 	use crate::protocol::packets::packet_definer::{PacketDirection, PacketState};
 	use crate::protocol::packets::{HandshakingPacket, Packet};
 	use crate::protocol::serialization::serializer_testing::{Group, StringMix, VarIntMix};
+	use crate::protocol::serialization::serializer_error::SerializingErr;
+	use crate::protocol::serialization::serializer_types::{BoundedArray, BoundedString, PrefixedArray};
 	use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, StateBasedDeserializer};
-	use crate::protocol_types::datatypes::var_types::{VarInt, VarLong};
+	use crate::protocol_types::datatypes::game_types::Position;
+	use crate::protocol_types::datatypes::var_types::{RangedVarInt, VarInt, VarLong};
 
 	#[test]
 	fn struct_serialization() {
@@ -223,9 +227,9 @@ mod tests {
 
 		let p = Packet::Handshaking(HandshakingPacket {
 			protocol_version: VarInt(3),
-			server_address: "".to_string(),
+			server_address: BoundedString::new("").unwrap(),
 			port: 0,
-			next_state: VarInt(6),
+			next_state: RangedVarInt::new(3).unwrap(),
 		});
 
 		p.mc_serialize(&mut serializer).unwrap();
@@ -246,4 +250,115 @@ mod tests {
 		assert_eq!([7, 8, 9], deserializer.slice(3));
 		assert_eq!([10], deserializer.slice(3));
 	}
+
+	#[test]
+	fn test_bounded_string() {
+		assert!(BoundedString::<16>::new("dec4234").is_ok());
+		assert!(BoundedString::<16>::new("a".repeat(17)).is_err());
+		// the length is counted in UTF-16 code units, not bytes
+		assert!(BoundedString::<4>::new("éééé").is_ok());
+		assert!(BoundedString::<3>::new("🎉🎉").is_err());
+
+		let mut serializer = McSerializer::new();
+		BoundedString::<16>::new("hello").unwrap().mc_serialize(&mut serializer).unwrap();
+		assert_eq!(serializer.output, vec![5, 104, 101, 108, 108, 111]);
+
+		let mut deserializer = McDeserializer::new(&serializer.output);
+		assert_eq!(BoundedString::<16>::mc_deserialize(&mut deserializer).unwrap(), "hello");
+
+		let mut deserializer = McDeserializer::new(&serializer.output);
+		assert!(BoundedString::<4>::mc_deserialize(&mut deserializer).is_err());
+
+		// a length prefix over the limit is rejected before the string is read
+		let mut deserializer = McDeserializer::new(&[100, 1, 2, 3]);
+		assert!(matches!(BoundedString::<16>::mc_deserialize(&mut deserializer), Err(SerializingErr::OutOfBounds(_))));
+	}
+
+	#[test]
+	fn test_bounded_array() {
+		assert!(BoundedArray::<u8, 3>::new(vec![1, 2, 3]).is_ok());
+		assert!(BoundedArray::<u8, 3>::new(vec![1, 2, 3, 4]).is_err());
+
+		let mut serializer = McSerializer::new();
+		BoundedArray::<u8, 3>::new(vec![1, 2, 3]).unwrap().mc_serialize(&mut serializer).unwrap();
+		assert_eq!(serializer.output, vec![3, 1, 2, 3]);
+
+		let mut deserializer = McDeserializer::new(&serializer.output);
+		assert_eq!(*BoundedArray::<u8, 3>::mc_deserialize(&mut deserializer).unwrap(), [1, 2, 3]);
+
+		// a count over the limit is rejected before the items are read
+		let mut deserializer = McDeserializer::new(&serializer.output);
+		assert!(matches!(BoundedArray::<u8, 2>::mc_deserialize(&mut deserializer), Err(SerializingErr::OutOfBounds(_))));
+		assert_eq!(deserializer.index, 1);
+
+		// items keep their own bounds
+		let mut deserializer = McDeserializer::new(&[1, 5, 104, 101, 108, 108, 111]);
+		assert!(BoundedArray::<BoundedString<4>, 1>::mc_deserialize(&mut deserializer).is_err());
+	}
+
+	#[test]
+	fn test_length_prefixed() {
+		// lengths whose VarInt is shorter, as long as, and longer than the reserved 3 bytes
+		for len in [0, 5, 200, 20_000, 3_000_000] {
+			let data = vec![7u8; len];
+
+			let mut serializer = McSerializer::new();
+			serializer.serialize_u8(42);
+			serializer.serialize_length_prefixed(|s| {
+				s.serialize_bytes(&data);
+				Ok(())
+			}).unwrap();
+
+			let mut expected = vec![42];
+			expected.extend(VarInt(len as i32).to_bytes());
+			expected.extend(&data);
+			assert_eq!(serializer.output, expected, "length {len}");
+		}
+	}
+
+	#[test]
+	fn test_forged_lengths() {
+		// a negative string length
+		let mut deserializer = McDeserializer::new(&[0xFF, 0xFF, 0xFF, 0xFF, 0x0F, b'a']);
+		assert!(matches!(String::mc_deserialize(&mut deserializer), Err(SerializingErr::OutOfBounds(_))));
+
+		// an array that claims far more items than the input holds
+		let mut deserializer = McDeserializer::new(&[0xFF, 0xFF, 0xFF, 0xFF, 0x07, 1]);
+		assert!(PrefixedArray::<u8>::mc_deserialize(&mut deserializer).is_err());
+
+		// a negative packet length
+		let mut deserializer = McDeserializer::new(&[0xFF, 0xFF, 0xFF, 0xFF, 0x0F, 0]);
+		assert!(Packet::deserialize_state(&mut deserializer, PacketState::HANDSHAKING, PacketDirection::SERVER).is_err());
+	}
+
+	#[test]
+	#[cfg(not(feature = "field-spans"))]
+	fn test_deserializer_is_send() {
+		fn assert_send_sync<T: Send + Sync>() {}
+		assert_send_sync::<McDeserializer>();
+	}
+
+	#[test]
+	fn test_serverbound_string_limits() {
+		// a structure block name within the limit, with metadata one character over its limit of 128
+		let mut serializer = McSerializer::new();
+		serializer.serialize_u8(0x39);
+		Position::new(0, 0, 0).mc_serialize(&mut serializer).unwrap();
+		VarInt(0).mc_serialize(&mut serializer).unwrap();
+		VarInt(0).mc_serialize(&mut serializer).unwrap();
+		"a".repeat(32767).mc_serialize(&mut serializer).unwrap();
+		serializer.serialize_bytes(&[0; 6]);
+		VarInt(0).mc_serialize(&mut serializer).unwrap();
+		VarInt(0).mc_serialize(&mut serializer).unwrap();
+		"a".repeat(129).mc_serialize(&mut serializer).unwrap();
+
+		let mut frame = McSerializer::new();
+		VarInt(serializer.output.len() as i32).mc_serialize(&mut frame).unwrap();
+		frame.serialize_bytes(&serializer.output);
+
+		let mut deserializer = McDeserializer::new(&frame.output);
+		let err = Packet::deserialize_state(&mut deserializer, PacketState::PLAY, PacketDirection::SERVER).unwrap_err();
+		assert!(matches!(err.root(), SerializingErr::OutOfBounds(_)), "{err}");
+		assert_eq!(err.context().unwrap().path, "ProgramStructureBlock.metadata");
+	}
 }
@@ -1,10 +1,14 @@
 //! Implementations of the McSerialize and McDeserialize traits for primitive types and some common Rust types.
 
+use alloc::borrow::Cow;
+
+use crate::prelude::*;
 use crate::protocol::serialization::serializer_error::SerializingErr;
-use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, SerializingResult};
+use crate::protocol::serialization::{McDeserialize, McDeserializeBorrowed, McDeserializer, McSerialize, McSerializer, SerializingResult};
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::var_types::VarInt;
 use crate::serialize_primitives;
+use serde::{Serialize, Serializer};
 
 impl McSerialize for String {
 	/// Serializes a String as a VarInt length prefix followed by the UTF-8 bytes of the string.
@@ -18,28 +22,88 @@ impl McSerialize for String {
 
 impl McDeserialize for String {
 	fn mc_deserialize<'a>(deserializer: &'a mut McDeserializer) -> SerializingResult<'a, Self> {
-		let var_output = VarInt::mc_deserialize(deserializer)?;
-		let bounds: (usize, usize) = (deserializer.index, deserializer.index + var_output.0 as usize);
+		let length = VarInt::mc_deserialize(deserializer)?.0;
+		let remaining = deserializer.remaining();
 
-		if bounds.1 > deserializer.data.len() {
+		if length < 0 || length as usize > remaining {
 			return Err(SerializingErr::OutOfBounds(format!(
 				"String length prefix of {} exceeds remaining input length of {}",
-				var_output.0,
-				deserializer.data.len() - deserializer.index
+				length, remaining
 			)));
 		}
 
-		let s = String::from_utf8(deserializer.data[bounds.0..bounds.1].to_vec())?;
+		Ok(String::from_utf8(deserializer.slice(length as usize).to_vec())?)
+	}
+}
 
-		deserializer.increment(var_output.0 as usize); // length of string
+impl McSerialize for &str {
+	fn mc_serialize(&self, serializer: &mut McSerializer) -> SerializingResult<()> {
+		VarInt(self.len() as i32).mc_serialize(serializer)?;
+		serializer.serialize_bytes(self.as_bytes());
 
-		Ok(s)
+		Ok(())
 	}
 }
 
-impl McSerialize for &str {
+impl<'de> McDeserializeBorrowed<'de> for &'de str {
+	/// Borrows the string out of the input. Encoded the same way as a String, with a VarInt length prefix.
+	fn mc_deserialize_borrowed(deserializer: &mut McDeserializer<'de>) -> SerializingResult<'de, Self> {
+		let length = VarInt::mc_deserialize(deserializer)?.0;
+		let remaining = deserializer.remaining();
+
+		if length < 0 || length as usize > remaining {
+			return Err(SerializingErr::OutOfBounds(format!(
+				"String length prefix of {} exceeds remaining input length of {}",
+				length, remaining
+			)));
+		}
+
+		Ok(core::str::from_utf8(deserializer.slice(length as usize))?)
+	}
+}
+
+impl McSerialize for Cow<'_, str> {
 	fn mc_serialize(&self, serializer: &mut McSerializer) -> SerializingResult<()> {
-		self.to_string().mc_serialize(serializer)
+		self.as_ref().mc_serialize(serializer)
+	}
+}
+
+impl<'de> McDeserializeBorrowed<'de> for Cow<'de, str> {
+	fn mc_deserialize_borrowed(deserializer: &mut McDeserializer<'de>) -> SerializingResult<'de, Self> {
+		Ok(Cow::Borrowed(<&str>::mc_deserialize_borrowed(deserializer)?))
+	}
+}
+
+impl McSerialize for [u8] {
+	/// Serialize the bytes as is, without any length prefix. Matches the encoding of Vec<u8>.
+	fn mc_serialize(&self, serializer: &mut McSerializer) -> SerializingResult<()> {
+		serializer.serialize_bytes(self);
+
+		Ok(())
+	}
+}
+
+impl<'de> McDeserializeBorrowed<'de> for &'de [u8] {
+	/// Borrows the rest of the input. Like Vec<u8>, this consumes everything left in the deserializer.
+	fn mc_deserialize_borrowed(deserializer: &mut McDeserializer<'de>) -> SerializingResult<'de, Self> {
+		let rest = deserializer.collect_remaining();
+		deserializer.increment(rest.len());
+
+		Ok(rest)
+	}
+}
+
+impl McSerialize for Cow<'_, [u8]> {
+	fn mc_serialize(&self, serializer: &mut McSerializer) -> SerializingResult<()> {
+		serializer.serialize_bytes(self);
+
+		Ok(())
+	}
+}
+
+impl<'de> McDeserializeBorrowed<'de> for Cow<'de, [u8]> {
+	fn mc_deserialize_borrowed(deserializer: &mut McDeserializer<'de>) -> SerializingResult<'de, Self> {
+		Ok(Cow::Borrowed(<&[u8]>::mc_deserialize_borrowed(deserializer)?))
 	}
 }
 
@@ -95,7 +159,7 @@ mod macros {
                     if deserializer.data.len() == 0 {
                         return Err(SerializingErr::InputEnded);
                     }
-					let length = std::mem::size_of::<$t>();
+					let length = core::mem::size_of::<$t>();
 
 					if deserializer.index + length > deserializer.data.len() {
 						return Err(SerializingErr::InputEnded);
@@ -104,7 +168,7 @@ mod macros {
                     let split = deserializer.data[deserializer.index..].split_at(length);
 
                     let b = <$t>::from_be_bytes(split.0.try_into()?);
-                    deserializer.increment(std::mem::size_of::<$t>());
+                    deserializer.increment(core::mem::size_of::<$t>());
 
                     return Ok(b);
                 }
@@ -139,7 +203,8 @@ impl<T: McDeserialize> McDeserialize for Vec<T> {
 		let mut vec = vec![];
 
 		while !deserializer.is_at_end() {
-			vec.push(T::mc_deserialize(deserializer)?);
+			let item = deserializer.read_item(vec.len(), T::mc_deserialize)?;
+			vec.push(item);
 		}
 
 		Ok(vec)
@@ -258,16 +323,23 @@ impl<T: McSerialize + McDeserialize> McDeserialize for PrefixedArray<T> {
 		T: McDeserialize,
 	{
 		let var_output = VarInt::mc_deserialize(deserializer)?;
-		let mut vec = Vec::with_capacity(var_output.0 as usize);
+		let mut vec = Vec::with_capacity(deserializer.capacity_for(var_output.0)?);
 
-		for _ in 0..var_output.0 {
-			vec.push(T::mc_deserialize(deserializer)?);
+		for i in 0..var_output.0 as usize {
+			vec.push(deserializer.read_item(i, T::mc_deserialize)?);
 		}
 
 		Ok(PrefixedArray::new(vec))
 	}
 }
 
+impl<T: McSerialize + McDeserialize + Serialize> Serialize for PrefixedArray<T> {
+	/// Serialized as a sequence, the same as the inner Vec<T>.
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		self.vec.serialize(serializer)
+	}
+}
+
 /// # PrefixedOptional (Protocol Type)
 /// An Optional<T> with a bool prefix indicating if the value is present.
 /// This has started to replace most occurrences of Option<T> in the protocol, as it is more explicit.
@@ -331,6 +403,13 @@ impl<T: McSerialize + McDeserialize> McDeserialize for PrefixedOptional<T> {
 	}
 }
 
+impl<T: McSerialize + McDeserialize + Serialize> Serialize for PrefixedOptional<T> {
+	/// Serialized as an Option<T>.
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		self.value.serialize(serializer)
+	}
+}
+
 impl<T: McSerialize + McDeserialize + McDefault> McDefault for PrefixedOptional<T> {
 	fn mc_default() -> Self {
 		PrefixedOptional {
@@ -339,3 +418,243 @@ impl<T: McSerialize + McDeserialize + McDefault> McDefault for PrefixedOptional<
 		}
 	}
 }
+
+/// # BoundedString (Protocol Type)
+/// A String with a maximum length of `N`, counted in UTF-16 code units like the Notchian implementation.
+/// Most strings in the protocol are limited to 32767, but some have a lower limit, like usernames (16) and chat
+/// messages (256). The length is checked when the string is created, serialized and deserialized.
+#[derive(Default, Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Clone)]
+pub struct BoundedString<const N: usize> {
+	pub(crate) string: String,
+}
+
+impl<const N: usize> BoundedString<N> {
+	/// Errors if the string is longer than `N`.
+	pub fn new(string: impl Into<String>) -> Result<Self, SerializingErr> {
+		let string = string.into();
+		Self::check_length(&string)?;
+
+		Ok(Self {
+			string,
+		})
+	}
+
+	pub fn as_str(&self) -> &str {
+		&self.string
+	}
+
+	pub fn into_inner(self) -> String {
+		self.string
+	}
+
+	pub(crate) fn check_length(string: &str) -> Result<(), SerializingErr> {
+		// each UTF-16 code unit takes at most 3 bytes of UTF-8, so short strings can skip counting
+		if string.len() <= N {
+			return Ok(());
+		}
+
+		let length = string.encode_utf16().count();
+
+		if length > N {
+			return Err(SerializingErr::OutOfBounds(format!("String of length {} exceeds the maximum length of {}", length, N)));
+		}
+
+		Ok(())
+	}
+}
+
+impl<const N: usize> core::ops::Deref for BoundedString<N> {
+	type Target = str;
+
+	fn deref(&self) -> &Self::Target {
+		&self.string
+	}
+}
+
+impl<const N: usize> core::fmt::Display for BoundedString<N> {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		f.write_str(&self.string)
+	}
+}
+
+impl<const N: usize> TryFrom<String> for BoundedString<N> {
+	type Error = SerializingErr;
+
+	fn try_from(value: String) -> Result<Self, Self::Error> {
+		Self::new(value)
+	}
+}
+
+impl<const N: usize> TryFrom<&str> for BoundedString<N> {
+	type Error = SerializingErr;
+
+	fn try_from(value: &str) -> Result<Self, Self::Error> {
+		Self::new(value)
+	}
+}
+
+impl<const N: usize> From<BoundedString<N>> for String {
+	fn from(value: BoundedString<N>) -> Self {
+		value.string
+	}
+}
+
+impl<const N: usize> PartialEq<str> for BoundedString<N> {
+	fn eq(&self, other: &str) -> bool {
+		self.string == other
+	}
+}
+
+impl<const N: usize> PartialEq<&str> for BoundedString<N> {
+	fn eq(&self, other: &&str) -> bool {
+		self.string == *other
+	}
+}
+
+impl<const N: usize> McSerialize for BoundedString<N> {
+	fn mc_serialize(&self, serializer: &mut McSerializer) -> SerializingResult<()> {
+		// the string can be changed within the crate, so it is checked again
+		Self::check_length(&self.string)?;
+		self.string.mc_serialize(serializer)
+	}
+}
+
+impl<const N: usize> McDeserialize for BoundedString<N> {
+	fn mc_deserialize<'a>(deserializer: &'a mut McDeserializer) -> SerializingResult<'a, Self> {
+		let start = deserializer.index;
+		let length = VarInt::mc_deserialize(deserializer)?.0;
+		deserializer.index = start;
+
+		// reject the prefix before reading anything, the same as the Notchian limit of N * 3 bytes
+		if length < 0 || length as usize > N * 3 {
+			return Err(SerializingErr::OutOfBounds(format!("String length prefix of {} exceeds the maximum of {} bytes", length, N * 3)));
+		}
+
+		let string = String::mc_deserialize(deserializer)?;
+		Self::check_length(&string)?;
+
+		Ok(Self {
+			string,
+		})
+	}
+}
+
+impl<const N: usize> Serialize for BoundedString<N> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		self.string.serialize(serializer)
+	}
+}
+
+impl<const N: usize> McDefault for BoundedString<N> {
+	fn mc_default() -> Self {
+		Self {
+			string: String::mc_default().chars().take(N).collect(),
+		}
+	}
+}
+
+/// # BoundedArray (Protocol Type)
+/// A [PrefixedArray] with at most `N` items, for arrays the Notchian server caps, like the pages of a book (100)
+/// or the argument signatures of a signed command (20). The count is checked when the array is created,
+/// serialized and deserialized.
+#[derive(Default, Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Clone)]
+pub struct BoundedArray<T: McSerialize + McDeserialize, const N: usize> {
+	pub(crate) vec: Vec<T>,
+}
+
+impl<T: McSerialize + McDeserialize, const N: usize> BoundedArray<T, N> {
+	/// Errors if there are more than `N` items.
+	pub fn new(vec: Vec<T>) -> Result<Self, SerializingErr> {
+		Self::check_length(vec.len())?;
+
+		Ok(Self {
+			vec,
+		})
+	}
+
+	pub fn slice(&self) -> &[T] {
+		&self.vec
+	}
+
+	pub fn into_inner(self) -> Vec<T> {
+		self.vec
+	}
+
+	fn check_length(length: usize) -> Result<(), SerializingErr> {
+		if length > N {
+			return Err(SerializingErr::OutOfBounds(format!("Array of length {} exceeds the maximum length of {}", length, N)));
+		}
+
+		Ok(())
+	}
+}
+
+impl<T: McSerialize + McDeserialize, const N: usize> core::ops::Deref for BoundedArray<T, N> {
+	type Target = [T];
+
+	fn deref(&self) -> &Self::Target {
+		&self.vec
+	}
+}
+
+impl<T: McSerialize + McDeserialize, const N: usize> TryFrom<Vec<T>> for BoundedArray<T, N> {
+	type Error = SerializingErr;
+
+	fn try_from(value: Vec<T>) -> Result<Self, Self::Error> {
+		Self::new(value)
+	}
+}
+
+impl<T: McSerialize + McDeserialize, const N: usize> From<BoundedArray<T, N>> for Vec<T> {
+	fn from(value: BoundedArray<T, N>) -> Self {
+		value.vec
+	}
+}
+
+impl<T: McSerialize + McDeserialize, const N: usize> McSerialize for BoundedArray<T, N> {
+	fn mc_serialize(&self, serializer: &mut McSerializer) -> SerializingResult<()> {
+		// the vec can be changed within the crate, so it is checked again
+		Self::check_length(self.vec.len())?;
+		VarInt(self.vec.len() as i32).mc_serialize(serializer)?;
+		for item in &self.vec {
+			item.mc_serialize(serializer)?;
+		}
+
+		Ok(())
+	}
+}
+
+impl<T: McSerialize + McDeserialize, const N: usize> McDeserialize for BoundedArray<T, N> {
+	fn mc_deserialize<'a>(deserializer: &'a mut McDeserializer) -> SerializingResult<'a, Self> {
+		let length = VarInt::mc_deserialize(deserializer)?.0;
+
+		// reject the count before reading any items
+		if length < 0 || length as usize > N {
+			return Err(SerializingErr::OutOfBounds(format!("Array length prefix of {} exceeds the maximum length of {}", length, N)));
+		}
+
+		let mut vec = Vec::with_capacity(deserializer.capacity_for(length)?);
+		for i in 0..length as usize {
+			vec.push(deserializer.read_item(i, T::mc_deserialize)?);
+		}
+
+		Ok(Self {
+			vec,
+		})
+	}
+}
+
+impl<T: McSerialize + McDeserialize + Serialize, const N: usize> Serialize for BoundedArray<T, N> {
+	/// Serialized as a sequence, the same as the inner Vec<T>.
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		self.vec.serialize(serializer)
+	}
+}
+
+impl<T: McSerialize + McDeserialize + McDefault, const N: usize> McDefault for BoundedArray<T, N> {
+	fn mc_default() -> Self {
+		Self {
+			vec: (0..N.min(3)).map(|_| T::mc_default()).collect(),
+		}
+	}
+}
@@ -1,23 +1,27 @@
 //! Status protocol components.
 
+use crate::prelude::*;
 use crate::protocol::testing::McDefault;
-use std::io::Cursor;
 
 use crate::protocol::packets::StatusResponsePacket;
+use crate::protocol::serialization::serializer_error::SerializingErr;
 use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, SerializingResult};
 use crate::protocol_types::datatypes::chat::TextComponent;
 use crate::protocol_types::protocol_verison::ProtocolVerison;
+#[cfg(feature = "favicon")]
 use base64::engine::general_purpose;
+#[cfg(feature = "favicon")]
 use base64::Engine;
+#[cfg(feature = "favicon")]
 use image::{DynamicImage, ImageFormat};
-use sandstone_derive::McDefault;
+use sandstone_derive::{McArbitrary, McDefault};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 /// # Status Response Spec (Packet Part)
 /// A prepared response to a status request from a client. This provides useful functions for building
 /// the complicated nested structure of the status response.
-#[derive(McDefault, Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(McDefault, McArbitrary, Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(non_snake_case)]
 pub struct StatusResponseSpec {
 	pub version: VersionInfo,
@@ -60,14 +64,39 @@ impl StatusResponseSpec {
 	}
 
 	/// Set the image returned to the user as the server logo.
-	/// This must be a 64x64 PNG image.
-	pub fn set_favicon_image(&mut self, image: DynamicImage) {
+	/// This must be a 64x64 PNG image. Returns an error if the image could not be encoded as a PNG.
+	#[cfg(feature = "favicon")]
+	pub fn set_favicon_image(&mut self, image: DynamicImage) -> SerializingResult<'static, ()> {
 		let mut image_data: Vec<u8> = Vec::new();
-		image.write_to(&mut Cursor::new(&mut image_data), ImageFormat::Png).unwrap();
+		image
+			.write_to(&mut std::io::Cursor::new(&mut image_data), ImageFormat::Png)
+			.map_err(|e| SerializingErr::UniqueFailure(format!("Could not encode the favicon as a PNG: {e}")))?;
 		let res_base64 = general_purpose::STANDARD.encode(image_data);
 		let s = format!("data:image/png;base64,{}", res_base64);
 
 		self.favicon = Some(s);
+
+		Ok(())
+	}
+
+	/// Decode the favicon sent by the server back into an image. Returns `None` if the server did not
+	/// send a favicon, or an error if the favicon is not a base64 encoded PNG data URI.
+	#[cfg(feature = "favicon")]
+	pub fn get_favicon_image(&self) -> SerializingResult<Option<DynamicImage>> {
+		let Some(favicon) = &self.favicon else {
+			return Ok(None);
+		};
+
+		let Some(encoded) = favicon.strip_prefix("data:image/png;base64,") else {
+			return Err(SerializingErr::DeserializationError("Favicon is not a PNG data URI".to_string()));
+		};
+
+		// some servers wrap the base64 body across multiple lines
+		let encoded: String = encoded.chars().filter(|c| !c.is_whitespace()).collect();
+		let bytes = general_purpose::STANDARD.decode(encoded).map_err(|e| SerializingErr::DeserializationError(format!("Favicon is not valid base64: {e}")))?;
+		let image = image::load_from_memory_with_format(&bytes, ImageFormat::Png).map_err(|e| SerializingErr::DeserializationError(format!("Favicon is not a valid PNG: {e}")))?;
+
+		Ok(Some(image))
 	}
 
 	/// Unknown purpose. Might be related to post 1.18 chat security.
@@ -119,7 +148,7 @@ impl McDeserialize for StatusResponseSpec {
 	where
 		Self: Sized,
 	{
-		let raw = serde_json::from_str(&String::mc_deserialize(deserializer)?).unwrap(); // TODO: test
+		let raw = serde_json::from_str(&String::mc_deserialize(deserializer)?).map_err(|e| SerializingErr::DeserializationError(format!("Invalid status response JSON: {e}")))?;
 
 		Ok(raw)
 	}
@@ -143,7 +172,7 @@ impl From<StatusResponseSpec> for StatusResponsePacket {
 /// Represents the version information for the server. The `name` of the version can be anything you want.
 /// The `protocol` must be a valid protocol version number, and must match the protocol version of the
 /// connecting client.
-#[derive(McDefault, Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(McDefault, McArbitrary, Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct VersionInfo {
 	// On 1.20+ servers `name` may be omitted; the Notchian client treats it as "Old" if so.
 	#[serde(default = "version_name_default")]
@@ -160,7 +189,7 @@ fn version_name_default() -> String {
 /// Player info which includes the current number of online users, max slots for the server, and a sample of online users.
 ///
 /// Many servers will customize this information so it shouldn't be taken literally.
-#[derive(McDefault, Default, Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(McDefault, McArbitrary, Default, Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PlayerInfo {
 	pub max: i32,
 	pub online: i32,
@@ -179,7 +208,7 @@ impl PlayerInfo {
 
 /// # Player Sample (Packet Part)
 /// Represents a single entry in the player list sample response, seen when the user hovers over the player count.
-#[derive(McDefault, Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(McDefault, McArbitrary, Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct PlayerSample {
 	pub name: String,
 	pub id: String,
@@ -197,6 +226,7 @@ impl PlayerSample {
 
 	/// Create a new player sample with the given name and a random UUID.
 	/// The name will have its color codes translated from the symbol '&' to the symbol '§'.
+	#[cfg(feature = "std")]
 	pub fn new_random<S: Into<String>>(name: S) -> Self {
 		Self {
 			name: name.into().replace("&", "§"),
@@ -211,6 +241,7 @@ mod test {
 	use crate::protocol::status::status_components::{PlayerSample, StatusResponseSpec};
 	use crate::protocol_types::datatypes::chat::ComponentType;
 	use crate::protocol_types::protocol_verison::ProtocolVerison;
+	use image::DynamicImage;
 	use uuid::Uuid;
 
 	#[test]
@@ -275,6 +306,20 @@ mod test {
 		assert!(spec.previewsChat);
 	}
 
+	#[test]
+	fn test_favicon_round_trip() {
+		// A favicon set from an image must decode back into an image of the same dimensions.
+		let mut spec = StatusResponseSpec::new(ProtocolVerison::latest(), "Test");
+		assert!(spec.get_favicon_image().unwrap().is_none());
+
+		spec.set_favicon_image(DynamicImage::new_rgba8(64, 64)).unwrap();
+		let decoded = spec.get_favicon_image().unwrap().unwrap();
+		assert_eq!((decoded.width(), decoded.height()), (64, 64));
+
+		spec.favicon = Some("not a data uri".to_string());
+		assert!(spec.get_favicon_image().is_err());
+	}
+
 	#[test]
 	fn test_serialize_deserialize_round_trip() {
 		// The spec travels over the wire as a length-prefixed JSON string; it must survive a round-trip.
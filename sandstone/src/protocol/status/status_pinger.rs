@@ -0,0 +1,308 @@
+//! A client-side server list pinger. This performs the full status sequence the Notchian client uses to
+//! populate the multiplayer screen: handshake, status request/response and a ping/pong round trip used
+//! to measure latency.
+//!
+//! Many servers can be scanned at once with [StatusPinger::scan], which runs a bounded number of pings
+//! concurrently.
+//!
+//! The status procedure can be found [here](https://minecraft.wiki/w/Java_Edition_protocol/Server_List_Ping)
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "favicon")]
+use image::DynamicImage;
+use log::{debug, trace};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+
+use crate::network::network_error::NetworkError;
+use crate::network::CraftConnection;
+use crate::protocol::packets::packet_definer::PacketState;
+use crate::protocol::packets::{HandshakingPacket, Packet, PingRequestPacket, StatusRequestPacket};
+use crate::protocol::serialization::serializer_types::BoundedString;
+#[cfg(feature = "favicon")]
+use crate::protocol::serialization::SerializingResult;
+use crate::protocol::status::status_components::StatusResponseSpec;
+use crate::protocol_types::datatypes::var_types::{RangedVarInt, VarInt};
+use crate::protocol_types::protocol_verison::ProtocolVerison;
+
+/// The default time allowed for each step of a ping (connecting, status and pong).
+pub const DEFAULT_PING_TIMEOUT: Duration = Duration::from_secs(5);
+/// The default number of servers that [StatusPinger::scan] will ping at the same time.
+pub const DEFAULT_PING_CONCURRENCY: usize = 16;
+
+/// The result of successfully pinging a server.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerStatus {
+	/// The address that was pinged, as it was provided to the pinger.
+	pub address: String,
+	/// The status returned by the server. This is what is displayed in the server list.
+	pub response: StatusResponseSpec,
+	/// The round trip time of the ping request, measured from sending `PingRequest` to receiving `PingResponse`.
+	pub latency: Duration,
+}
+
+impl ServerStatus {
+	/// Decode the favicon of the server, if it sent one.
+	#[cfg(feature = "favicon")]
+	pub fn favicon(&self) -> SerializingResult<Option<DynamicImage>> {
+		self.response.get_favicon_image()
+	}
+}
+
+/// Pings servers for their server list status and latency. Each step of the ping is bounded by the
+/// configured timeout so that unresponsive servers cannot stall a scan.
+///
+/// ```rust,no_run
+/// use sandstone::protocol::status::status_pinger::StatusPinger;
+///
+/// # async fn run() {
+/// let pinger = StatusPinger::new();
+/// let status = pinger.ping("hypixel.net").await.unwrap();
+/// println!("{} players online, {}ms", status.response.players.online, status.latency.as_millis());
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct StatusPinger {
+	timeout: Duration,
+	concurrency: usize,
+	protocol_version: ProtocolVerison,
+}
+
+impl StatusPinger {
+	/// Create a new pinger using [DEFAULT_PING_TIMEOUT], [DEFAULT_PING_CONCURRENCY] and the latest protocol version.
+	pub fn new() -> Self {
+		Self {
+			timeout: DEFAULT_PING_TIMEOUT,
+			concurrency: DEFAULT_PING_CONCURRENCY,
+			protocol_version: ProtocolVerison::latest(),
+		}
+	}
+
+	/// Set the time allowed for each step of a ping. Connecting, receiving the status and receiving the pong
+	/// are timed separately.
+	pub fn set_timeout(&mut self, timeout: Duration) {
+		self.timeout = timeout;
+	}
+
+	/// Set the maximum number of servers that are pinged at once by [StatusPinger::scan]. A value of 0 is treated as 1.
+	pub fn set_concurrency(&mut self, concurrency: usize) {
+		self.concurrency = concurrency.max(1);
+	}
+
+	/// Set the protocol version sent in the handshake. Servers typically respond to any version, but will
+	/// mark themselves as incompatible in the response if it does not match their own.
+	pub fn set_protocol_version(&mut self, protocol_version: ProtocolVerison) {
+		self.protocol_version = protocol_version;
+	}
+
+	pub fn get_timeout(&self) -> Duration {
+		self.timeout
+	}
+
+	pub fn get_concurrency(&self) -> usize {
+		self.concurrency
+	}
+
+	/// Ping a single server. The address follows the same rules as [CraftConnection::connect], so an SRV
+	/// record is resolved if no port is given.
+	pub async fn ping(&self, address: impl AsRef<str>) -> Result<ServerStatus, NetworkError> {
+		let address = address.as_ref();
+
+		let mut connection = self.with_timeout("connect", CraftConnection::connect(address)).await?;
+		let result = self.ping_connection(&mut connection).await;
+		connection.close().await;
+
+		let (response, latency) = result?;
+
+		Ok(ServerStatus {
+			address: address.to_string(),
+			response,
+			latency,
+		})
+	}
+
+	/// Perform the status sequence on an already opened connection that is still in the HANDSHAKING state.
+	/// Returns the status response and the measured latency. The connection is left in the STATUS state.
+	pub async fn ping_connection(&self, connection: &mut CraftConnection) -> Result<(StatusResponseSpec, Duration), NetworkError> {
+		if connection.packet_state != PacketState::HANDSHAKING {
+			return Err(NetworkError::InvalidPacketState);
+		}
+
+		let handshake = Packet::Handshaking(HandshakingPacket {
+			protocol_version: VarInt(self.protocol_version.get_version_number() as i32),
+			server_address: BoundedString::new(connection.hostname.clone().unwrap_or_else(|| connection.socket_addr.ip().to_string()))?,
+			port: connection.socket_addr.port(),
+			next_state: RangedVarInt::new(PacketState::STATUS.get_id().unwrap() as i32)?,
+		});
+
+		connection.send_packet(handshake).await?;
+		connection.change_state(PacketState::STATUS);
+
+		connection.send_packet(Packet::StatusRequest(StatusRequestPacket {})).await?;
+
+		let response = match self.with_timeout("status response", connection.receive_packet()).await? {
+			Packet::StatusResponse(response) => response.response,
+			_ => return Err(NetworkError::ExpectedDifferentPacket("Invalid packet received, expected status response".to_string())),
+		};
+
+		trace!("Received status response from {connection}");
+
+		// the payload can be anything, but the Notchian client uses the current time in millis
+		let payload = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as i64).unwrap_or_default();
+
+		let start = Instant::now();
+		connection.send_packet(Packet::PingRequest(PingRequestPacket::new(payload))).await?;
+
+		match self.with_timeout("ping response", connection.receive_packet()).await? {
+			Packet::PingResponse(_) => {}
+			_ => return Err(NetworkError::ExpectedDifferentPacket("Invalid packet received, expected ping response".to_string())),
+		}
+
+		let latency = start.elapsed();
+
+		debug!("Pinged {connection} in {}ms", latency.as_millis());
+
+		Ok((response, latency))
+	}
+
+	/// Ping every address, running at most [StatusPinger::get_concurrency] pings at once. The results are
+	/// returned in the same order as the provided addresses, paired with the address they belong to.
+	pub async fn scan<I, S>(&self, addresses: I) -> Vec<(String, Result<ServerStatus, NetworkError>)>
+	where
+		I: IntoIterator<Item = S>,
+		S: Into<String>,
+	{
+		let addresses: Vec<String> = addresses.into_iter().map(Into::into).collect();
+		let permits = Arc::new(Semaphore::new(self.concurrency));
+		let mut tasks = JoinSet::new();
+
+		for (index, address) in addresses.iter().cloned().enumerate() {
+			let pinger = self.clone();
+			let permits = permits.clone();
+
+			tasks.spawn(async move {
+				// the semaphore is never closed, so acquiring can only fail if that changes
+				let _permit = permits.acquire_owned().await;
+				(index, pinger.ping(&address).await)
+			});
+		}
+
+		let mut results: Vec<Option<Result<ServerStatus, NetworkError>>> = addresses.iter().map(|_| None).collect();
+
+		while let Some(joined) = tasks.join_next().await {
+			match joined {
+				Ok((index, result)) => results[index] = Some(result),
+				Err(e) => debug!("Ping task failed to complete: {e}"),
+			}
+		}
+
+		addresses
+			.into_iter()
+			.zip(results)
+			.map(|(address, result)| {
+				let result = result.unwrap_or(Err(NetworkError::ConnectionAbortedLocally));
+				(address, result)
+			})
+			.collect()
+	}
+
+	/// Run the future, returning [NetworkError::TimedOut] if it does not complete within the configured timeout.
+	async fn with_timeout<T>(&self, step: &str, future: impl Future<Output = Result<T, NetworkError>>) -> Result<T, NetworkError> {
+		match tokio::time::timeout(self.timeout, future).await {
+			Ok(result) => result,
+			Err(_) => Err(NetworkError::TimedOut(format!("{step} took longer than {}ms", self.timeout.as_millis()))),
+		}
+	}
+}
+
+impl Default for StatusPinger {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use std::time::Duration;
+
+	use tokio::net::TcpListener;
+
+	use crate::network::client::client_handlers::{ServerHandshakeHandler, ServerStatusHandler};
+	use crate::network::network_error::NetworkError;
+	use crate::network::CraftConnection;
+	use crate::protocol::packets::packet_definer::PacketDirection;
+	use crate::protocol::packets::StatusResponsePacket;
+	use crate::protocol::status::status_components::StatusResponseSpec;
+	use crate::protocol::status::status_pinger::StatusPinger;
+	use crate::protocol::status::{DefaultServerHandshakeHandler, DefaultServerPingHandler, DefaultServerStatusHandler};
+	use crate::protocol_types::protocol_verison::ProtocolVerison;
+
+	/// Serve the status sequence to `count` connections on a random local port, returning the address.
+	async fn spawn_status_server(count: usize) -> String {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let address = listener.local_addr().unwrap().to_string();
+
+		tokio::spawn(async move {
+			for _ in 0..count {
+				let (socket, _) = listener.accept().await.unwrap();
+				let mut client = CraftConnection::from_connection(socket, PacketDirection::SERVER).unwrap();
+				let response = StatusResponseSpec::new(ProtocolVerison::latest(), "Pinger test");
+
+				DefaultServerHandshakeHandler::handle_handshake(&mut client).await.unwrap();
+				DefaultServerStatusHandler::handle_status(&mut client, StatusResponsePacket::new(response), DefaultServerPingHandler).await.unwrap();
+			}
+		});
+
+		address
+	}
+
+	#[tokio::test]
+	async fn test_ping_local_server() {
+		let address = spawn_status_server(1).await;
+
+		let status = StatusPinger::new().ping(&address).await.unwrap();
+
+		assert_eq!(status.address, address);
+		assert_eq!(status.response, StatusResponseSpec::new(ProtocolVerison::latest(), "Pinger test"));
+		assert!(status.favicon().unwrap().is_none());
+	}
+
+	#[tokio::test]
+	async fn test_scan_preserves_order() {
+		let first = spawn_status_server(2).await;
+		let second = spawn_status_server(1).await;
+
+		let mut pinger = StatusPinger::new();
+		pinger.set_concurrency(2);
+
+		let results = pinger.scan([first.clone(), second.clone(), first.clone()]).await;
+
+		assert_eq!(results.len(), 3);
+		assert_eq!(results[0].0, first);
+		assert_eq!(results[1].0, second);
+		assert_eq!(results[2].0, first);
+		assert!(results.iter().all(|(_, r)| r.is_ok()));
+	}
+
+	#[tokio::test]
+	async fn test_ping_times_out() {
+		// accept the connection but never respond to it
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let address = listener.local_addr().unwrap().to_string();
+		tokio::spawn(async move {
+			let _socket = listener.accept().await.unwrap();
+			tokio::time::sleep(Duration::from_secs(5)).await;
+		});
+
+		let mut pinger = StatusPinger::new();
+		pinger.set_timeout(Duration::from_millis(100));
+
+		match pinger.ping(&address).await {
+			Err(NetworkError::TimedOut(_)) => {}
+			other => panic!("Expected timeout, got {other:?}"),
+		}
+	}
+}
@@ -0,0 +1,168 @@
+//! Random values of the protocol types for property based testing with [proptest].
+//!
+//! Where [McDefault](crate::protocol::testing::McDefault) gives one fixed value for a type, [McArbitrary] gives a
+//! strategy for many, including the edge cases like empty strings, negative VarInts, NaN floats and deeply nested NBT.
+//! Every value a strategy gives can be serialized, and must deserialize back into the same value.
+//!
+//! Values are compared rather than bytes, since NBT compounds don't keep the order of their tags. This is also why
+//! plain floats are never NaN, which isn't equal to itself; NBT compares its floats by their bits, so NaN is covered
+//! there.
+//!
+//! This module is only available for tests, or with the `proptest` feature.
+
+use crate::prelude::*;
+use crate::protocol::serialization::serializer_types::{BoundedArray, BoundedString, PrefixedArray, PrefixedOptional};
+use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer};
+use crate::protocol_types::datatypes::var_types::{RangedVarInt, VarInt, VarLong};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use uuid::Uuid;
+
+/// The most items generated for a list, which keeps nested lists from getting too large.
+pub const MAX_ARBITRARY_ITEMS: usize = 4;
+
+/// A registry id for an "ID or X" type. The id is written plus one, where 0 means that an inline value follows,
+/// so it cannot be negative.
+pub fn registry_id() -> BoxedStrategy<VarInt> {
+	(0..i32::MAX).prop_map(VarInt).boxed()
+}
+
+/// Serialize the value, and check that it deserializes back into the same value without any input left over.
+/// This is the round trip property checked for every packet and protocol type with an [McArbitrary] strategy.
+pub fn check_round_trip<T: McSerialize + McDeserialize + PartialEq + core::fmt::Debug>(value: &T) -> Result<(), TestCaseError> {
+	let mut serializer = McSerializer::new();
+	value.mc_serialize(&mut serializer).map_err(|e| TestCaseError::fail(format!("failed to serialize: {e}")))?;
+
+	let mut deserializer = McDeserializer::new(&serializer.output);
+	let out = T::mc_deserialize(&mut deserializer).map_err(|e| TestCaseError::fail(format!("failed to deserialize: {e}")))?;
+
+	prop_assert_eq!(&out, value);
+	prop_assert!(deserializer.is_at_end(), "left over input");
+	Ok(())
+}
+
+/// A type that can give a [proptest] strategy for its random values, used for property based testing.
+/// It can be derived with `#[derive(McArbitrary)]`.
+pub trait McArbitrary: Sized + core::fmt::Debug {
+	/// A strategy for random values of this type.
+	fn mc_arbitrary() -> BoxedStrategy<Self>;
+}
+
+macro_rules! arbitrary_from_any {
+	($($t:ty),*) => {
+		$(
+			impl McArbitrary for $t {
+				fn mc_arbitrary() -> BoxedStrategy<Self> {
+					any::<$t>().boxed()
+				}
+			}
+		)*
+	};
+}
+
+arbitrary_from_any!(String, u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, usize, isize, bool);
+
+impl McArbitrary for f32 {
+	/// Any float but NaN, see the module docs.
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		use proptest::num::f32::*;
+		(POSITIVE | NEGATIVE | NORMAL | SUBNORMAL | ZERO | INFINITE).boxed()
+	}
+}
+
+impl McArbitrary for f64 {
+	/// Any float but NaN, see the module docs.
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		use proptest::num::f64::*;
+		(POSITIVE | NEGATIVE | NORMAL | SUBNORMAL | ZERO | INFINITE).boxed()
+	}
+}
+
+impl<T: McArbitrary + 'static> McArbitrary for Vec<T> {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		vec(T::mc_arbitrary(), 0..=MAX_ARBITRARY_ITEMS).boxed()
+	}
+}
+
+impl<const N: usize> McArbitrary for [u8; N] {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		vec(any::<u8>(), N).prop_map(|bytes| bytes.try_into().unwrap()).boxed()
+	}
+}
+
+/// A plain Option is only `None` at the end of the input, so only `Some` is generated.
+/// An `#[mc(include_if = ...)]` field is `None` whenever its condition is false.
+impl<T: McArbitrary + 'static> McArbitrary for Option<T> {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		T::mc_arbitrary().prop_map(Some).boxed()
+	}
+}
+
+impl<T: McArbitrary + 'static> McArbitrary for Box<T> {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		T::mc_arbitrary().prop_map(Box::new).boxed()
+	}
+}
+
+impl McArbitrary for VarInt {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		any::<i32>().prop_map(VarInt).boxed()
+	}
+}
+
+impl<const MIN: i32, const MAX: i32> McArbitrary for RangedVarInt<MIN, MAX> {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		(MIN..=MAX).prop_map(RangedVarInt).boxed()
+	}
+}
+
+impl McArbitrary for VarLong {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		any::<i64>().prop_map(VarLong).boxed()
+	}
+}
+
+impl McArbitrary for Uuid {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		any::<u128>().prop_map(Uuid::from_u128).boxed()
+	}
+}
+
+impl<T: McArbitrary + McSerialize + McDeserialize + 'static> McArbitrary for PrefixedArray<T> {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		Vec::<T>::mc_arbitrary().prop_map(PrefixedArray::new).boxed()
+	}
+}
+
+impl<T: McArbitrary + McSerialize + McDeserialize + 'static> McArbitrary for PrefixedOptional<T> {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		proptest::option::of(T::mc_arbitrary()).prop_map(PrefixedOptional::new).boxed()
+	}
+}
+
+impl<T: McArbitrary + McSerialize + McDeserialize + 'static, const N: usize> McArbitrary for BoundedArray<T, N> {
+	/// Arrays of at most `N` items.
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		vec(T::mc_arbitrary(), 0..=N.min(MAX_ARBITRARY_ITEMS)).prop_map(|vec| BoundedArray::new(vec).unwrap()).boxed()
+	}
+}
+
+impl<const N: usize> McArbitrary for BoundedString<N> {
+	/// Strings cut down to at most `N` UTF-16 code units.
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		any::<String>()
+			.prop_map(|string| {
+				let mut length = 0;
+				let string = string
+					.chars()
+					.take_while(|c| {
+						length += c.len_utf16();
+						length <= N
+					})
+					.collect::<String>();
+
+				BoundedString::new(string).unwrap()
+			})
+			.boxed()
+	}
+}
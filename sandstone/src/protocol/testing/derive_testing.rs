@@ -1,21 +1,59 @@
 //! Tests the functionality of the 'McDeserialize' and 'McSerialize' derive macros.
 
+use crate::prelude::*;
+#[cfg(feature = "field-spans")]
+use crate::protocol::serialization::field_spans::FieldRecorder;
 use crate::protocol::serialization::serializer_error::SerializingErr;
-use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, SerializingResult};
+use crate::protocol::serialization::{McDeserialize, McDeserializeBorrowed, McDeserializer, McSerialize, McSerializer, SerializingResult};
 use crate::protocol::testing::McDefault;
-use crate::protocol_types::datatypes::var_types::VarInt;
+use crate::protocol_types::datatypes::var_types::{VarInt, VarLong};
 use sandstone_derive::{McDeserialize, McSerialize};
+use serde::Serialize;
 
-#[derive(McSerialize, McDeserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(McSerialize, Serialize, McDeserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TestPacket {
 	pub field1: bool,
-	#[mc(deserialize_if = field1)]
+	#[mc(include_if = field1)]
 	pub field2: Option<String>,
 }
 
+/// Exercises the field attributes that change how a field is encoded.
+#[derive(McSerialize, Serialize, McDeserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TestAttributes {
+	#[mc(varint)]
+	pub count: u16,
+	#[mc(varlong)]
+	pub big: i64,
+	#[mc(include_if = count > 2 && big != 0)]
+	pub extra: Option<u8>,
+	#[mc(length_prefixed)]
+	pub names: Vec<String>,
+	#[mc(rest)]
+	pub rest: Vec<u8>,
+}
+
+/// A view of a plugin message that borrows from the input instead of copying out of it.
+#[derive(McSerialize, Serialize, McDeserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TestBorrowed<'a> {
+	pub channel: &'a str,
+	#[mc(length_prefixed)]
+	pub header: &'a [u8],
+	pub owned: u8,
+	#[mc(rest)]
+	pub data: &'a [u8],
+}
+
+/// Nests other derived types, to check the field path of deserialization errors.
+#[derive(McSerialize, Serialize, McDeserialize, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TestNested {
+	pub header: u8,
+	#[mc(length_prefixed)]
+	pub packets: Vec<TestPacket>,
+}
+
 /// Exercises the enum support of the derives: a leading VarInt discriminant selects the variant,
 /// then that variant's body is (de)serialized. Covers named, unit, and unnamed variants.
-#[derive(McSerialize, McDeserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(McSerialize, Serialize, McDeserialize, Debug, Clone, PartialEq, Eq, Hash)]
 #[repr(i32)]
 pub enum TestEnum {
 	Named { a: u8, b: String } = 0,
@@ -25,7 +63,7 @@ pub enum TestEnum {
 
 #[cfg(test)]
 mod tests {
-	use crate::protocol::serialization::{McDeserialize, McSerialize};
+	use crate::protocol::serialization::{McDeserialize, McDeserializeBorrowed, McSerialize};
 
 	#[test]
 	fn test_serialize_deserialize() {
@@ -101,4 +139,121 @@ mod tests {
 		super::TestEnum::Unit.mc_serialize(&mut serializer).unwrap();
 		assert_eq!(serializer.output.as_slice(), &[1u8]);
 	}
+
+	#[test]
+	fn test_field_attributes() {
+		let value = super::TestAttributes {
+			count: 300,
+			big: 1,
+			extra: Some(9),
+			names: vec!["a".to_string(), "bc".to_string()],
+			rest: vec![1, 2, 3],
+		};
+		let mut serializer = super::McSerializer::new();
+		value.mc_serialize(&mut serializer).unwrap();
+		assert_eq!(serializer.output, vec![172, 2, 1, 9, 2, 1, b'a', 2, b'b', b'c', 1, 2, 3]);
+
+		let mut deserializer = super::McDeserializer::new(&serializer.output);
+		assert_eq!(super::TestAttributes::mc_deserialize(&mut deserializer).unwrap(), value);
+		assert!(deserializer.is_at_end());
+
+		// the condition is false, so the field is neither written nor read
+		let value = super::TestAttributes {
+			count: 1,
+			extra: None,
+			..value
+		};
+		let mut serializer = super::McSerializer::new();
+		value.mc_serialize(&mut serializer).unwrap();
+
+		let mut deserializer = super::McDeserializer::new(&serializer.output);
+		assert_eq!(super::TestAttributes::mc_deserialize(&mut deserializer).unwrap(), value);
+	}
+
+	#[test]
+	fn test_field_attribute_errors() {
+		// the condition is true, but the field is missing
+		let value = super::TestAttributes {
+			count: 3,
+			big: 1,
+			extra: None,
+			names: vec![],
+			rest: vec![],
+		};
+		assert!(value.mc_serialize(&mut super::McSerializer::new()).is_err());
+
+		// a VarInt that does not fit in the u16 field
+		let mut serializer = super::McSerializer::new();
+		super::VarInt(70000).mc_serialize(&mut serializer).unwrap();
+		super::VarLong(0).mc_serialize(&mut serializer).unwrap();
+		super::VarInt(0).mc_serialize(&mut serializer).unwrap();
+
+		let mut deserializer = super::McDeserializer::new(&serializer.output);
+		assert!(super::TestAttributes::mc_deserialize(&mut deserializer).is_err());
+	}
+
+	#[test]
+	fn test_error_context() {
+		// the second packet has a string that is longer than the input
+		let input = [9, 2, 1, 2, b'a', b'b', 1, 5, b'x'];
+		let mut deserializer = super::McDeserializer::new(&input);
+		let err = super::TestNested::mc_deserialize(&mut deserializer).unwrap_err();
+
+		let context = err.context().unwrap();
+		assert_eq!(context.path, "packets[1].field2");
+		assert_eq!(context.offset, 8);
+		assert_eq!(context.window, input);
+		assert_eq!(err.root(), &context.source);
+		assert!(err.to_string().ends_with("at byte 8 in packets[1].field2 [09 02 01 02 61 62 01 05 | 78]"), "{}", err);
+	}
+
+	#[test]
+	#[cfg(feature = "field-spans")]
+	fn test_field_spans() {
+		// the second packet skips its string, so it has no span
+		let input = [9, 2, 1, 2, b'a', b'b', 0];
+		let recorder = super::FieldRecorder::new(&input);
+		let mut deserializer = super::McDeserializer::with_recorder(&input, &recorder);
+		super::TestNested::mc_deserialize(&mut deserializer).unwrap();
+
+		let spans: Vec<_> = recorder.into_spans().into_iter().map(|span| (span.path, span.depth, span.range)).collect();
+		assert_eq!(
+			spans,
+			vec![
+				("header".to_string(), 0, 0..1),
+				("packets".to_string(), 0, 1..7),
+				("packets[0]".to_string(), 1, 2..6),
+				("packets[0].field1".to_string(), 2, 2..3),
+				("packets[0].field2".to_string(), 2, 3..6),
+				("packets[1]".to_string(), 1, 6..7),
+				("packets[1].field1".to_string(), 2, 6..7),
+			]
+		);
+	}
+
+	#[test]
+	fn test_borrowed_fields() {
+		let value = super::TestBorrowed {
+			channel: "minecraft:brand",
+			header: &[7, 8],
+			owned: 5,
+			data: &[1, 2, 3],
+		};
+		let mut serializer = super::McSerializer::new();
+		value.mc_serialize(&mut serializer).unwrap();
+
+		let mut deserializer = super::McDeserializer::new(&serializer.output);
+		let deserialized = super::TestBorrowed::mc_deserialize_borrowed(&mut deserializer).unwrap();
+		assert_eq!(deserialized, value);
+		assert!(deserializer.is_at_end());
+
+		// the borrowed fields point into the input buffer
+		let range = serializer.output.as_ptr_range();
+		assert!(range.contains(&deserialized.channel.as_ptr()));
+		assert!(range.contains(&deserialized.data.as_ptr()));
+
+		// a length prefix past the end of the input
+		let mut deserializer = super::McDeserializer::new(&[2, b'a']);
+		assert!(<&str>::mc_deserialize_borrowed(&mut deserializer).is_err());
+	}
 }
@@ -2,16 +2,37 @@
 
 //! Traits and implementations for testing packet serialization and deserialization.
 
+use crate::prelude::*;
+#[cfg(feature = "network")]
+use crate::network::{ConnectionRole, CraftConnection};
 use crate::protocol;
+use crate::protocol::packets::packet_definer::PacketState;
 use crate::protocol::serialization::serializer_types::PrefixedArray;
-use crate::protocol_types::datatypes::var_types::{VarInt, VarLong};
+use crate::protocol_types::datatypes::var_types::{RangedVarInt, VarInt, VarLong};
 use uuid::Uuid;
 
-mod benchmarking;
+#[cfg(any(test, feature = "proptest"))]
+pub mod arbitrary;
 mod derive_testing;
 pub mod packet_testing;
 mod primitive_serialization_testing;
 
+/// Open a connected pair of `(client, server)` connections over the loopback interface, both set to `state`.
+/// Used to test packet sequences that need both ends of a connection.
+#[cfg(all(test, feature = "network"))]
+pub(crate) async fn connection_pair(state: PacketState) -> (CraftConnection, CraftConnection) {
+	let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+	let client = tokio::net::TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+	let (server, _) = listener.accept().await.unwrap();
+
+	let mut client = CraftConnection::from_connection(client, ConnectionRole::CLIENT).unwrap();
+	let mut server = CraftConnection::from_connection(server, ConnectionRole::SERVER).unwrap();
+	client.change_state(state);
+	server.change_state(state);
+
+	(client, server)
+}
+
 // TODO: derive macro
 /// A trait that defines the default value for a type. This is used for testing packet serialization.
 /// This is different from the typical `Default` trait in Rust, as it returns more random and meaningful
@@ -88,6 +109,7 @@ impl McDefault for i128 {
 }
 
 impl McDefault for f32 {
+	#[allow(clippy::excessive_precision)]
 	fn mc_default() -> Self {
 		1387137.48347
 	}
@@ -99,15 +121,16 @@ impl McDefault for f64 {
 	}
 }
 
+// fits in the 32 bit usize of targets like wasm32
 impl McDefault for usize {
 	fn mc_default() -> Self {
-		1234567890123456789
+		1234567890
 	}
 }
 
 impl McDefault for isize {
 	fn mc_default() -> Self {
-		-1234567890123456789
+		-1234567890
 	}
 }
 
@@ -147,6 +170,12 @@ impl McDefault for VarInt {
 	}
 }
 
+impl<const MIN: i32, const MAX: i32> McDefault for RangedVarInt<MIN, MAX> {
+	fn mc_default() -> Self {
+		RangedVarInt(MAX)
+	}
+}
+
 impl McDefault for VarLong {
 	fn mc_default() -> Self {
 		VarLong(1234567890123456789)
@@ -1,10 +1,42 @@
 //! Tests basic packet serialization and deserialization functionality.
 
-use crate::protocol::packets::packet_definer::{PacketDirection, PacketState};
-use crate::protocol::packets::packet_parts::LoginPluginSpec;
-use crate::protocol::packets::{LoginDisconnectPacket, LoginPluginResponsePacket, Packet};
-use crate::protocol::serialization::{McDeserializer, McSerialize, McSerializer, StateBasedDeserializer};
+use crate::protocol::packets::packet_definer::{PacketDirection, PacketField, PacketInfo, PacketState};
+use crate::protocol::game::player::player_action::{AddPlayerData, EnumSet, PlayerAction, PlayerInfoEntry, PlayerInfoUpdateData};
+use crate::protocol::packets::packet_parts::{LoginPluginSpec, ProtocolPropertyElement};
+use crate::protocol::packets::{CookieRequestPacket, KeepAlivePacket, LoginDisconnectPacket, LoginPluginResponsePacket, Packet, PlayerInfoUpdatePacket, PACKET_REGISTRY};
+use crate::protocol::serialization::serializer_error::SerializingErr;
+use crate::protocol::serialization::serializer_types::{PrefixedArray, PrefixedOptional};
+use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, StateBasedDeserializer};
 use crate::protocol_types::datatypes::chat::{JsonTextComponent, TextComponent};
+use crate::protocol_types::datatypes::var_types::VarInt;
+use crate::protocol::testing::McDefault;
+use crate::protocol_types::protocol_verison::ProtocolVerison;
+use uuid::Uuid;
+
+/// Serialize the packet in every state it is used in, and check that it is written with its id in that state
+/// and deserializes back into the same packet. Used by the round trip test generated for every packet, which
+/// also checks that the packet can be dumped as JSON.
+#[cfg(test)]
+pub(crate) fn assert_round_trip(packet: &Packet) {
+	let json = packet.to_json();
+	assert!(!json.starts_with("\"failed to dump"), "{packet:?} could not be dumped: {json}");
+
+	for &state in packet.states() {
+		let mut serializer = McSerializer::new();
+		packet
+			.serialize_state_version(&mut serializer, state, ProtocolVerison::latest())
+			.unwrap_or_else(|e| panic!("{packet:?} failed to serialize in {state:?}: {e}"));
+
+		let mut deserializer = McDeserializer::new(&serializer.output);
+		VarInt::mc_deserialize(&mut deserializer).unwrap();
+		assert_eq!(Some(VarInt::mc_deserialize(&mut deserializer).unwrap()), packet.packet_id_in(state), "wrong id in {state:?}");
+
+		let mut deserializer = McDeserializer::new(&serializer.output);
+		let out = Packet::deserialize_state(&mut deserializer, state, packet.direction()).unwrap_or_else(|e| panic!("{packet:?} failed to deserialize in {state:?}: {e}"));
+		assert_eq!(&out, packet, "did not round trip in {state:?}");
+		assert!(deserializer.is_at_end(), "left over input in {state:?}");
+	}
+}
 
 #[test]
 pub fn test_basic_deserialization() {
@@ -29,6 +61,60 @@ pub fn test_basic_deserialization() {
 	}
 }
 
+#[test]
+pub fn test_deserialization_error_context() {
+	let vec: Vec<u8> = vec![6, 0, 254, 5, 9, 108, 111]; // Handshake, with the server address cut off
+
+	let mut deserializer = McDeserializer::new(&vec);
+	let err = Packet::deserialize_state(&mut deserializer, PacketState::HANDSHAKING, PacketDirection::SERVER).unwrap_err();
+
+	// the offset is in the whole frame, not just the body of the packet
+	let context = err.context().unwrap();
+	assert_eq!(context.path, "Handshaking.server_address");
+	assert_eq!(context.offset, 5);
+	assert_eq!(context.window, vec);
+}
+
+#[test]
+pub fn test_player_info_error_context() {
+	let entries = (0..4)
+		.map(|i| {
+			let mut actions = PlayerAction::mc_default();
+			actions.add_player = Some(AddPlayerData {
+				name: format!("player{i}"),
+				properties: PrefixedArray::new(vec![ProtocolPropertyElement {
+					name: "textures".to_string(),
+					value: "value".to_string(),
+					signature: PrefixedOptional::new(Some("signature".to_string())),
+				}]),
+			});
+			PlayerInfoEntry { uuid: Uuid::nil(), actions }
+		})
+		.collect();
+
+	let packet = Packet::PlayerInfoUpdate(PlayerInfoUpdatePacket {
+		players: PlayerInfoUpdateData {
+			actions: EnumSet::from_raw(0x01),
+			entries,
+		},
+	});
+
+	let mut serializer = McSerializer::new();
+	packet.serialize_state_version(&mut serializer, PacketState::PLAY, ProtocolVerison::latest()).unwrap();
+
+	// cut off the end of the last signature and fix up the frame length to match
+	let mut deserializer = McDeserializer::new(&serializer.output);
+	VarInt::mc_deserialize(&mut deserializer).unwrap();
+	let body = &serializer.output[deserializer.index..serializer.output.len() - 4];
+	let mut frame = McSerializer::new();
+	VarInt(body.len() as i32).mc_serialize(&mut frame).unwrap();
+	frame.serialize_bytes(body);
+
+	let mut deserializer = McDeserializer::new(&frame.output);
+	let err = Packet::deserialize_state(&mut deserializer, PacketState::PLAY, PacketDirection::CLIENT).unwrap_err();
+	assert_eq!(err.context().unwrap().path, "PlayerInfoUpdate.players[3].actions.add_player.properties[0].signature");
+}
+
 #[test]
 pub fn test_optional_vec_serialization() {
 	let mut serializer = McSerializer::new();
@@ -86,3 +172,64 @@ pub fn test_cross_serialization() {
 
 	serializer.clear();
 }
+
+#[test]
+pub fn test_packet_shared_across_states() {
+	let packet = Packet::KeepAlive(KeepAlivePacket::new(42));
+	assert_eq!(packet.states(), &[PacketState::CONFIGURATION, PacketState::PLAY]);
+	assert_eq!(packet.packet_id_in(PacketState::CONFIGURATION), Some(VarInt(0x04)));
+	assert_eq!(packet.packet_id_in(PacketState::PLAY), Some(VarInt(0x2B)));
+	assert_eq!(packet.packet_id_in(PacketState::LOGIN), None);
+
+	for state in [PacketState::CONFIGURATION, PacketState::PLAY] {
+		let mut serializer = McSerializer::new();
+		packet.serialize_state_version(&mut serializer, state, ProtocolVerison::latest()).unwrap();
+		assert_eq!(serializer.output[1], packet.packet_id_in(state).unwrap().0 as u8);
+
+		let mut deserializer = McDeserializer::new(&serializer.output);
+		let out = Packet::deserialize_state(&mut deserializer, state, PacketDirection::CLIENT).unwrap();
+		assert_eq!(packet, out);
+	}
+
+	let packet = Packet::CookieRequest(CookieRequestPacket::new("minecraft:cookie".try_into().unwrap()));
+	assert_eq!(packet.states(), &[PacketState::LOGIN, PacketState::CONFIGURATION, PacketState::PLAY]);
+
+	let mut serializer = McSerializer::new();
+	packet.serialize_state_version(&mut serializer, PacketState::CONFIGURATION, ProtocolVerison::latest()).unwrap();
+	let mut deserializer = McDeserializer::new(&serializer.output);
+	assert_eq!(Packet::deserialize_state(&mut deserializer, PacketState::CONFIGURATION, PacketDirection::CLIENT).unwrap(), packet);
+
+	// the packet has no id in a state it is not used in
+	let packet = Packet::KeepAlive(KeepAlivePacket::new(42));
+	let mut serializer = McSerializer::new();
+	assert_eq!(packet.serialize_state_version(&mut serializer, PacketState::LOGIN, ProtocolVerison::latest()), Err(SerializingErr::InvalidPacketState));
+}
+
+#[test]
+pub fn test_packet_registry() {
+	let info = PacketInfo::by_name("KeepAlive").unwrap();
+	assert_eq!(info.state, PacketState::CONFIGURATION);
+	assert_eq!(info.direction, PacketDirection::CLIENT);
+	assert_eq!(info.id, 0x04);
+	assert_eq!(info.other_states, &[(PacketState::PLAY, 0x2B)]);
+	assert_eq!(info.fields, &[PacketField { name: "keep_alive_id", typ: "i64" }]);
+	assert_eq!(Packet::KeepAlive(KeepAlivePacket::new(42)).info(), info);
+
+	// found by its id in any state it is used in
+	assert_eq!(PacketInfo::by_id(PacketState::PLAY, PacketDirection::CLIENT, 0x2B), Some(info));
+	assert_eq!(PacketInfo::by_id(PacketState::PLAY, PacketDirection::SERVER, 0x2B).map(|i| i.name), Some("PlayerLoaded"));
+	assert_eq!(PacketInfo::by_name("NotAPacket"), None);
+
+	let info = PacketInfo::by_name("Handshaking").unwrap();
+	assert!(info.doc.starts_with("Used to switch server into a different connection state."));
+	assert_eq!(info.fields.iter().map(|f| f.typ).collect::<Vec<_>>(), ["VarInt", "BoundedString<255>", "u16", "RangedVarInt<1, 3>"]);
+
+	// every doc attribute is part of the description, and other attributes are skipped
+	assert_eq!(crate::packet_doc!([] [doc = "First line"] [allow(dead_code)] [doc = "Second line"]), "First line\nSecond line");
+	assert_eq!(crate::packet_doc!([] [must_use]), "");
+
+	let mut names: Vec<&str> = PACKET_REGISTRY.iter().map(|info| info.name).collect();
+	names.sort();
+	names.dedup();
+	assert_eq!(names.len(), PACKET_REGISTRY.len(), "packet names must be unique");
+}
@@ -1,6 +1,7 @@
 //! Testing for the serialization and deserialization of primitive types.
 
 #[cfg(test)]
+#[allow(clippy::unnecessary_mut_passed, clippy::bool_assert_comparison)]
 mod primitive_testing {
 	use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer};
 
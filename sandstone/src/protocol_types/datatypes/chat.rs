@@ -3,23 +3,33 @@
 //!
 //! https://minecraft.wiki/w/Text_component_format
 
+use crate::prelude::*;
+use crate::protocol::serialization::serde_format::byte_array;
 use crate::protocol::serialization::serializer_error::SerializingErr;
 use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, SerializingResult};
+#[cfg(any(test, feature = "proptest"))]
+use crate::protocol::testing::arbitrary::{McArbitrary, MAX_ARBITRARY_ITEMS};
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::nbt::{NbtCompound, NbtList, NbtTag};
 use crate::protocol_types::datatypes::var_types::VarInt;
-use sandstone_derive::{McDefault, McDeserialize, McSerialize, VarIntEnum};
+use sandstone_derive::{McArbitrary, McDefault, McDeserialize, McSerialize, VarIntEnum};
 use serde::de::value::MapAccessDeserializer;
 use serde::de::{self, MapAccess, SeqAccess, Visitor};
 use serde::{Deserialize, Serialize};
-use std::fmt;
+use core::fmt;
+#[cfg(any(test, feature = "proptest"))]
+use proptest::collection::vec;
+#[cfg(any(test, feature = "proptest"))]
+use proptest::option;
+#[cfg(any(test, feature = "proptest"))]
+use proptest::prelude::{any, prop_oneof, BoxedStrategy, Strategy};
 
 /// A TextComponent is a fancy way to display text inside the game. This is most commonly seen
 /// in chat messages and book messages. The only thing that is required to be included is a String
 /// representing the text to be displayed. Everything else is an optional modifier.
 ///
 /// See https://minecraft.wiki/w/Text_component_format for more information.
-#[derive(McDefault, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, Hash)]
 #[allow(non_snake_case)]
 pub struct TextComponent {
 	/// The content of this component, determining how the displayed text is produced
@@ -129,6 +139,54 @@ impl TextComponent {
 	}
 }
 
+impl McDefault for TextComponent {
+	/// A styled component without children, since every default child would have a default child of its own.
+	fn mc_default() -> Self {
+		let mut component = Self::from_content(ComponentType::mc_default());
+		component.color = Some("gold".to_string());
+		component.bold = Some(true);
+		component.insertion = Some(String::mc_default());
+		component
+	}
+}
+
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for TextComponent {
+	/// Components of every content type, with children nested up to 3 deep.
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		let leaf = String::mc_arbitrary().prop_map(TextComponent::new);
+
+		leaf.prop_recursive(3, 16, MAX_ARBITRARY_ITEMS as u32, |component| {
+			let style = (
+				option::of(String::mc_arbitrary()),
+				option::of(any::<bool>()),
+				option::of(any::<bool>()),
+				option::of(any::<bool>()),
+				option::of(any::<bool>()),
+				option::of(any::<bool>()),
+				option::of(String::mc_arbitrary()),
+				option::of(String::mc_arbitrary()),
+			);
+
+			(ComponentType::arbitrary_with(component.clone()), option::of(vec(component, 0..=MAX_ARBITRARY_ITEMS)), style).prop_map(
+				|(content, extra, (color, bold, italic, underlined, strikethrough, obfuscated, font, insertion))| Self {
+					content,
+					extra,
+					color,
+					bold,
+					italic,
+					underlined,
+					strikethrough,
+					obfuscated,
+					font,
+					insertion,
+				},
+			)
+		})
+		.boxed()
+	}
+}
+
 impl From<NbtTag> for TextComponent {
 	fn from(tag: NbtTag) -> Self {
 		match tag {
@@ -193,6 +251,7 @@ impl From<NbtTag> for TextComponent {
 }
 
 impl From<TextComponent> for NbtTag {
+	#[allow(clippy::collapsible_if)]
 	fn from(component: TextComponent) -> Self {
 		if component.is_plain() {
 			if let ComponentType::Text {
@@ -206,10 +265,7 @@ impl From<TextComponent> for NbtTag {
 		let mut compound = NbtCompound::new_no_name();
 		component.content.write_to_compound(&mut compound);
 		if let Some(extra) = component.extra {
-			let tags: Vec<NbtTag> = extra.into_iter().map(NbtTag::from).collect();
-			if let Ok(list) = NbtList::from_vec(tags) {
-				compound.add("extra", NbtTag::List(list));
-			}
+			compound.add("extra", NbtTag::List(component_list(extra)));
 		}
 		if let Some(color) = component.color {
 			compound.add("color", NbtTag::String(color));
@@ -239,19 +295,30 @@ impl From<TextComponent> for NbtTag {
 	}
 }
 
-impl McSerialize for TextComponent {
-	fn mc_serialize(&self, serializer: &mut McSerializer) -> SerializingResult<()> {
-		let nbt = NbtTag::from(self.clone());
-		match &nbt {
-			NbtTag::Compound(compound) => {
-				compound.mc_serialize(serializer)?;
-			}
-			_ => {
-				nbt.get_type_id().mc_serialize(serializer)?;
-				nbt.mc_serialize(serializer)?;
+/// Convert a list of components to NBT. A list can only hold one type of tag, so if only some of the components
+/// are plain text, the plain ones are written as compounds as well.
+fn component_list(components: Vec<TextComponent>) -> NbtList {
+	let mut tags: Vec<NbtTag> = components.into_iter().map(NbtTag::from).collect();
+
+	if tags.iter().any(|tag| matches!(tag, NbtTag::Compound(_))) {
+		for tag in tags.iter_mut() {
+			if let NbtTag::String(text) = tag {
+				let mut compound = NbtCompound::new_no_name();
+				compound.add("text", NbtTag::String(core::mem::take(text)));
+				*tag = NbtTag::Compound(compound);
 			}
 		}
-		Ok(())
+	}
+
+	NbtList {
+		type_id: tags.first().map_or(0, NbtTag::get_type_id),
+		list: tags,
+	}
+}
+
+impl McSerialize for TextComponent {
+	fn mc_serialize(&self, serializer: &mut McSerializer) -> SerializingResult<()> {
+		NbtTag::from(self.clone()).mc_serialize(serializer)
 	}
 }
 
@@ -359,7 +426,7 @@ impl<'de> Deserialize<'de> for TextComponent {
 }
 
 /// A wrapper around TextComponent that is serialized as a JSON string.
-#[derive(McDefault, Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(McDefault, McArbitrary, Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct JsonTextComponent(TextComponent);
 
 impl From<TextComponent> for JsonTextComponent {
@@ -543,6 +610,54 @@ impl ComponentType {
 		}
 	}
 
+	/// A strategy for content of every type, where `component` gives the components that it holds.
+	#[cfg(any(test, feature = "proptest"))]
+	fn arbitrary_with(component: BoxedStrategy<TextComponent>) -> BoxedStrategy<Self> {
+		let separator = option::of(component.clone().prop_map(Box::new));
+
+		prop_oneof![
+			String::mc_arbitrary().prop_map(|text| ComponentType::Text {
+				text,
+			}),
+			(String::mc_arbitrary(), option::of(String::mc_arbitrary()), option::of(vec(component, 0..=MAX_ARBITRARY_ITEMS))).prop_map(|(translate, fallback, with)| {
+				ComponentType::Translatable(Box::new(TranslatableContent {
+					translate,
+					fallback,
+					with,
+				}))
+			}),
+			ScoreContent::mc_arbitrary().prop_map(|score| ComponentType::Score {
+				score,
+			}),
+			(String::mc_arbitrary(), separator.clone()).prop_map(|(selector, separator)| ComponentType::Selector {
+				selector,
+				separator,
+			}),
+			String::mc_arbitrary().prop_map(|keybind| ComponentType::Keybind {
+				keybind,
+			}),
+			(
+				String::mc_arbitrary(),
+				option::of(any::<bool>()),
+				separator,
+				option::of(String::mc_arbitrary()),
+				option::of(String::mc_arbitrary()),
+				option::of(String::mc_arbitrary())
+			)
+				.prop_map(|(nbt, interpret, separator, block, entity, storage)| {
+					ComponentType::Nbt(Box::new(NbtContent {
+						nbt,
+						interpret,
+						separator,
+						block,
+						entity,
+						storage,
+					}))
+				}),
+		]
+		.boxed()
+	}
+
 	/// Write this content type's fields (including an explicit `type` tag for non-text variants)
 	/// into the given NBT compound.
 	fn write_to_compound(self, compound: &mut NbtCompound) {
@@ -564,10 +679,7 @@ impl ComponentType {
 					compound.add("fallback", NbtTag::String(fallback));
 				}
 				if let Some(with) = with {
-					let tags: Vec<NbtTag> = with.into_iter().map(NbtTag::from).collect();
-					if let Ok(list) = NbtList::from_vec(tags) {
-						compound.add("with", NbtTag::List(list));
-					}
+					compound.add("with", NbtTag::List(component_list(with)));
 				}
 			}
 			ComponentType::Score {
@@ -624,7 +736,7 @@ impl ComponentType {
 }
 
 /// The `score` object of a [`ComponentType::Score`] component.
-#[derive(McDefault, Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(McDefault, McArbitrary, Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub struct ScoreContent {
 	pub name: String,
 	pub objective: String,
@@ -801,14 +913,38 @@ pub struct ItemHover {
 	pub tag: Option<String>,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct PlayerChatSignature {
 	message_id: VarInt,
-	#[mc(deserialize_if = message_id.0 == 0)]
+	#[mc(include_if = message_id.0 == 0)]
+	#[serde(serialize_with = "byte_array::option")]
 	signature: Option<[u8; 256]>,
 }
 
-#[derive(VarIntEnum, McDefault, Debug, Clone, PartialEq)]
+impl PlayerChatSignature {
+	/// A signature sent in full.
+	pub fn full(signature: [u8; 256]) -> Self {
+		Self {
+			message_id: VarInt(0),
+			signature: Some(signature),
+		}
+	}
+
+	/// A signature the client has cached, referenced by its id in the cache.
+	pub fn cached(id: i32) -> Self {
+		Self {
+			message_id: VarInt(id + 1),
+			signature: None,
+		}
+	}
+
+	/// The signature, if it was sent in full.
+	pub fn get_signature(&self) -> Option<&[u8; 256]> {
+		self.signature.as_ref()
+	}
+}
+
+#[derive(VarIntEnum, McDefault, McArbitrary, Serialize, Debug, Clone, PartialEq)]
 pub enum PlayerChatFilter {
 	PassThrough = 0,
 	FullyFiltered = 1,
@@ -818,6 +954,8 @@ pub enum PlayerChatFilter {
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use crate::protocol::testing::arbitrary::check_round_trip;
+	use proptest::proptest;
 
 	/// Serialize via the NBT wire path and read it back. The content type must survive the round
 	/// trip, since the receiving client relies on it to know how to render the component.
@@ -945,4 +1083,16 @@ mod tests {
 
 		assert_eq!(component, serde_json::from_str::<TextComponent>(&json).unwrap());
 	}
+
+	proptest! {
+		#[test]
+		fn text_component_round_trip(component in TextComponent::mc_arbitrary()) {
+			check_round_trip(&component)?;
+		}
+
+		#[test]
+		fn json_text_component_round_trip(component in JsonTextComponent::mc_arbitrary()) {
+			check_round_trip(&component)?;
+		}
+	}
 }
@@ -1,12 +1,18 @@
+use crate::prelude::*;
 use crate::protocol::serialization::serializer_error::SerializingErr;
 use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, SerializingResult};
+#[cfg(any(test, feature = "proptest"))]
+use crate::protocol::testing::arbitrary::McArbitrary;
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::var_types::VarInt;
-use sandstone_derive::{McDefault, McSerialize};
-use std::hash::{Hash, Hasher};
+use sandstone_derive::{McArbitrary, McDefault, McSerialize};
+use serde::Serialize;
+use core::hash::{Hash, Hasher};
+#[cfg(any(test, feature = "proptest"))]
+use proptest::prelude::{any, prop_oneof, BoxedStrategy, Strategy};
 
 /// A Node used for representing graphs
-#[derive(McSerialize, McDefault, Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(McSerialize, Serialize, Debug, Clone, Hash, PartialEq, Eq)]
 pub struct Node {
 	pub flags: NodeFlags,
 	pub children_count: VarInt,
@@ -66,8 +72,49 @@ impl McDeserialize for Node {
 	}
 }
 
+impl McDefault for Node {
+	/// An argument node, with every optional field present and matching its flags.
+	fn mc_default() -> Self {
+		let children: Vec<VarInt> = McDefault::mc_default();
+
+		Self {
+			flags: NodeFlags {
+				typ: NodeType::Argument,
+				is_executable: true,
+				has_redirect: true,
+				has_suggestions: true,
+				is_restricted: false,
+			},
+			children_count: VarInt(children.len() as i32),
+			children,
+			redirect_node: Some(VarInt::mc_default()),
+			name: Some(String::mc_default()),
+			parser: Some(Parser::mc_default()),
+			suggestions: Some(String::mc_default()),
+		}
+	}
+}
+
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for Node {
+	/// A node whose optional fields are present exactly when its flags say so.
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		(NodeFlags::mc_arbitrary(), Vec::<VarInt>::mc_arbitrary(), VarInt::mc_arbitrary(), String::mc_arbitrary(), Parser::mc_arbitrary(), String::mc_arbitrary())
+			.prop_map(|(flags, children, redirect_node, name, parser, suggestions)| Self {
+				children_count: VarInt(children.len() as i32),
+				children,
+				redirect_node: flags.has_redirect.then_some(redirect_node),
+				name: matches!(flags.typ, NodeType::Literal | NodeType::Argument).then_some(name),
+				parser: matches!(flags.typ, NodeType::Argument).then_some(parser),
+				suggestions: flags.has_suggestions.then_some(suggestions),
+				flags,
+			})
+			.boxed()
+	}
+}
+
 /// Internal node flags represented as a byte with masking
-#[derive(McDefault, Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, Hash, PartialEq, Eq)]
 pub struct NodeFlags {
 	pub typ: NodeType,
 	pub is_executable: bool,
@@ -132,14 +179,14 @@ impl McDeserialize for NodeFlags {
 }
 
 /// Type of node in a graph
-#[derive(McDefault, Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, Hash, PartialEq, Eq)]
 pub enum NodeType {
 	Root = 0,
 	Literal = 1,
 	Argument = 2,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub enum Parser {
 	BrigadierBool,
 	BrigadierFloat { flags: u8, min: Option<f32>, max: Option<f32> },
@@ -527,6 +574,91 @@ impl McDefault for Parser {
 	}
 }
 
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for Parser {
+	/// The bounds of a number range are present exactly when its flags say so.
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		prop_oneof![
+			proptest::sample::select(vec![
+				Parser::BrigadierBool,
+				Parser::MinecraftGameProfile,
+				Parser::MinecraftBlockPos,
+				Parser::MinecraftColumnPos,
+				Parser::MinecraftVec3,
+				Parser::MinecraftVec2,
+				Parser::MinecraftBlockState,
+				Parser::MinecraftBlockPredicate,
+				Parser::MinecraftItemStack,
+				Parser::MinecraftItemPredicate,
+				Parser::MinecraftColor,
+				Parser::MinecraftHexColor,
+				Parser::MinecraftComponent,
+				Parser::MinecraftStyle,
+				Parser::MinecraftMessage,
+				Parser::MinecraftNbtCompoundTag,
+				Parser::MinecraftNbtTag,
+				Parser::MinecraftNbtPath,
+				Parser::MinecraftObjective,
+				Parser::MinecraftObjectiveCriteria,
+				Parser::MinecraftOperation,
+				Parser::MinecraftParticle,
+				Parser::MinecraftAngle,
+				Parser::MinecraftRotation,
+				Parser::MinecraftScoreboardSlot,
+				Parser::MinecraftSwizzle,
+				Parser::MinecraftTeam,
+				Parser::MinecraftItemSlot,
+				Parser::MinecraftItemSlots,
+				Parser::MinecraftResourceLocation,
+				Parser::MinecraftFunction,
+				Parser::MinecraftEntityAnchor,
+				Parser::MinecraftIntRange,
+				Parser::MinecraftFloatRange,
+				Parser::MinecraftDimension,
+				Parser::MinecraftGamemode,
+				Parser::MinecraftTemplateMirror,
+				Parser::MinecraftTemplateRotation,
+				Parser::MinecraftHeightmap,
+				Parser::MinecraftLootTable,
+				Parser::MinecraftLootPredicate,
+				Parser::MinecraftLootModifier,
+				Parser::MinecraftDialog,
+				Parser::MinecraftUuid,
+			]),
+			(any::<u8>(), f32::mc_arbitrary(), f32::mc_arbitrary()).prop_map(|(flags, min, max)| Parser::BrigadierFloat {
+				flags,
+				min: (flags & 0x01 != 0).then_some(min),
+				max: (flags & 0x02 != 0).then_some(max),
+			}),
+			(any::<u8>(), f64::mc_arbitrary(), f64::mc_arbitrary()).prop_map(|(flags, min, max)| Parser::BrigadierDouble {
+				flags,
+				min: (flags & 0x01 != 0).then_some(min),
+				max: (flags & 0x02 != 0).then_some(max),
+			}),
+			(any::<u8>(), any::<i32>(), any::<i32>()).prop_map(|(flags, min, max)| Parser::BrigadierInteger {
+				flags,
+				min: (flags & 0x01 != 0).then_some(min),
+				max: (flags & 0x02 != 0).then_some(max),
+			}),
+			(any::<u8>(), any::<i64>(), any::<i64>()).prop_map(|(flags, min, max)| Parser::BrigadierLong {
+				flags,
+				min: (flags & 0x01 != 0).then_some(min),
+				max: (flags & 0x02 != 0).then_some(max),
+			}),
+			VarInt::mc_arbitrary().prop_map(Parser::BrigadierString),
+			any::<u8>().prop_map(Parser::MinecraftEntity),
+			any::<u8>().prop_map(Parser::MinecraftScoreHolder),
+			any::<i32>().prop_map(Parser::MinecraftTime),
+			String::mc_arbitrary().prop_map(Parser::MinecraftResourceOrTag),
+			String::mc_arbitrary().prop_map(Parser::MinecraftResourceOrTagKey),
+			String::mc_arbitrary().prop_map(Parser::MinecraftResource),
+			String::mc_arbitrary().prop_map(Parser::MinecraftResourceKey),
+			String::mc_arbitrary().prop_map(Parser::MinecraftResourceSelector),
+		]
+		.boxed()
+	}
+}
+
 impl Hash for Parser {
 	fn hash<H: Hasher>(&self, state: &mut H) {
 		core::mem::discriminant(self).hash(state);
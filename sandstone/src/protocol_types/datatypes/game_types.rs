@@ -8,10 +8,11 @@ use crate::protocol::serialization::McSerializer;
 use crate::protocol::serialization::SerializingResult;
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::var_types::VarLong;
-use sandstone_derive::{McDefault, McDeserialize, McSerialize, TypeEnum};
+use sandstone_derive::{McArbitrary, McDefault, McDeserialize, McSerialize, TypeEnum};
+use serde::Serialize;
 
 /// A Minecraft position, internally represented as a 64-bit integer.
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Copy, Clone)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Copy, Clone)]
 pub struct Position {
 	data: u64,
 }
@@ -45,7 +46,7 @@ impl Position {
 	}
 }
 
-#[derive(TypeEnum, Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Clone)]
+#[derive(McArbitrary, Serialize, TypeEnum, Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Clone)]
 #[type_enum(u8)]
 pub enum GameDifficulty {
 	Peaceful = 0,
@@ -60,7 +61,7 @@ impl McDefault for GameDifficulty {
 	}
 }
 
-#[derive(McDefault, TypeEnum, Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Clone)]
+#[derive(McDefault, McArbitrary, Serialize, TypeEnum, Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Clone)]
 #[type_enum(u8)]
 pub enum EquipmentSlot {
 	MainHand = 0,
@@ -73,7 +74,7 @@ pub enum EquipmentSlot {
 	Saddle = 7,
 }
 
-#[derive(McDefault, TypeEnum, Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Clone)]
+#[derive(McDefault, McArbitrary, Serialize, TypeEnum, Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Clone)]
 #[type_enum(i32)]
 pub enum WorldEventType {
 	DispenserDispenses = 1000,
@@ -162,7 +163,7 @@ pub enum WorldEventType {
 /// Packed i64 for coordinates of the affected chunk.
 ///
 /// 22 bits for x, z and 20 bits for y
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Clone)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Clone)]
 pub struct ChunkSectionPosition {
 	data: i64,
 }
@@ -192,7 +193,7 @@ impl ChunkSectionPosition {
 }
 
 /// Packed VarLong of block state id, and local x, y, z coords
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Clone)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Clone)]
 pub struct SectionBlockEntry {
 	data: VarLong,
 }
@@ -233,7 +234,7 @@ pub enum SmokeDirection {
 	East = 5,
 }
 
-#[derive(McDefault, McSerialize, McDeserialize, Debug, PartialOrd, PartialEq, Clone)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, PartialOrd, PartialEq, Clone)]
 pub struct SourcePosition {
 	x: f64,
 	y: f64,
@@ -1,13 +1,19 @@
+use crate::prelude::*;
 use crate::protocol::serialization::serializer_error::SerializingErr;
 use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, SerializingResult};
+#[cfg(any(test, feature = "proptest"))]
+use crate::protocol::testing::arbitrary::{registry_id, McArbitrary};
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::var_types::VarInt;
-use sandstone_derive::{McDefault, McDeserialize, McSerialize};
+use sandstone_derive::{McArbitrary, McDefault, McDeserialize, McSerialize};
+use serde::Serialize;
+#[cfg(any(test, feature = "proptest"))]
+use proptest::prelude::{any, prop_oneof, BoxedStrategy, Just, Strategy};
 
 /// Represents a packed i64 (long) that contains block or biome data. See
 /// https://minecraft.wiki/w/Java_Edition_protocol/Chunk_format#Data_Array_format for more info. This
 /// matches the spec for packed data after 1.16
-#[derive(McDefault, Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(McDefault, McArbitrary, Serialize, Debug, Clone, Hash, PartialEq, Eq)]
 pub struct PackedEntries {
 	data: i64,
 	/// The number of bits allocated to each entry
@@ -36,6 +42,17 @@ impl PackedEntries {
 		self.data |= ((value & mask as u64) << shift) as i64;
 	}
 
+	/// Random packed entries with the given bits per entry, see [McArbitrary].
+	#[cfg(any(test, feature = "proptest"))]
+	pub(crate) fn arbitrary_with(bpe: u8) -> BoxedStrategy<Self> {
+		any::<i64>()
+			.prop_map(move |data| Self {
+				data,
+				bpe,
+			})
+			.boxed()
+	}
+
 	/// A nonstandard deserializer that utilizes bits per entry
 	pub(crate) fn mc_deserialize<'a>(deserializer: &'a mut McDeserializer, bpe: u8) -> SerializingResult<'a, Self>
 	where
@@ -58,7 +75,7 @@ impl McSerialize for PackedEntries {
 }
 
 /// ID set used for representing a set of ids in a registry either directly enumerated or indirectly via tag name
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct IDSet {
 	pub typ: VarInt,
 	pub tag_name: Option<String>,
@@ -75,6 +92,26 @@ impl McDefault for IDSet {
 	}
 }
 
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for IDSet {
+	/// Either a tag name, or a list of ids with the type matching its length.
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		prop_oneof![
+			String::mc_arbitrary().prop_map(|tag_name| Self {
+				typ: VarInt(0),
+				tag_name: Some(tag_name),
+				ids: None,
+			}),
+			Vec::<VarInt>::mc_arbitrary().prop_map(|ids| Self {
+				typ: VarInt(ids.len() as i32 + 1),
+				tag_name: None,
+				ids: Some(ids),
+			}),
+		]
+		.boxed()
+	}
+}
+
 impl McSerialize for IDSet {
 	fn mc_serialize(&self, serializer: &mut McSerializer) -> SerializingResult<()> {
 		self.typ.mc_serialize(serializer)?;
@@ -138,7 +175,7 @@ impl McDeserialize for IDSet {
 /// Used when representing a data record of type T or by reference to a registry.
 ///
 /// [Doc Link](https://minecraft.wiki/w/Java_Edition_protocol/Packets#ID_or_X)
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum IDorX<T: McSerialize + McDeserialize + Clone + PartialEq> {
 	Registry(VarInt),
 	Inline(T),
@@ -179,10 +216,17 @@ impl<T: McSerialize + McDeserialize + Clone + PartialEq> McDefault for IDorX<T>
 	}
 }
 
+#[cfg(any(test, feature = "proptest"))]
+impl<T: McSerialize + McDeserialize + Clone + PartialEq + McArbitrary + 'static> McArbitrary for IDorX<T> {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		prop_oneof![registry_id().prop_map(IDorX::Registry), T::mc_arbitrary().prop_map(IDorX::Inline),].boxed()
+	}
+}
+
 /// A union type of either X or Y, determined by a leading boolean. Order of generics matters.
 ///
 /// https://minecraft.wiki/w/Java_Edition_protocol/Packets#Type:Either
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub enum Either<X, Y>
 where
 	X: McSerialize + McDeserialize + Clone + PartialEq,
@@ -228,15 +272,22 @@ impl<X: McSerialize + McDeserialize + Clone + PartialEq + McDefault, Y: McSerial
 	}
 }
 
+#[cfg(any(test, feature = "proptest"))]
+impl<X: McSerialize + McDeserialize + Clone + PartialEq + McArbitrary + 'static, Y: McSerialize + McDeserialize + Clone + PartialEq + McArbitrary + 'static> McArbitrary for Either<X, Y> {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		prop_oneof![X::mc_arbitrary().prop_map(Either::X), Y::mc_arbitrary().prop_map(Either::Y),].boxed()
+	}
+}
+
 /// A rotation angle in steps of 1/256 of a full turn
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq, Eq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq, Eq)]
 pub struct Angle {
 	pub angle: u8,
 }
 
 /// Compressed / low-precision 3-component vector (1.21.9+). Used for entity velocity and other
 /// small motion vectors. Wire format is 1 byte for zero vectors, 6-7+ bytes otherwise.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct LpVec3 {
 	pub x: f64,
 	pub y: f64,
@@ -253,6 +304,32 @@ impl McDefault for LpVec3 {
 	}
 }
 
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for LpVec3 {
+	/// Only vectors that the encoding can hold exactly, which are made from the values it writes.
+	/// The largest component must be a whole number, since the scale is written as an integer.
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		const MAX_RAW: u16 = 32766;
+
+		let vector = (1..=1_000_000u32, 0..=MAX_RAW, 0..=MAX_RAW, 0..=MAX_RAW, 0..3usize, any::<bool>()).prop_map(|(scale, x, y, z, largest, negative)| {
+			let mut raw = [x, y, z];
+			raw[largest] = if negative { 0 } else { MAX_RAW };
+
+			Self {
+				x: Self::from_raw(raw[0], scale),
+				y: Self::from_raw(raw[1], scale),
+				z: Self::from_raw(raw[2], scale),
+			}
+		});
+
+		prop_oneof![
+			1 => Just(Self::mc_default()),
+			9 => vector,
+		]
+		.boxed()
+	}
+}
+
 impl McSerialize for LpVec3 {
 	fn mc_serialize(&self, serializer: &mut McSerializer) -> SerializingResult<()> {
 		if self.x == 0.0 && self.y == 0.0 && self.z == 0.0 {
@@ -260,7 +337,9 @@ impl McSerialize for LpVec3 {
 			return Ok(());
 		}
 
-		let scale = self.x.abs().max(self.y.abs()).max(self.z.abs()).ceil() as u32;
+		// `ceil` and `round` are not in `core`, so they are done by hand for these non-negative values
+		let max = self.x.abs().max(self.y.abs()).max(self.z.abs());
+		let scale = max as u32 + u32::from((max as u32 as f64) < max);
 		let scale = scale.max(1);
 
 		let x_raw = Self::encode_raw(self.x, scale);
@@ -338,7 +417,7 @@ impl McDeserialize for LpVec3 {
 impl LpVec3 {
 	fn encode_raw(component: f64, scale: u32) -> u16 {
 		let normalized = component / scale as f64;
-		((normalized + 1.0) / 2.0 * 32766.0).round().clamp(0.0, 32766.0) as u16
+		(((normalized + 1.0) / 2.0 * 32766.0).clamp(0.0, 32766.0) + 0.5) as u16
 	}
 
 	fn from_raw(raw: u16, scale: u32) -> f64 {
@@ -348,7 +427,7 @@ impl LpVec3 {
 }
 
 /// Standard RGB color type
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct RgbColor {
 	pub red: u8,
 	pub green: u8,
@@ -356,7 +435,7 @@ pub struct RgbColor {
 }
 
 /// Map a string identifier to a given value of type T.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct Mapping<T: McSerialize + McDeserialize + Clone + PartialEq> {
 	pub key: String,
 	pub value: T,
@@ -391,8 +470,19 @@ impl<T: McSerialize + McDeserialize + Clone + PartialEq + McDefault> McDefault f
 	}
 }
 
+#[cfg(any(test, feature = "proptest"))]
+impl<T: McSerialize + McDeserialize + Clone + PartialEq + McArbitrary + 'static> McArbitrary for Mapping<T> {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		(String::mc_arbitrary(), T::mc_arbitrary()).prop_map(|(key, value)| Mapping {
+			key,
+			value,
+		})
+		.boxed()
+	}
+}
+
 /// A struct of 3 doubles that could be used for position or velocity.
-#[derive(McDefault, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
+#[derive(McDefault, McArbitrary, Serialize, McSerialize, McDeserialize, Debug, Clone, PartialEq)]
 pub struct TripleDouble {
 	pub x: f64,
 	pub y: f64,
@@ -3,28 +3,45 @@
 
 #![allow(clippy::from_over_into)]
 
+use crate::prelude::*;
 use crate::protocol::serialization::serializer_error::SerializingErr;
 use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, SerializingResult};
+#[cfg(any(test, feature = "proptest"))]
+use crate::protocol::testing::arbitrary::{McArbitrary, MAX_ARBITRARY_ITEMS};
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::nbt::nbt_error::NbtError;
 use crate::protocol_types::datatypes::nbt::NbtTag::List;
+use crate::util::java::modified_utf8;
 use crate::{list_nbtvalue, primvalue_nbtvalue};
 use log::debug;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::ops::Index;
+use core::ops::Index;
+#[cfg(any(test, feature = "proptest"))]
+use proptest::collection::vec;
+#[cfg(any(test, feature = "proptest"))]
+use proptest::prelude::{any, prop_oneof, BoxedStrategy, Strategy};
 
 pub mod nbt_error;
 pub mod nbt_utils;
 mod snbt_testing;
 
+/// The tags of a compound by name. `alloc` has no `HashMap`, so builds without `std` keep them sorted by name.
+#[cfg(feature = "std")]
+type TagMap = std::collections::HashMap<String, NbtTag>;
+#[cfg(not(feature = "std"))]
+type TagMap = alloc::collections::BTreeMap<String, NbtTag>;
+
+/// How deep lists and compounds can be nested when reading NBT. This is half of the Notchian limit of 512,
+/// so that reading stays within the 2 MiB stack of a spawned thread, even in debug builds.
+const MAX_NBT_DEPTH: usize = 256;
+
 primvalue_nbtvalue!((i8, Byte), (i16, Short), (i32, Int), (i64, Long), (f32, Float), (f64, Double));
 
 list_nbtvalue!((i8, ByteArray, NbtByteArray, 7), (i32, IntArray, NbtIntArray, 11), (i64, LongArray, NbtLongArray, 12));
 
 /// Serialize a string using Java's UTF format prefixed with a u16 of the length.
 fn serialize_nbt_string<'a>(serializer: &mut McSerializer, s: &str) -> SerializingResult<'a, ()> {
-	let encoded = cesu8::to_java_cesu8(s);
+	let encoded = modified_utf8::encode(s);
 	(encoded.len() as u16).mc_serialize(serializer)?;
 	serializer.serialize_bytes(&encoded);
 	Ok(())
@@ -35,14 +52,13 @@ fn serialize_nbt_string<'a>(serializer: &mut McSerializer, s: &str) -> Serializi
 fn deserialize_nbt_string<'a>(deserializer: &mut McDeserializer) -> SerializingResult<'a, String> {
 	let len = u16::mc_deserialize(deserializer)?;
 	let bytes = deserializer.slice(len as usize);
-	let decoded = cesu8::from_java_cesu8(bytes).map_err(|e| SerializingErr::UniqueFailure(format!("Invalid modified UTF-8 NBT string: {e}")))?;
-	Ok(decoded.into_owned())
+	modified_utf8::decode(bytes)
 }
 
 /// # NBT Tag (Protocol Type)
 /// A tag is a component of an NBT compound/map. Each type of tag represents a different primitive datatype or list type.
 /// Also check out [NbtCompound]
-#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub enum NbtTag {
 	/// Used to mark the end of a compound or list
 	End,
@@ -82,6 +98,15 @@ impl NbtTag {
 
 	/// Given the type ID, deserialize the corresponding NbtTag.
 	pub fn deserialize_specific<'a>(deserializer: &mut McDeserializer, ty: u8) -> SerializingResult<'a, Self> {
+		Self::deserialize_nested(deserializer, ty, 0)
+	}
+
+	/// Like [NbtTag::deserialize_specific], for a tag nested `depth` lists and compounds deep.
+	fn deserialize_nested<'a>(deserializer: &mut McDeserializer, ty: u8, depth: usize) -> SerializingResult<'a, Self> {
+		if depth > MAX_NBT_DEPTH {
+			return Err(NbtError::NestingTooDeep(MAX_NBT_DEPTH).into());
+		}
+
 		match ty {
 			// Primitives
 			0 => Ok(NbtTag::End),
@@ -106,20 +131,20 @@ impl NbtTag {
 			}
 			9 => {
 				// List
-				Ok(NbtTag::List(NbtList::mc_deserialize(deserializer)?))
+				Ok(NbtTag::List(NbtList::deserialize_nested(deserializer, depth + 1)?))
 			}
 			10 => {
 				// compound
-				Ok(NbtTag::Compound(NbtCompound::from_no_tag(deserializer)?))
+				Ok(NbtTag::Compound(NbtCompound::from_no_tag(deserializer, depth + 1)?))
 			}
 			_ => Err(SerializingErr::UniqueFailure("Could not identify tag type".to_string())),
 		}
 	}
 }
 
-impl McSerialize for NbtTag {
-	fn mc_serialize(&self, serializer: &mut McSerializer) -> SerializingResult<()> {
-		// do not include type id here - list and compound tags will include it themselves
+impl NbtTag {
+	/// Serialize the tag without its type id, the way it is written inside of lists and compounds.
+	pub fn serialize_payload(&self, serializer: &mut McSerializer) -> SerializingResult<()> {
 		match self {
 			// stuff with special cases
 			NbtTag::End => {}
@@ -155,6 +180,14 @@ impl McSerialize for NbtTag {
 	}
 }
 
+impl McSerialize for NbtTag {
+	/// Serialize the type id of the tag followed by the tag, the same as it is read by [McDeserialize].
+	fn mc_serialize(&self, serializer: &mut McSerializer) -> SerializingResult<()> {
+		self.get_type_id().mc_serialize(serializer)?;
+		self.serialize_payload(serializer)
+	}
+}
+
 impl McDeserialize for NbtTag {
 	fn mc_deserialize<'a>(deserializer: &'a mut McDeserializer) -> SerializingResult<'a, NbtTag> {
 		let ty = u8::mc_deserialize(deserializer)?;
@@ -163,12 +196,61 @@ impl McDeserialize for NbtTag {
 	}
 }
 
+/// Floats are compared by their bits, so that a NaN that is read back is equal to the one that was written.
+impl PartialEq for NbtTag {
+	fn eq(&self, other: &Self) -> bool {
+		match (self, other) {
+			(NbtTag::End, NbtTag::End) => true,
+			(NbtTag::Byte(a), NbtTag::Byte(b)) => a == b,
+			(NbtTag::Short(a), NbtTag::Short(b)) => a == b,
+			(NbtTag::Int(a), NbtTag::Int(b)) => a == b,
+			(NbtTag::Long(a), NbtTag::Long(b)) => a == b,
+			(NbtTag::Float(a), NbtTag::Float(b)) => a.to_bits() == b.to_bits(),
+			(NbtTag::Double(a), NbtTag::Double(b)) => a.to_bits() == b.to_bits(),
+			(NbtTag::ByteArray(a), NbtTag::ByteArray(b)) => a == b,
+			(NbtTag::String(a), NbtTag::String(b)) => a == b,
+			(NbtTag::List(a), NbtTag::List(b)) => a == b,
+			(NbtTag::Compound(a), NbtTag::Compound(b)) => a == b,
+			(NbtTag::IntArray(a), NbtTag::IntArray(b)) => a == b,
+			(NbtTag::LongArray(a), NbtTag::LongArray(b)) => a == b,
+			_ => false,
+		}
+	}
+}
+
 impl McDefault for NbtTag {
 	fn mc_default() -> Self {
 		NbtTag::String("default".to_string())
 	}
 }
 
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for NbtTag {
+	/// Any tag but End, with lists and compounds nested up to 8 deep.
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		let leaf = prop_oneof![
+			any::<i8>().prop_map(NbtTag::Byte),
+			any::<i16>().prop_map(NbtTag::Short),
+			any::<i32>().prop_map(NbtTag::Int),
+			any::<i64>().prop_map(NbtTag::Long),
+			any::<f32>().prop_map(NbtTag::Float),
+			any::<f64>().prop_map(NbtTag::Double),
+			vec(any::<i8>(), 0..=MAX_ARBITRARY_ITEMS).prop_map(|list| NbtTag::ByteArray(NbtByteArray::new(list))),
+			String::mc_arbitrary().prop_map(NbtTag::String),
+			vec(any::<i32>(), 0..=MAX_ARBITRARY_ITEMS).prop_map(|list| NbtTag::IntArray(NbtIntArray::new(list))),
+			vec(any::<i64>(), 0..=MAX_ARBITRARY_ITEMS).prop_map(|list| NbtTag::LongArray(NbtLongArray::new(list))),
+		];
+
+		leaf.prop_recursive(8, 64, MAX_ARBITRARY_ITEMS as u32, |tag| {
+			prop_oneof![
+				vec(tag.clone(), 0..=MAX_ARBITRARY_ITEMS).prop_map(|tags| NbtTag::List(NbtList::from_arbitrary(tags))),
+				vec((String::mc_arbitrary(), tag), 0..=MAX_ARBITRARY_ITEMS).prop_map(|entries| NbtTag::Compound(NbtCompound::from_arbitrary(entries))),
+			]
+		})
+		.boxed()
+	}
+}
+
 impl From<&str> for NbtTag {
 	fn from(value: &str) -> Self {
 		NbtTag::String(value.to_string())
@@ -223,17 +305,37 @@ impl From<NbtTag> for Option<String> {
 	}
 }
 
+/// A list can only hold one type of tag, so a list of mixed tags is written as a list of compounds, with each tag
+/// that isn't a compound wrapped in one under an empty name. A compound that would be read back as such a wrapper is
+/// wrapped as well, in any list of compounds. This matches vanilla since 1.21.5.
 impl<T: Into<NbtTag>> From<Vec<T>> for NbtTag {
 	fn from(value: Vec<T>) -> Self {
-		let tags: Vec<NbtTag> = value.into_iter().map(Into::into).collect();
+		let mut tags: Vec<NbtTag> = value.into_iter().map(Into::into).collect();
+
+		let mixed = tags.windows(2).any(|pair| pair[0].get_type_id() != pair[1].get_type_id());
+		if mixed || matches!(tags.first(), Some(NbtTag::Compound(_))) {
+			for tag in tags.iter_mut() {
+				if !matches!(tag, NbtTag::Compound(compound) if !is_list_wrapper(compound)) {
+					let mut compound = NbtCompound::new_no_name();
+					compound.add("", core::mem::replace(tag, NbtTag::End));
+					*tag = NbtTag::Compound(compound);
+				}
+			}
+		}
+
 		match NbtList::from_vec(tags) {
 			Ok(list) => List(list),
-			// Fall back to an empty list if mixed types are in a list
+			// End tags can't be in a list
 			Err(_) => List(NbtList::new()),
 		}
 	}
 }
 
+/// Whether a compound in a list is the wrapper of another tag, see `From<Vec<T>> for NbtTag`.
+fn is_list_wrapper(compound: &NbtCompound) -> bool {
+	compound.map.len() == 1 && compound.map.contains_key("")
+}
+
 impl<T: TryFrom<NbtTag>> TryFrom<NbtTag> for Vec<T>
 where
 	NbtError: From<<T as TryFrom<NbtTag>>::Error>,
@@ -252,9 +354,12 @@ where
 			List(list) => {
 				let mut vec = vec![];
 				for tag in list.list {
-					if tag == NbtTag::End {
-						continue;
-					}
+					let tag = match tag {
+						NbtTag::End => continue,
+						// an element of a mixed list, see `From<Vec<T>> for NbtTag`
+						NbtTag::Compound(mut compound) if is_list_wrapper(&compound) => compound.map.remove("").unwrap(),
+						tag => tag,
+					};
 					vec.push(T::try_from(tag)?);
 				}
 				Ok(vec)
@@ -289,7 +394,7 @@ where
 pub struct NbtCompound {
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub root_name: Option<String>,
-	pub(crate) map: HashMap<String, NbtTag>,
+	pub(crate) map: TagMap,
 }
 
 impl NbtCompound {
@@ -298,7 +403,7 @@ impl NbtCompound {
 		let option: Option<String> = root_name.map(|root_name| root_name.into());
 
 		Self {
-			map: HashMap::new(),
+			map: TagMap::new(),
 			root_name: option,
 		}
 	}
@@ -351,40 +456,30 @@ impl NbtCompound {
 		}
 
 		let name = deserialize_nbt_string(deserializer)?;
-		let mut compound = NbtCompound::new(Some(name));
-
-		loop {
-			let tag = deserializer.pop();
-
-			if tag.is_none() || tag.unwrap() == 0 {
-				// END Tag
-				break;
-			}
-
-			let name = deserialize_nbt_string(deserializer)?;
-
-			let tag = NbtTag::deserialize_specific(deserializer, tag.unwrap())?;
-			compound.add(name, tag);
-		}
+		let mut compound = Self::from_no_tag(deserializer, 0)?;
+		compound.root_name = Some(name);
 
 		Ok(compound)
 	}
 
 	/// Deserialize a compound's contents immediately without looking for a root name or tag id.
-	fn from_no_tag<'a>(deserializer: &mut McDeserializer) -> SerializingResult<'a, NbtCompound> {
+	/// `depth` is how many lists and compounds this compound is nested in.
+	fn from_no_tag<'a>(deserializer: &mut McDeserializer, depth: usize) -> SerializingResult<'a, NbtCompound> {
 		let mut compound = NbtCompound::new_no_name();
 
 		loop {
-			let tag = deserializer.pop();
+			let Some(tag) = deserializer.pop() else {
+				return Err(NbtError::MissingEndTag.into());
+			};
 
-			if tag.is_none() || tag.unwrap() == 0 {
+			if tag == 0 {
 				// END Tag
 				break;
 			}
 
 			let name = deserialize_nbt_string(deserializer)?;
 
-			let tag = NbtTag::deserialize_specific(deserializer, tag.unwrap())?;
+			let tag = deserializer.read_field(&name, |deserializer| NbtTag::deserialize_nested(deserializer, tag, depth))?;
 			compound.add(name, tag);
 		}
 
@@ -409,7 +504,7 @@ impl NbtCompound {
 			if *tag != NbtTag::End {
 				serializer.serialize_u8(tag.get_type_id());
 				serialize_nbt_string(serializer, name)?;
-				tag.mc_serialize(serializer)?;
+				tag.serialize_payload(serializer)?;
 			}
 		}
 		serializer.serialize_u8(0); // end tag
@@ -436,7 +531,7 @@ impl NbtCompound {
 impl Index<&str> for NbtCompound {
 	type Output = NbtTag;
 
-	/// Returns a reference to the value inside of the HashMap mapped to the given key.
+	/// Returns a reference to the value inside of the map mapped to the given key.
 	/// Returns `NbtTag::End` (which `add` never stores) as an absent-key sentinel if the key does
 	/// not exist; use [NbtCompound::get] for an `Option` instead.
 	fn index(&self, index: &str) -> &Self::Output {
@@ -477,7 +572,7 @@ impl McDeserialize for NbtCompound {
 			return Err(SerializingErr::UniqueFailure(format!("Expected compound tag id, got {t} instead")));
 		}
 
-		Self::from_no_tag(deserializer)
+		Self::from_no_tag(deserializer, 0)
 	}
 }
 
@@ -493,6 +588,27 @@ impl McDefault for NbtCompound {
 	}
 }
 
+#[cfg(any(test, feature = "proptest"))]
+impl NbtCompound {
+	fn from_arbitrary(entries: Vec<(String, NbtTag)>) -> Self {
+		let mut compound = NbtCompound::new_no_name();
+
+		for (name, tag) in entries {
+			compound.add(name, tag);
+		}
+
+		compound
+	}
+}
+
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for NbtCompound {
+	/// A network compound, without a root name.
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		vec((String::mc_arbitrary(), NbtTag::mc_arbitrary()), 0..=MAX_ARBITRARY_ITEMS).prop_map(NbtCompound::from_arbitrary).boxed()
+	}
+}
+
 impl Into<NbtTag> for NbtCompound {
 	fn into(self) -> NbtTag {
 		NbtTag::Compound(self)
@@ -588,7 +704,7 @@ impl NbtList {
 
 impl IntoIterator for NbtList {
 	type Item = NbtTag;
-	type IntoIter = std::vec::IntoIter<NbtTag>;
+	type IntoIter = alloc::vec::IntoIter<NbtTag>;
 
 	fn into_iter(self) -> Self::IntoIter {
 		self.list.into_iter()
@@ -597,7 +713,7 @@ impl IntoIterator for NbtList {
 
 impl<'a> IntoIterator for &'a NbtList {
 	type Item = &'a NbtTag;
-	type IntoIter = std::slice::Iter<'a, NbtTag>;
+	type IntoIter = core::slice::Iter<'a, NbtTag>;
 
 	fn into_iter(self) -> Self::IntoIter {
 		self.list.iter()
@@ -609,14 +725,15 @@ impl McSerialize for NbtList {
 		self.type_id.mc_serialize(serializer)?;
 		(self.list.len() as i32).mc_serialize(serializer)?;
 		for tag in &self.list {
-			tag.mc_serialize(serializer)?;
+			tag.serialize_payload(serializer)?;
 		}
 		Ok(())
 	}
 }
 
-impl McDeserialize for NbtList {
-	fn mc_deserialize<'a>(deserializer: &'a mut McDeserializer) -> SerializingResult<'a, NbtList> {
+impl NbtList {
+	/// Deserialize a list that is nested `depth` lists and compounds deep.
+	fn deserialize_nested<'a>(deserializer: &mut McDeserializer, depth: usize) -> SerializingResult<'a, NbtList> {
 		let t = u8::mc_deserialize(deserializer)?;
 		let length = i32::mc_deserialize(deserializer)?;
 
@@ -626,8 +743,8 @@ impl McDeserialize for NbtList {
 
 		let mut list = NbtList::new();
 
-		for _ in 0..length {
-			let tag = NbtTag::deserialize_specific(deserializer, t)?;
+		for i in 0..length {
+			let tag = deserializer.read_item(i as usize, |deserializer| NbtTag::deserialize_nested(deserializer, t, depth))?;
 
 			if tag.get_type_id() != t {
 				return Err(SerializingErr::UniqueFailure("Type must be the same as the type for the list".to_string()));
@@ -642,6 +759,12 @@ impl McDeserialize for NbtList {
 	}
 }
 
+impl McDeserialize for NbtList {
+	fn mc_deserialize<'a>(deserializer: &'a mut McDeserializer) -> SerializingResult<'a, NbtList> {
+		Self::deserialize_nested(deserializer, 0)
+	}
+}
+
 impl TryFrom<NbtTag> for NbtList {
 	type Error = NbtError;
 
@@ -675,11 +798,30 @@ impl McDefault for NbtList {
 	}
 }
 
+#[cfg(any(test, feature = "proptest"))]
+impl NbtList {
+	/// A list holds a single type, so only the tags of the same type as the first one are kept.
+	fn from_arbitrary(tags: Vec<NbtTag>) -> Self {
+		let type_id = tags.first().map(NbtTag::get_type_id);
+
+		NbtList::from_vec(tags.into_iter().filter(|tag| Some(tag.get_type_id()) == type_id).collect()).unwrap()
+	}
+}
+
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for NbtList {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		vec(NbtTag::mc_arbitrary(), 0..=MAX_ARBITRARY_ITEMS).prop_map(NbtList::from_arbitrary).boxed()
+	}
+}
+
 #[cfg(test)]
 mod test {
 	use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer};
+	use crate::protocol::testing::arbitrary::{check_round_trip, McArbitrary};
 	use crate::protocol_types::datatypes::nbt::NbtError;
 	use crate::protocol_types::datatypes::nbt::{NbtByteArray, NbtCompound, NbtIntArray, NbtList, NbtLongArray, NbtTag};
+	use proptest::proptest;
 	use sandstone_derive::{AsNbt, FromNbt};
 
 	/// Test standard serialization of a NbtCompound.
@@ -965,6 +1107,14 @@ mod test {
 		let nbt_list = NbtTag::from(v.clone());
 		let deserialized: Vec<ListTestStruct> = nbt_list.try_into().unwrap();
 		assert_eq!(deserialized, v);
+
+		// a compound that looks like the wrapper of a mixed list element is wrapped itself
+		let mut wrapper = NbtCompound::new_no_name();
+		wrapper.add("", 0i8);
+		let v = vec![wrapper, NbtCompound::new_no_name()];
+		let nbt_list = NbtTag::from(v.clone());
+		let deserialized: Vec<NbtCompound> = nbt_list.try_into().unwrap();
+		assert_eq!(deserialized, v);
 	}
 
 	#[derive(FromNbt, AsNbt, Debug, PartialEq, Clone)]
@@ -1085,4 +1235,36 @@ mod test {
 		let nul_deserialized = NbtCompound::mc_deserialize(&mut nul_deserializer).unwrap();
 		assert_eq!(nul_deserialized["a\0b"], NbtTag::String("x\0y".to_string()));
 	}
+
+	#[test]
+	fn test_malformed_input() {
+		// a compound that ends before its end tag
+		let mut deserializer = McDeserializer::new(&[10, 1, 0, 0, 0, 1, 5]);
+		assert!(NbtCompound::mc_deserialize(&mut deserializer).is_err());
+
+		// a huge list of empty compounds, truncated after the first one
+		let mut deserializer = McDeserializer::new(&[9, 10, 0x7F, 0xFF, 0xFF, 0xFF, 0]);
+		assert!(NbtTag::deserialize_specific(&mut deserializer, 9).is_err());
+
+		// lists nested deeper than the limit
+		let mut nested = vec![];
+		for _ in 0..600 {
+			nested.extend([9, 0, 0, 0, 1]);
+		}
+		let mut deserializer = McDeserializer::new(&nested);
+		let err = NbtList::mc_deserialize(&mut deserializer).unwrap_err();
+		assert!(matches!(err.root(), crate::protocol::serialization::serializer_error::SerializingErr::NbtError(NbtError::NestingTooDeep(_))));
+	}
+
+	proptest! {
+		#[test]
+		fn nbt_tag_round_trip(tag in NbtTag::mc_arbitrary()) {
+			check_round_trip(&tag)?;
+		}
+
+		#[test]
+		fn nbt_compound_round_trip(compound in NbtCompound::mc_arbitrary()) {
+			check_round_trip(&compound)?;
+		}
+	}
 }
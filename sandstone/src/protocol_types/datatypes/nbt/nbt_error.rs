@@ -1,6 +1,7 @@
 //! Error types that may occur when serializing or deserializing NBT data.
 
-use std::fmt::Debug;
+use crate::prelude::*;
+use core::fmt::Debug;
 
 use thiserror::Error;
 
@@ -23,6 +24,8 @@ pub enum NbtError {
 	IncompatibleTypes,
 	#[error("Invalid NBT type")]
 	InvalidType,
+	#[error("Nesting is deeper than {0} lists and compounds")]
+	NestingTooDeep(usize),
 	#[error("Missing field: {0}")]
 	MissingField(String),
 }
@@ -71,7 +71,7 @@ mod macros {
 
                 impl IntoIterator for $fancyname {
                     type Item = $t;
-                    type IntoIter = std::vec::IntoIter<$t>;
+                    type IntoIter = alloc::vec::IntoIter<$t>;
 
                     fn into_iter(self) -> Self::IntoIter {
                         self.list.into_iter()
@@ -80,7 +80,7 @@ mod macros {
 
                 impl<'a> IntoIterator for &'a $fancyname {
                     type Item = &'a $t;
-                    type IntoIter = std::slice::Iter<'a, $t>;
+                    type IntoIter = core::slice::Iter<'a, $t>;
 
                     fn into_iter(self) -> Self::IntoIter {
                         self.list.iter()
@@ -1,7 +1,7 @@
 //! TODO: need to pick this up again.
 //! Conversion functionality to and from NBT to sNBT.
 
-#![allow(unused_imports)]
+#![allow(unused_imports, clippy::no_effect, clippy::unused_enumerate_index)]
 
 use crate::protocol_types::datatypes::nbt::{NbtCompound, NbtTag};
 
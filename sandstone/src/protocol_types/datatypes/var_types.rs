@@ -1,12 +1,17 @@
 //! The purpose of this file is to define the custom integer types for the Minecraft protocol, VarInt and VarLong.
 //! See more details here: https://wiki.vg/Protocol#VarInt_and_VarLong
 
+use crate::prelude::*;
+#[cfg(feature = "network")]
 use crate::network::network_error::NetworkError;
+use crate::protocol::serialization::serde_format::{varint, varlong};
 use crate::protocol::serialization::serializer_error::SerializingErr;
 use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, SerializingResult};
-use std::fmt;
-use std::fmt::{Display, Error, Formatter};
-use std::str::FromStr;
+use core::fmt;
+use core::fmt::{Display, Error, Formatter};
+use core::str::FromStr;
+use serde::{Serialize, Serializer};
+#[cfg(feature = "network")]
 use tokio::net::TcpStream;
 use uuid::Uuid;
 
@@ -58,6 +63,7 @@ impl VarInt {
 	/// Extract a VarInt from a TcpStream. This reads the bytes until it finds a byte that does not have the continue bit set.
 	///
 	/// This is usually used for reading the packet length VarInt from the start of a packet.
+	#[cfg(feature = "network")]
 	pub fn from_tcp_stream(stream: &TcpStream) -> Result<Self, NetworkError> {
 		let mut buf = [0u8; 3];
 		let mut len = 0usize;
@@ -197,6 +203,13 @@ impl McDeserialize for VarInt {
 	}
 }
 
+impl Serialize for VarInt {
+	/// Serialized as its i32, marked so that [serde_format](crate::protocol::serialization::serde_format) encodes it as a VarInt.
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		varint::serialize(&self.0, serializer)
+	}
+}
+
 impl From<i32> for VarInt {
 	fn from(i: i32) -> Self {
 		VarInt(i)
@@ -212,6 +225,81 @@ impl From<&[u8]> for VarInt {
 	}
 }
 
+/// A VarInt that must be within `MIN..=MAX`, such as an enum ordinal or a count with a known limit. The range
+/// is checked when the value is created, serialized and deserialized.
+#[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Hash, Clone, Copy)]
+pub struct RangedVarInt<const MIN: i32, const MAX: i32>(pub(crate) i32);
+
+impl<const MIN: i32, const MAX: i32> RangedVarInt<MIN, MAX> {
+	/// Errors if `value` is outside of `MIN..=MAX`.
+	pub fn new(value: i32) -> Result<Self, SerializingErr> {
+		Self::check_range(value)?;
+		Ok(Self(value))
+	}
+
+	pub fn get(&self) -> i32 {
+		self.0
+	}
+
+	fn check_range(value: i32) -> Result<(), SerializingErr> {
+		if !(MIN..=MAX).contains(&value) {
+			return Err(SerializingErr::OutOfBounds(format!("VarInt {} is out of bounds. Valid values are {}-{}", value, MIN, MAX)));
+		}
+
+		Ok(())
+	}
+}
+
+impl<const MIN: i32, const MAX: i32> Display for RangedVarInt<MIN, MAX> {
+	fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+		write!(f, "{}", self.0)
+	}
+}
+
+impl<const MIN: i32, const MAX: i32> McSerialize for RangedVarInt<MIN, MAX> {
+	fn mc_serialize(&self, serializer: &mut McSerializer) -> SerializingResult<()> {
+		Self::check_range(self.0)?;
+		VarInt(self.0).mc_serialize(serializer)
+	}
+}
+
+impl<const MIN: i32, const MAX: i32> McDeserialize for RangedVarInt<MIN, MAX> {
+	fn mc_deserialize<'a>(deserializer: &'a mut McDeserializer) -> SerializingResult<'a, Self> {
+		let value = VarInt::mc_deserialize(deserializer)?.0;
+		Self::check_range(value)?;
+
+		Ok(Self(value))
+	}
+}
+
+impl<const MIN: i32, const MAX: i32> Serialize for RangedVarInt<MIN, MAX> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		varint::serialize(&self.0, serializer)
+	}
+}
+
+impl<const MIN: i32, const MAX: i32> TryFrom<i32> for RangedVarInt<MIN, MAX> {
+	type Error = SerializingErr;
+
+	fn try_from(value: i32) -> Result<Self, Self::Error> {
+		Self::new(value)
+	}
+}
+
+impl<const MIN: i32, const MAX: i32> TryFrom<VarInt> for RangedVarInt<MIN, MAX> {
+	type Error = SerializingErr;
+
+	fn try_from(value: VarInt) -> Result<Self, Self::Error> {
+		Self::new(value.0)
+	}
+}
+
+impl<const MIN: i32, const MAX: i32> From<RangedVarInt<MIN, MAX>> for VarInt {
+	fn from(value: RangedVarInt<MIN, MAX>) -> Self {
+		VarInt(value.0)
+	}
+}
+
 /// A VarLong is a packaged i64. It is represented in a more compressed (on average) byte format than
 /// a typical i64. The most significant bit of each byte is used to indicate if there are more bytes
 /// to be read, up to a max of 10.
@@ -333,6 +421,13 @@ impl McDeserialize for VarLong {
 	}
 }
 
+impl Serialize for VarLong {
+	/// Serialized as its i64, marked so that [serde_format](crate::protocol::serialization::serde_format) encodes it as a VarLong.
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		varlong::serialize(&self.0, serializer)
+	}
+}
+
 impl From<i64> for VarLong {
 	fn from(i: i64) -> Self {
 		VarLong(i)
@@ -370,9 +465,10 @@ impl McDeserialize for Uuid {
 }
 
 #[cfg(test)]
+#[allow(clippy::unnecessary_mut_passed)]
 mod tests {
 	use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer};
-	use crate::protocol_types::datatypes::var_types::{VarInt, VarLong};
+	use crate::protocol_types::datatypes::var_types::{RangedVarInt, VarInt, VarLong};
 
 	#[test]
 	fn basic_varint_from_slice() {
@@ -582,4 +678,24 @@ mod tests {
 		assert_eq!(VarLong(9223372036854775807).to_string(), "9223372036854775807");
 		assert_eq!(VarLong(-1).to_string(), "-1");
 	}
+
+	#[test]
+	fn ranged_varint_checks_range() {
+		assert!(RangedVarInt::<1, 3>::new(0).is_err());
+		assert!(RangedVarInt::<1, 3>::new(4).is_err());
+
+		let mut serializer = McSerializer::new();
+		RangedVarInt::<1, 3>::new(3).unwrap().mc_serialize(&mut serializer).unwrap();
+		assert_eq!(serializer.output, vec![3]);
+
+		let mut deserializer = McDeserializer::new(&serializer.output);
+		assert_eq!(RangedVarInt::<1, 3>::mc_deserialize(&mut deserializer).unwrap().get(), 3);
+
+		let mut deserializer = McDeserializer::new(&serializer.output);
+		assert!(RangedVarInt::<0, 2>::mc_deserialize(&mut deserializer).is_err());
+
+		// values outside of the range can only be made within the crate
+		let mut serializer = McSerializer::new();
+		assert!(RangedVarInt::<0, 2>(5).mc_serialize(&mut serializer).is_err());
+	}
 }
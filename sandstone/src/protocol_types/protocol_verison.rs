@@ -3,6 +3,7 @@
 //!
 //! https://minecraft.wiki/w/Minecraft_Wiki:Projects/wiki.vg_merge/Protocol_version_numbers
 
+use crate::prelude::*;
 use crate::protocol::serialization::serializer_error::SerializingErr;
 use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, SerializingResult};
 use crate::protocol::testing::McDefault;
@@ -21,12 +22,14 @@ macro_rules! versions {
             /// # Protocol Version (Packet Part)
             /// Protocol version describes each major version of Minecraft: Java Edition since 1.8.9 <br>
             /// For each major version (ie. 1.8, 1.9, etc) the last released sub-version is used, since there
-            /// is no conceivable reason to use any of the previous sub-versions.<br>
+            /// is no conceivable reason to use any of the previous sub-versions. The exception is 1.21, where the
+            /// sub-versions the library can still communicate with are listed as well.<br>
             /// Provided is also the protocol number associated with the last sub-version for that major version,
             /// as well as the name typically associated with that version.
             ///
             /// Please keep in mind that while protocol numbers are provided back all the way to 1.8.9,
-            /// the library typically only supports the latest version of Minecraft: Java Edition.
+            /// the library only supports the latest few versions of Minecraft: Java Edition.
+            /// See `protocol::packets::packet_versions` for which.
             ///
             /// Get the latest version with
             /// ```rust
@@ -77,8 +80,16 @@ macro_rules! versions {
             }
         }
 
-        impl std::fmt::Display for $name {
-            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        #[cfg(any(test, feature = "proptest"))]
+        impl $crate::protocol::testing::arbitrary::McArbitrary for $name {
+            fn mc_arbitrary() -> ::proptest::strategy::BoxedStrategy<Self> {
+                use ::proptest::strategy::Strategy;
+                ::proptest::sample::select($name::get_all()).boxed()
+            }
+        }
+
+        impl core::fmt::Display for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
                 write!(f, "{}", self.get_fancy_name())
             }
         }
@@ -112,6 +123,8 @@ versions!(ProtocolVerison, i16 => {
     V1_18, 758, "1.18.2",
     V1_19, 762, "1.19.4",
     V1_20, 766, "1.20.6",
+    V1_21_7, 772, "1.21.8",
+    V1_21_9, 773, "1.21.10",
     V1_21, 774, "1.21.11",
     V26_1, 776, "26.2"
 });
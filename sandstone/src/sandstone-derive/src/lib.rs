@@ -1,13 +1,15 @@
 //! Derive traits for `McSerialize` and `McDeserialize` much like `serde` has for `Serialize` and `Deserialize`.
 
 use proc_macro::TokenStream;
+use proc_macro2::{Group, TokenTree};
 use quote::{quote, ToTokens};
 use syn::__private::Span;
 use syn::spanned::Spanned;
-use syn::{parse_macro_input, Attribute, Data, DataEnum, DeriveInput, Expr, Field, Fields, GenericArgument, Ident, LitStr, PathArguments, Type, Variant};
+use syn::{parse_macro_input, Attribute, Data, DataEnum, DeriveInput, Expr, Field, Fields, GenericArgument, Ident, Lifetime, LitStr, PathArguments, Type, Variant};
 
 /// Derive the `McSerialize` trait for a struct. This implies that all fields of the struct also
-/// implement `McSerialize`.
+/// implement `McSerialize`, unless they are changed by one of the `#[mc(...)]` attributes described on
+/// [derive_mc_deserialize].
 ///
 /// ```rust,ignore
 /// #[derive(McSerialize)]
@@ -16,22 +18,43 @@ use syn::{parse_macro_input, Attribute, Data, DataEnum, DeriveInput, Expr, Field
 ///   field2: bool,
 /// }
 /// ```
-#[proc_macro_derive(McSerialize)]
+#[proc_macro_derive(McSerialize, attributes(mc))]
 pub fn derive_mc_serialize(input: TokenStream) -> TokenStream {
 	let input = parse_macro_input!(input as DeriveInput);
 	let name = &input.ident;
 	let fields = match &input.data {
 		Data::Struct(data) => match &data.fields {
-			Fields::Named(fields) => fields
-				.named
-				.iter()
-				.map(|field| {
-					let field_name = field.ident.as_ref().unwrap();
-					quote! {
-						self.#field_name.mc_serialize(serializer)?;
-					}
-				})
-				.collect(),
+			Fields::Named(fields) => {
+				let names: Vec<String> = fields.named.iter().map(|f| f.ident.as_ref().unwrap().to_string()).collect();
+
+				fields
+					.named
+					.iter()
+					.enumerate()
+					.map(|(i, field)| {
+						let field_name = field.ident.as_ref().unwrap();
+						let opts = McFieldOpts::parse(field, i + 1 == fields.named.len());
+
+						match &opts.include_if {
+							Some(cond) => {
+								let cond_str = cond.to_token_stream().to_string();
+								let cond = self_field_expr(cond.to_token_stream(), &names);
+								let write = opts.serialize_value(quote! { __value });
+
+								quote! {
+									if #cond {
+										match &self.#field_name {
+											Some(__value) => { #write }
+											None => return Err(SerializingErr::MissingField(format!("{} must be present when {}", stringify!(#field_name), #cond_str))),
+										}
+									}
+								}
+							}
+							None => opts.serialize_value(quote! { &self.#field_name }),
+						}
+					})
+					.collect()
+			}
 			Fields::Unnamed(fields) => fields
 				.unnamed
 				.iter()
@@ -99,8 +122,9 @@ pub fn derive_mc_serialize(input: TokenStream) -> TokenStream {
 		}
 		Data::Union(_) => panic!("Unions are not supported"),
 	};
+	let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
 	let expanded = quote! {
-		impl McSerialize for #name {
+		impl #impl_generics McSerialize for #name #ty_generics #where_clause {
 			fn mc_serialize(&self, serializer: &mut McSerializer) -> Result<(), SerializingErr> {
 				#(#fields)*
 				Ok(())
@@ -113,16 +137,28 @@ pub fn derive_mc_serialize(input: TokenStream) -> TokenStream {
 /// Derive the `McDeserialize` trait for a struct. This implies that all fields of the struct also implement
 /// `McDeserialize`.
 ///
-/// This macro supports the `#[mc(deserialize_if = ...)]` attribute on fields, which allows for conditional
-/// deserialization of Option<T> fields according to if another boolean field is true.
+/// The fields of a struct support these attributes, which are applied the same way by `McSerialize`:
+/// - `#[mc(include_if = ...)]` on an `Option<T>` field only includes the field if the expression is true. The
+///   expression can use the fields before it. When serializing, the field must be `Some` if the expression is true.
+/// - `#[mc(varint)]` and `#[mc(varlong)]` encode a plain integer as a VarInt or VarLong.
+/// - `#[mc(length_prefixed)]` on a `Vec<T>` writes the length as a VarInt before the items.
+/// - `#[mc(rest)]` on a `Vec<u8>` reads every remaining byte. It must be the last field.
+///
+/// If the struct has a lifetime parameter, `McDeserializeBorrowed<'a>` is implemented instead so that fields can
+/// borrow from the input, such as `&'a str` or `&'a [u8]`. `length_prefixed` and `rest` also accept `&'a [u8]`.
+///
+/// Errors are returned with the name of the field they happened in added to their path, see `SerializingErr::in_field`.
 ///
 /// ```rust,ignore
 /// #[derive(McDeserialize)]
 /// struct MyStruct {
+///   #[mc(varint)]
 ///   field1: u32,
 ///   field2: bool,
-///   #[mc(deserialize_if = field2)]
+///   #[mc(include_if = field2 && field1 > 3)]
 ///   field3: Option<u64>,
+///   #[mc(length_prefixed)]
+///   field4: Vec<String>,
 /// }
 /// ```
 #[proc_macro_derive(McDeserialize, attributes(mc))]
@@ -147,17 +183,19 @@ pub fn derive_mc_deserialize(input: TokenStream) -> TokenStream {
 			let construct = match &variant.fields {
 				Fields::Named(fields) => {
 					let names: Vec<_> = fields.named.iter().map(|f| f.ident.as_ref().unwrap()).collect();
+					let paths: Vec<_> = names.iter().map(|n| format!("{}.{}", variant_ident, n)).collect();
 					let types: Vec<_> = fields.named.iter().map(|f| &f.ty).collect();
 					quote! {
-						#(let #names = <#types as McDeserialize>::mc_deserialize(deserializer)?;)*
+						#(let #names = deserializer.read_field(#paths, |deserializer| <#types as McDeserialize>::mc_deserialize(deserializer))?;)*
 						Ok(#name::#variant_ident { #(#names),* })
 					}
 				}
 				Fields::Unnamed(fields) => {
 					let vars: Vec<_> = (0..fields.unnamed.len()).map(|i| Ident::new(&format!("f{}", i), Span::call_site())).collect();
+					let paths: Vec<_> = (0..fields.unnamed.len()).map(|i| format!("{}.{}", variant_ident, i)).collect();
 					let types: Vec<_> = fields.unnamed.iter().map(|f| &f.ty).collect();
 					quote! {
-						#(let #vars = <#types as McDeserialize>::mc_deserialize(deserializer)?;)*
+						#(let #vars = deserializer.read_field(#paths, |deserializer| <#types as McDeserialize>::mc_deserialize(deserializer))?;)*
 						Ok(#name::#variant_ident(#(#vars),*))
 					}
 				}
@@ -186,86 +224,37 @@ pub fn derive_mc_deserialize(input: TokenStream) -> TokenStream {
 		return TokenStream::from(expanded);
 	}
 
+	let lifetime = input.generics.lifetimes().next().map(|param| param.lifetime.clone());
 	let mut init_stmts = Vec::new();
 	let mut field_names = Vec::new();
 
 	match &input.data {
 		Data::Struct(data) => match &data.fields {
 			Fields::Named(fields) => {
-				for field in &fields.named {
+				for (i, field) in fields.named.iter().enumerate() {
 					let field_name = field.ident.as_ref().unwrap();
-					let mut current_ty = &field.ty;
-					// Unwrap all Type::Group layers (e.g., types wrapped in parentheses)
-					// This is needed to use "mc" attributes within packets!
-					while let Type::Group(group) = current_ty {
-						current_ty = &group.elem;
-					}
+					let opts = McFieldOpts::parse(field, i + 1 == fields.named.len());
 
-					let mut condition: Option<Expr> = None;
+					match &opts.include_if {
+						Some(cond) => {
+							let inner_type = option_inner_type(&field.ty).unwrap_or_else(|| panic!("include_if can only be applied to Option fields, but field {field_name} is {}", field.ty.to_token_stream()));
+							let read = opts.deserialize_value(inner_type, field_name, lifetime.as_ref());
 
-					// Parse attributes to find #[mc(deserialize_if = ...)]
-					for attr in &field.attrs {
-						if !attr.path().is_ident("mc") {
-							continue;
+							init_stmts.push(quote! {
+								let #field_name = if #cond {
+									Some(deserializer.read_field(stringify!(#field_name), |deserializer| #read)?)
+								} else {
+									None
+								};
+							});
 						}
+						None => {
+							let read = opts.deserialize_value(&field.ty, field_name, lifetime.as_ref());
 
-						// Parse nested meta: like deserialize_if = field1
-						attr.parse_nested_meta(|meta| {
-							if meta.path.is_ident("deserialize_if") {
-								let value_expr = meta.value()?;
-								condition = Some(value_expr.parse()?);
-								Ok(())
-							} else {
-								Err(meta.error("unsupported mc attribute argument"))
-							}
-						})
-						.unwrap_or_else(|e| panic!("Error parsing mc attribute: {}", e));
-					}
-
-					if let Some(cond) = condition {
-						// Validate that the field is an Option<T>
-						let inner_type = match current_ty {
-							Type::Path(type_path) => {
-								let segments = &type_path.path.segments;
-								if let Some(segment) = segments.last() {
-									// Check the last segment instead of the first
-									if segment.ident == "Option" {
-										match &segment.arguments {
-											PathArguments::AngleBracketed(args) => {
-												if let Some(GenericArgument::Type(ty)) = args.args.first() {
-													ty
-												} else {
-													panic!("Option must have an inner type for field {field_name}");
-												}
-											}
-											_ => panic!("Option must have angle bracketed arguments for field {field_name}"),
-										}
-									} else {
-										panic!("deserialize_if can only be applied to Option fields, but field {field_name} is {}", segment.ident);
-									}
-								} else {
-									panic!("Invalid type path for field {field_name}");
-								}
-							}
-							_ => panic!(
-								"deserialize_if can only be applied to Option fields with a type path for field {field_name} and field type {}",
-								current_ty.to_token_stream()
-							),
-						};
-
-						// Conditional deserialization
-						init_stmts.push(quote! {
-							let #field_name = if #cond {
-								Some(<#inner_type as McDeserialize>::mc_deserialize(deserializer)?)
-							} else {
-								None
-							};
-						});
-					} else {
-						// Regular deserialization
-						init_stmts.push(quote! {
-							let #field_name = <#current_ty as McDeserialize>::mc_deserialize(deserializer)?;
-						});
+							init_stmts.push(quote! {
+								let #field_name = deserializer.read_field(stringify!(#field_name), |deserializer| #read)?;
+							});
+						}
 					}
 
 					field_names.push(quote! { #field_name });
@@ -274,10 +263,11 @@ pub fn derive_mc_deserialize(input: TokenStream) -> TokenStream {
 			Fields::Unnamed(fields) => {
 				for (i, field) in fields.unnamed.iter().enumerate() {
 					let field_ident = Ident::new(&format!("__{}", i), field.span());
-					let field_type = &field.ty;
+					let path = i.to_string();
+					let read = read_type(&field.ty, lifetime.as_ref());
 
 					init_stmts.push(quote! {
-						let #field_ident = <#field_type as McDeserialize>::mc_deserialize(deserializer)?;
+						let #field_ident = deserializer.read_field(#path, |deserializer| #read)?;
 					});
 
 					field_names.push(quote! { #field_ident });
@@ -305,19 +295,230 @@ pub fn derive_mc_deserialize(input: TokenStream) -> TokenStream {
 		_ => unreachable!(),
 	};
 
-	let expanded = quote! {
-		impl McDeserialize for #name {
-			fn mc_deserialize<'a>(deserializer: &'a mut McDeserializer) -> SerializingResult<'a, Self> {
-				#(#init_stmts)*
+	let expanded = match &lifetime {
+		Some(lifetime) => {
+			let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+			quote! {
+				impl #impl_generics McDeserializeBorrowed<#lifetime> for #name #ty_generics #where_clause {
+					fn mc_deserialize_borrowed(deserializer: &mut McDeserializer<#lifetime>) -> SerializingResult<#lifetime, Self> {
+						#(#init_stmts)*
 
-				Ok(#struct_expr)
+						Ok(#struct_expr)
+					}
+				}
 			}
 		}
+		None => quote! {
+			impl McDeserialize for #name {
+				fn mc_deserialize<'a>(deserializer: &'a mut McDeserializer) -> SerializingResult<'a, Self> {
+					#(#init_stmts)*
+
+					Ok(#struct_expr)
+				}
+			}
+		},
 	};
 
 	TokenStream::from(expanded)
 }
 
+/// How a field is encoded, from its `#[mc(...)]` attributes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldEncoding {
+	/// The `McSerialize` and `McDeserialize` implementations of the type.
+	Default,
+	VarInt,
+	VarLong,
+	LengthPrefixed,
+	Rest,
+}
+
+/// The `#[mc(...)]` attributes of a struct field.
+struct McFieldOpts {
+	include_if: Option<Expr>,
+	encoding: FieldEncoding,
+}
+
+impl McFieldOpts {
+	fn parse(field: &Field, is_last: bool) -> Self {
+		let field_name = field.ident.as_ref().unwrap();
+		let mut opts = McFieldOpts {
+			include_if: None,
+			encoding: FieldEncoding::Default,
+		};
+
+		for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("mc")) {
+			attr.parse_nested_meta(|meta| {
+				let encoding = if meta.path.is_ident("include_if") {
+					opts.include_if = Some(meta.value()?.parse()?);
+					return Ok(());
+				} else if meta.path.is_ident("varint") {
+					FieldEncoding::VarInt
+				} else if meta.path.is_ident("varlong") {
+					FieldEncoding::VarLong
+				} else if meta.path.is_ident("length_prefixed") {
+					FieldEncoding::LengthPrefixed
+				} else if meta.path.is_ident("rest") {
+					FieldEncoding::Rest
+				} else {
+					return Err(meta.error("unsupported mc attribute argument"));
+				};
+
+				if opts.encoding != FieldEncoding::Default {
+					return Err(meta.error("only one of varint, varlong, length_prefixed and rest can be used"));
+				}
+
+				opts.encoding = encoding;
+				Ok(())
+			})
+			.unwrap_or_else(|e| panic!("Error parsing mc attribute on field {field_name}: {}", e));
+		}
+
+		if opts.encoding == FieldEncoding::Rest && !is_last {
+			panic!("#[mc(rest)] can only be applied to the last field, but field {field_name} is not");
+		}
+
+		opts
+	}
+
+	/// Write a value, given an expression that is a reference to it.
+	fn serialize_value(&self, value: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+		match self.encoding {
+			FieldEncoding::Default => quote! {
+				(#value).mc_serialize(serializer)?;
+			},
+			FieldEncoding::VarInt => quote! {
+				VarInt(i32::try_from(*#value).map_err(|_| SerializingErr::OutOfBounds(format!("{} does not fit in a VarInt", #value)))?).mc_serialize(serializer)?;
+			},
+			FieldEncoding::VarLong => quote! {
+				VarLong(i64::try_from(*#value).map_err(|_| SerializingErr::OutOfBounds(format!("{} does not fit in a VarLong", #value)))?).mc_serialize(serializer)?;
+			},
+			FieldEncoding::LengthPrefixed => quote! {
+				VarInt((#value).len() as i32).mc_serialize(serializer)?;
+				for __item in (#value).iter() {
+					__item.mc_serialize(serializer)?;
+				}
+			},
+			FieldEncoding::Rest => quote! {
+				serializer.serialize_bytes(#value);
+			},
+		}
+	}
+
+	/// Read a value of type `ty`, as an expression that evaluates to a `SerializingResult`. If `lifetime` is set, the
+	/// value may borrow from the input for that lifetime.
+	fn deserialize_value(&self, ty: &Type, field_name: &Ident, lifetime: Option<&Lifetime>) -> proc_macro2::TokenStream {
+		let ty = unwrap_type_groups(ty);
+
+		match self.encoding {
+			FieldEncoding::Default => read_type(ty, lifetime),
+			FieldEncoding::VarInt => quote! {{
+				let __value = VarInt::mc_deserialize(deserializer)?.0;
+				<#ty>::try_from(__value).map_err(|_| SerializingErr::OutOfBounds(format!("VarInt {} does not fit in field {}", __value, stringify!(#field_name))))
+			}},
+			FieldEncoding::VarLong => quote! {{
+				let __value = VarLong::mc_deserialize(deserializer)?.0;
+				<#ty>::try_from(__value).map_err(|_| SerializingErr::OutOfBounds(format!("VarLong {} does not fit in field {}", __value, stringify!(#field_name))))
+			}},
+			FieldEncoding::LengthPrefixed if is_byte_slice(ty) => quote! {{
+				let __length = VarInt::mc_deserialize(deserializer)?.0;
+
+				if __length < 0 {
+					return Err(SerializingErr::OutOfBounds(format!("Negative length {} for field {}", __length, stringify!(#field_name))));
+				}
+
+				deserializer.slice_option(__length as usize).ok_or(SerializingErr::InputEnded)
+			}},
+			FieldEncoding::LengthPrefixed => {
+				let item_type = vec_inner_type(ty).unwrap_or_else(|| panic!("length_prefixed can only be applied to Vec or &[u8] fields, but field {field_name} is {}", ty.to_token_stream()));
+				let read_item = read_type(item_type, lifetime);
+
+				quote! {{
+					let __length = VarInt::mc_deserialize(deserializer)?.0;
+
+					if __length < 0 {
+						return Err(SerializingErr::OutOfBounds(format!("Negative length {} for field {}", __length, stringify!(#field_name))));
+					}
+
+					// the length is not trusted for the allocation, every item takes at least a byte
+					let mut __items = Vec::with_capacity(deserializer.capacity_for(__length)?);
+
+					for __i in 0..__length as usize {
+						__items.push(deserializer.read_item(__i, |deserializer| #read_item)?);
+					}
+
+					Ok(__items)
+				}}
+			}
+			FieldEncoding::Rest if is_byte_slice(ty) => quote! {{
+				let __rest = deserializer.collect_remaining();
+				deserializer.increment(__rest.len());
+				Ok(__rest)
+			}},
+			FieldEncoding::Rest => quote! {{
+				let __rest = deserializer.collect_remaining().to_vec();
+				deserializer.increment(__rest.len());
+				Ok(__rest)
+			}},
+		}
+	}
+}
+
+/// Read a value of type `ty` with its own deserialize implementation, borrowing from the input if `lifetime` is set.
+/// The expression evaluates to a `SerializingResult`.
+fn read_type(ty: &Type, lifetime: Option<&Lifetime>) -> proc_macro2::TokenStream {
+	match lifetime {
+		Some(lifetime) => quote! {
+			<#ty as McDeserializeBorrowed<#lifetime>>::mc_deserialize_borrowed(deserializer)
+		},
+		None => quote! {
+			<#ty as McDeserialize>::mc_deserialize(deserializer)
+		},
+	}
+}
+
+/// Whether `ty` is a borrowed byte slice, `&[u8]`.
+fn is_byte_slice(ty: &Type) -> bool {
+	match unwrap_type_groups(ty) {
+		Type::Reference(reference) => match unwrap_type_groups(&reference.elem) {
+			Type::Slice(slice) => unwrap_type_groups(&slice.elem).to_token_stream().to_string() == "u8",
+			_ => false,
+		},
+		_ => false,
+	}
+}
+
+/// Rewrite an `include_if` expression so that the fields it uses are read from `self`, since the fields are
+/// local variables when deserializing but not when serializing. Field accesses, method calls and paths are left alone.
+fn self_field_expr(tokens: proc_macro2::TokenStream, fields: &[String]) -> proc_macro2::TokenStream {
+	let tokens: Vec<TokenTree> = tokens.into_iter().collect();
+	let mut out = proc_macro2::TokenStream::new();
+
+	for (i, token) in tokens.iter().enumerate() {
+		match token {
+			TokenTree::Ident(ident) if fields.contains(&ident.to_string()) => {
+				let after_dot_or_path = i > 0 && matches!(&tokens[i - 1], TokenTree::Punct(p) if p.as_char() == '.' || p.as_char() == ':');
+				let before_path = matches!(tokens.get(i + 1), Some(TokenTree::Punct(p)) if p.as_char() == ':');
+
+				if after_dot_or_path || before_path {
+					out.extend([token.clone()]);
+				} else {
+					out.extend(quote! { self.#ident });
+				}
+			}
+			TokenTree::Group(group) => {
+				let mut new_group = Group::new(group.delimiter(), self_field_expr(group.stream(), fields));
+				new_group.set_span(group.span());
+				out.extend([TokenTree::Group(new_group)]);
+			}
+			_ => out.extend([token.clone()]),
+		}
+	}
+
+	out
+}
+
 #[proc_macro_attribute]
 pub fn mc(_attr: TokenStream, item: TokenStream) -> TokenStream {
 	item
@@ -434,6 +635,21 @@ fn option_inner_type(ty: &Type) -> Option<&Type> {
 	None
 }
 
+/// Extract `T` from a field type of the form `Vec<T>`, returning `None` for any other type.
+fn vec_inner_type(ty: &Type) -> Option<&Type> {
+	if let Type::Path(type_path) = unwrap_type_groups(ty) {
+		let segment = type_path.path.segments.last()?;
+		if segment.ident == "Vec" {
+			if let PathArguments::AngleBracketed(args) = &segment.arguments {
+				if let Some(GenericArgument::Type(inner)) = args.args.first() {
+					return Some(inner);
+				}
+			}
+		}
+	}
+	None
+}
+
 /// Returns the inner type of a `Box<T>`, if `ty` is one. The orphan rule blocks a blanket
 /// `TryFrom<NbtCompound> for Box<T>`, so reading into a boxed field is generated by converting the
 /// inner type and wrapping the result in `Box::new`.
@@ -601,7 +817,7 @@ fn from_nbt_struct(name: &Ident, fields: &Fields) -> TokenStream {
 		if flatten {
 			// A flattened field is rebuilt from the entire compound rather than a single key.
 			quote! {
-				#field_ident: <#field_ty as ::std::convert::TryFrom<NbtCompound>>::try_from(nbt.clone())?,
+				#field_ident: <#field_ty as ::core::convert::TryFrom<NbtCompound>>::try_from(nbt.clone())?,
 			}
 		} else if let Some(inner_ty) = option_inner_type(field_ty) {
 			// Optional field: an absent key or a present-but-wrong-type value both resolve to `None`,
@@ -609,7 +825,7 @@ fn from_nbt_struct(name: &Ident, fields: &Fields) -> TokenStream {
 			quote! {
 				#field_ident: match nbt.get(#key_str) {
 					Some(tag) => {
-						<#inner_ty as ::std::convert::TryFrom<NbtTag>>::try_from(tag.clone()).ok()
+						<#inner_ty as ::core::convert::TryFrom<NbtTag>>::try_from(tag.clone()).ok()
 					}
 					None => None,
 				},
@@ -618,7 +834,7 @@ fn from_nbt_struct(name: &Ident, fields: &Fields) -> TokenStream {
 			quote! {
 				#field_ident: match nbt.get(#key_str) {
 					Some(tag) => {
-						<#field_ty as ::std::convert::TryFrom<NbtTag>>::try_from(tag.clone())
+						<#field_ty as ::core::convert::TryFrom<NbtTag>>::try_from(tag.clone())
 							.map_err(|_| NbtError::MissingField(
 								format!("Invalid type for field '{}' in '{}'", #key_str, #sname)
 							))?
@@ -634,7 +850,7 @@ fn from_nbt_struct(name: &Ident, fields: &Fields) -> TokenStream {
 	});
 
 	let expanded = quote! {
-		impl ::std::convert::TryFrom<NbtCompound> for #name {
+		impl ::core::convert::TryFrom<NbtCompound> for #name {
 			type Error = NbtError;
 
 			fn try_from(nbt: NbtCompound) -> Result<Self, Self::Error> {
@@ -644,7 +860,7 @@ fn from_nbt_struct(name: &Ident, fields: &Fields) -> TokenStream {
 			}
 		}
 
-		impl ::std::convert::TryFrom<NbtTag> for #name {
+		impl ::core::convert::TryFrom<NbtTag> for #name {
 			type Error = NbtError;
 
 			fn try_from(value: NbtTag) -> Result<Self, Self::Error> {
@@ -684,15 +900,15 @@ fn from_nbt_enum(name: &Ident, attrs: &[Attribute], data: &DataEnum) -> TokenStr
 				if let Some(boxed_ty) = box_inner_type(inner_ty) {
 					quote! {
 						#tag_value => Ok(#name::#variant_ident(
-							::std::boxed::Box::new(
-								<#boxed_ty as ::std::convert::TryFrom<NbtCompound>>::try_from(nbt.clone())?
+							Box::new(
+								<#boxed_ty as ::core::convert::TryFrom<NbtCompound>>::try_from(nbt.clone())?
 							)
 						)),
 					}
 				} else {
 					quote! {
 						#tag_value => Ok(#name::#variant_ident(
-							<#inner_ty as ::std::convert::TryFrom<NbtCompound>>::try_from(nbt.clone())?
+							<#inner_ty as ::core::convert::TryFrom<NbtCompound>>::try_from(nbt.clone())?
 						)),
 					}
 				}
@@ -702,7 +918,7 @@ fn from_nbt_enum(name: &Ident, attrs: &[Attribute], data: &DataEnum) -> TokenStr
 	});
 
 	let expanded = quote! {
-		impl ::std::convert::TryFrom<NbtCompound> for #name {
+		impl ::core::convert::TryFrom<NbtCompound> for #name {
 			type Error = NbtError;
 
 			fn try_from(nbt: NbtCompound) -> Result<Self, Self::Error> {
@@ -719,7 +935,7 @@ fn from_nbt_enum(name: &Ident, attrs: &[Attribute], data: &DataEnum) -> TokenStr
 			}
 		}
 
-		impl ::std::convert::TryFrom<NbtTag> for #name {
+		impl ::core::convert::TryFrom<NbtTag> for #name {
 			type Error = NbtError;
 
 			fn try_from(value: NbtTag) -> Result<Self, Self::Error> {
@@ -803,6 +1019,7 @@ pub fn derive_var_int_enum(input: TokenStream) -> TokenStream {
 				all_unit = false;
 
 				let bindings: Vec<Ident> = (0..fields.unnamed.len()).map(|i| Ident::new(&format!("f{}", i), Span::call_site())).collect();
+				let paths: Vec<String> = (0..fields.unnamed.len()).map(|i| format!("{}.{}", variant_ident, i)).collect();
 				let field_types: Vec<&Type> = fields.unnamed.iter().map(|f| &f.ty).collect();
 
 				serialize_arms.push(quote! {
@@ -815,7 +1032,7 @@ pub fn derive_var_int_enum(input: TokenStream) -> TokenStream {
 
 				deserialize_arms.push(quote! {
 					#expr => {
-						#( let #bindings = <#field_types>::mc_deserialize(deserializer)?; )*
+						#( let #bindings = deserializer.read_field(#paths, |deserializer| <#field_types>::mc_deserialize(deserializer))?; )*
 						Ok(#name::#variant_ident( #(#bindings),* ))
 					}
 				});
@@ -958,8 +1175,9 @@ pub fn derive_type_enum(input: TokenStream) -> TokenStream {
 }
 
 /// Derive the `McDefault` trait for a struct. This trait provides a default value for the struct,
-/// which can be used for automated packet testing.
-#[proc_macro_derive(McDefault)]
+/// which can be used for automated packet testing. A field with `#[mc(include_if = ...)]` is only `Some`
+/// when its condition holds, so that the default value round trips.
+#[proc_macro_derive(McDefault, attributes(mc))]
 pub fn derive_mc_default(input: TokenStream) -> TokenStream {
 	let input = parse_macro_input!(input as DeriveInput);
 	let name = &input.ident;
@@ -967,13 +1185,23 @@ pub fn derive_mc_default(input: TokenStream) -> TokenStream {
 	let mc_default_impl = match &input.data {
 		Data::Struct(data) => match &data.fields {
 			Fields::Named(fields) => {
-				let default_fields = fields.named.iter().map(|field| {
+				// fields are built in order, so that an include_if condition can use the fields before it
+				let default_fields = fields.named.iter().enumerate().map(|(i, field)| {
 					let field_name = field.ident.as_ref().unwrap();
-					quote! { #field_name: McDefault::mc_default() }
+					let ty = &field.ty;
+					let opts = McFieldOpts::parse(field, i + 1 == fields.named.len());
+
+					match &opts.include_if {
+						Some(cond) => quote! { let #field_name: #ty = if #cond { McDefault::mc_default() } else { None }; },
+						None => quote! { let #field_name: #ty = McDefault::mc_default(); },
+					}
 				});
+				let field_names = fields.named.iter().map(|field| field.ident.as_ref().unwrap());
 				quote! {
+					#(#default_fields)*
+
 					Self {
-						#(#default_fields),*
+						#(#field_names),*
 					}
 				}
 			}
@@ -1029,6 +1257,7 @@ pub fn derive_mc_default(input: TokenStream) -> TokenStream {
 
 	let expanded = quote! {
 		impl McDefault for #name {
+			#[allow(non_snake_case)] // the fields are locals, named the same as the fields
 			fn mc_default() -> Self {
 				#mc_default_impl
 			}
@@ -1037,3 +1266,136 @@ pub fn derive_mc_default(input: TokenStream) -> TokenStream {
 
 	TokenStream::from(expanded)
 }
+
+/// Derive `McArbitrary`, which gives a [proptest](https://docs.rs/proptest) strategy for random values of a type.
+/// Every field is generated with its own `McArbitrary` strategy, and an enum picks one of its variants.
+///
+/// The `#[mc(...)]` attributes are followed so that every value can be serialized and read back: an `include_if`
+/// field is only `Some` if its condition is true, and a `varint` or `varlong` field fits in a VarInt or VarLong.
+///
+/// The implementation is only compiled for tests, or with the `proptest` feature of sandstone.
+///
+/// ```rust,ignore
+/// #[derive(McArbitrary)]
+/// struct MyStruct {
+///   field1: bool,
+///   #[mc(include_if = field1)]
+///   field2: Option<u64>,
+/// }
+/// ```
+#[proc_macro_derive(McArbitrary, attributes(mc))]
+pub fn derive_mc_arbitrary(input: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+	let name = &input.ident;
+
+	let strategy = match &input.data {
+		Data::Struct(data) => arbitrary_fields(quote! { #name }, &data.fields),
+		Data::Enum(data_enum) => {
+			let variants = data_enum.variants.iter().map(|variant| {
+				let variant_ident = &variant.ident;
+				arbitrary_fields(quote! { #name::#variant_ident }, &variant.fields)
+			});
+
+			quote! {
+				::proptest::strategy::Union::new(vec![#(#variants),*]).boxed()
+			}
+		}
+		Data::Union(_) => panic!("#[derive(McArbitrary)] is not supported for unions"),
+	};
+
+	let expanded = quote! {
+		#[cfg(any(test, feature = "proptest"))]
+		impl crate::protocol::testing::arbitrary::McArbitrary for #name {
+			#[allow(non_snake_case)] // the fields are locals, named the same as the fields
+			fn mc_arbitrary() -> ::proptest::strategy::BoxedStrategy<Self> {
+				use ::proptest::strategy::Strategy;
+
+				#strategy
+			}
+		}
+	};
+
+	TokenStream::from(expanded)
+}
+
+/// A boxed strategy that builds `path` out of `fields`. The field strategies are combined into nested pairs, since
+/// proptest only implements `Strategy` for tuples of up to 12 items.
+fn arbitrary_fields(path: proc_macro2::TokenStream, data_fields: &Fields) -> proc_macro2::TokenStream {
+	let fields: Vec<&Field> = data_fields.iter().collect();
+	let opts: Vec<McFieldOpts> = fields.iter().enumerate().map(|(i, field)| McFieldOpts::parse_arbitrary(field, i + 1 == fields.len())).collect();
+	let locals: Vec<Ident> = (0..fields.len()).map(|i| Ident::new(&format!("__f{}", i), Span::call_site())).collect();
+
+	let mut strategies = quote! { ::proptest::strategy::Just(()) };
+	let mut pattern = quote! { () };
+
+	for (i, field) in fields.iter().enumerate().rev() {
+		let strategy = opts[i].arbitrary_value(&field.ty);
+		let local = &locals[i];
+
+		strategies = quote! { (#strategy, #strategies) };
+		pattern = quote! { (#local, #pattern) };
+	}
+
+	// fields are built in order, so that an include_if condition can use the fields before it
+	let build = match data_fields {
+		Fields::Named(_) => {
+			let lets = fields.iter().zip(&opts).zip(&locals).map(|((field, opts), local)| {
+				let field_name = field.ident.as_ref().unwrap();
+				let ty = &field.ty;
+
+				match &opts.include_if {
+					Some(cond) => quote! { let #field_name: #ty = if #cond { Some(#local) } else { None }; },
+					None => quote! { let #field_name: #ty = #local; },
+				}
+			});
+			let field_names = fields.iter().map(|field| field.ident.as_ref().unwrap());
+
+			quote! {
+				#(#lets)*
+
+				#path {
+					#(#field_names),*
+				}
+			}
+		}
+		Fields::Unnamed(_) => quote! { #path(#(#locals),*) },
+		Fields::Unit => quote! { #path },
+	};
+
+	quote! {
+		(#strategies).prop_map(move |#pattern| { #build }).boxed()
+	}
+}
+
+impl McFieldOpts {
+	/// Like [McFieldOpts::parse], but a tuple field has no name to put in the error messages.
+	fn parse_arbitrary(field: &Field, is_last: bool) -> Self {
+		match field.ident {
+			Some(_) => Self::parse(field, is_last),
+			None => McFieldOpts {
+				include_if: None,
+				encoding: FieldEncoding::Default,
+			},
+		}
+	}
+
+	/// A strategy for the values of a field of type `ty` that can be written with these options.
+	fn arbitrary_value(&self, ty: &Type) -> proc_macro2::TokenStream {
+		let ty = match &self.include_if {
+			Some(_) => option_inner_type(ty).expect("include_if can only be applied to Option fields"),
+			None => unwrap_type_groups(ty),
+		};
+
+		match self.encoding {
+			FieldEncoding::VarInt => quote! {
+				::proptest::prelude::any::<#ty>().prop_filter("does not fit in a VarInt", |__value| i32::try_from(*__value).is_ok())
+			},
+			FieldEncoding::VarLong => quote! {
+				::proptest::prelude::any::<#ty>().prop_filter("does not fit in a VarLong", |__value| i64::try_from(*__value).is_ok())
+			},
+			_ => quote! {
+				<#ty as crate::protocol::testing::arbitrary::McArbitrary>::mc_arbitrary()
+			},
+		}
+	}
+}
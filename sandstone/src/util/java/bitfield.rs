@@ -6,8 +6,13 @@ use crate::protocol::serialization::McDeserializer;
 use crate::protocol::serialization::McSerialize;
 use crate::protocol::serialization::McSerializer;
 use crate::protocol::serialization::SerializingResult;
+#[cfg(any(test, feature = "proptest"))]
+use crate::protocol::testing::arbitrary::McArbitrary;
 use crate::protocol::testing::McDefault;
-use std::ops::{BitAnd, BitOr, Not, Shl, Shr, Sub};
+use core::ops::{BitAnd, BitOr, Not, Shl, Shr, Sub};
+#[cfg(any(test, feature = "proptest"))]
+use proptest::prelude::{BoxedStrategy, Strategy};
+use serde::{Serialize, Serializer};
 
 /// A simple bit field internally represented by any primitive signed or unsigned integer.
 #[derive(Default, Debug, Clone, Hash, PartialEq, Eq)]
@@ -94,6 +99,20 @@ impl<T: BitFieldInteger + McSerialize + McDeserialize + McDefault> McDefault for
 	}
 }
 
+impl<T: BitFieldInteger + McSerialize + McDeserialize + Serialize> Serialize for BitField<T> {
+	/// Serialized as the backing integer.
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		self.bits.serialize(serializer)
+	}
+}
+
+#[cfg(any(test, feature = "proptest"))]
+impl<T: BitFieldInteger + McSerialize + McDeserialize + McArbitrary + 'static> McArbitrary for BitField<T> {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		T::mc_arbitrary().prop_map(BitField::new).boxed()
+	}
+}
+
 /// Define a struct backed by a [`BitField`] from just a list of flag names.
 ///
 /// Each flag is assigned a bit index in declaration order (the first flag is bit 0).
@@ -142,6 +161,32 @@ macro_rules! bitflag {
 				}
 			}
 		}
+
+		impl ::serde::Serialize for $name {
+			/// Each flag by name in a human readable format like JSON, otherwise the bits.
+			fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+				use ::serde::ser::SerializeStruct;
+
+				if !serializer.is_human_readable() {
+					return self.flags.serialize(serializer);
+				}
+
+				let mut state = serializer.serialize_struct(stringify!($name), [$(stringify!($flag)),*].len())?;
+				$(state.serialize_field(stringify!($flag), &self.$flag())?;)*
+				state.end()
+			}
+		}
+
+		#[cfg(any(test, feature = "proptest"))]
+		impl $crate::protocol::testing::arbitrary::McArbitrary for $name {
+			fn mc_arbitrary() -> ::proptest::strategy::BoxedStrategy<Self> {
+				use ::proptest::strategy::Strategy;
+
+				<$crate::util::java::bitfield::BitField<$repr> as $crate::protocol::testing::arbitrary::McArbitrary>::mc_arbitrary()
+					.prop_map(|flags| Self { flags })
+					.boxed()
+			}
+		}
 	};
 	(@methods $index:expr; ) => {};
 	(@methods $index:expr; $flag:ident $(, $rest:ident)*) => {
@@ -181,6 +226,7 @@ macro_rules! impl_bitfield_integer {
 impl_bitfield_integer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod test {
 	use crate::util::java::bitfield::BitField;
 
@@ -1,16 +1,23 @@
 //! Implementation of https://docs.oracle.com/javase/8/docs/api/java/util/BitSet.html
 
+use crate::prelude::*;
 use crate::protocol::serialization::McDeserialize;
 use crate::protocol::serialization::McDeserializer;
 use crate::protocol::serialization::McSerialize;
 use crate::protocol::serialization::McSerializer;
 use crate::protocol::serialization::SerializingResult;
+use crate::protocol::serialization::serializer_error::SerializingErr;
+#[cfg(any(test, feature = "proptest"))]
+use crate::protocol::testing::arbitrary::McArbitrary;
 use crate::protocol::testing::McDefault;
 use crate::protocol_types::datatypes::var_types::VarInt;
-use std::ops::Range;
+use core::ops::Range;
+use serde::Serialize;
+#[cfg(any(test, feature = "proptest"))]
+use proptest::prelude::{any, BoxedStrategy, Strategy};
 
 /// A BitSet is a bitmask datatype of infinite size. It is stored as a Vec of u64
-#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize)]
 pub struct BitSet {
 	bits: Vec<u64>,
 }
@@ -31,7 +38,7 @@ impl McDeserialize for BitSet {
 		Self: Sized,
 	{
 		let len = VarInt::mc_deserialize(deserializer)?;
-		let mut bits = Vec::with_capacity(len.0 as usize);
+		let mut bits = Vec::with_capacity(deserializer.capacity_for(len.0)?);
 		for _ in 0..len.0 {
 			bits.push(u64::mc_deserialize(deserializer)?);
 		}
@@ -167,6 +174,17 @@ impl McDefault for BitSet {
 	}
 }
 
+#[cfg(any(test, feature = "proptest"))]
+impl McArbitrary for BitSet {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		Vec::<u64>::mc_arbitrary()
+			.prop_map(|bits| Self {
+				bits,
+			})
+			.boxed()
+	}
+}
+
 impl From<&[u8]> for BitSet {
 	fn from(bytes: &[u8]) -> Self {
 		let mut bits = vec![0; bytes.len().div_ceil(8)];
@@ -189,9 +207,119 @@ impl From<BitSet> for Vec<u8> {
 	}
 }
 
+/// A BitSet with a fixed number of bits `N`. Unlike [BitSet] it is sent as `ceil(N / 8)` bytes without a
+/// length prefix, where bit `i` is stored in byte `i / 8`.
+#[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize)]
+pub struct FixedBitSet<const N: usize> {
+	bytes: Vec<u8>,
+}
+
+impl<const N: usize> FixedBitSet<N> {
+	pub fn new() -> Self {
+		Self {
+			bytes: vec![0; N.div_ceil(8)],
+		}
+	}
+
+	/// Get the value of a specific bit. Bits outside of the set are always false.
+	pub fn get_bit(&self, index: usize) -> bool {
+		index < N && self.bytes[index / 8] & (1 << (index % 8)) != 0
+	}
+
+	/// Set a specific bit to true (1) or false (0). Bits outside of the set are ignored.
+	pub fn set_bit(&mut self, index: usize, value: bool) {
+		if index >= N {
+			return;
+		}
+
+		if value {
+			self.bytes[index / 8] |= 1 << (index % 8);
+		} else {
+			self.bytes[index / 8] &= !(1 << (index % 8));
+		}
+	}
+
+	/// The number of bits set to true.
+	pub fn cardinality(&self) -> usize {
+		self.bytes.iter().map(|b| b.count_ones() as usize).sum()
+	}
+}
+
+impl<const N: usize> Default for FixedBitSet<N> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<const N: usize> McSerialize for FixedBitSet<N> {
+	fn mc_serialize(&self, serializer: &mut McSerializer) -> SerializingResult<()> {
+		serializer.serialize_bytes(&self.bytes);
+		Ok(())
+	}
+}
+
+impl<const N: usize> McDeserialize for FixedBitSet<N> {
+	fn mc_deserialize<'a>(deserializer: &'a mut McDeserializer) -> SerializingResult<'a, Self>
+	where
+		Self: Sized,
+	{
+		let bytes = deserializer.slice_option(N.div_ceil(8)).ok_or(SerializingErr::InputEnded)?;
+
+		Ok(Self {
+			bytes: bytes.to_vec(),
+		})
+	}
+}
+
+impl<const N: usize> McDefault for FixedBitSet<N> {
+	fn mc_default() -> Self {
+		let mut bits = Self::new();
+		bits.set_bit(0, true);
+		bits
+	}
+}
+
+#[cfg(any(test, feature = "proptest"))]
+impl<const N: usize> McArbitrary for FixedBitSet<N> {
+	fn mc_arbitrary() -> BoxedStrategy<Self> {
+		proptest::collection::vec(any::<bool>(), N)
+			.prop_map(|values| {
+				let mut bits = Self::new();
+
+				for (i, value) in values.into_iter().enumerate() {
+					bits.set_bit(i, value);
+				}
+
+				bits
+			})
+			.boxed()
+	}
+}
+
 #[cfg(test)]
 mod test {
-	use crate::util::java::bitset::BitSet;
+	use crate::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer};
+	use crate::util::java::bitset::{BitSet, FixedBitSet};
+
+	#[test]
+	fn test_fixed_bitset() {
+		let mut bits = FixedBitSet::<20>::new();
+		bits.set_bit(0, true);
+		bits.set_bit(9, true);
+		bits.set_bit(19, true);
+		bits.set_bit(20, true);
+
+		assert!(bits.get_bit(0) && bits.get_bit(9) && bits.get_bit(19));
+		assert!(!bits.get_bit(20));
+		assert_eq!(bits.cardinality(), 3);
+
+		let mut serializer = McSerializer::new();
+		bits.mc_serialize(&mut serializer).unwrap();
+		assert_eq!(serializer.output, vec![0b0000_0001, 0b0000_0010, 0b0000_1000]);
+
+		let mut deserializer = McDeserializer::new(&serializer.output);
+		assert_eq!(FixedBitSet::<20>::mc_deserialize(&mut deserializer).unwrap(), bits);
+	}
 
 	#[test]
 	fn test_bitset() {
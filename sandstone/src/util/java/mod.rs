@@ -1,3 +1,4 @@
 //! Contains utilities like those found in Java (programming language)
 pub mod bitset;
-pub mod bitfield;
\ No newline at end of file
+pub mod bitfield;
+pub mod modified_utf8;
\ No newline at end of file
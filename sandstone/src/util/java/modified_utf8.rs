@@ -0,0 +1,80 @@
+//! Java's modified UTF-8, as written by `DataOutput.writeUTF` and used for NBT strings.
+//! It differs from UTF-8 in two ways: NUL is written as the two bytes `0xC0 0x80`, and characters
+//! outside of the BMP are written as a surrogate pair of two 3 byte sequences instead of 4 bytes.
+//!
+//! https://docs.oracle.com/javase/8/docs/api/java/io/DataInput.html#modified-utf-8
+
+use crate::prelude::*;
+use crate::protocol::serialization::serializer_error::SerializingErr;
+
+/// Encode a string as modified UTF-8.
+pub fn encode(s: &str) -> Vec<u8> {
+	let mut out = Vec::with_capacity(s.len());
+
+	for unit in s.encode_utf16() {
+		match unit {
+			0x01..=0x7F => out.push(unit as u8),
+			0x00 | 0x80..=0x7FF => {
+				out.push(0xC0 | (unit >> 6) as u8);
+				out.push(0x80 | (unit & 0x3F) as u8);
+			}
+			_ => {
+				out.push(0xE0 | (unit >> 12) as u8);
+				out.push(0x80 | ((unit >> 6) & 0x3F) as u8);
+				out.push(0x80 | (unit & 0x3F) as u8);
+			}
+		}
+	}
+
+	out
+}
+
+/// Decode modified UTF-8, failing on malformed sequences and unpaired surrogates.
+pub fn decode(bytes: &[u8]) -> Result<String, SerializingErr> {
+	let invalid = |at: usize| SerializingErr::UniqueFailure(format!("Invalid modified UTF-8 at byte {at}"));
+	let continuation = |at: usize| match bytes.get(at) {
+		Some(b) if b & 0xC0 == 0x80 => Ok((b & 0x3F) as u16),
+		_ => Err(invalid(at)),
+	};
+
+	let mut units = Vec::with_capacity(bytes.len());
+	let mut i = 0;
+
+	while i < bytes.len() {
+		let b = bytes[i];
+
+		let (unit, len) = match b {
+			0x01..=0x7F => (b as u16, 1),
+			0xC0..=0xDF => (((b & 0x1F) as u16) << 6 | continuation(i + 1)?, 2),
+			0xE0..=0xEF => (((b & 0x0F) as u16) << 12 | continuation(i + 1)? << 6 | continuation(i + 2)?, 3),
+			_ => return Err(invalid(i)),
+		};
+
+		units.push(unit);
+		i += len;
+	}
+
+	String::from_utf16(&units).map_err(|_| SerializingErr::UniqueFailure("Invalid surrogate pair in modified UTF-8".to_string()))
+}
+
+#[cfg(test)]
+mod test {
+	use crate::util::java::modified_utf8::{decode, encode};
+
+	#[test]
+	fn test_modified_utf8() {
+		assert_eq!(encode("abc"), b"abc");
+		assert_eq!(encode("a\0b"), [b'a', 0xC0, 0x80, b'b']);
+		assert_eq!(encode("é"), "é".as_bytes());
+		assert_eq!(encode("😀"), [0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80]);
+
+		for s in ["", "hello", "a\0b", "héllo wörld", "日本語", "😀 and 🎉"] {
+			assert_eq!(decode(&encode(s)).unwrap(), s);
+		}
+
+		// a raw NUL, a truncated sequence and an unpaired surrogate
+		assert!(decode(&[0x00]).is_err());
+		assert!(decode(&[0xE6, 0x97]).is_err());
+		assert!(decode(&[0xED, 0xA0, 0xBD]).is_err());
+	}
+}
@@ -0,0 +1,425 @@
+//! Decodes packet frames, NBT and chunk data, and records the bytes each field was read from.
+
+use std::io::Read;
+use std::ops::Range;
+
+use flate2::read::GzDecoder;
+use flate2::read::ZlibDecoder;
+use sandstone::protocol::game::world::chunk::ChunkData;
+use sandstone::protocol::packets::packet_definer::{PacketDirection, PacketInfo, PacketState};
+use sandstone::protocol::packets::packet_versions;
+use sandstone::protocol::packets::Packet;
+use sandstone::protocol::serialization::dump::{hex_summary, to_json, MAX_DUMP_ITEMS};
+use sandstone::protocol::serialization::field_spans::{FieldRecorder, FieldSpan};
+use sandstone::protocol::serialization::{McDeserialize, McDeserializer, McSerialize, McSerializer, StateBasedDeserializer};
+use sandstone::protocol_types::datatypes::nbt::{NbtCompound, NbtTag};
+use sandstone::protocol_types::datatypes::var_types::VarInt;
+use sandstone::protocol_types::protocol_verison::ProtocolVerison;
+
+/// The largest packet a frame can claim to decompress to, the same limit as a connection.
+const PACKET_MAX_SIZE: usize = 2097151;
+
+/// What the input contains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+	/// Packet frames, one after another, as they are sent over a connection.
+	Packet,
+	/// A network NBT tag: its type id and then the tag, with no root name.
+	Nbt,
+	/// An NBT file: a compound with a root name, which may be gzipped.
+	NbtFile,
+	/// The chunk data of a `Chunk Data and Update Light` packet, from the heightmaps to the block entities.
+	Chunk,
+}
+
+#[derive(Debug, Clone)]
+pub struct Options {
+	pub kind: Kind,
+	pub state: PacketState,
+	pub direction: PacketDirection,
+	/// Whether the frames use the compressed format, with a data length after the packet length.
+	pub compressed: bool,
+	pub version: ProtocolVerison,
+	/// Show every item of long arrays instead of the first [MAX_DUMP_ITEMS].
+	pub all_items: bool,
+}
+
+/// A field and the bytes it was read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+	pub path: String,
+	pub depth: usize,
+	/// The bytes of the input the field was read from, or of the decompressed data if the frame is compressed.
+	pub range: Range<usize>,
+	/// The bytes of the field, see [hex_summary].
+	pub hex: String,
+}
+
+/// One decoded frame, or the decoded NBT or chunk data.
+#[derive(Debug, Clone)]
+pub struct Dissection {
+	pub title: String,
+	/// The length of the decompressed data, if the decoded fields are in it rather than in the input.
+	pub decompressed: Option<usize>,
+	pub fields: Vec<Field>,
+	/// The decoded value as JSON, or why it could not be decoded.
+	pub result: Result<String, String>,
+}
+
+/// Parse a hex string, which may have whitespace and a `0x` prefix, or a list of bytes like `[16, 0, 254]` as
+/// they are written by `trace!` and `{:?}`.
+pub fn parse_input(text: &str) -> Result<Vec<u8>, String> {
+	let text = text.trim();
+
+	if let Some(list) = text.strip_prefix('[') {
+		let list = list.strip_suffix(']').ok_or("a byte list must end with `]`")?;
+
+		return list
+			.split(',')
+			.map(str::trim)
+			.filter(|b| !b.is_empty())
+			.map(|b| b.parse::<u8>().map_err(|e| format!("`{b}` is not a byte: {e}")))
+			.collect();
+	}
+
+	let digits: String = text.strip_prefix("0x").unwrap_or(text).chars().filter(|c| !c.is_whitespace()).collect();
+	hex::decode(&digits).map_err(|e| format!("input is not hex: {e}"))
+}
+
+/// Decode the input, see [Kind]. Packet input is split into frames until it runs out, or a frame does not fit.
+pub fn dissect(input: &[u8], options: &Options) -> Vec<Dissection> {
+	match options.kind {
+		Kind::Packet => {
+			let mut dissections = vec![];
+			let mut start = 0;
+
+			while start < input.len() {
+				match dissect_frame(input, start, dissections.len(), options) {
+					Ok((dissection, end)) => {
+						dissections.push(dissection);
+						start = end;
+					}
+					Err(e) => {
+						dissections.push(Dissection {
+							title: format!("Frame {} at byte {}", dissections.len(), start),
+							decompressed: None,
+							fields: vec![],
+							result: Err(e),
+						});
+						break;
+					}
+				}
+			}
+
+			dissections
+		}
+		Kind::Nbt => vec![dissect_value(input, None, "NBT", options, |d| NbtTag::mc_deserialize(d).map(|tag| to_json(&tag)))],
+		Kind::NbtFile => {
+			if input.starts_with(&[0x1f, 0x8b]) {
+				let mut decompressed = vec![];
+
+				if let Err(e) = GzDecoder::new(input).read_to_end(&mut decompressed) {
+					return vec![error("NBT file", format!("could not decompress the file: {e}"))];
+				}
+
+				vec![dissect_value(&decompressed, Some(decompressed.len()), "NBT file", options, |d| NbtCompound::from_root(d).map(|c| to_json(&c)))]
+			} else {
+				vec![dissect_value(input, None, "NBT file", options, |d| NbtCompound::from_root(d).map(|c| to_json(&c)))]
+			}
+		}
+		Kind::Chunk => vec![dissect_value(input, None, "Chunk data", options, |d| ChunkData::mc_deserialize(d).map(|c| to_json(&c)))],
+	}
+}
+
+/// Decode a single value that fills the whole input.
+fn dissect_value<F>(input: &[u8], decompressed: Option<usize>, title: &str, options: &Options, f: F) -> Dissection
+where
+	F: FnOnce(&mut McDeserializer) -> Result<String, sandstone::protocol::serialization::serializer_error::SerializingErr>,
+{
+	let recorder = FieldRecorder::new(input);
+	let mut deserializer = McDeserializer::with_recorder(input, &recorder);
+
+	let result = match f(&mut deserializer) {
+		Ok(_) if !deserializer.is_at_end() => Err(format!("{} bytes are left over", deserializer.remaining())),
+		Ok(json) => Ok(json),
+		Err(e) => Err(e.to_string()),
+	};
+
+	Dissection {
+		title: format!("{title}, {} bytes", input.len()),
+		decompressed,
+		fields: fields(input, recorder.into_spans(), 0, options),
+		result,
+	}
+}
+
+fn error(title: &str, message: String) -> Dissection {
+	Dissection {
+		title: title.to_string(),
+		decompressed: None,
+		fields: vec![],
+		result: Err(message),
+	}
+}
+
+/// Decode the frame that starts at `start`. Returns the frame and the index it ends at.
+fn dissect_frame(input: &[u8], start: usize, number: usize, options: &Options) -> Result<(Dissection, usize), String> {
+	let mut deserializer = McDeserializer::new(&input[start..]);
+	let length = VarInt::mc_deserialize(&mut deserializer).map_err(|e| format!("could not read the frame length: {e}"))?.0;
+	let body_start = start + deserializer.index;
+	let end = body_start + length.max(0) as usize;
+
+	if end > input.len() {
+		return Err(format!("the frame is {length} bytes long, but only {} bytes are left", input.len() - body_start));
+	}
+
+	let mut header = vec![field(input, "length", start..body_start)];
+
+	// the buffer that the packet is read from, which starts with the length of the packet, and where the
+	// packet id is in the input. Fields in the buffer at or after `id_at` are at `base + (offset - id_at)`.
+	let (buffer, id_at, base, decompressed) = if options.compressed {
+		let mut body = McDeserializer::new(&input[body_start..end]);
+		let data_length = VarInt::mc_deserialize(&mut body).map_err(|e| format!("could not read the data length: {e}"))?.0;
+		let data_start = body_start + body.index;
+		header.push(field(input, "data length", body_start..data_start));
+
+		if data_length == 0 {
+			let (buffer, id_at) = length_prefixed(&input[data_start..end]);
+			(buffer, id_at, data_start, None)
+		} else {
+			if data_length < 0 || data_length as usize > PACKET_MAX_SIZE {
+				return Err(format!("the data length {data_length} is not a valid packet size"));
+			}
+
+			let mut data = Vec::with_capacity(data_length as usize);
+			ZlibDecoder::new(&input[data_start..end]).take(data_length as u64).read_to_end(&mut data).map_err(|e| format!("could not decompress the packet: {e}"))?;
+
+			let (buffer, id_at) = length_prefixed(&data);
+			(buffer, id_at, 0, Some(data.len()))
+		}
+	} else {
+		(input[start..end].to_vec(), body_start - start, body_start, None)
+	};
+	let to_input = |offset: usize| base + offset - id_at;
+
+	let mut id_deserializer = McDeserializer::new(&buffer[id_at..]);
+	let id = VarInt::mc_deserialize(&mut id_deserializer).map_err(|e| format!("could not read the packet id: {e}"))?.0;
+	let id_range = to_input(id_at)..to_input(id_at + id_deserializer.index);
+	header.push(Field {
+		path: "packet id".to_string(),
+		depth: 0,
+		hex: hex_summary(&buffer[id_at..id_at + id_deserializer.index]),
+		range: id_range,
+	});
+
+	let recorder = FieldRecorder::new(&buffer);
+	let mut deserializer = McDeserializer::with_recorder(&buffer, &recorder);
+	let packet = Packet::deserialize_state_version(&mut deserializer, options.state, options.direction, options.version);
+
+	let name = match &packet {
+		Ok(packet) => packet.name(),
+		Err(_) => packet_versions::latest_id(options.version, options.state, id).and_then(|id| PacketInfo::by_id(options.state, options.direction, id)).map_or("Unknown", |info| info.name),
+	};
+
+	let mut fields = header;
+	fields.extend(self::fields(&buffer, recorder.into_spans(), 0, options).into_iter().map(|field| Field {
+		range: to_input(field.range.start)..to_input(field.range.end),
+		..field
+	}));
+
+	let dissection = Dissection {
+		title: format!("Frame {number}: {name} (0x{id:02X}), bytes {start}..{end}"),
+		decompressed,
+		fields,
+		result: packet.map(|packet| packet.to_json()).map_err(|e| e.to_string()),
+	};
+
+	Ok((dissection, end))
+}
+
+/// Prefix `data` with its length, the way `Packet::deserialize_state` reads it. Returns the buffer and the index
+/// that `data` starts at.
+fn length_prefixed(data: &[u8]) -> (Vec<u8>, usize) {
+	let mut serializer = McSerializer::new();
+	VarInt(data.len() as i32).mc_serialize(&mut serializer).expect("a VarInt always serializes");
+	let start = serializer.output.len();
+	serializer.serialize_bytes(data);
+
+	(serializer.output, start)
+}
+
+fn field(input: &[u8], path: &str, range: Range<usize>) -> Field {
+	Field {
+		path: path.to_string(),
+		depth: 0,
+		hex: hex_summary(&input[range.clone()]),
+		range,
+	}
+}
+
+/// The fields of the recorded spans, with `offset` added to their ranges. Unless [Options::all_items] is set, only
+/// the first [MAX_DUMP_ITEMS] items of an array are kept, followed by a field that counts the rest.
+fn fields(buffer: &[u8], spans: Vec<FieldSpan>, offset: usize, options: &Options) -> Vec<Field> {
+	let mut fields = vec![];
+	let mut i = 0;
+
+	while i < spans.len() {
+		let span = &spans[i];
+
+		if !options.all_items && item_index(&span.path).is_some_and(|index| index >= MAX_DUMP_ITEMS) {
+			// skip the rest of the items and everything inside of them
+			let rest = spans[i..].iter().take_while(|s| s.depth >= span.depth).collect::<Vec<_>>();
+			let count = rest.iter().filter(|s| s.depth == span.depth).count();
+			let end = rest.last().map_or(span.range.end, |s| s.range.end);
+
+			fields.push(Field {
+				path: format!("... {count} more items"),
+				depth: span.depth,
+				range: span.range.start + offset..end + offset,
+				hex: hex_summary(&buffer[span.range.start..end]),
+			});
+
+			i += rest.len();
+			continue;
+		}
+
+		fields.push(Field {
+			path: span.path.clone(),
+			depth: span.depth,
+			range: span.range.start + offset..span.range.end + offset,
+			hex: hex_summary(&buffer[span.range.clone()]),
+		});
+		i += 1;
+	}
+
+	fields
+}
+
+/// The index of an array item, from the end of its path like `players[3]`.
+fn item_index(path: &str) -> Option<usize> {
+	let (_, index) = path.strip_suffix(']')?.rsplit_once('[')?;
+	index.parse().ok()
+}
+
+/// Write a dissection as text: the title, then a line for each field with its range, path and bytes, then the value.
+pub fn render(dissection: &Dissection) -> String {
+	let mut out = format!("{}\n", dissection.title);
+
+	if let Some(len) = dissection.decompressed {
+		out.push_str(&format!("  decompressed to {len} bytes, the decoded fields are at offsets in those bytes\n"));
+	}
+
+	let ranges: Vec<String> = dissection.fields.iter().map(|f| format!("{}..{}", f.range.start, f.range.end)).collect();
+	let range_width = ranges.iter().map(String::len).max().unwrap_or(0);
+	let path_width = dissection.fields.iter().map(|f| f.depth * 2 + f.path.len()).max().unwrap_or(0);
+
+	for (field, range) in dissection.fields.iter().zip(ranges) {
+		let path = format!("{}{}", "  ".repeat(field.depth), field.path);
+		out.push_str(&format!("  {range:<range_width$}  {path:<path_width$}  {}\n", field.hex));
+	}
+
+	match &dissection.result {
+		Ok(json) => out.push_str(&format!("{json}\n")),
+		Err(e) => out.push_str(&format!("error: {e}\n")),
+	}
+
+	out
+}
+
+#[cfg(test)]
+mod test {
+	use sandstone::protocol::packets::packet_definer::{PacketDirection, PacketState};
+	use sandstone::protocol_types::protocol_verison::ProtocolVerison;
+
+	use crate::dissect::{dissect, parse_input, render, Kind, Options};
+
+	/// A Handshake frame, the first of the frames in `test_basic_deserialization`.
+	const HANDSHAKE: [u8; 17] = [16, 0, 254, 5, 9, 108, 111, 99, 97, 108, 104, 111, 115, 116, 99, 221, 1];
+
+	fn options(compressed: bool) -> Options {
+		Options {
+			kind: Kind::Packet,
+			state: PacketState::HANDSHAKING,
+			direction: PacketDirection::SERVER,
+			compressed,
+			version: ProtocolVerison::latest(),
+			all_items: false,
+		}
+	}
+
+	fn paths_and_ranges(input: &[u8], options: &Options) -> Vec<(String, usize, usize)> {
+		dissect(input, options)[0].fields.iter().map(|f| (f.path.clone(), f.range.start, f.range.end)).collect()
+	}
+
+	#[test]
+	fn test_parse_input() {
+		assert_eq!(parse_input("0x10 00 fe05").unwrap(), vec![16, 0, 254, 5]);
+		assert_eq!(parse_input("[16, 0, 254, 5]").unwrap(), vec![16, 0, 254, 5]);
+		assert!(parse_input("0g").is_err());
+		assert!(parse_input("[16, 256]").is_err());
+	}
+
+	#[test]
+	fn test_frame_fields() {
+		let dissections = dissect(&HANDSHAKE, &options(false));
+		assert_eq!(dissections.len(), 1);
+		assert!(dissections[0].title.starts_with("Frame 0: Handshaking (0x00)"));
+		assert!(dissections[0].result.is_ok());
+
+		let expected = [("length", 0, 1), ("packet id", 1, 2), ("protocol_version", 2, 4), ("server_address", 4, 14), ("port", 14, 16), ("next_state", 16, 17)];
+		let expected: Vec<_> = expected.iter().map(|(p, s, e)| (p.to_string(), *s, *e)).collect();
+		assert_eq!(paths_and_ranges(&HANDSHAKE, &options(false))[..6], expected[..]);
+
+		assert!(render(&dissections[0]).contains("2..4    protocol_version  fe05 (2 bytes)"));
+	}
+
+	#[test]
+	fn test_several_frames() {
+		let mut input = HANDSHAKE.to_vec();
+		input.extend_from_slice(&HANDSHAKE);
+		input.extend_from_slice(&HANDSHAKE[..5]);
+
+		let dissections = dissect(&input, &options(false));
+		assert_eq!(dissections.len(), 3);
+		assert!(dissections[1].title.contains("bytes 17..34"));
+		assert!(dissections[2].result.as_ref().unwrap_err().contains("only 4 bytes are left"));
+	}
+
+	#[test]
+	fn test_compressed_frame() {
+		// below the threshold, so the data length is 0 and the packet follows it
+		let mut input = vec![17, 0];
+		input.extend_from_slice(&HANDSHAKE[1..]);
+
+		let fields = paths_and_ranges(&input, &options(true));
+		assert_eq!(fields[1], ("data length".to_string(), 1, 2));
+		assert_eq!(fields[2], ("packet id".to_string(), 2, 3));
+		assert_eq!(fields[3], ("protocol_version".to_string(), 3, 5));
+	}
+
+	#[test]
+	fn test_failed_frame_keeps_fields() {
+		// the port is cut off
+		let mut input = HANDSHAKE[..15].to_vec();
+		input[0] = 14;
+
+		let dissection = &dissect(&input, &options(false))[0];
+		assert!(dissection.result.as_ref().unwrap_err().contains("port"));
+		assert!(dissection.fields.iter().any(|f| f.path == "server_address" && f.range == (4..14)));
+	}
+
+	#[test]
+	fn test_nbt() {
+		// a compound with a single byte named "a"
+		let input = [10, 1, 0, 1, b'a', 5, 0];
+		let options = Options {
+			kind: Kind::Nbt,
+			..options(false)
+		};
+
+		let dissection = &dissect(&input, &options)[0];
+		assert!(dissection.result.is_ok());
+		assert_eq!(dissection.fields[0].path, "a");
+		assert_eq!(dissection.fields[0].range, 5..6);
+	}
+}
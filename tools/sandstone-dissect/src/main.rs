@@ -0,0 +1,118 @@
+//! Annotate raw packet frames, NBT or chunk data with the bytes each field was read from.
+//!
+//! The input is hex, a byte list like the ones logged at the `trace` level, or a file of raw bytes. See the README
+//! for the options.
+
+mod dissect;
+
+use std::error::Error;
+use std::fs;
+use std::io::Read;
+use std::path::PathBuf;
+
+use sandstone::protocol::packets::packet_definer::{PacketDirection, PacketState};
+use sandstone::protocol_types::protocol_verison::ProtocolVerison;
+
+use crate::dissect::{dissect, parse_input, render, Kind, Options};
+
+struct Args {
+	options: Options,
+	text: Option<String>,
+	file: Option<PathBuf>,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+	let args = parse_args()?;
+
+	let input = match (&args.file, args.text.as_deref()) {
+		(Some(path), None) => fs::read(path)?,
+		(None, Some("-")) => {
+			let mut text = String::new();
+			std::io::stdin().read_to_string(&mut text)?;
+			parse_input(&text)?
+		}
+		(None, Some(text)) => parse_input(text)?,
+		_ => return Err("expected either hex input, `-` to read it from stdin, or --file".into()),
+	};
+
+	let dissections = dissect(&input, &args.options);
+	let failed = dissections.iter().any(|d| d.result.is_err());
+
+	for dissection in &dissections {
+		println!("{}", render(dissection));
+	}
+
+	if failed {
+		std::process::exit(1);
+	}
+
+	Ok(())
+}
+
+fn parse_args() -> Result<Args, String> {
+	let mut args = Args {
+		options: Options {
+			kind: Kind::Packet,
+			state: PacketState::PLAY,
+			direction: PacketDirection::CLIENT,
+			compressed: false,
+			version: ProtocolVerison::latest(),
+			all_items: false,
+		},
+		text: None,
+		file: None,
+	};
+
+	let mut raw = std::env::args().skip(1);
+	while let Some(arg) = raw.next() {
+		let mut value = || raw.next().ok_or_else(|| format!("`{arg}` needs a value"));
+		match arg.as_str() {
+			"--file" => args.file = Some(value()?.into()),
+			"--kind" => {
+				args.options.kind = match value()?.as_str() {
+					"packet" => Kind::Packet,
+					"nbt" => Kind::Nbt,
+					"nbt-file" => Kind::NbtFile,
+					"chunk" => Kind::Chunk,
+					other => return Err(format!("unknown kind `{other}`, expected packet, nbt, nbt-file or chunk")),
+				}
+			}
+			"--state" => {
+				args.options.state = match value()?.as_str() {
+					"handshaking" => PacketState::HANDSHAKING,
+					"status" => PacketState::STATUS,
+					"login" => PacketState::LOGIN,
+					"configuration" => PacketState::CONFIGURATION,
+					"play" => PacketState::PLAY,
+					other => return Err(format!("unknown state `{other}`, expected handshaking, status, login, configuration or play")),
+				}
+			}
+			"--direction" => {
+				args.options.direction = match value()?.as_str() {
+					"server" => PacketDirection::SERVER,
+					"client" => PacketDirection::CLIENT,
+					other => return Err(format!("unknown direction `{other}`, expected server or client")),
+				}
+			}
+			"--protocol" => {
+				let number = value()?;
+				let number: i16 = number.parse().map_err(|_| format!("`{number}` is not a protocol number"))?;
+				args.options.version = ProtocolVerison::try_from(number).map_err(|_| format!("protocol {number} is not supported"))?;
+			}
+			"--compressed" => args.options.compressed = true,
+			"--all" => args.options.all_items = true,
+			_ if arg.starts_with("--") => {
+				return Err(format!("unknown argument `{arg}`, expected --file, --kind, --state, --direction, --protocol, --compressed or --all"));
+			}
+			_ if args.text.is_none() => args.text = Some(arg),
+			// hex that was pasted with spaces and not quoted
+			_ => {
+				let text = args.text.as_mut().unwrap();
+				text.push(' ');
+				text.push_str(&arg);
+			}
+		}
+	}
+
+	Ok(args)
+}